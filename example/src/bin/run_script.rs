@@ -0,0 +1,35 @@
+//! Runs a JSON or TOML command script against the first detected signal generator and prints
+//! every response, see `minicircuit_driver::script` for the layout of a script.
+//!
+//! ```text
+//! cargo run -p example --bin run_script -- example/scripts/set_and_read.json
+//! ```
+
+use std::{env, process::ExitCode};
+
+use minicircuit_driver::{prelude::*, script::run_script};
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let Some(path) = env::args().nth(1) else {
+        eprintln!("Usage: run_script <script.json | script.toml>");
+        return ExitCode::FAILURE;
+    };
+
+    let mut controller = MiniCircuitDriver::new(TargetProperties::default());
+    if let Err(e) = controller.connect() {
+        eprintln!("Unable to connect to the controller: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    let result = run_script(&path, &controller).await;
+    controller.disconnect().await;
+
+    match result {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
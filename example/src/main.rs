@@ -1,11 +1,7 @@
 use tokio::spawn;
 
-use minicircuit_commands::properties::TargetProperties;
-use minicircuit_commands::{
-    basic::frequency::{GetFrequency, SetFrequency},
-    command::{Command, Message, Priority},
-};
-use minicircuit_driver::{connection::print_available_ports, driver::MiniCircuitDriver};
+use minicircuit_commands::basic::frequency::{GetFrequency, SetFrequency};
+use minicircuit_driver::prelude::*;
 
 #[tokio::main]
 async fn main() {
@@ -55,10 +51,16 @@ async fn main() {
     let _ = channel_tx.send(Message {
         priority: Priority::High,
         command: set_frequency.clone(),
+        timeout: None,
+        tag: None,
+        channel: None,
     });
     let _ = channel_tx.send(Message {
         priority: Priority::Low,
         command: get_frequency.clone(),
+        timeout: None,
+        tag: None,
+        channel: None,
     });
 
     handle.await.unwrap();
@@ -0,0 +1,39 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use minicircuit_commands::{dll::sweep::PerformSweepWatt, prelude::*};
+
+/// Compares turning a command into the string written to the device by cloning it into the
+/// owned conversion, against the borrowed conversion that leaves the command as it is.
+fn serialize(c: &mut Criterion) {
+    let commands: [(&str, Command); 3] = [
+        (
+            "SetFrequency",
+            SetFrequency::new(Channel::default(), Frequency::new(2450)).into(),
+        ),
+        (
+            "PerformSweepWatt",
+            PerformSweepWatt::new(
+                Channel::default(),
+                Frequency::new(2400),
+                Frequency::new(2500),
+                Frequency::new(10),
+                Watt::new(100.0),
+            )
+            .into(),
+        ),
+        ("Raw", Command::Raw("$FCS,1,2450".to_string())),
+    ];
+
+    for (name, command) in &commands {
+        let mut group = c.benchmark_group(*name);
+        group.bench_function("owned", |b| {
+            b.iter(|| String::from(black_box(command).clone()))
+        });
+        group.bench_function("borrowed", |b| b.iter(|| String::from(black_box(command))));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, serialize);
+criterion_main!(benches);
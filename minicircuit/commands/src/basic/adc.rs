@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Adc, Channel},
 };
 
@@ -31,18 +31,8 @@ impl TryFrom<String> for GetPAPowerADCResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let forward: Adc = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Adc::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let reflected: Adc = match parts[3].trim().parse::<f32>() {
-            Ok(value) => Adc::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let forward: Adc = Adc::new(parse_field("forward", parts[2], &response)?);
+        let reflected: Adc = Adc::new(parse_field("reflected", parts[3], &response)?);
 
         Ok(GetPAPowerADCResponse { forward, reflected })
     }
@@ -59,9 +49,9 @@ pub struct GetPAPowerADC {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPAPowerADC {
-    fn into(self) -> String {
-        format!("$PAG,{}", self.channel)
+impl From<&GetPAPowerADC> for String {
+    fn from(command: &GetPAPowerADC) -> String {
+        format!("$PAG,{}", command.channel)
     }
 }
 
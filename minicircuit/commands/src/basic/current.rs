@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Amperes, Channel},
 };
 
@@ -29,12 +29,7 @@ impl TryFrom<String> for GetPACurrentResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let current: Amperes = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Amperes::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let current: Amperes = Amperes::new(parse_field("current", parts[2], &response)?);
 
         Ok(GetPACurrentResponse { current })
     }
@@ -47,9 +42,9 @@ pub struct GetPACurrent {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPACurrent {
-    fn into(self) -> String {
-        format!("$PIG,{}", self.channel)
+impl From<&GetPACurrent> for String {
+    fn from(command: &GetPACurrent) -> String {
+        format!("$PIG,{}", command.channel)
     }
 }
 
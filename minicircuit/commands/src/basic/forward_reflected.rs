@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Dbm, Watt},
 };
 
@@ -13,6 +13,62 @@ pub struct GetPAPowerWattResponse {
     pub reflected: Watt,
 }
 
+impl GetPAPowerWattResponse {
+    /// The magnitude of the reflection coefficient, `|Γ| = sqrt(P_reflected / P_forward)`.
+    ///
+    /// Ranges from 0 for a matched load to 1 for total reflection. Reflected power read above
+    /// the forward power is taken as total reflection, and negative readings as zero.
+    /// Returns `None` while there is no forward power to compare against.
+    ///
+    /// ```
+    /// use minicircuit_commands::prelude::*;
+    ///
+    /// let response = GetPAPowerWattResponse::try_from("OK,1,100.0,4.0".to_string()).unwrap();
+    /// assert!((response.reflection_coefficient().unwrap() - 0.2).abs() < 1e-6);
+    /// assert!((response.vswr().unwrap() - 1.5).abs() < 1e-5);
+    /// assert!((response.return_loss_db().unwrap() - 13.98).abs() < 0.01);
+    ///
+    /// // Without RF output there is nothing to reflect.
+    /// let response = GetPAPowerWattResponse::try_from("OK,1,0.0,0.0".to_string()).unwrap();
+    /// assert_eq!(response.vswr(), None);
+    /// ```
+    pub fn reflection_coefficient(&self) -> Option<f32> {
+        let ratio = self.reflected_ratio()?;
+        Some(ratio.sqrt())
+    }
+
+    /// The return loss in dB, `RL = -10 * log10(P_reflected / P_forward)`.
+    ///
+    /// Positive, and higher for a better match. Infinite when no power is reflected.
+    /// Returns `None` while there is no forward power, see `reflection_coefficient`.
+    pub fn return_loss_db(&self) -> Option<f32> {
+        let ratio = self.reflected_ratio()?;
+        Some(-10.0 * ratio.log10())
+    }
+
+    /// The voltage standing wave ratio, `VSWR = (1 + |Γ|) / (1 - |Γ|)`.
+    ///
+    /// 1 for a matched load, and infinite for total reflection.
+    /// Returns `None` while there is no forward power, see `reflection_coefficient`.
+    pub fn vswr(&self) -> Option<f32> {
+        let gamma = self.reflection_coefficient()?;
+        if gamma >= 1.0 {
+            return Some(f32::INFINITY);
+        }
+
+        Some((1.0 + gamma) / (1.0 - gamma))
+    }
+
+    /// The share of the forward power that is reflected, between 0 and 1.
+    fn reflected_ratio(&self) -> Option<f32> {
+        if self.forward.power <= 0.0 {
+            return None;
+        }
+
+        Some((self.reflected.power / self.forward.power).clamp(0.0, 1.0))
+    }
+}
+
 impl TryFrom<String> for GetPAPowerWattResponse {
     type Error = MWError;
 
@@ -31,18 +87,8 @@ impl TryFrom<String> for GetPAPowerWattResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let forward: Watt = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Watt::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let reflected: Watt = match parts[3].trim().parse::<f32>() {
-            Ok(value) => Watt::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let forward: Watt = Watt::new(parse_field("forward", parts[2], &response)?);
+        let reflected: Watt = Watt::new(parse_field("reflected", parts[3], &response)?);
 
         Ok(GetPAPowerWattResponse { forward, reflected })
     }
@@ -55,9 +101,9 @@ pub struct GetPAPowerWatt {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPAPowerWatt {
-    fn into(self) -> String {
-        format!("$PPG,{}", self.channel)
+impl From<&GetPAPowerWatt> for String {
+    fn from(command: &GetPAPowerWatt) -> String {
+        format!("$PPG,{}", command.channel)
     }
 }
 
@@ -104,18 +150,8 @@ impl TryFrom<String> for GetPAPowerDBMResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let forward: Dbm = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Dbm::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let reflected: Dbm = match parts[3].trim().parse::<f32>() {
-            Ok(value) => Dbm::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let forward: Dbm = Dbm::new(parse_field("forward", parts[2], &response)?);
+        let reflected: Dbm = Dbm::new(parse_field("reflected", parts[3], &response)?);
 
         Ok(GetPAPowerDBMResponse { forward, reflected })
     }
@@ -128,9 +164,9 @@ pub struct GetPAPowerDBM {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPAPowerDBM {
-    fn into(self) -> String {
-        format!("$PPDG,{}", self.channel)
+impl From<&GetPAPowerDBM> for String {
+    fn from(command: &GetPAPowerDBM) -> String {
+        format!("$PPDG,{}", command.channel)
     }
 }
 
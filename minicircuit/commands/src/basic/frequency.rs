@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-use crate::data_types::{
-    errors::MWError,
-    types::{Channel, Frequency},
+use crate::{
+    basic::forward_reflected::GetPAPowerWatt,
+    command::Command,
+    data_types::{
+        errors::{parse_field, MWError, ValidationError},
+        types::{Channel, Frequency},
+    },
+    properties::DeviceLimits,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -33,9 +38,9 @@ pub struct SetFrequency {
     pub frequency: Frequency,
 }
 
-impl Into<String> for SetFrequency {
-    fn into(self) -> String {
-        format!("$FCS,{},{}", self.channel, self.frequency)
+impl From<&SetFrequency> for String {
+    fn from(command: &SetFrequency) -> String {
+        format!("$FCS,{},{}", command.channel, command.frequency)
     }
 }
 
@@ -44,6 +49,27 @@ impl SetFrequency {
     pub fn new(channel: Channel, frequency: Frequency) -> Self {
         Self { channel, frequency }
     }
+
+    /// Returns a handler to call the command, if the frequency lies within the default device limits.
+    pub fn try_new(channel: Channel, frequency: Frequency) -> Result<Self, ValidationError> {
+        Self::try_new_within(channel, frequency, &DeviceLimits::default())
+    }
+
+    /// Returns a handler to call the command, if the frequency lies within the given device limits.
+    pub fn try_new_within(
+        channel: Channel,
+        frequency: Frequency,
+        limits: &DeviceLimits,
+    ) -> Result<Self, ValidationError> {
+        ValidationError::check_range(
+            "frequency",
+            frequency.frequency as f64,
+            limits.min_frequency.frequency as f64,
+            limits.max_frequency.frequency as f64,
+        )?;
+
+        Ok(Self::new(channel, frequency))
+    }
 }
 
 impl Default for SetFrequency {
@@ -82,13 +108,7 @@ impl TryFrom<String> for GetFrequencyResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let frequency: Frequency = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u16>()
-        {
-            Ok(value) => Frequency::new(value),
-            Err(_) => return Err(Self::Error::FailedParseResponse),
-        };
+        let frequency: Frequency = Frequency::new(parse_field("frequency", parts[2], &response)?);
 
         Ok(GetFrequencyResponse { frequency })
     }
@@ -101,9 +121,9 @@ pub struct GetFrequency {
     pub channel: Channel,
 }
 
-impl Into<String> for GetFrequency {
-    fn into(self) -> String {
-        format!("$FCG,{}", self.channel)
+impl From<&GetFrequency> for String {
+    fn from(command: &GetFrequency) -> String {
+        format!("$FCG,{}", command.channel)
     }
 }
 
@@ -123,3 +143,81 @@ impl Default for GetFrequency {
         }
     }
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+/// Steps the RF output across a band from the host, yielding a `SetFrequency` for every point.
+///
+/// Unlike `PerformSweepDBM` and `PerformSweepWatt`, which run on the device, this lets the caller
+/// run custom logic at every point. Both `start` and `stop` are included when the step lands on them.
+pub struct FrequencySweep {
+    /// Channel identification number.
+    pub channel: Channel,
+    /// The first frequency of the sweep (in MHz).
+    pub start: Frequency,
+    /// The last frequency the sweep may reach (in MHz).
+    pub stop: Frequency,
+    /// The distance between consecutive points (in MHz).
+    pub step: Frequency,
+    /// The next frequency to yield, or `None` once the sweep is done.
+    next: Option<Frequency>,
+}
+
+impl FrequencySweep {
+    /// Returns a sweep from `start` to `stop` in increments of `step`.
+    ///
+    /// Fails if `start` lies above `stop` or if `step` is zero.
+    pub fn new(
+        channel: Channel,
+        start: Frequency,
+        stop: Frequency,
+        step: Frequency,
+    ) -> Result<Self, ValidationError> {
+        if start > stop {
+            return Err(ValidationError::InvertedRange {
+                start: start.frequency as f64,
+                stop: stop.frequency as f64,
+            });
+        }
+        if step.frequency == 0 {
+            return Err(ValidationError::NonPositiveStep);
+        }
+
+        Ok(Self {
+            channel,
+            start,
+            stop,
+            step,
+            next: Some(start),
+        })
+    }
+
+    /// Follows every `SetFrequency` with a `GetPAPowerWatt`, so the forward and reflected power
+    /// are measured at each point.
+    pub fn with_measurement(self) -> impl Iterator<Item = Command> {
+        let channel = self.channel.clone();
+
+        self.flat_map(move |set_frequency| {
+            [
+                Command::SetFrequency(set_frequency),
+                Command::GetPAPowerWatt(GetPAPowerWatt::new(channel.clone())),
+            ]
+        })
+    }
+}
+
+impl Iterator for FrequencySweep {
+    type Item = SetFrequency;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+
+        // Stop once the next point would pass the end of the band (or overflow).
+        self.next = current
+            .frequency
+            .checked_add(self.step.frequency)
+            .map(Frequency::new)
+            .filter(|next| *next <= self.stop);
+
+        Some(SetFrequency::new(self.channel.clone(), current))
+    }
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 
-use crate::data_types::{errors::MWError, types::Channel};
+use crate::data_types::{
+    errors::{parse_field, MWError},
+    types::Channel,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SetRFOutputResponse {
@@ -36,13 +39,13 @@ pub struct SetRFOutput {
     pub enabled: bool,
 }
 
-impl Into<String> for SetRFOutput {
-    fn into(self) -> String {
-        let numeric_value = match self.enabled {
+impl From<&SetRFOutput> for String {
+    fn from(command: &SetRFOutput) -> String {
+        let numeric_value = match command.enabled {
             true => 1,
             false => 0,
         };
-        format!("$ECS,{},{}", self.channel, numeric_value)
+        format!("$ECS,{},{}", command.channel, numeric_value)
     }
 }
 
@@ -93,18 +96,7 @@ impl TryFrom<String> for GetRFOutputResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let enabled: bool = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u8>()
-        {
-            Ok(value) => match value {
-                1 => true,
-                _ => false,
-            },
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let enabled: bool = parse_field::<u8>("enabled", parts[2], &response)? == 1;
 
         Ok(GetRFOutputResponse { enabled })
     }
@@ -120,9 +112,9 @@ pub struct GetRFOutput {
     pub channel: Channel,
 }
 
-impl Into<String> for GetRFOutput {
-    fn into(self) -> String {
-        format!("$ECG,{}", self.channel)
+impl From<&GetRFOutput> for String {
+    fn from(command: &GetRFOutput) -> String {
+        format!("$ECG,{}", command.channel)
     }
 }
 
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError, ValidationError},
     types::{Channel, Phase},
 };
 
@@ -37,9 +37,9 @@ pub struct SetPhase {
     pub phase: Phase,
 }
 
-impl Into<String> for SetPhase {
-    fn into(self) -> String {
-        format!("$PCS,{},{}", self.channel, self.phase)
+impl From<&SetPhase> for String {
+    fn from(command: &SetPhase) -> String {
+        format!("$PCS,{},{}", command.channel, command.phase)
     }
 }
 
@@ -48,6 +48,13 @@ impl SetPhase {
     pub fn new(channel: Channel, phase: Phase) -> Self {
         Self { channel, phase }
     }
+
+    /// Returns a handler to call the command, if the phase lies between 0 and 359 degrees.
+    pub fn try_new(channel: Channel, phase: Phase) -> Result<Self, ValidationError> {
+        ValidationError::check_range("phase", phase.phase as f64, 0., 359.)?;
+
+        Ok(Self::new(channel, phase))
+    }
 }
 
 impl Default for SetPhase {
@@ -86,15 +93,7 @@ impl TryFrom<String> for GetPhaseResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let phase: Phase = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<i16>()
-        {
-            Ok(value) => Phase::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let phase: Phase = Phase::new(parse_field("phase", parts[2], &response)?);
 
         Ok(GetPhaseResponse { phase })
     }
@@ -107,9 +106,9 @@ pub struct GetPhase {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPhase {
-    fn into(self) -> String {
-        format!("$PCG,{}", self.channel)
+impl From<&GetPhase> for String {
+    fn from(command: &GetPhase) -> String {
+        format!("$PCG,{}", command.channel)
     }
 }
 
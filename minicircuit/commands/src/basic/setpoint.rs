@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-use crate::data_types::{
-    errors::MWError,
-    types::{Channel, Dbm, Watt},
+use crate::{
+    data_types::{
+        errors::{parse_field, MWError, ValidationError},
+        types::{Channel, Dbm, Watt},
+    },
+    properties::DeviceLimits,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -33,9 +36,9 @@ pub struct SetPAPowerSetpointWatt {
     pub power: Watt,
 }
 
-impl Into<String> for SetPAPowerSetpointWatt {
-    fn into(self) -> String {
-        format!("$PWRS,{},{}", self.channel, self.power)
+impl From<&SetPAPowerSetpointWatt> for String {
+    fn from(command: &SetPAPowerSetpointWatt) -> String {
+        format!("$PWRS,{},{}", command.channel, command.power)
     }
 }
 
@@ -44,6 +47,28 @@ impl SetPAPowerSetpointWatt {
     pub fn new(channel: Channel, power: Watt) -> Self {
         Self { channel, power }
     }
+
+    /// Returns a handler to call the command, if the power lies within the default device limits.
+    pub fn try_new(channel: Channel, power: Watt) -> Result<Self, ValidationError> {
+        Self::try_new_within(channel, power, &DeviceLimits::default())
+    }
+
+    /// Returns a handler to call the command, if the power lies between 0W and the
+    /// maximum power of the given device limits.
+    pub fn try_new_within(
+        channel: Channel,
+        power: Watt,
+        limits: &DeviceLimits,
+    ) -> Result<Self, ValidationError> {
+        ValidationError::check_range(
+            "power setpoint",
+            power.power as f64,
+            0.,
+            limits.max_power.power as f64,
+        )?;
+
+        Ok(Self::new(channel, power))
+    }
 }
 
 impl Default for SetPAPowerSetpointWatt {
@@ -80,12 +105,7 @@ impl TryFrom<String> for GetPAPowerSetpointWattResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let power: Watt = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Watt::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let power: Watt = Watt::new(parse_field("power", parts[2], &response)?);
 
         Ok(GetPAPowerSetpointWattResponse { power })
     }
@@ -98,9 +118,9 @@ pub struct GetPAPowerSetpointWatt {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPAPowerSetpointWatt {
-    fn into(self) -> String {
-        format!("$PWRG,{}", self.channel)
+impl From<&GetPAPowerSetpointWatt> for String {
+    fn from(command: &GetPAPowerSetpointWatt) -> String {
+        format!("$PWRG,{}", command.channel)
     }
 }
 
@@ -121,7 +141,7 @@ impl Default for GetPAPowerSetpointWatt {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetPAPowerSetpointDBMResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -149,9 +169,9 @@ pub struct SetPAPowerSetpointDBM {
     pub power: Dbm,
 }
 
-impl Into<String> for SetPAPowerSetpointDBM {
-    fn into(self) -> String {
-        format!("$PWRDS,{},{}", self.channel, self.power)
+impl From<&SetPAPowerSetpointDBM> for String {
+    fn from(command: &SetPAPowerSetpointDBM) -> String {
+        format!("$PWRDS,{},{}", command.channel, command.power)
     }
 }
 
@@ -160,6 +180,30 @@ impl SetPAPowerSetpointDBM {
     pub fn new(channel: Channel, power: Dbm) -> Self {
         Self { channel, power }
     }
+
+    /// Returns a handler to call the command, if the power lies within the default device limits.
+    pub fn try_new(channel: Channel, power: Dbm) -> Result<Self, ValidationError> {
+        Self::try_new_within(channel, power, &DeviceLimits::default())
+    }
+
+    /// Returns a handler to call the command, if the power lies between 0dBm and the
+    /// maximum power of the given device limits.
+    pub fn try_new_within(
+        channel: Channel,
+        power: Dbm,
+        limits: &DeviceLimits,
+    ) -> Result<Self, ValidationError> {
+        let max_power: Dbm = limits.max_power.into();
+
+        ValidationError::check_range(
+            "power setpoint",
+            power.power as f64,
+            0.,
+            max_power.power as f64,
+        )?;
+
+        Ok(Self::new(channel, power))
+    }
 }
 
 impl Default for SetPAPowerSetpointDBM {
@@ -196,12 +240,7 @@ impl TryFrom<String> for GetPAPowerSetpointDBMResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let power: Dbm = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Dbm::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let power: Dbm = Dbm::new(parse_field("power", parts[2], &response)?);
 
         Ok(GetPAPowerSetpointDBMResponse { power })
     }
@@ -214,9 +253,9 @@ pub struct GetPAPowerSetpointDBM {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPAPowerSetpointDBM {
-    fn into(self) -> String {
-        format!("$PWRDG,{}", self.channel)
+impl From<&GetPAPowerSetpointDBM> for String {
+    fn from(command: &GetPAPowerSetpointDBM) -> String {
+        format!("$PWRDG,{}", command.channel)
     }
 }
 
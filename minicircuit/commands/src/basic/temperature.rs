@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Temperature},
 };
 
@@ -29,15 +29,8 @@ impl TryFrom<String> for GetPATempResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let temperature: Temperature = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u8>()
-        {
-            Ok(value) => Temperature::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let temperature: Temperature =
+            Temperature::new(parse_field("temperature", parts[2], &response)?);
 
         Ok(GetPATempResponse { temperature })
     }
@@ -50,9 +43,9 @@ pub struct GetPATemp {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPATemp {
-    fn into(self) -> String {
-        format!("$PTG,{}", self.channel)
+impl From<&GetPATemp> for String {
+    fn from(command: &GetPATemp) -> String {
+        format!("$PTG,{}", command.channel)
     }
 }
 
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Volts},
 };
 
@@ -29,12 +29,7 @@ impl TryFrom<String> for GetPAVoltageResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let voltage: Volts = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Volts::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let voltage: Volts = Volts::new(parse_field("voltage", parts[2], &response)?);
 
         Ok(GetPAVoltageResponse { voltage })
     }
@@ -47,9 +42,9 @@ pub struct GetPAVoltage {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPAVoltage {
-    fn into(self) -> String {
-        format!("$PVG,{}", self.channel)
+impl From<&GetPAVoltage> for String {
+    fn from(command: &GetPAVoltage) -> String {
+        format!("$PVG,{}", command.channel)
     }
 }
 
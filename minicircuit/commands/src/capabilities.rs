@@ -0,0 +1,137 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command::Command,
+    information::{
+        identity::{BoardModel, GetIdentityResponse},
+        version::{FirmwareVersion, GetVersionResponse},
+    },
+};
+
+/// The boards that don't implement any of the command groups.
+const LIMITED_BOARDS: [&str; 1] = ["ISC-2425-25+"];
+
+/// A group of commands that not every controller implements.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandGroup {
+    /// `GetPAVoltage`.
+    PaVoltage,
+    /// `GetPAErrors`.
+    PaErrors,
+    /// The SOA current, forward power and voltage limits, the grace timer and the watchdog.
+    ExtendedSoa,
+    /// `SetUartBaudRate`.
+    UartBaudRate,
+    /// `GetPowerMaxDbm`, `GetPowerMinDbm` and `SetPowerMinDbm`.
+    PowerLimits,
+    /// `GetPowerOffset` and `SetPowerOffset`.
+    PowerOffset,
+    /// `SetZHLTriggerDelay`.
+    ZhlTriggerDelay,
+}
+
+impl CommandGroup {
+    pub const ALL: [CommandGroup; 7] = [
+        CommandGroup::PaVoltage,
+        CommandGroup::PaErrors,
+        CommandGroup::ExtendedSoa,
+        CommandGroup::UartBaudRate,
+        CommandGroup::PowerLimits,
+        CommandGroup::PowerOffset,
+        CommandGroup::ZhlTriggerDelay,
+    ];
+
+    /// The group the command belongs to, `None` for commands every controller implements.
+    #[allow(deprecated)]
+    pub fn of(command: &Command) -> Option<Self> {
+        match command {
+            Command::GetPAVoltage(_) => Some(CommandGroup::PaVoltage),
+            Command::GetPAErrors(_) => Some(CommandGroup::PaErrors),
+            Command::GetSOACurrentConfig(_)
+            | Command::SetSOACurrentConfig(_)
+            | Command::GetSOAForwardPowerLimits(_)
+            | Command::SetSOAForwardPowerLimits(_)
+            | Command::SetSOAGraceTimer(_)
+            | Command::GetSOAVoltageConfig(_)
+            | Command::SetSOAVoltageConfig(_)
+            | Command::SetSOAWatchdogConfig(_) => Some(CommandGroup::ExtendedSoa),
+            Command::SetUartBaudRate(_) => Some(CommandGroup::UartBaudRate),
+            Command::GetPowerMaxDbm(_)
+            | Command::GetPowerMinDbm(_)
+            | Command::SetPowerMinDbm(_) => Some(CommandGroup::PowerLimits),
+            Command::GetPowerOffset(_) | Command::SetPowerOffset(_) => {
+                Some(CommandGroup::PowerOffset)
+            }
+            Command::SetZHLTriggerDelay(_) => Some(CommandGroup::ZhlTriggerDelay),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CommandGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandGroup::PaVoltage => write!(f, "PA voltage"),
+            CommandGroup::PaErrors => write!(f, "PA error"),
+            CommandGroup::ExtendedSoa => write!(f, "extended SOA"),
+            CommandGroup::UartBaudRate => write!(f, "UART baud rate"),
+            CommandGroup::PowerLimits => write!(f, "power limit"),
+            CommandGroup::PowerOffset => write!(f, "power offset"),
+            CommandGroup::ZhlTriggerDelay => write!(f, "ZHL trigger delay"),
+        }
+    }
+}
+
+/// What the connected signal generator supports, detected from its version and identity.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Capabilities {
+    /// The firmware version, `None` if it couldn't be read.
+    pub firmware: Option<FirmwareVersion>,
+    /// The type of ISC board, such as `ISC-2425-25+`, `None` if it couldn't be read.
+    pub isc_board: Option<String>,
+    /// The board type broken into its parts, `None` if it couldn't be read.
+    pub model: Option<BoardModel>,
+}
+
+impl Capabilities {
+    pub fn new(
+        version: Option<&GetVersionResponse>,
+        identity: Option<&GetIdentityResponse>,
+    ) -> Self {
+        Self {
+            firmware: version.and_then(GetVersionResponse::version),
+            isc_board: identity
+                .map(|identity| identity.isc_board.clone())
+                .filter(|isc_board| !isc_board.is_empty()),
+            model: identity.and_then(|identity| identity.model),
+        }
+    }
+
+    /// Whether the commands of the group are available.
+    ///
+    /// When the board type is unknown every group is assumed to be available.
+    pub fn supports(&self, _group: CommandGroup) -> bool {
+        match &self.isc_board {
+            Some(isc_board) => !LIMITED_BOARDS.contains(&isc_board.as_str()),
+            None => true,
+        }
+    }
+
+    /// Whether the command is available, see `supports`.
+    pub fn supports_command(&self, command: &Command) -> bool {
+        match CommandGroup::of(command) {
+            Some(group) => self.supports(group),
+            None => true,
+        }
+    }
+
+    /// The groups the connected controller doesn't support.
+    pub fn unsupported(&self) -> Vec<CommandGroup> {
+        CommandGroup::ALL
+            .into_iter()
+            .filter(|group| !self.supports(*group))
+            .collect()
+    }
+}
@@ -1,5 +1,9 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+use crate::data_types::types::Channel;
+
 pub use crate::{
     basic::{
         adc::GetPAPowerADC,
@@ -521,12 +525,583 @@ pub enum Command {
         note = "This function isn't implemented for the ISC-2425-25+ controller. If you're not using this controller, you can ignore this warning."
     )]
     SetZHLTriggerDelay(SetZHLTriggerDelay),
+    /// Writes the string to the signal generator verbatim and returns its reply unparsed
+    /// in `Response::Raw`.
+    ///
+    /// An escape hatch for commands the crate has no bindings for, such as those of newer firmware.
+    /// The string must not include the line terminator, the transport appends it.
+    Raw(String),
+    /// Marks a place in the queue without sending anything to the signal generator.
+    ///
+    /// Answered with `Response::Barrier` once the commands queued ahead of it have been processed,
+    /// see `MiniCircuitDriver::flush`.
+    Barrier,
+}
+
+#[allow(deprecated)]
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let enable = |enabled: bool| if enabled { "Enable" } else { "Disable" };
+
+        match self {
+            Command::GetPAPowerADC(_) => write!(f, "Get PA forward/reflected power (ADC counts)"),
+            Command::GetPACurrent(_) => write!(f, "Get PA current"),
+            Command::GetPAPowerDBM(_) => write!(f, "Get PA forward/reflected power (dBm)"),
+            Command::GetPAPowerWatt(_) => write!(f, "Get PA forward/reflected power (Watts)"),
+            Command::GetFrequency(_) => write!(f, "Get frequency"),
+            Command::SetFrequency(cmd) => {
+                write!(f, "Set frequency to {} MHz", cmd.frequency.frequency)
+            }
+            Command::GetRFOutput(_) => write!(f, "Get RF output state"),
+            Command::SetRFOutput(cmd) => write!(f, "{} RF output", enable(cmd.enabled)),
+            Command::GetPhase(_) => write!(f, "Get phase"),
+            Command::SetPhase(cmd) => write!(f, "Set phase to {} degrees", cmd.phase.phase),
+            Command::GetPAPowerSetpointDBM(_) => write!(f, "Get PA power setpoint (dBm)"),
+            Command::GetPAPowerSetpointWatt(_) => write!(f, "Get PA power setpoint (Watts)"),
+            Command::SetPAPowerSetpointDBM(cmd) => {
+                write!(f, "Set PA power setpoint to {} dBm", cmd.power.power)
+            }
+            Command::SetPAPowerSetpointWatt(cmd) => {
+                write!(f, "Set PA power setpoint to {} W", cmd.power.power)
+            }
+            Command::GetPATemp(_) => write!(f, "Get PA temperature"),
+            Command::GetPAVoltage(_) => write!(f, "Get PA voltage"),
+            Command::GetDLLConfig(_) => write!(f, "Get DLL configuration"),
+            Command::SetDLLConfig(cmd) => write!(
+                f,
+                "Set DLL configuration to {} - {} MHz, starting at {} MHz in {} MHz steps, with a {} dB threshold and {} ms delay",
+                cmd.lower_frequency.frequency,
+                cmd.upper_frequency.frequency,
+                cmd.start_frequency.frequency,
+                cmd.step_frequency.frequency,
+                cmd.threshold.threshold,
+                cmd.main_delay.main_delay
+            ),
+            Command::GetDLLEnabled(_) => write!(f, "Get DLL enable state"),
+            Command::SetDLLEnabled(cmd) => write!(f, "{} DLL", enable(cmd.enabled)),
+            Command::PerformSweepDBM(cmd) => write!(
+                f,
+                "Sweep {} - {} MHz in {} MHz steps at {} dBm",
+                cmd.start_frequency.frequency,
+                cmd.stop_frequency.frequency,
+                cmd.step_frequency.frequency,
+                cmd.power.power
+            ),
+            Command::PerformSweepWatt(cmd) => write!(
+                f,
+                "Sweep {} - {} MHz in {} MHz steps at {} W",
+                cmd.start_frequency.frequency,
+                cmd.stop_frequency.frequency,
+                cmd.step_frequency.frequency,
+                cmd.power.power
+            ),
+            Command::ClearErrors(_) => write!(f, "Clear errors"),
+            Command::GetPAErrors(_) => write!(f, "Get PA errors"),
+            Command::GetStatus(_) => write!(f, "Get status"),
+            Command::GetIdentity(_) => write!(f, "Get identity"),
+            Command::GetISCTemp(_) => write!(f, "Get ISC temperature"),
+            Command::GetUptime(_) => write!(f, "Get uptime"),
+            Command::GetVersion(_) => write!(f, "Get firmware version"),
+            Command::GetAttenuation(_) => write!(f, "Get attenuation"),
+            Command::SetAttenuation(cmd) => {
+                write!(f, "Set attenuation to {} dB", cmd.attenuation.attenuation)
+            }
+            Command::GetAutoGainState(_) => write!(f, "Get auto-gain state"),
+            Command::SetAutoGainState(cmd) => write!(f, "{} auto-gain", enable(cmd.enabled)),
+            Command::GetMagnitude(_) => write!(f, "Get magnitude"),
+            Command::SetMagnitude(cmd) => {
+                write!(f, "Set magnitude to {}%", cmd.magnitude.percentage)
+            }
+            Command::GetISCPowerOutput(_) => write!(f, "Get ISC power output"),
+            Command::SetISCPowerOutput(cmd) => {
+                write!(f, "Set ISC power output to {} dBm", cmd.power_dbm.power)
+            }
+            Command::GetPWMDutyCycle(_) => write!(f, "Get PWM duty cycle"),
+            Command::SetPWMDutyCycle(cmd) => {
+                write!(f, "Set PWM duty cycle to {}%", cmd.duty_cycle.percentage)
+            }
+            Command::SetPWMFrequency(cmd) => {
+                write!(f, "Set PWM frequency to {} Hz", cmd.frequency.frequency)
+            }
+            Command::SetTimedRFEnable(cmd) => {
+                write!(f, "Enable RF output for {} μs", cmd.duration)
+            }
+            Command::GetSOAConfig(_) => write!(f, "Get SOA configuration"),
+            Command::SetSOAConfig(cmd) => write!(
+                f,
+                "Set SOA configuration (temperature: {}, reflection: {}, external watchdog: {}, dissipation: {})",
+                cmd.temp_enabled,
+                cmd.reflection_enabled,
+                cmd.external_watchdog_enabled,
+                cmd.dissipation_enabled
+            ),
+            Command::GetSOACurrentConfig(_) => write!(f, "Get SOA current limits"),
+            Command::SetSOACurrentConfig(cmd) => write!(
+                f,
+                "Set SOA current limits to {} A (high) and {} A (shutdown)",
+                cmd.high_current.current, cmd.shutdown_current.current
+            ),
+            Command::GetSOADissipationConfig(_) => write!(f, "Get SOA dissipation limits"),
+            Command::SetSOADissipationConfig(cmd) => write!(
+                f,
+                "Set SOA dissipation limits to {} W (high) and {} W (shutdown)",
+                cmd.high_dissipation.power, cmd.shutdown_dissipation.power
+            ),
+            Command::GetSOAForwardPowerLimits(_) => write!(f, "Get SOA forward power limits"),
+            Command::SetSOAForwardPowerLimits(cmd) => write!(
+                f,
+                "Set SOA forward power limits to {} W (high) and {} W (shutdown)",
+                cmd.high_forward_power.power, cmd.shutdown_forward_power.power
+            ),
+            Command::SetSOAGraceTimer(cmd) => {
+                write!(f, "Set SOA grace timer to {} ms", cmd.grace_period)
+            }
+            Command::GetSOAPowerConfig(_) => write!(f, "Get SOA reflected power limits"),
+            Command::SetSOAPowerConfig(cmd) => write!(
+                f,
+                "Set SOA reflected power limits to {} dBm (high) and {} dBm (shutdown)",
+                cmd.high_reflection.power, cmd.shutdown_reflection.power
+            ),
+            Command::GetSOATempConfig(_) => write!(f, "Get SOA temperature limits"),
+            Command::SetSOATempConfig(cmd) => write!(
+                f,
+                "Set SOA temperature limits to {} degC (high) and {} degC (shutdown)",
+                cmd.high_temp.temperature, cmd.shutdown_temp.temperature
+            ),
+            Command::GetSOAVoltageConfig(_) => write!(f, "Get SOA voltage limits"),
+            Command::SetSOAVoltageConfig(cmd) => write!(
+                f,
+                "Set SOA voltage limits to {} V (shutdown min), {} V (low), {} V (high) and {} V (shutdown max)",
+                cmd.shutdown_min_voltage.voltage,
+                cmd.low_voltage.voltage,
+                cmd.high_voltage.voltage,
+                cmd.shutdown_max_voltage.voltage
+            ),
+            Command::SetSOAWatchdogConfig(cmd) => {
+                write!(f, "{} SOA watchdog", enable(cmd.enabled))
+            }
+            Command::SetUartBaudRate(cmd) => {
+                write!(f, "Set UART baud rate to {}", cmd.baud_rate.baud_rate)
+            }
+            Command::GetChannelID(_) => write!(f, "Get channel ID"),
+            Command::SetChannelID(cmd) => {
+                write!(f, "Set channel ID to {}", cmd.new_channel.channel_id)
+            }
+            Command::GetClockSource(_) => write!(f, "Get clock source"),
+            Command::SetClockSource(cmd) => {
+                write!(f, "Set clock source to {:?}", cmd.clock_source)
+            }
+            Command::SetCommunicationInterface(cmd) => {
+                write!(f, "Set communication interface to {:?}", cmd.interface)
+            }
+            Command::GetPowerMaxDbm(_) => write!(f, "Get maximum power (dBm)"),
+            Command::SetPowerMaxDbm(cmd) => {
+                write!(f, "Set maximum power to {} dBm", cmd.max.power)
+            }
+            Command::GetPowerMinDbm(_) => write!(f, "Get minimum power (dBm)"),
+            Command::SetPowerMinDbm(cmd) => {
+                write!(f, "Set minimum power to {} dBm", cmd.min.power)
+            }
+            Command::GetPowerOffset(_) => write!(f, "Get power offset"),
+            Command::SetPowerOffset(cmd) => write!(f, "Set power offset to {} dB", cmd.offset),
+            Command::ResetSystem(_) => write!(f, "Reset system"),
+            Command::SetZHLTriggerDelay(cmd) => {
+                write!(f, "Set ZHL trigger delay to {} μs", cmd.delay)
+            }
+            Command::Raw(wire) => write!(f, "Send raw command {}", wire),
+            Command::Barrier => write!(f, "Queue barrier"),
+        }
+    }
+}
+
+impl From<Command> for String {
+    /// The exact string written to the signal generator for the command, without the line terminator.
+    fn from(command: Command) -> Self {
+        String::from(&command)
+    }
+}
+
+#[allow(deprecated)]
+impl From<&Command> for String {
+    /// Like the conversion of an owned `Command`, without giving up or cloning the command.
+    fn from(command: &Command) -> Self {
+        match command {
+            Command::GetPAPowerADC(command) => command.into(),
+            Command::GetPACurrent(command) => command.into(),
+            Command::GetPAPowerDBM(command) => command.into(),
+            Command::GetPAPowerWatt(command) => command.into(),
+            Command::GetFrequency(command) => command.into(),
+            Command::SetFrequency(command) => command.into(),
+            Command::GetRFOutput(command) => command.into(),
+            Command::SetRFOutput(command) => command.into(),
+            Command::GetPhase(command) => command.into(),
+            Command::SetPhase(command) => command.into(),
+            Command::GetPAPowerSetpointDBM(command) => command.into(),
+            Command::GetPAPowerSetpointWatt(command) => command.into(),
+            Command::SetPAPowerSetpointDBM(command) => command.into(),
+            Command::SetPAPowerSetpointWatt(command) => command.into(),
+            Command::GetPATemp(command) => command.into(),
+            Command::GetPAVoltage(command) => command.into(),
+            Command::GetDLLConfig(command) => command.into(),
+            Command::SetDLLConfig(command) => command.into(),
+            Command::GetDLLEnabled(command) => command.into(),
+            Command::SetDLLEnabled(command) => command.into(),
+            Command::PerformSweepDBM(command) => command.into(),
+            Command::PerformSweepWatt(command) => command.into(),
+            Command::ClearErrors(command) => command.into(),
+            Command::GetPAErrors(command) => command.into(),
+            Command::GetStatus(command) => command.into(),
+            Command::GetIdentity(command) => command.into(),
+            Command::GetISCTemp(command) => command.into(),
+            Command::GetUptime(command) => command.into(),
+            Command::GetVersion(command) => command.into(),
+            Command::GetAttenuation(command) => command.into(),
+            Command::SetAttenuation(command) => command.into(),
+            Command::GetAutoGainState(command) => command.into(),
+            Command::SetAutoGainState(command) => command.into(),
+            Command::GetMagnitude(command) => command.into(),
+            Command::SetMagnitude(command) => command.into(),
+            Command::GetISCPowerOutput(command) => command.into(),
+            Command::SetISCPowerOutput(command) => command.into(),
+            Command::GetPWMDutyCycle(command) => command.into(),
+            Command::SetPWMDutyCycle(command) => command.into(),
+            Command::SetPWMFrequency(command) => command.into(),
+            Command::SetTimedRFEnable(command) => command.into(),
+            Command::GetSOAConfig(command) => command.into(),
+            Command::SetSOAConfig(command) => command.into(),
+            Command::GetSOACurrentConfig(command) => command.into(),
+            Command::SetSOACurrentConfig(command) => command.into(),
+            Command::GetSOADissipationConfig(command) => command.into(),
+            Command::SetSOADissipationConfig(command) => command.into(),
+            Command::GetSOAForwardPowerLimits(command) => command.into(),
+            Command::SetSOAForwardPowerLimits(command) => command.into(),
+            Command::SetSOAGraceTimer(command) => command.into(),
+            Command::GetSOAPowerConfig(command) => command.into(),
+            Command::SetSOAPowerConfig(command) => command.into(),
+            Command::GetSOATempConfig(command) => command.into(),
+            Command::SetSOATempConfig(command) => command.into(),
+            Command::GetSOAVoltageConfig(command) => command.into(),
+            Command::SetSOAVoltageConfig(command) => command.into(),
+            Command::SetSOAWatchdogConfig(command) => command.into(),
+            Command::SetUartBaudRate(command) => command.into(),
+            Command::GetChannelID(command) => command.into(),
+            Command::SetChannelID(command) => command.into(),
+            Command::GetClockSource(command) => command.into(),
+            Command::SetClockSource(command) => command.into(),
+            Command::SetCommunicationInterface(command) => command.into(),
+            Command::GetPowerMaxDbm(command) => command.into(),
+            Command::SetPowerMaxDbm(command) => command.into(),
+            Command::GetPowerMinDbm(command) => command.into(),
+            Command::SetPowerMinDbm(command) => command.into(),
+            Command::GetPowerOffset(command) => command.into(),
+            Command::SetPowerOffset(command) => command.into(),
+            Command::ResetSystem(command) => command.into(),
+            Command::SetZHLTriggerDelay(command) => command.into(),
+            Command::Raw(wire) => wire.clone(),
+            // Never written to the signal generator.
+            Command::Barrier => String::new(),
+        }
+    }
+}
+
+/// Wraps each command struct in the `Command` variant of the same name,
+/// so it can be passed wherever an `impl Into<Command>` is accepted,
+/// and names each variant for `Command::name()`.
+///
+/// Each struct is also turned into its wire string by value, through the conversion of a reference.
+/// The commands listed as addressed carry the channel they are sent to, see `Command::channel()`.
+macro_rules! define_command_conversions {
+    (
+        addressed: [$($addressed:ident),+ $(,)?],
+        unaddressed: [$($unaddressed:ident),+ $(,)?] $(,)?
+    ) => {
+        $(
+            #[allow(deprecated)]
+            impl From<$addressed> for Command {
+                fn from(command: $addressed) -> Self {
+                    Command::$addressed(command)
+                }
+            }
+
+            #[allow(deprecated)]
+            impl From<$addressed> for String {
+                fn from(command: $addressed) -> Self {
+                    String::from(&command)
+                }
+            }
+        )+
+        $(
+            impl From<$unaddressed> for Command {
+                fn from(command: $unaddressed) -> Self {
+                    Command::$unaddressed(command)
+                }
+            }
+
+            impl From<$unaddressed> for String {
+                fn from(command: $unaddressed) -> Self {
+                    String::from(&command)
+                }
+            }
+        )+
+
+        impl Command {
+            /// The name of every variant, as returned by `name()`.
+            pub const NAMES: &'static [&'static str] = &[
+                $(stringify!($addressed),)+
+                $(stringify!($unaddressed),)+
+                "Raw",
+                "Barrier",
+            ];
+
+            /// The name of the command's variant, such as `"GetFrequency"`, whatever its arguments.
+            #[allow(deprecated)]
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(
+                        Command::$addressed(_) => stringify!($addressed),
+                    )+
+                    $(
+                        Command::$unaddressed(_) => stringify!($unaddressed),
+                    )+
+                    Command::Raw(_) => "Raw",
+                    Command::Barrier => "Barrier",
+                }
+            }
+
+            /// The channel the command is addressed to.
+            ///
+            /// `None` for commands that don't carry a channel, `GetChannelID`, `Raw` and `Barrier`.
+            #[allow(deprecated)]
+            pub fn channel(&self) -> Option<Channel> {
+                match self {
+                    $(
+                        Command::$addressed(command) => Some(command.channel.clone()),
+                    )+
+                    _ => None,
+                }
+            }
+
+            /// Readdresses the command to another channel.
+            ///
+            /// Commands that don't carry a channel are left as they are.
+            #[allow(deprecated)]
+            pub fn set_channel(&mut self, channel: Channel) {
+                match self {
+                    $(
+                        Command::$addressed(command) => command.channel = channel,
+                    )+
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+define_command_conversions! {
+    addressed: [
+        GetPAPowerADC,
+        GetPACurrent,
+        GetPAPowerDBM,
+        GetPAPowerWatt,
+        GetFrequency,
+        SetFrequency,
+        GetRFOutput,
+        SetRFOutput,
+        GetPhase,
+        SetPhase,
+        GetPAPowerSetpointDBM,
+        GetPAPowerSetpointWatt,
+        SetPAPowerSetpointDBM,
+        SetPAPowerSetpointWatt,
+        GetPATemp,
+        GetPAVoltage,
+        GetDLLConfig,
+        SetDLLConfig,
+        GetDLLEnabled,
+        SetDLLEnabled,
+        PerformSweepDBM,
+        PerformSweepWatt,
+        ClearErrors,
+        GetPAErrors,
+        GetStatus,
+        GetIdentity,
+        GetISCTemp,
+        GetUptime,
+        GetVersion,
+        GetAttenuation,
+        SetAttenuation,
+        GetAutoGainState,
+        SetAutoGainState,
+        GetMagnitude,
+        SetMagnitude,
+        GetISCPowerOutput,
+        SetISCPowerOutput,
+        GetPWMDutyCycle,
+        SetPWMDutyCycle,
+        SetPWMFrequency,
+        SetTimedRFEnable,
+        GetSOAConfig,
+        SetSOAConfig,
+        GetSOACurrentConfig,
+        SetSOACurrentConfig,
+        GetSOADissipationConfig,
+        SetSOADissipationConfig,
+        GetSOAForwardPowerLimits,
+        SetSOAForwardPowerLimits,
+        SetSOAGraceTimer,
+        GetSOAPowerConfig,
+        SetSOAPowerConfig,
+        GetSOATempConfig,
+        SetSOATempConfig,
+        GetSOAVoltageConfig,
+        SetSOAVoltageConfig,
+        SetSOAWatchdogConfig,
+        SetUartBaudRate,
+        SetChannelID,
+        GetClockSource,
+        SetClockSource,
+        SetCommunicationInterface,
+        GetPowerMaxDbm,
+        SetPowerMaxDbm,
+        GetPowerMinDbm,
+        SetPowerMinDbm,
+        GetPowerOffset,
+        SetPowerOffset,
+        ResetSystem,
+        SetZHLTriggerDelay,
+    ],
+    unaddressed: [GetChannelID],
+}
+
+/// How long a sweep may take to respond, as every point is measured before replying.
+const SWEEP_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a reset may take to respond, as the ISC board reboots first.
+const RESET_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl Command {
+    /// The time this command is allowed to take by default, if it is known to respond slowly.
+    ///
+    /// Returns `None` for commands that respond within the connection timeout.
+    pub fn default_timeout(&self) -> Option<Duration> {
+        match self {
+            Command::PerformSweepDBM(_) | Command::PerformSweepWatt(_) => Some(SWEEP_TIMEOUT),
+            Command::ResetSystem(_) => Some(RESET_TIMEOUT),
+            _ => None,
+        }
+    }
+
+    /// Whether the signal generator replies to the command.
+    ///
+    /// Commands without a reply are written without waiting for one and answered with
+    /// `Response::Ack`, rather than running into the response timeout. Of the documented
+    /// commands only `SetUartBaudRate` doesn't reply, as the device switches rates straight away.
+    /// `Raw` is assumed to reply, and `Barrier` is answered by the queue without being sent.
+    #[allow(deprecated)]
+    pub fn expects_response(&self) -> bool {
+        !matches!(self, Command::SetUartBaudRate(_))
+    }
+
+    /// Whether the command only reads from the signal generator, so sending it twice is harmless.
+    #[allow(deprecated)]
+    pub fn is_query(&self) -> bool {
+        matches!(
+            self,
+            Command::GetPAPowerADC(_)
+                | Command::GetPACurrent(_)
+                | Command::GetPAPowerDBM(_)
+                | Command::GetPAPowerWatt(_)
+                | Command::GetFrequency(_)
+                | Command::GetRFOutput(_)
+                | Command::GetPhase(_)
+                | Command::GetPAPowerSetpointDBM(_)
+                | Command::GetPAPowerSetpointWatt(_)
+                | Command::GetPATemp(_)
+                | Command::GetPAVoltage(_)
+                | Command::GetDLLConfig(_)
+                | Command::GetDLLEnabled(_)
+                | Command::GetPAErrors(_)
+                | Command::GetStatus(_)
+                | Command::GetIdentity(_)
+                | Command::GetISCTemp(_)
+                | Command::GetUptime(_)
+                | Command::GetVersion(_)
+                | Command::GetAttenuation(_)
+                | Command::GetAutoGainState(_)
+                | Command::GetMagnitude(_)
+                | Command::GetISCPowerOutput(_)
+                | Command::GetPWMDutyCycle(_)
+                | Command::GetSOAConfig(_)
+                | Command::GetSOACurrentConfig(_)
+                | Command::GetSOADissipationConfig(_)
+                | Command::GetSOAForwardPowerLimits(_)
+                | Command::GetSOAPowerConfig(_)
+                | Command::GetSOATempConfig(_)
+                | Command::GetSOAVoltageConfig(_)
+                | Command::GetChannelID(_)
+                | Command::GetClockSource(_)
+                | Command::GetPowerMaxDbm(_)
+                | Command::GetPowerMinDbm(_)
+                | Command::GetPowerOffset(_)
+        )
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Message {
     pub priority: Priority,
     pub command: Command,
+    /// Overrides the time allowed for the response to this command alone.
+    ///
+    /// When `None` the command's default timeout is used, falling back to the connection timeout.
+    #[serde(default)]
+    pub timeout: Option<Duration>,
+    /// A label of the caller's choosing, returned on the response so subscribers can filter on it.
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Sends the command to this channel instead of the one it was built with.
+    ///
+    /// Every command carries the channel it is addressed to as its first argument, such as the
+    /// `2` of `$FCG,2`. Boards sharing a bus, such as a daisy chain, each answer to the channel
+    /// set with `SetChannelID`, so there's no active channel to switch between commands.
+    /// Commands that don't carry a channel, `GetChannelID` and `Raw`, are sent as they are.
+    #[serde(default)]
+    pub channel: Option<Channel>,
+}
+
+impl Message {
+    pub fn new(priority: Priority, command: impl Into<Command>) -> Self {
+        Self {
+            priority,
+            command: command.into(),
+            timeout: None,
+            tag: None,
+            channel: None,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    pub fn with_channel(mut self, channel: Channel) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// The command as it is sent, readdressed to the channel of the message if one is set.
+    pub fn addressed_command(&self) -> Command {
+        let mut command = self.command.clone();
+        if let Some(channel) = &self.channel {
+            command.set_channel(channel.clone());
+        }
+        command
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -3,7 +3,7 @@ use std::{error::Error, fmt};
 
 use crate::command::Command;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum MWError {
     /// Error code is reserved.
     Reserved,
@@ -29,6 +29,92 @@ pub enum MWError {
     Unknown,
     /// An error occurred parsing the response to the given command.
     FailedParseResponse,
+    /// A field of the response wasn't a valid value, such as a number out of range for its
+    /// type, or a `NaN`. Carries the name of the field and the whole response.
+    ///
+    /// No reply makes a response panic, however malformed:
+    ///
+    /// ```
+    /// use minicircuit_commands::{basic::temperature::GetPATempResponse, prelude::*};
+    ///
+    /// let replies = [
+    ///     "", ",", "OK", "OK,1", "OK,1,", "OK,1,,", "OK,1,abc", "OK,1,99999999999999999999",
+    ///     "OK,1,NaN", "OK,1,inf", "OK,1,1e40", "OK,1,2450,1,2", "OK,1,\u{fffd}",
+    /// ];
+    ///
+    /// for reply in replies {
+    ///     let reply = reply.to_string();
+    ///     assert!(GetFrequencyResponse::try_from(reply.clone()).is_err());
+    ///     assert!(GetPAPowerWattResponse::try_from(reply.clone()).is_err());
+    ///     assert!(GetPATempResponse::try_from(reply.clone()).is_err());
+    ///     assert!(GetPhaseResponse::try_from(reply.clone()).is_err());
+    ///     assert!(GetUptimeResponse::try_from(reply).is_err());
+    /// }
+    ///
+    /// // Negative for a field that can't be.
+    /// assert!(GetFrequencyResponse::try_from("OK,1,-1".to_string()).is_err());
+    ///
+    /// let error = GetPATempResponse::try_from("OK,1,300".to_string()).unwrap_err();
+    /// assert_eq!(
+    ///     error,
+    ///     MWError::InvalidField {
+    ///         field: "temperature".to_string(),
+    ///         raw: "OK,1,300".to_string(),
+    ///     }
+    /// );
+    /// ```
+    InvalidField { field: String, raw: String },
+}
+
+impl MWError {
+    /// Whether the response couldn't be understood, rather than the device reporting an error.
+    pub fn is_parse_failure(&self) -> bool {
+        matches!(self, Self::FailedParseResponse | Self::InvalidField { .. })
+    }
+}
+
+/// A numeric field of a response, see `parse_field`.
+pub(crate) trait FieldValue: Sized {
+    fn parse_value(value: &str) -> Option<Self>;
+}
+
+macro_rules! integer_field {
+    ($($integer:ty),*) => {
+        $(
+            impl FieldValue for $integer {
+                // Some firmware sends integer fields with a fraction, such as `2450.0`.
+                fn parse_value(value: &str) -> Option<Self> {
+                    value.split('.').next()?.trim().parse().ok()
+                }
+            }
+        )*
+    };
+}
+
+integer_field!(u8, u16, u32, u64, i16);
+
+impl FieldValue for f32 {
+    // `NaN` and the infinities parse, but no measurement or setting takes them.
+    fn parse_value(value: &str) -> Option<Self> {
+        value
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+    }
+}
+
+/// Parses a field of a response, failing with `MWError::InvalidField` if it isn't a valid
+/// value of its type, such as a number that overflows it.
+pub(crate) fn parse_field<T: FieldValue>(
+    field: &str,
+    value: &str,
+    response: &str,
+) -> Result<T, MWError> {
+    T::parse_value(value).ok_or_else(|| MWError::InvalidField {
+        field: field.to_string(),
+        raw: response.to_string(),
+    })
 }
 
 impl From<String> for MWError {
@@ -76,7 +162,7 @@ impl Error for MWError {
 
 impl fmt::Display for MWError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
+        match self {
             Self::Reserved => write!(f, "Reserved error."),
             Self::MaxLengthExceeded => write!(f, "The serial message exceeded the maximum length."),
             Self::TooFewArgs => write!(f, "The serial message had too few arguments."),
@@ -107,25 +193,190 @@ impl fmt::Display for MWError {
                 f,
                 "An error occurred parsing the response to the given command."
             ),
+            Self::InvalidField { field, raw } => {
+                write!(
+                    f,
+                    "The {} field of the response {:?} is invalid.",
+                    field, raw
+                )
+            }
+        }
+    }
+}
+
+/// The cause of a failed exchange with the signal generator, to branch on without reading
+/// the description.
+///
+/// A slow device and a bad connection are told apart by whether the device answered at all:
+///
+/// - `Busy`: the device answered `ERR06`, returned as `Response::Busy`. The command wasn't
+///   carried out, so the queue resends it after `TargetProperties::busy_retry_delay`.
+/// - `Timeout`: nothing came back, the host gave up waiting. Queries are resent with backoff,
+///   see `TargetProperties::max_retries`.
+/// - `PortClosed`: the port is gone, which the queue reconnects for when
+///   `TargetProperties::auto_reconnect` is set.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub enum ReadWriteErrorKind {
+    /// No complete response arrived in time. The device may never have received the command,
+    /// such as with a loose cable or a mismatched baud rate.
+    Timeout,
+    /// The signal generator answered that it was busy and didn't carry out the command.
+    Busy,
+    /// The port or connection is gone, the device was most likely unplugged.
+    PortClosed,
+    /// Any other failure of the port or connection.
+    #[default]
+    Io,
+    /// The response arrived but couldn't be understood.
+    Parse,
+    /// The signal generator reported an error.
+    DeviceError { code: MWError },
+}
+
+impl ReadWriteErrorKind {
+    /// Whether sending the command again could succeed without anything else changing.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Timeout | Self::Busy | Self::Io | Self::Parse)
+    }
+}
+
+impl From<&serialport::Error> for ReadWriteErrorKind {
+    fn from(error: &serialport::Error) -> Self {
+        match error.kind {
+            serialport::ErrorKind::NoDevice => Self::PortClosed,
+            serialport::ErrorKind::Io(kind) => match kind {
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => Self::Timeout,
+                std::io::ErrorKind::UnexpectedEof
+                | std::io::ErrorKind::BrokenPipe
+                | std::io::ErrorKind::ConnectionReset
+                | std::io::ErrorKind::ConnectionAborted
+                | std::io::ErrorKind::NotConnected => Self::PortClosed,
+                _ => Self::Io,
+            },
+            _ => Self::Io,
+        }
+    }
+}
+
+impl From<MWError> for ReadWriteErrorKind {
+    fn from(error: MWError) -> Self {
+        match error {
+            MWError::FailedParseResponse | MWError::InvalidField { .. } => Self::Parse,
+            MWError::SystemBusy => Self::Busy,
+            code => Self::DeviceError { code },
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl fmt::Display for ReadWriteErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout => write!(f, "timed out"),
+            Self::Busy => write!(f, "device busy"),
+            Self::PortClosed => write!(f, "port closed"),
+            Self::Io => write!(f, "I/O error"),
+            Self::Parse => write!(f, "unparseable response"),
+            Self::DeviceError { code } => write!(f, "device error ({})", code),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ReadWriteError {
     /// The command the error is associated with.
     pub command: Command,
-    // pub error_kind: ErrorKind,
+    /// The cause of the error.
+    #[serde(default)]
+    pub kind: ReadWriteErrorKind,
     /// A description of the error.
     pub description: String,
+    /// How many times the command was resent before giving up.
+    #[serde(default)]
+    pub retries: u32,
 }
 
 impl ReadWriteError {
-    pub fn new(command: Command, description: String) -> Self {
+    pub fn new(command: Command, kind: ReadWriteErrorKind, description: String) -> Self {
         Self {
             command,
-            // error_kind,
+            kind,
             description,
+            retries: 0,
+        }
+    }
+
+    /// Describes the port error that stopped the command from going through.
+    pub fn from_port_error(command: Command, error: serialport::Error) -> Self {
+        Self::new(command, (&error).into(), error.description)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// Raised when a command is built with parameters the signal generator would reject,
+/// so the mistake is caught before a serial round-trip.
+pub enum ValidationError {
+    /// The start of a range lies above its end.
+    InvertedRange { start: f64, stop: f64 },
+    /// The step size of a range must be greater than zero.
+    NonPositiveStep,
+    /// Power of zero watts or less can't be expressed in dBm.
+    NonPositivePower { watt: f64 },
+    /// A parameter lies outside of the range it is allowed to take (inclusive).
+    OutOfRange {
+        parameter: String,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+    /// No command goes by the name, see `Command::NAMES`.
+    UnknownCommand { name: String },
+}
+
+impl ValidationError {
+    /// Returns `OutOfRange` if the value lies outside of `min` - `max` (inclusive) or isn't a number.
+    pub(crate) fn check_range(parameter: &str, value: f64, min: f64, max: f64) -> Result<(), Self> {
+        if (min..=max).contains(&value) {
+            return Ok(());
+        }
+
+        Err(Self::OutOfRange {
+            parameter: parameter.to_string(),
+            value,
+            min,
+            max,
+        })
+    }
+}
+
+impl Error for ValidationError {}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvertedRange { start, stop } => {
+                write!(
+                    f,
+                    "The start ({}) must not exceed the stop ({}).",
+                    start, stop
+                )
+            }
+            Self::NonPositiveStep => write!(f, "The step size must be greater than zero."),
+            Self::NonPositivePower { watt } => write!(
+                f,
+                "Power of {}W can't be converted to dBm, it must be greater than zero.",
+                watt
+            ),
+            Self::OutOfRange {
+                parameter,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "The {} of {} is outside of the allowed range ({} - {}).",
+                parameter, value, min, max
+            ),
+            Self::UnknownCommand { name } => write!(f, "There is no command named {}.", name),
         }
     }
 }
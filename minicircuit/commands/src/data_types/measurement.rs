@@ -0,0 +1,55 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The unit a `Measurement` is given in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Megahertz,
+    Degrees,
+    Watt,
+    Dbm,
+    Celsius,
+    Ampere,
+    Volt,
+    Second,
+}
+
+impl Unit {
+    /// The symbol of the unit, such as `MHz` or `dBm`.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Megahertz => "MHz",
+            Unit::Degrees => "deg",
+            Unit::Watt => "W",
+            Unit::Dbm => "dBm",
+            Unit::Celsius => "degC",
+            Unit::Ampere => "A",
+            Unit::Volt => "V",
+            Unit::Second => "s",
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// A single measured value, see `Response::to_measurements`.
+///
+/// The name is a snake case field name such as `forward_power`, which doesn't include the
+/// unit, so the same quantity read in watts and in dBm shares its name.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    pub name: &'static str,
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Measurement {
+    pub fn new(name: &'static str, value: f64, unit: Unit) -> Self {
+        Self { name, value, unit }
+    }
+}
@@ -1,2 +1,4 @@
 pub mod errors;
+pub mod measurement;
+pub mod power;
 pub mod types;
@@ -0,0 +1,126 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    errors::ValidationError,
+    types::{Dbm, Watt},
+};
+
+/// Converts a power level in dBm to watts. 0 dBm is 1 mW and 30 dBm is 1 W.
+///
+/// ```
+/// use minicircuit_commands::data_types::power::{dbm_to_watt, watt_to_dbm};
+///
+/// assert_eq!(dbm_to_watt(0.0), 0.001);
+/// assert_eq!(dbm_to_watt(30.0), 1.0);
+///
+/// // Converting back gives the level that was started from.
+/// for dbm in [-10.0, 0.0, 17.5, 30.0, 50.0] {
+///     assert!((watt_to_dbm(dbm_to_watt(dbm)).unwrap() - dbm).abs() < 1e-9);
+/// }
+/// ```
+pub fn dbm_to_watt(dbm: f64) -> f64 {
+    10.0_f64.powf(dbm / 10.0) / 1000.0
+}
+
+/// Converts a power level in watts to dBm.
+///
+/// Fails for zero or negative power, which has no representation in dBm.
+///
+/// ```
+/// use minicircuit_commands::data_types::{errors::ValidationError, power::watt_to_dbm};
+///
+/// assert_eq!(watt_to_dbm(1.0), Ok(30.0));
+/// assert_eq!(watt_to_dbm(0.001), Ok(0.0));
+///
+/// // Rather than `-inf` or NaN.
+/// for watt in [0.0, -1.0, f64::NAN] {
+///     assert!(matches!(
+///         watt_to_dbm(watt),
+///         Err(ValidationError::NonPositivePower { .. })
+///     ));
+/// }
+/// ```
+pub fn watt_to_dbm(watt: f64) -> Result<f64, ValidationError> {
+    if watt <= 0.0 || watt.is_nan() {
+        return Err(ValidationError::NonPositivePower { watt });
+    }
+
+    Ok(10.0 * (watt * 1000.0).log10())
+}
+
+/// A power level kept in the unit it was given in and converted only when the other unit is asked for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Power {
+    Watt(f64),
+    Dbm(f64),
+}
+
+/// The unit a power level is given or asked for in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerUnit {
+    Watt,
+    Dbm,
+}
+
+impl Power {
+    pub fn from_watt(watt: f64) -> Self {
+        Self::Watt(watt)
+    }
+
+    pub fn from_dbm(dbm: f64) -> Self {
+        Self::Dbm(dbm)
+    }
+
+    /// The unit the power level was given in.
+    pub fn unit(&self) -> PowerUnit {
+        match self {
+            Self::Watt(_) => PowerUnit::Watt,
+            Self::Dbm(_) => PowerUnit::Dbm,
+        }
+    }
+
+    /// The power level in watts.
+    pub fn watt(&self) -> f64 {
+        match *self {
+            Self::Watt(watt) => watt,
+            Self::Dbm(dbm) => dbm_to_watt(dbm),
+        }
+    }
+
+    /// The power level in dBm, failing if it was given as zero or negative watts.
+    pub fn dbm(&self) -> Result<f64, ValidationError> {
+        match *self {
+            Self::Watt(watt) => watt_to_dbm(watt),
+            Self::Dbm(dbm) => Ok(dbm),
+        }
+    }
+}
+
+impl fmt::Display for Power {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Watt(watt) => write!(f, "{:.1}W", watt),
+            Self::Dbm(dbm) => write!(f, "{:.1}dBm", dbm),
+        }
+    }
+}
+
+impl From<Watt> for Power {
+    fn from(watt: Watt) -> Self {
+        Self::Watt(watt.power as f64)
+    }
+}
+
+impl From<Dbm> for Power {
+    fn from(dbm: Dbm) -> Self {
+        Self::Dbm(dbm.power as f64)
+    }
+}
+
+impl From<Power> for Watt {
+    fn from(power: Power) -> Self {
+        Watt::new(power.watt() as f32)
+    }
+}
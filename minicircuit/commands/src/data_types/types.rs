@@ -5,6 +5,8 @@ use std::fmt::{Display, Formatter, Result};
 use std::ops::{Add, Div, Mul, Sub};
 use std::str::FromStr;
 
+use super::errors::ValidationError;
+
 // --------------------------------------------------------------- //
 //                                                                 //
 // --------------------------Frequency---------------------------- //
@@ -21,6 +23,29 @@ impl Frequency {
     pub fn new(frequency: u16) -> Self {
         Self { frequency }
     }
+
+    /// Converts a frequency in Hz, rounded to the nearest MHz.
+    ///
+    /// Fails if it doesn't fit in the 0 - 65535 MHz the signal generator takes.
+    ///
+    /// ```
+    /// use minicircuit_commands::data_types::types::Frequency;
+    ///
+    /// let frequency = Frequency::from_hz(2_450_200_000.).unwrap();
+    /// assert_eq!(frequency, Frequency::new(2450));
+    /// assert_eq!(frequency.to_hz(), 2_450_000_000);
+    /// assert!(Frequency::from_hz(-1e6).is_err());
+    /// ```
+    pub fn from_hz(hz: f64) -> std::result::Result<Self, ValidationError> {
+        let mhz = (hz / 1e6).round();
+        ValidationError::check_range("frequency (MHz)", mhz, 0., u16::MAX as f64)?;
+
+        Ok(Self::new(mhz as u16))
+    }
+
+    pub fn to_hz(&self) -> u64 {
+        self.frequency as u64 * 1_000_000
+    }
 }
 
 impl FromStr for Frequency {
@@ -113,6 +138,11 @@ impl Watt {
         Self { power }
     }
 }
+impl From<f32> for Watt {
+    fn from(power: f32) -> Self {
+        Self::new(power)
+    }
+}
 impl Into<f32> for Watt {
     fn into(self) -> f32 {
         self.power
@@ -127,7 +157,7 @@ impl From<Dbm> for Watt {
     fn from(dbm_value: Dbm) -> Watt {
         let dbm_value: f32 = dbm_value.into();
 
-        let converted = (10.0_f32.powf(dbm_value / 10.0_f32)) / 1000.0_f32;
+        let converted = super::power::dbm_to_watt(dbm_value as f64) as f32;
 
         Watt::new(converted)
     }
@@ -198,6 +228,11 @@ impl Dbm {
         Self { power }
     }
 }
+impl From<f32> for Dbm {
+    fn from(power: f32) -> Self {
+        Self::new(power)
+    }
+}
 impl Into<f32> for Dbm {
     fn into(self) -> f32 {
         self.power
@@ -303,16 +338,83 @@ impl Display for Volts {
 // --------------------------Temperature-------------------------- //
 //                                                                 //
 // --------------------------------------------------------------- //
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "stores", derive(Patch, Store))]
 /// Units of degC.
 pub struct Temperature {
     pub temperature: u8,
 }
 impl Temperature {
+    /// The highest temperature a reading or SOA threshold plausibly takes, in degC.
+    pub const MAX_PLAUSIBLE: u8 = 125;
+
+    /// Creates a new temperature in units of degC.
     pub fn new(temperature: u8) -> Self {
         Self { temperature }
     }
+
+    /// Like `new`, but fails above `MAX_PLAUSIBLE`, which hints at a value in another unit.
+    pub fn try_new(temperature: u8) -> std::result::Result<Self, ValidationError> {
+        ValidationError::check_range(
+            "temperature (degC)",
+            temperature as f64,
+            0.,
+            Self::MAX_PLAUSIBLE as f64,
+        )?;
+        Ok(Self::new(temperature))
+    }
+
+    /// Converts a temperature in degF, rounded to the nearest degC.
+    ///
+    /// Fails outside of 0 degC - `MAX_PLAUSIBLE`.
+    ///
+    /// ```
+    /// use minicircuit_commands::data_types::types::Temperature;
+    ///
+    /// let threshold = Temperature::from_fahrenheit(131.).unwrap();
+    /// assert_eq!(threshold, Temperature::new(55));
+    /// assert_eq!(threshold.as_fahrenheit(), 131.);
+    /// assert_eq!(threshold.as_kelvin(), 328.15);
+    /// assert!(Temperature::from_fahrenheit(500.).is_err());
+    /// ```
+    pub fn from_fahrenheit(fahrenheit: f64) -> std::result::Result<Self, ValidationError> {
+        Self::from_celsius((fahrenheit - 32.) * 5. / 9.)
+    }
+
+    /// Converts a temperature in K, rounded to the nearest degC.
+    ///
+    /// Fails outside of 0 degC - `MAX_PLAUSIBLE`.
+    pub fn from_kelvin(kelvin: f64) -> std::result::Result<Self, ValidationError> {
+        Self::from_celsius(kelvin - 273.15)
+    }
+
+    fn from_celsius(celsius: f64) -> std::result::Result<Self, ValidationError> {
+        let celsius = celsius.round();
+        ValidationError::check_range(
+            "temperature (degC)",
+            celsius,
+            0.,
+            Self::MAX_PLAUSIBLE as f64,
+        )?;
+        Ok(Self::new(celsius as u8))
+    }
+
+    pub fn as_celsius(&self) -> f64 {
+        self.temperature as f64
+    }
+
+    pub fn as_fahrenheit(&self) -> f64 {
+        self.as_celsius() * 9. / 5. + 32.
+    }
+
+    pub fn as_kelvin(&self) -> f64 {
+        self.as_celsius() + 273.15
+    }
+}
+impl From<u8> for Temperature {
+    fn from(temperature: u8) -> Self {
+        Self::new(temperature)
+    }
 }
 impl Into<u8> for Temperature {
     fn into(self) -> u8 {
@@ -372,6 +474,36 @@ impl Phase {
     pub fn new(phase: i16) -> Self {
         Self { phase }
     }
+
+    /// Creates a new phase operator in units of degrees, failing outside of the 0 - 359 degrees
+    /// the signal generator takes.
+    pub fn try_new(phase: i16) -> std::result::Result<Self, ValidationError> {
+        ValidationError::check_range("phase", phase as f64, 0., 359.)?;
+        Ok(Self::new(phase))
+    }
+
+    /// Converts an angle in radians, rounded to the nearest degree and wrapped into 0 - 359 degrees.
+    ///
+    /// ```
+    /// use minicircuit_commands::data_types::types::Phase;
+    ///
+    /// assert_eq!(Phase::from_radians(std::f64::consts::FRAC_PI_2), Phase::new(90));
+    /// assert_eq!(Phase::from_radians(-std::f64::consts::FRAC_PI_2), Phase::new(270));
+    /// ```
+    pub fn from_radians(radians: f64) -> Self {
+        let degrees = radians.to_degrees().round().rem_euclid(360.);
+        // Rounding can land on 360 itself, which is the same angle as 0.
+        Self::new(degrees as i16 % 360)
+    }
+
+    pub fn to_radians(&self) -> f64 {
+        (self.phase as f64).to_radians()
+    }
+}
+impl From<i16> for Phase {
+    fn from(phase: i16) -> Self {
+        Self::new(phase)
+    }
 }
 impl Into<i16> for Phase {
     fn into(self) -> i16 {
@@ -442,7 +574,9 @@ impl Into<f32> for Attenuation {
 }
 impl Display for Attenuation {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{:.1}", self.attenuation)
+        // Quarter steps need a second decimal to survive the trip to the signal generator.
+        let precision = if (self.attenuation * 2.).fract() == 0. { 1 } else { 2 };
+        write!(f, "{:.*}", precision, self.attenuation)
     }
 }
 
@@ -462,6 +596,17 @@ impl Percentage {
             percentage: percentage.clamp(0, 100),
         }
     }
+
+    /// Like `new`, but fails above 100 % instead of clamping.
+    pub fn try_new(percentage: u8) -> std::result::Result<Self, ValidationError> {
+        ValidationError::check_range("percentage", percentage as f64, 0., 100.)?;
+        Ok(Self::new(percentage))
+    }
+}
+impl From<u8> for Percentage {
+    fn from(percentage: u8) -> Self {
+        Self::new(percentage)
+    }
 }
 impl Into<u8> for Percentage {
     fn into(self) -> u8 {
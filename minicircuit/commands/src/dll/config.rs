@@ -1,11 +1,76 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError, ValidationError},
     types::{Channel, Frequency, MainDelay, Threshold},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// The parameters of the DLL mode, as set by `SetDLLConfig` and reported by `GetDLLConfig`.
+pub struct DLLConfig {
+    /// The lower boundary of the bandwidth for DLL in MHz.
+    pub lower_frequency: Frequency,
+    /// The upper boundary of the bandwidth for DLL in MHz.
+    pub upper_frequency: Frequency,
+    /// The frequency at which the DLL starts it's activities in MHz.
+    pub start_frequency: Frequency,
+    /// The step size of the DLL in MHz.
+    pub step_frequency: Frequency,
+    /// The match/efficiency threshold in dB to be met before DLL latches onto a frequency.
+    pub threshold: Threshold,
+    /// The delay between complete runs of the DLL in ms.
+    pub main_delay: MainDelay,
+}
+
+impl DLLConfig {
+    /// Checks the parameters make sense together.
+    ///
+    /// The bandwidth can't be inverted, the start frequency has to lie within it,
+    /// the step has to be greater than zero and fit within the bandwidth,
+    /// and the threshold can't be negative.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let lower = self.lower_frequency.frequency as f64;
+        let upper = self.upper_frequency.frequency as f64;
+
+        if lower > upper {
+            return Err(ValidationError::InvertedRange {
+                start: lower,
+                stop: upper,
+            });
+        }
+        if self.step_frequency.frequency == 0 {
+            return Err(ValidationError::NonPositiveStep);
+        }
+
+        ValidationError::check_range(
+            "DLL start frequency (MHz)",
+            self.start_frequency.frequency as f64,
+            lower,
+            upper,
+        )?;
+        ValidationError::check_range(
+            "DLL step frequency (MHz)",
+            self.step_frequency.frequency as f64,
+            1.,
+            (upper - lower).max(1.),
+        )?;
+        ValidationError::check_range(
+            "DLL threshold (dB)",
+            self.threshold.threshold as f64,
+            0.,
+            f64::MAX,
+        )
+    }
+}
+
+impl Default for DLLConfig {
+    /// Returns the parameters `SetDLLConfig::default()` sets.
+    fn default() -> Self {
+        SetDLLConfig::default().config()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetDLLConfigResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -43,17 +108,17 @@ pub struct SetDLLConfig {
     pub main_delay: MainDelay,
 }
 
-impl Into<String> for SetDLLConfig {
-    fn into(self) -> String {
+impl From<&SetDLLConfig> for String {
+    fn from(command: &SetDLLConfig) -> String {
         format!(
             "$DLCS,{},{},{},{},{},{},{}",
-            self.channel,
-            self.lower_frequency,
-            self.upper_frequency,
-            self.start_frequency,
-            self.step_frequency,
-            self.threshold,
-            self.main_delay
+            command.channel,
+            command.lower_frequency,
+            command.upper_frequency,
+            command.start_frequency,
+            command.step_frequency,
+            command.threshold,
+            command.main_delay
         )
     }
 }
@@ -81,6 +146,50 @@ impl SetDLLConfig {
     }
 }
 
+impl SetDLLConfig {
+    /// Returns a handler to call the command, if the parameters pass `DLLConfig::validate`.
+    ///
+    /// Setting the config a `GetDLLConfig` reported reproduces it on the wire:
+    ///
+    /// ```
+    /// use minicircuit_commands::{
+    ///     data_types::types::Channel,
+    ///     dll::config::{GetDLLConfigResponse, SetDLLConfig},
+    /// };
+    ///
+    /// let response =
+    ///     GetDLLConfigResponse::try_from("OK,1,2400,2500,2410,5,0.5,25".to_string()).unwrap();
+    /// let command = SetDLLConfig::from_config(Channel::default(), response.config()).unwrap();
+    ///
+    /// assert_eq!(String::from(&command), "$DLCS,1,2400,2500,2410,5,0.5,25");
+    /// ```
+    pub fn from_config(channel: Channel, config: DLLConfig) -> Result<Self, ValidationError> {
+        config.validate()?;
+
+        Ok(Self::new(
+            channel,
+            config.lower_frequency,
+            config.upper_frequency,
+            config.start_frequency,
+            config.step_frequency,
+            config.threshold,
+            config.main_delay,
+        ))
+    }
+
+    /// The parameters the command sets.
+    pub fn config(&self) -> DLLConfig {
+        DLLConfig {
+            lower_frequency: self.lower_frequency,
+            upper_frequency: self.upper_frequency,
+            start_frequency: self.start_frequency,
+            step_frequency: self.step_frequency,
+            threshold: self.threshold.clone(),
+            main_delay: self.main_delay.clone(),
+        }
+    }
+}
+
 impl Default for SetDLLConfig {
     /// Returns the default handler to call the command.
     ///
@@ -126,6 +235,20 @@ pub struct GetDLLConfigResponse {
     pub main_delay: MainDelay,
 }
 
+impl GetDLLConfigResponse {
+    /// The reported parameters, in the form `SetDLLConfig::from_config` takes.
+    pub fn config(&self) -> DLLConfig {
+        DLLConfig {
+            lower_frequency: self.lower_frequency,
+            upper_frequency: self.upper_frequency,
+            start_frequency: self.start_frequency,
+            step_frequency: self.step_frequency,
+            threshold: self.threshold.clone(),
+            main_delay: self.main_delay.clone(),
+        }
+    }
+}
+
 impl TryFrom<String> for GetDLLConfigResponse {
     type Error = MWError;
 
@@ -144,57 +267,16 @@ impl TryFrom<String> for GetDLLConfigResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let lower_frequency: Frequency = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u16>()
-        {
-            Ok(value) => Frequency::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let upper_frequency: Frequency = match parts[3].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u16>()
-        {
-            Ok(value) => Frequency::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let start_frequency: Frequency = match parts[4].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u16>()
-        {
-            Ok(value) => Frequency::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let step_frequency: Frequency = match parts[5].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u16>()
-        {
-            Ok(value) => Frequency::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let threshold: Threshold = match parts[6].trim().parse::<f32>() {
-            Ok(value) => Threshold::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let main_delay: MainDelay = match parts[7].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u16>()
-        {
-            Ok(value) => MainDelay::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let lower_frequency: Frequency =
+            Frequency::new(parse_field("lower_frequency", parts[2], &response)?);
+        let upper_frequency: Frequency =
+            Frequency::new(parse_field("upper_frequency", parts[3], &response)?);
+        let start_frequency: Frequency =
+            Frequency::new(parse_field("start_frequency", parts[4], &response)?);
+        let step_frequency: Frequency =
+            Frequency::new(parse_field("step_frequency", parts[5], &response)?);
+        let threshold: Threshold = Threshold::new(parse_field("threshold", parts[6], &response)?);
+        let main_delay: MainDelay = MainDelay::new(parse_field("main_delay", parts[7], &response)?);
 
         Ok(GetDLLConfigResponse {
             lower_frequency,
@@ -214,9 +296,9 @@ pub struct GetDLLConfig {
     pub channel: Channel,
 }
 
-impl Into<String> for GetDLLConfig {
-    fn into(self) -> String {
-        format!("$DLCG,{}", self.channel)
+impl From<&GetDLLConfig> for String {
+    fn from(command: &GetDLLConfig) -> String {
+        format!("$DLCG,{}", command.channel)
     }
 }
 
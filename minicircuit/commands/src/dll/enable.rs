@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-use crate::data_types::{errors::MWError, types::Channel};
+use crate::data_types::{
+    errors::{parse_field, MWError},
+    types::Channel,
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetDLLEnabledResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -37,13 +40,13 @@ pub struct SetDLLEnabled {
     pub enabled: bool,
 }
 
-impl Into<String> for SetDLLEnabled {
-    fn into(self) -> String {
-        let numeric_value = match self.enabled {
+impl From<&SetDLLEnabled> for String {
+    fn from(command: &SetDLLEnabled) -> String {
+        let numeric_value = match command.enabled {
             true => 1,
             false => 0,
         };
-        format!("$DLES,{},{}", self.channel, numeric_value)
+        format!("$DLES,{},{}", command.channel, numeric_value)
     }
 }
 
@@ -89,18 +92,7 @@ impl TryFrom<String> for GetDLLEnabledResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let enabled: bool = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u8>()
-        {
-            Ok(value) => match value {
-                1 => true,
-                _ => false,
-            },
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let enabled: bool = parse_field::<u8>("enabled", parts[2], &response)? == 1;
 
         Ok(GetDLLEnabledResponse { enabled })
     }
@@ -113,9 +105,9 @@ pub struct GetDLLEnabled {
     pub channel: Channel,
 }
 
-impl Into<String> for GetDLLEnabled {
-    fn into(self) -> String {
-        format!("$DLEG,{}", self.channel)
+impl From<&GetDLLEnabled> for String {
+    fn from(command: &GetDLLEnabled) -> String {
+        format!("$DLEG,{}", command.channel)
     }
 }
 
@@ -1,10 +1,134 @@
 use serde::{Deserialize, Serialize};
 
+use std::fmt;
+
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Dbm, Frequency, Watt},
 };
 
+/// A single measurement taken during a sweep.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SweepPoint<P> {
+    /// The frequency the measurement was taken at.
+    pub frequency: Frequency,
+    /// The forward power measured at the frequency.
+    pub forward_power: P,
+    /// The reflected power measured at the frequency.
+    pub reflected_power: P,
+}
+
+/// Why the measurements of a sweep can't be trusted.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SweepError {
+    /// The controller reported fewer measurements than the sweep was asked for.
+    Truncated { expected: usize, received: usize },
+    /// The controller reported more measurements than the sweep was asked for.
+    TooManyPoints { expected: usize, received: usize },
+    /// The measurement at `index` doesn't lie above the frequency of the one before it.
+    NotMonotonic { index: usize },
+}
+
+impl fmt::Display for SweepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SweepError::Truncated { expected, received } => write!(
+                f,
+                "The sweep was cut short, only {} of {} measurements were received.",
+                received, expected
+            ),
+            SweepError::TooManyPoints { expected, received } => write!(
+                f,
+                "The sweep reported {} measurements where {} were expected.",
+                received, expected
+            ),
+            SweepError::NotMonotonic { index } => write!(
+                f,
+                "Measurement {} of the sweep doesn't lie above the frequency of the one before it.",
+                index
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SweepError {}
+
+/// Parses every line of a sweep reply, such as `OK,1,2450,100.0,2.0`, into a measurement.
+///
+/// Also returns the index of the measurement with the least reflected power.
+fn parse_points<P>(
+    response: &str,
+    power: fn(f32) -> P,
+) -> Result<(Vec<SweepPoint<P>>, usize), MWError> {
+    let mut points = Vec::new();
+    let mut best: Option<(usize, f32)> = None;
+
+    for line in response.split(['\r', '\n']).map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+
+        let parts: Vec<&str> = line.split(',').collect();
+
+        // Ensure the line has the expected number of parts
+        if parts.len() != 5 {
+            return Err(MWError::FailedParseResponse);
+        }
+
+        let frequency: u16 = parse_field("frequency", parts[2], line)?;
+        let forward_power: f32 = parse_field("forward_power", parts[3], line)?;
+        let reflected_power: f32 = parse_field("reflected_power", parts[4], line)?;
+
+        let is_best = match best {
+            Some((_, least)) => reflected_power < least,
+            None => true,
+        };
+        if is_best {
+            best = Some((points.len(), reflected_power));
+        }
+        points.push(SweepPoint {
+            frequency: Frequency::new(frequency),
+            forward_power: power(forward_power),
+            reflected_power: power(reflected_power),
+        });
+    }
+
+    match best {
+        Some((index, _)) => Ok((points, index)),
+        None => Err(MWError::FailedParseResponse),
+    }
+}
+
+/// Checks that the measurements are as many as expected and climb in frequency.
+fn validate_points<P>(points: &[SweepPoint<P>], expected: Option<usize>) -> Result<(), SweepError> {
+    if let Some(expected) = expected {
+        let received = points.len();
+        if received < expected {
+            return Err(SweepError::Truncated { expected, received });
+        }
+        if received > expected {
+            return Err(SweepError::TooManyPoints { expected, received });
+        }
+    }
+
+    match points
+        .windows(2)
+        .position(|pair| pair[1].frequency <= pair[0].frequency)
+    {
+        Some(index) => Err(SweepError::NotMonotonic { index: index + 1 }),
+        None => Ok(()),
+    }
+}
+
+/// The number of measurements a sweep from `start` to `stop` takes, both included.
+fn point_count(start: Frequency, stop: Frequency, step: Frequency) -> Option<usize> {
+    if step.frequency == 0 || start > stop {
+        return None;
+    }
+
+    Some(((stop.frequency - start.frequency) / step.frequency) as usize + 1)
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 /// The best frequency to be at given the requested power output.
 pub struct PerformSweepWattResponse {
@@ -14,6 +138,64 @@ pub struct PerformSweepWattResponse {
     pub forward_power: Watt,
     /// The reflected power measured at the measured frequency.
     pub reflected_power: Watt,
+    /// Every measurement the controller reported, in the order it reported them.
+    #[serde(default)]
+    pub points: Vec<SweepPoint<Watt>>,
+    /// The number of measurements the sweep was asked for, `None` if it isn't known.
+    #[serde(default)]
+    pub expected_points: Option<usize>,
+}
+
+impl PerformSweepWattResponse {
+    /// Every measurement of the sweep, checked to be as many as were asked for and to climb in
+    /// frequency.
+    ///
+    /// The count can only be checked on responses returned by executing the sweep, see
+    /// `expected_points`.
+    ///
+    /// ```
+    /// use minicircuit_commands::dll::sweep::{PerformSweepWatt, PerformSweepWattResponse, SweepError};
+    ///
+    /// let mut response =
+    ///     PerformSweepWattResponse::try_from("OK,1,2400,100.0,8.0\r\nOK,1,2410,100.0,2.0".to_string())
+    ///         .unwrap();
+    /// assert_eq!(response.measurement_frequency.frequency, 2410);
+    ///
+    /// let points = response.points().unwrap();
+    /// assert_eq!(points[0].frequency.frequency, 2400);
+    /// assert_eq!(points[0].reflected_power.power, 8.0);
+    ///
+    /// // A sweep from 2400 to 2500 MHz in 10 MHz steps takes 11 measurements.
+    /// response.expected_points = PerformSweepWatt::default().point_count();
+    /// assert_eq!(
+    ///     response.points(),
+    ///     Err(SweepError::Truncated { expected: 11, received: 2 })
+    /// );
+    /// ```
+    pub fn points(&self) -> Result<Vec<SweepPoint<Watt>>, SweepError> {
+        validate_points(&self.points, self.expected_points)?;
+        Ok(self.points.clone())
+    }
+
+    /// Adds the measurements of a line the controller sent after the reply, such as while a sweep
+    /// is streamed, keeping the best measurement up to date.
+    pub fn push_line(&mut self, line: &str) -> Result<(), MWError> {
+        if line.contains("ERR") {
+            return Err(line.to_string().into());
+        }
+
+        let (points, _) = parse_points(line, Watt::new)?;
+        for point in points {
+            if point.reflected_power.power < self.reflected_power.power {
+                self.measurement_frequency = point.frequency;
+                self.forward_power = point.forward_power;
+                self.reflected_power = point.reflected_power;
+            }
+            self.points.push(point);
+        }
+
+        Ok(())
+    }
 }
 
 impl TryFrom<String> for PerformSweepWattResponse {
@@ -26,40 +208,16 @@ impl TryFrom<String> for PerformSweepWattResponse {
             return Err(response_error);
         }
 
-        // If there are no errors parse the response into struct components
-        let parts: Vec<&str> = response.split(',').collect();
-
-        // Ensure the input has the expected number of parts
-        if parts.len() != 5 {
-            return Err(Self::Error::FailedParseResponse);
-        }
-
-        let measurement_frequency: Frequency = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u16>()
-        {
-            Ok(value) => Frequency::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let forward_power: Watt = match parts[3].trim().parse::<f32>() {
-            Ok(value) => Watt::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let reflected_power: Watt = match parts[4].trim().parse::<f32>() {
-            Ok(value) => Watt::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        // If there are no errors parse every line of the response into a measurement
+        let (points, best) = parse_points(&response, Watt::new)?;
+        let best = &points[best];
 
         Ok(PerformSweepWattResponse {
-            measurement_frequency,
-            forward_power,
-            reflected_power,
+            measurement_frequency: best.frequency,
+            forward_power: best.forward_power,
+            reflected_power: best.reflected_power,
+            points,
+            expected_points: None,
         })
     }
 }
@@ -84,20 +242,29 @@ pub struct PerformSweepWatt {
     pub power: Watt,
 }
 
-impl Into<String> for PerformSweepWatt {
-    fn into(self) -> String {
+impl From<&PerformSweepWatt> for String {
+    fn from(command: &PerformSweepWatt) -> String {
         format!(
             "$SWP,{},{},{},{},{},1",
-            self.channel,
+            command.channel,
+            command.start_frequency,
+            command.stop_frequency,
+            command.step_frequency,
+            command.power
+        )
+    }
+}
+
+impl PerformSweepWatt {
+    /// The number of measurements the sweep takes, `None` if the range can't be swept.
+    pub fn point_count(&self) -> Option<usize> {
+        point_count(
             self.start_frequency,
             self.stop_frequency,
             self.step_frequency,
-            self.power
         )
     }
-}
 
-impl PerformSweepWatt {
     /// Returns a handler to call the command using the given inputs.
     ///
     /// Channel identification number.
@@ -148,6 +315,44 @@ pub struct PerformSweepDBMResponse {
     pub forward_power: Dbm,
     /// The reflected power measured at the measured frequency.
     pub reflected_power: Dbm,
+    /// Every measurement the controller reported, in the order it reported them.
+    #[serde(default)]
+    pub points: Vec<SweepPoint<Dbm>>,
+    /// The number of measurements the sweep was asked for, `None` if it isn't known.
+    #[serde(default)]
+    pub expected_points: Option<usize>,
+}
+
+impl PerformSweepDBMResponse {
+    /// Every measurement of the sweep, checked to be as many as were asked for and to climb in
+    /// frequency.
+    ///
+    /// The count can only be checked on responses returned by executing the sweep, see
+    /// `expected_points`.
+    pub fn points(&self) -> Result<Vec<SweepPoint<Dbm>>, SweepError> {
+        validate_points(&self.points, self.expected_points)?;
+        Ok(self.points.clone())
+    }
+
+    /// Adds the measurements of a line the controller sent after the reply, such as while a sweep
+    /// is streamed, keeping the best measurement up to date.
+    pub fn push_line(&mut self, line: &str) -> Result<(), MWError> {
+        if line.contains("ERR") {
+            return Err(line.to_string().into());
+        }
+
+        let (points, _) = parse_points(line, Dbm::new)?;
+        for point in points {
+            if point.reflected_power.power < self.reflected_power.power {
+                self.measurement_frequency = point.frequency;
+                self.forward_power = point.forward_power.clone();
+                self.reflected_power = point.reflected_power.clone();
+            }
+            self.points.push(point);
+        }
+
+        Ok(())
+    }
 }
 
 impl TryFrom<String> for PerformSweepDBMResponse {
@@ -160,40 +365,16 @@ impl TryFrom<String> for PerformSweepDBMResponse {
             return Err(response_error);
         }
 
-        // If there are no errors parse the response into struct components
-        let parts: Vec<&str> = response.split(',').collect();
-
-        // Ensure the input has the expected number of parts
-        if parts.len() != 5 {
-            return Err(Self::Error::FailedParseResponse);
-        }
-
-        let measurement_frequency: Frequency = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u16>()
-        {
-            Ok(value) => Frequency::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let forward_power: Dbm = match parts[3].trim().parse::<f32>() {
-            Ok(value) => Dbm::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let reflected_power: Dbm = match parts[4].trim().parse::<f32>() {
-            Ok(value) => Dbm::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        // If there are no errors parse every line of the response into a measurement
+        let (points, best) = parse_points(&response, Dbm::new)?;
+        let best = &points[best];
 
         Ok(PerformSweepDBMResponse {
-            measurement_frequency,
-            forward_power,
-            reflected_power,
+            measurement_frequency: best.frequency,
+            forward_power: best.forward_power.clone(),
+            reflected_power: best.reflected_power.clone(),
+            points,
+            expected_points: None,
         })
     }
 }
@@ -218,20 +399,29 @@ pub struct PerformSweepDBM {
     pub power: Dbm,
 }
 
-impl Into<String> for PerformSweepDBM {
-    fn into(self) -> String {
+impl From<&PerformSweepDBM> for String {
+    fn from(command: &PerformSweepDBM) -> String {
         format!(
             "$SWPD,{},{},{},{},{},1",
-            self.channel,
+            command.channel,
+            command.start_frequency,
+            command.stop_frequency,
+            command.step_frequency,
+            command.power
+        )
+    }
+}
+
+impl PerformSweepDBM {
+    /// The number of measurements the sweep takes, `None` if the range can't be swept.
+    pub fn point_count(&self) -> Option<usize> {
+        point_count(
             self.start_frequency,
             self.stop_frequency,
             self.step_frequency,
-            self.power
         )
     }
-}
 
-impl PerformSweepDBM {
     /// Returns a handler to call the command using the given inputs.
     ///
     /// Channel identification number.
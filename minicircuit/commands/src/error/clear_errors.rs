@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::data_types::{errors::MWError, types::Channel};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ClearErrorsResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -36,9 +36,9 @@ pub struct ClearErrors {
     pub channel: Channel,
 }
 
-impl Into<String> for ClearErrors {
-    fn into(self) -> String {
-        format!("$ERRC,{}", self.channel)
+impl From<&ClearErrors> for String {
+    fn from(command: &ClearErrors) -> String {
+        format!("$ERRC,{}", command.channel)
     }
 }
 
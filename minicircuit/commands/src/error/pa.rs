@@ -33,6 +33,39 @@ pub struct GetPAErrorsResponse {
     /// Note: there is no protection limit set, so there should never be an internal
     /// alarm for these parameters.
     pub pa_errors: Vec<AlarmCause>,
+    /// The error code as the PA reported it, with one bit per alarm cause.
+    #[serde(default)]
+    pub code: u16,
+}
+
+impl GetPAErrorsResponse {
+    /// The alarms that are raised, leaving out `SystemOk`.
+    ///
+    /// ```
+    /// use minicircuit_commands::error::pa::{AlarmCause, AlarmSeverity, GetPAErrorsResponse};
+    ///
+    /// // Bits 1 and 10: reflected power below its lower limit and temperature above its upper limit.
+    /// let response = GetPAErrorsResponse::try_from("OK,1,402".to_string()).unwrap();
+    ///
+    /// assert_eq!(
+    ///     response.errors(),
+    ///     vec![AlarmCause::ReflectedPowerLower, AlarmCause::TemperatureUpper]
+    /// );
+    /// assert_eq!(AlarmCause::ReflectedPowerLower.severity(), AlarmSeverity::Warning);
+    /// assert!(response.has_critical());
+    /// ```
+    pub fn errors(&self) -> Vec<AlarmCause> {
+        self.pa_errors
+            .iter()
+            .copied()
+            .filter(|alarm| *alarm != AlarmCause::SystemOk)
+            .collect()
+    }
+
+    /// Whether any of the raised alarms is critical, see `AlarmCause::severity`.
+    pub fn has_critical(&self) -> bool {
+        self.pa_errors.iter().any(AlarmCause::is_critical)
+    }
 }
 
 impl TryFrom<String> for GetPAErrorsResponse {
@@ -64,6 +97,7 @@ impl TryFrom<String> for GetPAErrorsResponse {
 
         Ok(GetPAErrorsResponse {
             pa_errors: from_bitmask(hex_status_code),
+            code: hex_status_code,
         })
     }
 }
@@ -81,6 +115,42 @@ pub enum AlarmCause {
     VSupplyLower,        // bit 7
     TemperatureUpper,    // bit 10
     TemperatureLower,    // bit 11
+    /// A bit the PA documentation lists as reserved, holding its position.
+    Reserved(u8),
+}
+
+/// How urgently a raised PA alarm needs attention.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlarmSeverity {
+    /// Nothing is wrong.
+    Info,
+    /// A measurement fell below its lower limit, or the cause isn't documented.
+    Warning,
+    /// A measurement rose above its upper limit, which usually shuts the PA down.
+    Critical,
+}
+
+impl AlarmCause {
+    pub fn severity(&self) -> AlarmSeverity {
+        match self {
+            AlarmCause::SystemOk => AlarmSeverity::Info,
+            AlarmCause::ReflectedPowerUpper
+            | AlarmCause::ForwardPowerUpper
+            | AlarmCause::CurrentUpper
+            | AlarmCause::VSupplyUpper
+            | AlarmCause::TemperatureUpper => AlarmSeverity::Critical,
+            AlarmCause::ReflectedPowerLower
+            | AlarmCause::ForwardPowerLower
+            | AlarmCause::CurrentLower
+            | AlarmCause::VSupplyLower
+            | AlarmCause::TemperatureLower
+            | AlarmCause::Reserved(_) => AlarmSeverity::Warning,
+        }
+    }
+
+    pub fn is_critical(&self) -> bool {
+        self.severity() == AlarmSeverity::Critical
+    }
 }
 
 impl Into<String> for AlarmCause {
@@ -117,6 +187,9 @@ impl Into<String> for AlarmCause {
             AlarmCause::TemperatureLower => {
                 String::from("The temperature of the PA is less than the allowed lower limit.")
             }
+            AlarmCause::Reserved(bit) => {
+                format!("The PA raised the undocumented alarm bit {}.", bit)
+            }
         }
     }
 }
@@ -140,7 +213,7 @@ pub fn from_bitmask(alarm_code: u16) -> Vec<AlarmCause> {
                 7 => AlarmCause::VSupplyLower,
                 10 => AlarmCause::TemperatureUpper,
                 11 => AlarmCause::TemperatureLower,
-                _ => continue,
+                _ => AlarmCause::Reserved(bit_position),
             };
 
             alarms.push(alarm);
@@ -165,9 +238,9 @@ pub struct GetPAErrors {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPAErrors {
-    fn into(self) -> String {
-        format!("$PSG,{}", self.channel)
+impl From<&GetPAErrors> for String {
+    fn from(command: &GetPAErrors) -> String {
+        format!("$PSG,{}", command.channel)
     }
 }
 
@@ -16,6 +16,13 @@ macro_rules! define_status_codes {
         }
 
         impl StatusCode {
+            /// Every status code, in the order of their bits.
+            pub const ALL: &'static [StatusCode] = &[
+                $(
+                    StatusCode::$variant
+                ),+
+            ];
+
             /// Convert an enum variant to its bit value.
             pub fn to_bit_value(&self) -> u64 {
                 match self {
@@ -79,6 +86,9 @@ define_status_codes! {
 /// List of status codes stored on the ISC board.
 pub struct GetStatusResponse {
     pub status_codes: Vec<Status>,
+    /// The raw status bitfield, with a named accessor per condition.
+    #[serde(default)]
+    pub flags: StatusFlags,
 }
 
 impl TryFrom<String> for GetStatusResponse {
@@ -110,7 +120,10 @@ impl TryFrom<String> for GetStatusResponse {
 
         let status_codes: Vec<Status> = Status::from_hex_code(hex_status_code);
 
-        Ok(GetStatusResponse { status_codes })
+        Ok(GetStatusResponse {
+            status_codes,
+            flags: StatusFlags::from_bits(hex_status_code),
+        })
     }
 }
 
@@ -129,9 +142,9 @@ pub struct GetStatus {
     pub channel: Channel,
 }
 
-impl Into<String> for GetStatus {
-    fn into(self) -> String {
-        format!("$ST,{}", self.channel)
+impl From<&GetStatus> for String {
+    fn from(command: &GetStatus) -> String {
+        format!("$ST,{}", command.channel)
     }
 }
 
@@ -404,3 +417,141 @@ impl Status {
         statuses
     }
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The status bitfield of the ISC board, as reported by `GetStatus`.
+///
+/// The bitfield only holds faults and warnings. Whether RF output or the DLL is enabled
+/// has to be queried with `GetRFOutput` and `GetDLLEnabled`.
+pub struct StatusFlags {
+    bits: u64,
+}
+
+impl StatusFlags {
+    /// SOA conditions that switch RF output off.
+    const SOA_SHUTDOWN: [StatusCode; 7] = [
+        StatusCode::ShutdownPATemperature,
+        StatusCode::ShutdownReflectedPower,
+        StatusCode::SOAShutdownDissipation,
+        StatusCode::SOAShutdownCurrent,
+        StatusCode::SOAShutdownForwardPower,
+        StatusCode::SOAShutdownMinimumVoltage,
+        StatusCode::SOAShutdownMaximumVoltage,
+    ];
+
+    /// SOA conditions that are reported without taking action.
+    const SOA_WARNING: [StatusCode; 7] = [
+        StatusCode::HighPATemperature,
+        StatusCode::HighReflectedPower,
+        StatusCode::SOAHighDissipation,
+        StatusCode::SOAHighCurrent,
+        StatusCode::SOAHighForwardPower,
+        StatusCode::SOALowVoltage,
+        StatusCode::SOAHighVoltage,
+    ];
+
+    pub fn from_bits(bits: u64) -> Self {
+        Self { bits }
+    }
+
+    /// The raw bitfield, including bits that don't map to a known status code.
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    pub fn contains(&self, code: StatusCode) -> bool {
+        self.bits & code.to_bit_value() != 0
+    }
+
+    fn contains_any(&self, codes: &[StatusCode]) -> bool {
+        codes.iter().any(|code| self.contains(*code))
+    }
+
+    /// Whether no errors or warnings are raised.
+    pub fn is_ok(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Whether a SOA shutdown limit was exceeded, which switches RF output off until cleared.
+    pub fn soa_tripped(&self) -> bool {
+        self.contains_any(&Self::SOA_SHUTDOWN)
+    }
+
+    /// Whether a SOA warning limit was exceeded.
+    pub fn soa_warning(&self) -> bool {
+        self.contains_any(&Self::SOA_WARNING)
+    }
+
+    /// Whether the PA temperature reached the high or the shutdown limit.
+    pub fn over_temperature(&self) -> bool {
+        self.contains_any(&[
+            StatusCode::HighPATemperature,
+            StatusCode::ShutdownPATemperature,
+        ])
+    }
+
+    /// Whether the reflected power reached the high or the shutdown limit.
+    pub fn high_reflected_power(&self) -> bool {
+        self.contains_any(&[
+            StatusCode::HighReflectedPower,
+            StatusCode::ShutdownReflectedPower,
+        ])
+    }
+
+    pub fn reset_detected(&self) -> bool {
+        self.contains(StatusCode::ResetDetected)
+    }
+
+    /// Whether an external shutdown, protection or alarm signal was received.
+    pub fn external_shutdown(&self) -> bool {
+        self.contains_any(&[
+            StatusCode::ExternalShutdownTriggered,
+            StatusCode::ExternalProtectionTriggered,
+            StatusCode::AlarmIn,
+        ])
+    }
+
+    pub fn external_watchdog_timeout(&self) -> bool {
+        self.contains(StatusCode::ExternalWatchdogTimeout)
+    }
+
+    /// Whether the PA reported an error, see `GetPAErrors` for the cause.
+    pub fn pa_error(&self) -> bool {
+        self.contains_any(&[
+            StatusCode::PAError,
+            StatusCode::PAResetFailure,
+            StatusCode::PAHighCurrent,
+        ])
+    }
+
+    /// Whether a measurement or the internal I2C or SPI communication failed.
+    pub fn hardware_error(&self) -> bool {
+        self.contains_any(&[
+            StatusCode::UnspecifiedError,
+            StatusCode::TemperatureReadoutError,
+            StatusCode::PowerMeasurementFailure,
+            StatusCode::RFEnableFailure,
+            StatusCode::MultiplexerFailure,
+            StatusCode::OutOfMemory,
+            StatusCode::I2CCommunicationError,
+            StatusCode::SPICommunicaitonError,
+            StatusCode::SOAMeasurementError,
+        ])
+    }
+
+    /// Whether the calibration is missing or outdated.
+    pub fn calibration_error(&self) -> bool {
+        self.contains_any(&[
+            StatusCode::CalibrationMissing,
+            StatusCode::CalibrationEEPROMOutdated,
+        ])
+    }
+
+    /// The known status codes that are raised.
+    pub fn active_flags(&self) -> impl Iterator<Item = StatusCode> + '_ {
+        StatusCode::ALL
+            .iter()
+            .copied()
+            .filter(move |code| self.contains(*code))
+    }
+}
@@ -19,6 +19,17 @@ pub struct GetIdentityResponse {
     pub isc_board: String,
     /// Unique serial number of the board.
     pub serial_number: String,
+    /// The board type broken into its parts, `None` if `isc_board` doesn't follow the pattern above.
+    #[serde(default)]
+    pub model: Option<BoardModel>,
+    /// The hardware revision, if the board reports one after its type.
+    #[serde(default)]
+    pub hardware_rev: Option<String>,
+    /// The identity as reported by the board, kept in case its layout wasn't recognized.
+    ///
+    /// When the layout isn't recognized, the other string fields are left empty.
+    #[serde(default)]
+    pub raw: String,
 }
 
 impl TryFrom<String> for GetIdentityResponse {
@@ -34,23 +45,73 @@ impl TryFrom<String> for GetIdentityResponse {
         // If there are no errors parse the response into struct components
         let parts: Vec<&str> = response.split(',').collect();
 
-        // Ensure the input has the expected number of parts
-        if parts.len() != 4 {
+        // Anything past the channel is the identity itself.
+        if parts.len() < 3 {
             return Err(Self::Error::FailedParseResponse);
         }
+        let raw = parts[2..].join(",").trim().to_string();
 
         let manufacturer_board: Vec<&str> = parts[2].split_whitespace().collect();
-        if manufacturer_board.len() != 2 {
-            return Err(Self::Error::FailedParseResponse);
+        if parts.len() != 4 || manufacturer_board.len() < 2 {
+            return Ok(GetIdentityResponse {
+                manufacturer: String::new(),
+                isc_board: String::new(),
+                serial_number: String::new(),
+                model: None,
+                hardware_rev: None,
+                raw,
+            });
         }
+
         let manufacturer = manufacturer_board[0].trim().to_string();
         let isc_board = manufacturer_board[1].trim().to_string();
+        let hardware_rev = match manufacturer_board[2..].join(" ") {
+            rev if rev.is_empty() => None,
+            rev => Some(rev),
+        };
         let serial_number = parts[3].trim().to_string();
 
         Ok(GetIdentityResponse {
+            model: BoardModel::parse(&isc_board),
             manufacturer,
             isc_board,
             serial_number,
+            hardware_rev,
+            raw,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+/// The frequency range and output power encoded in the type of an ISC board.
+pub struct BoardModel {
+    /// Lower frequency limit in MHz.
+    pub frequency_low: u16,
+    /// Upper frequency limit in MHz.
+    pub frequency_high: u16,
+    /// Maximum RF output power in dBm.
+    pub max_power_dbm: u8,
+}
+
+impl BoardModel {
+    /// Parses a board type such as `ISC-2425-25+`, returning `None` if it doesn't follow that pattern.
+    pub fn parse(isc_board: &str) -> Option<Self> {
+        let mut parts = isc_board.strip_prefix("ISC-")?.split('-');
+
+        let frequencies = parts.next()?;
+        let power = parts.next()?.trim_end_matches('+');
+        if frequencies.len() != 4 || parts.next().is_some() {
+            return None;
+        }
+
+        // Only the first two digits of each limit are given.
+        let frequency_low: u16 = frequencies.get(..2)?.parse().ok()?;
+        let frequency_high: u16 = frequencies.get(2..)?.parse().ok()?;
+
+        Some(Self {
+            frequency_low: frequency_low * 100,
+            frequency_high: frequency_high * 100,
+            max_power_dbm: power.parse().ok()?,
         })
     }
 }
@@ -62,9 +123,9 @@ pub struct GetIdentity {
     pub channel: Channel,
 }
 
-impl Into<String> for GetIdentity {
-    fn into(self) -> String {
-        format!("$IDN,{}", self.channel)
+impl From<&GetIdentity> for String {
+    fn from(command: &GetIdentity) -> String {
+        format!("$IDN,{}", command.channel)
     }
 }
 
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Seconds},
 };
 
@@ -31,15 +31,7 @@ impl TryFrom<String> for GetUptimeResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let uptime = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u64>()
-        {
-            Ok(value) => value,
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let uptime: u64 = parse_field("uptime", parts[2], &response)?;
 
         Ok(GetUptimeResponse {
             uptime: Seconds::new(uptime),
@@ -55,9 +47,9 @@ pub struct GetUptime {
     pub channel: Channel,
 }
 
-impl Into<String> for GetUptime {
-    fn into(self) -> String {
-        format!("$RTG,{}", self.channel)
+impl From<&GetUptime> for String {
+    fn from(command: &GetUptime) -> String {
+        format!("$RTG,{}", command.channel)
     }
 }
 
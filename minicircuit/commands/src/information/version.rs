@@ -1,3 +1,5 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{errors::MWError, types::Channel};
@@ -47,6 +49,59 @@ impl TryFrom<String> for GetVersionResponse {
     }
 }
 
+impl GetVersionResponse {
+    /// The numeric version, or `None` if a part of it isn't a number.
+    pub fn version(&self) -> Option<FirmwareVersion> {
+        Some(FirmwareVersion {
+            major: self.major_version.parse().ok()?,
+            minor: self.minor_version.parse().ok()?,
+            build: self.build.parse().ok()?,
+            hotfix: match &self.hotfix {
+                Some(hotfix) => hotfix.parse().ok()?,
+                None => 0,
+            },
+        })
+    }
+
+    /// Whether the firmware is the given version or newer.
+    ///
+    /// Returns false if the reported version couldn't be read as numbers.
+    pub fn firmware_at_least(&self, version: FirmwareVersion) -> bool {
+        self.version().is_some_and(|current| current >= version)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A firmware version, ordered by major, minor, build and hotfix number.
+pub struct FirmwareVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub build: u32,
+    /// Zero when the firmware isn't a hotfix.
+    pub hotfix: u32,
+}
+
+impl FirmwareVersion {
+    pub fn new(major: u32, minor: u32, build: u32) -> Self {
+        Self {
+            major,
+            minor,
+            build,
+            hotfix: 0,
+        }
+    }
+}
+
+impl fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.build)?;
+        if self.hotfix != 0 {
+            write!(f, ".{}", self.hotfix)?;
+        }
+        Ok(())
+    }
+}
+
 fn parse_with_hotfix(parts: Vec<&str>) -> GetVersionResponse {
     let manufacturer_id = parts[2].trim().to_string();
     let major_version = parts[3].trim().to_string();
@@ -92,9 +147,9 @@ pub struct GetVersion {
     pub channel: Channel,
 }
 
-impl Into<String> for GetVersion {
-    fn into(self) -> String {
-        format!("$VER,{}", self.channel)
+impl From<&GetVersion> for String {
+    fn from(command: &GetVersion) -> String {
+        format!("$VER,{}", command.channel)
     }
 }
 
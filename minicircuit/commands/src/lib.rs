@@ -2,6 +2,7 @@ pub mod command;
 pub mod response;
 
 pub mod basic;
+pub mod capabilities;
 pub mod dll;
 pub mod error;
 pub mod information;
@@ -20,4 +21,5 @@ pub mod prelude;
 pub use command::Command;
 pub use command::Message;
 pub use command::Priority;
+pub use response::CorrelatedResponse;
 pub use response::Response;
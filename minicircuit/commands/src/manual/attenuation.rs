@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Attenuation, Channel},
 };
 
@@ -35,12 +35,8 @@ impl TryFrom<String> for GetAttenuationResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let attenuation: Attenuation = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Attenuation::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let attenuation: Attenuation =
+            Attenuation::new(parse_field("attenuation", parts[2], &response)?);
 
         Ok(GetAttenuationResponse { attenuation })
     }
@@ -54,9 +50,9 @@ pub struct GetAttenuation {
     pub channel: Channel,
 }
 
-impl Into<String> for GetAttenuation {
-    fn into(self) -> String {
-        format!("$GCG,{}", self.channel)
+impl From<&GetAttenuation> for String {
+    fn from(command: &GetAttenuation) -> String {
+        format!("$GCG,{}", command.channel)
     }
 }
 
@@ -77,7 +73,7 @@ impl Default for GetAttenuation {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetAttenuationResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -117,9 +113,9 @@ pub struct SetAttenuation {
     pub attenuation: Attenuation,
 }
 
-impl Into<String> for SetAttenuation {
-    fn into(self) -> String {
-        format!("$GCS,{},{}", self.channel, self.attenuation)
+impl From<&SetAttenuation> for String {
+    fn from(command: &SetAttenuation) -> String {
+        format!("$GCS,{},{}", command.channel, command.attenuation)
     }
 }
 
@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-use crate::data_types::{errors::MWError, types::Channel};
+use crate::data_types::{
+    errors::{parse_field, MWError},
+    types::Channel,
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetAutoGainStateResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -52,13 +55,13 @@ pub struct SetAutoGainState {
     pub enabled: bool,
 }
 
-impl Into<String> for SetAutoGainState {
-    fn into(self) -> String {
-        let value: u8 = match self.enabled {
+impl From<&SetAutoGainState> for String {
+    fn from(command: &SetAutoGainState) -> String {
+        let value: u8 = match command.enabled {
             true => 1,
             false => 0,
         };
-        format!("$AGES,{},{}", self.channel, value)
+        format!("$AGES,{},{}", command.channel, value)
     }
 }
 
@@ -109,18 +112,7 @@ impl TryFrom<String> for GetAutoGainStateResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let enabled: bool = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u8>()
-        {
-            Ok(value) => match value {
-                1 => true,
-                _ => false,
-            },
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let enabled: bool = parse_field::<u8>("enabled", parts[2], &response)? == 1;
 
         Ok(GetAutoGainStateResponse { enabled })
     }
@@ -133,9 +125,9 @@ pub struct GetAutoGainState {
     pub channel: Channel,
 }
 
-impl Into<String> for GetAutoGainState {
-    fn into(self) -> String {
-        format!("$AGEG,{}", self.channel)
+impl From<&GetAutoGainState> for String {
+    fn from(command: &GetAutoGainState) -> String {
+        format!("$AGEG,{}", command.channel)
     }
 }
 
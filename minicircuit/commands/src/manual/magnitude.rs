@@ -1,11 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Percentage},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetMagnitudeResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -39,9 +39,9 @@ pub struct SetMagnitude {
     pub magnitude: Percentage,
 }
 
-impl Into<String> for SetMagnitude {
-    fn into(self) -> String {
-        format!("$MCS,{},{}", self.channel, self.magnitude)
+impl From<&SetMagnitude> for String {
+    fn from(command: &SetMagnitude) -> String {
+        format!("$MCS,{},{}", command.channel, command.magnitude)
     }
 }
 
@@ -86,15 +86,7 @@ impl TryFrom<String> for GetMagnitudeResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let magnitude: Percentage = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u8>()
-        {
-            Ok(value) => Percentage::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let magnitude: Percentage = Percentage::new(parse_field("magnitude", parts[2], &response)?);
 
         Ok(GetMagnitudeResponse { magnitude })
     }
@@ -107,9 +99,9 @@ pub struct GetMagnitude {
     pub channel: Channel,
 }
 
-impl Into<String> for GetMagnitude {
-    fn into(self) -> String {
-        format!("$MCG,{}", self.channel)
+impl From<&GetMagnitude> for String {
+    fn from(command: &GetMagnitude) -> String {
+        format!("$MCG,{}", command.channel)
     }
 }
 
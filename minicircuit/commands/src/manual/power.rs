@@ -1,11 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Dbm},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetISCPowerOutputResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -37,9 +37,9 @@ pub struct SetISCPowerOutput {
     pub power_dbm: Dbm,
 }
 
-impl Into<String> for SetISCPowerOutput {
-    fn into(self) -> String {
-        format!("$PWRSGDS,{},{}", self.channel, self.power_dbm)
+impl From<&SetISCPowerOutput> for String {
+    fn from(command: &SetISCPowerOutput) -> String {
+        format!("$PWRSGDS,{},{}", command.channel, command.power_dbm)
     }
 }
 
@@ -84,12 +84,7 @@ impl TryFrom<String> for GetISCPowerOutputResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let power: Dbm = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Dbm::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let power: Dbm = Dbm::new(parse_field("power", parts[2], &response)?);
 
         Ok(GetISCPowerOutputResponse { power })
     }
@@ -105,9 +100,9 @@ pub struct GetISCPowerOutput {
     pub channel: Channel,
 }
 
-impl Into<String> for GetISCPowerOutput {
-    fn into(self) -> String {
-        format!("$PWRSGDG,{}", self.channel)
+impl From<&GetISCPowerOutput> for String {
+    fn from(command: &GetISCPowerOutput) -> String {
+        format!("$PWRSGDG,{}", command.channel)
     }
 }
 
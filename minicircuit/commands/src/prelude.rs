@@ -9,10 +9,10 @@
 
 // Command types
 pub use crate::command::{Command, Message, Priority};
-pub use crate::response::Response;
+pub use crate::response::{CorrelatedResponse, Response};
 
 // Basic command types
-pub use crate::basic::frequency::{GetFrequency, SetFrequency, GetFrequencyResponse, SetFrequencyResponse};
+pub use crate::basic::frequency::{FrequencySweep, GetFrequency, SetFrequency, GetFrequencyResponse, SetFrequencyResponse};
 pub use crate::basic::output::{GetRFOutput, SetRFOutput, GetRFOutputResponse, SetRFOutputResponse};
 pub use crate::basic::phase::{GetPhase, SetPhase, GetPhaseResponse, SetPhaseResponse};
 pub use crate::basic::forward_reflected::{
@@ -30,19 +30,30 @@ pub use crate::basic::voltage::GetPAVoltage;
 pub use crate::basic::current::GetPACurrent;
 
 // Information command types
-pub use crate::information::identity::{GetIdentity, GetIdentityResponse};
+pub use crate::information::identity::{BoardModel, GetIdentity, GetIdentityResponse};
 pub use crate::information::isc_temp::{GetISCTemp, GetISCTempResponse};
 pub use crate::information::uptime::{GetUptime, GetUptimeResponse};
-pub use crate::information::version::{GetVersion, GetVersionResponse};
+pub use crate::information::version::{FirmwareVersion, GetVersion, GetVersionResponse};
 
 // Error command types
-pub use crate::error::status::{GetStatus, GetStatusResponse};
+pub use crate::error::status::{GetStatus, GetStatusResponse, StatusFlags};
 pub use crate::error::pa::{GetPAErrors, GetPAErrorsResponse};
 pub use crate::error::clear_errors::ClearErrors;
 
 // System command types
 pub use crate::system::system_reset::ResetSystem;
 
+// Firmware capabilities
+pub use crate::capabilities::{Capabilities, CommandGroup};
+
+// Connection properties
+pub use crate::properties::{
+    DeviceLimits, LineTerminator, PowerRamp, ProductId, TargetProperties, TargetPropertiesBuilder,
+    VendorId,
+};
+
 // Data types
 pub use crate::data_types::types::*;
-pub use crate::data_types::errors::*;
\ No newline at end of file
+pub use crate::data_types::errors::*;
+pub use crate::data_types::measurement::{Measurement, Unit};
+pub use crate::data_types::power::{dbm_to_watt, watt_to_dbm, Power, PowerUnit};
\ No newline at end of file
@@ -1,6 +1,12 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-use crate::prelude::BaudRate;
+use crate::{
+    command::Command,
+    data_types::errors::ValidationError,
+    prelude::{BaudRate, Frequency, Watt},
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 /// The properties that are used for automatically detecting and
@@ -31,6 +37,112 @@ pub struct TargetProperties {
     ///
     /// If the timeout limit is reached, the connection will fail.
     pub connection_timeout: std::time::Duration,
+    /// How long connecting may take altogether, including listing the serial ports and opening one.
+    ///
+    /// An unplugged device can make the OS stall on either, so connecting fails with `NoDevice`
+    /// once this has elapsed rather than waiting for the OS to give up.
+    pub connect_timeout: std::time::Duration,
+    /// The output envelope that frequency and power requests are validated against
+    /// before they are sent to the signal generator.
+    pub limits: DeviceLimits,
+    /// When set, power setpoints are stepped up to the requested value rather than applied at once.
+    pub power_ramp: Option<PowerRamp>,
+    /// The pause between batches of queued commands.
+    ///
+    /// An idle queue wakes up as soon as a message arrives, this only spaces out
+    /// consecutive batches so that messages have a chance to be ordered by priority.
+    pub queue_poll_interval: std::time::Duration,
+    /// How long `send_await` waits for the response to a command before giving up.
+    pub response_timeout: std::time::Duration,
+    /// When true, the queue loop reopens the port if the device disappears mid-operation.
+    pub auto_reconnect: bool,
+    /// The delay before the first reconnect attempt, doubled after every failed attempt.
+    pub reconnect_backoff: std::time::Duration,
+    /// The maximum number of messages that can wait in the command queue, counting the ones
+    /// the queue loop has taken up until they have been carried out.
+    ///
+    /// Once reached, sending fails with a full queue error (or waits, depending on the send method).
+    /// `None` leaves the queue unbounded.
+    pub max_queue_depth: Option<usize>,
+    /// The number of recent commands kept in the driver's command log.
+    ///
+    /// `None` disables the log.
+    pub command_log_capacity: Option<usize>,
+    /// Answers every command with the string that would have been written instead of sending it.
+    ///
+    /// No port is opened when connecting, so commands can be checked without a device attached.
+    pub dry_run: bool,
+    /// When the device doesn't answer at `baud_rate` while connecting, try the other common
+    /// baud rates so the error can suggest the one it is set to.
+    pub probe_baud_rates: bool,
+    /// Answers commands the connected controller doesn't support with `Response::Unsupported`
+    /// instead of sending them, see `Capabilities`.
+    pub reject_unsupported: bool,
+    /// Reads the device's power limits with `GetPowerMinDbm` and `GetPowerMaxDbm` when
+    /// connecting, then clamps power setpoints outside of them to the nearest limit instead
+    /// of sending them as they are.
+    ///
+    /// Every clamped setpoint is announced with `Response::PowerClamped`.
+    pub clamp_power: bool,
+    /// How many times a queued command is resent after a communication error, while the
+    /// device is still connected.
+    ///
+    /// Only queries are resent unless `retry_setters` is set.
+    pub max_retries: u32,
+    /// The delay before the first resend, doubled after every failed attempt.
+    pub retry_backoff: std::time::Duration,
+    /// Whether setters are resent too, which applies them twice if only the response was lost.
+    pub retry_setters: bool,
+    /// The delay before resending a command the device answered as busy.
+    ///
+    /// Unlike after a communication error every command is resent, as the device didn't carry
+    /// it out, and the delay isn't doubled. Counts towards `max_retries`.
+    pub busy_retry_delay: std::time::Duration,
+    /// Reads lines the device sends without being asked between commands and broadcasts them
+    /// as `Response::Unsolicited`, so they aren't mistaken for the reply to the next command.
+    pub read_unsolicited: bool,
+    /// How many responses the broadcast channel holds for subscribers that haven't read them yet.
+    ///
+    /// A subscriber that falls further behind misses the oldest responses, see `Response::Lagged`.
+    pub broadcast_capacity: usize,
+    /// The least time between the start of one exchange with the device and the next, for
+    /// firmware that locks up when commands arrive faster than it can process them.
+    ///
+    /// Unlike `queue_poll_interval` this spaces every exchange, however deep the queue is.
+    /// `Priority::Immediate` still jumps the queue but waits out the interval like any other
+    /// command, as do direct commands such as those of `apply_config`.
+    pub min_command_interval: Option<std::time::Duration>,
+    /// How long a queued message waits before its priority is raised a level, so `Low`
+    /// telemetry isn't starved by a steady stream of higher priority commands.
+    ///
+    /// Messages rise no higher than `High`, where the one that has waited longest goes first,
+    /// so `Immediate` and `Termination` still go ahead of everything. `None` never raises them.
+    pub priority_aging: Option<std::time::Duration>,
+    /// Broadcasts `Response::SweepProgress` after each measurement of a queued `PerformSweepDBM`
+    /// or `PerformSweepWatt` as the controller sends it.
+    ///
+    /// Every measurement is read either way, each waited for up to the sweep's timeout. The
+    /// connection is held for the whole sweep, so commands of any priority wait for it to finish.
+    pub stream_sweeps: bool,
+    /// The line ending written after each command.
+    ///
+    /// Replies are accepted with any of the line endings, whichever this is set to.
+    pub line_terminator: LineTerminator,
+    /// Whether the link echoes every command back before the reply, as some serial adapters
+    /// and terminal servers do.
+    ///
+    /// The echo is read and discarded before the reply, warning if it doesn't match the
+    /// command that was sent.
+    pub echo_enabled: bool,
+    /// How long `reset_and_reconnect()` waits for the device to answer again after a reset.
+    pub reset_timeout: std::time::Duration,
+    /// How long to wait after a command before sending the next, keyed by `Command::name`,
+    /// such as `SetFrequency`. Set it with `settle_after`, which rejects unknown names. Measurements taken right after a frequency change can be off
+    /// until the DLL has locked to the new frequency.
+    ///
+    /// The connection is held while waiting, so nothing is measured in between. Commands
+    /// that fail aren't waited after, and neither is anything in dry-run mode.
+    pub settle_times: BTreeMap<String, std::time::Duration>,
 }
 
 impl TargetProperties {
@@ -55,6 +167,32 @@ impl TargetProperties {
             flow_control,
             stop_bits,
             connection_timeout,
+            connect_timeout: std::time::Duration::from_secs(5),
+            limits: DeviceLimits::default(),
+            power_ramp: None,
+            queue_poll_interval: std::time::Duration::from_millis(10),
+            response_timeout: std::time::Duration::from_secs(5),
+            auto_reconnect: false,
+            reconnect_backoff: std::time::Duration::from_millis(500),
+            max_queue_depth: None,
+            command_log_capacity: None,
+            dry_run: false,
+            probe_baud_rates: false,
+            reject_unsupported: false,
+            clamp_power: false,
+            max_retries: 2,
+            retry_backoff: std::time::Duration::from_millis(20),
+            retry_setters: false,
+            busy_retry_delay: std::time::Duration::from_millis(10),
+            read_unsolicited: false,
+            broadcast_capacity: 100,
+            min_command_interval: None,
+            priority_aging: None,
+            stream_sweeps: false,
+            line_terminator: LineTerminator::default(),
+            echo_enabled: false,
+            reset_timeout: std::time::Duration::from_secs(30),
+            settle_times: BTreeMap::new(),
         };
     }
 }
@@ -71,10 +209,335 @@ impl Default for TargetProperties {
             flow_control: serialport::FlowControl::None,
             stop_bits: serialport::StopBits::One,
             connection_timeout: std::time::Duration::from_secs(1),
+            connect_timeout: std::time::Duration::from_secs(5),
+            limits: DeviceLimits::default(),
+            power_ramp: None,
+            queue_poll_interval: std::time::Duration::from_millis(10),
+            response_timeout: std::time::Duration::from_secs(5),
+            auto_reconnect: false,
+            reconnect_backoff: std::time::Duration::from_millis(500),
+            max_queue_depth: None,
+            command_log_capacity: None,
+            dry_run: false,
+            probe_baud_rates: false,
+            reject_unsupported: false,
+            clamp_power: false,
+            max_retries: 2,
+            retry_backoff: std::time::Duration::from_millis(20),
+            retry_setters: false,
+            busy_retry_delay: std::time::Duration::from_millis(10),
+            read_unsolicited: false,
+            broadcast_capacity: 100,
+            min_command_interval: None,
+            priority_aging: None,
+            stream_sweeps: false,
+            line_terminator: LineTerminator::default(),
+            echo_enabled: false,
+            reset_timeout: std::time::Duration::from_secs(30),
+            settle_times: BTreeMap::new(),
         };
     }
 }
 
+/// Builds `TargetProperties`, starting from the defaults and only changing what is set.
+///
+/// ```
+/// use minicircuit_commands::prelude::*;
+///
+/// let properties = TargetProperties::builder()
+///     .port("/dev/ttyUSB0")
+///     .baud_rate(BaudRate::new(9600))
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TargetPropertiesBuilder {
+    properties: TargetProperties,
+}
+
+impl TargetPropertiesBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The port to connect to when autodetection fails, or to connect to directly.
+    pub fn port(mut self, port: impl Into<String>) -> Self {
+        self.properties.port = Some(port.into());
+        self
+    }
+
+    /// The vendor ID used to recognise the signal generator when autodetecting.
+    pub fn vendor_id(mut self, vendor_id: VendorId) -> Self {
+        self.properties.vendor_id = vendor_id;
+        self
+    }
+
+    /// The product ID used to recognise the signal generator when autodetecting.
+    pub fn product_id(mut self, product_id: ProductId) -> Self {
+        self.properties.product_id = product_id;
+        self
+    }
+
+    /// The baud rate of the serial connection.
+    pub fn baud_rate(mut self, baud_rate: BaudRate) -> Self {
+        self.properties.baud_rate = baud_rate;
+        self
+    }
+
+    /// The data bit structure of the serial connection.
+    pub fn data_bits(mut self, data_bits: serialport::DataBits) -> Self {
+        self.properties.data_bits = data_bits;
+        self
+    }
+
+    /// The parity bit structure of the serial connection.
+    pub fn parity(mut self, parity: serialport::Parity) -> Self {
+        self.properties.parity = parity;
+        self
+    }
+
+    /// The flow control mode of the serial connection.
+    pub fn flow_control(mut self, flow_control: serialport::FlowControl) -> Self {
+        self.properties.flow_control = flow_control;
+        self
+    }
+
+    /// The stop bit structure of the serial connection.
+    pub fn stop_bits(mut self, stop_bits: serialport::StopBits) -> Self {
+        self.properties.stop_bits = stop_bits;
+        self
+    }
+
+    /// The timeout limit for connecting and for each response.
+    pub fn connection_timeout(mut self, connection_timeout: std::time::Duration) -> Self {
+        self.properties.connection_timeout = connection_timeout;
+        self
+    }
+
+    /// The output envelope frequency and power requests are validated against.
+    /// How long connecting may take altogether.
+    pub fn connect_timeout(mut self, connect_timeout: std::time::Duration) -> Self {
+        self.properties.connect_timeout = connect_timeout;
+        self
+    }
+
+    pub fn limits(mut self, limits: DeviceLimits) -> Self {
+        self.properties.limits = limits;
+        self
+    }
+
+    /// Steps power setpoints up to the requested value rather than applying them at once.
+    pub fn power_ramp(mut self, power_ramp: PowerRamp) -> Self {
+        self.properties.power_ramp = Some(power_ramp);
+        self
+    }
+
+    /// The pause between batches of queued commands.
+    pub fn queue_poll_interval(mut self, queue_poll_interval: std::time::Duration) -> Self {
+        self.properties.queue_poll_interval = queue_poll_interval;
+        self
+    }
+
+    /// How long `send_await` waits for a response.
+    pub fn response_timeout(mut self, response_timeout: std::time::Duration) -> Self {
+        self.properties.response_timeout = response_timeout;
+        self
+    }
+
+    /// Whether the port is reopened if the device disappears mid-operation.
+    pub fn auto_reconnect(mut self, auto_reconnect: bool) -> Self {
+        self.properties.auto_reconnect = auto_reconnect;
+        self
+    }
+
+    /// The delay before the first reconnect attempt.
+    pub fn reconnect_backoff(mut self, reconnect_backoff: std::time::Duration) -> Self {
+        self.properties.reconnect_backoff = reconnect_backoff;
+        self
+    }
+
+    /// The maximum number of messages that can wait in the command queue.
+    pub fn max_queue_depth(mut self, max_queue_depth: usize) -> Self {
+        self.properties.max_queue_depth = Some(max_queue_depth);
+        self
+    }
+
+    /// The number of recent commands kept in the command log.
+    pub fn command_log_capacity(mut self, command_log_capacity: usize) -> Self {
+        self.properties.command_log_capacity = Some(command_log_capacity);
+        self
+    }
+
+    /// Whether commands are answered with their wire string instead of being sent.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.properties.dry_run = dry_run;
+        self
+    }
+
+    /// Whether other common baud rates are tried when the device doesn't answer while connecting.
+    pub fn probe_baud_rates(mut self, probe_baud_rates: bool) -> Self {
+        self.properties.probe_baud_rates = probe_baud_rates;
+        self
+    }
+
+    /// Whether commands the connected controller doesn't support are rejected instead of sent.
+    pub fn reject_unsupported(mut self, reject_unsupported: bool) -> Self {
+        self.properties.reject_unsupported = reject_unsupported;
+        self
+    }
+
+    /// Whether power setpoints outside of the device's limits are clamped instead of sent as they are.
+    pub fn clamp_power(mut self, clamp_power: bool) -> Self {
+        self.properties.clamp_power = clamp_power;
+        self
+    }
+
+    /// How many times a queued command is resent after a communication error.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.properties.max_retries = max_retries;
+        self
+    }
+
+    /// The delay before the first resend.
+    pub fn retry_backoff(mut self, retry_backoff: std::time::Duration) -> Self {
+        self.properties.retry_backoff = retry_backoff;
+        self
+    }
+
+    /// Whether setters are resent after a communication error, not only queries.
+    pub fn retry_setters(mut self, retry_setters: bool) -> Self {
+        self.properties.retry_setters = retry_setters;
+        self
+    }
+
+    /// The delay before resending a command the device answered as busy.
+    pub fn busy_retry_delay(mut self, busy_retry_delay: std::time::Duration) -> Self {
+        self.properties.busy_retry_delay = busy_retry_delay;
+        self
+    }
+
+    /// Whether lines the device sends without being asked are read and broadcast.
+    pub fn read_unsolicited(mut self, read_unsolicited: bool) -> Self {
+        self.properties.read_unsolicited = read_unsolicited;
+        self
+    }
+
+    /// How many responses the broadcast channel holds for slow subscribers.
+    pub fn broadcast_capacity(mut self, broadcast_capacity: usize) -> Self {
+        self.properties.broadcast_capacity = broadcast_capacity;
+        self
+    }
+
+    /// The least time between successive exchanges with the device.
+    pub fn min_command_interval(mut self, min_command_interval: std::time::Duration) -> Self {
+        self.properties.min_command_interval = Some(min_command_interval);
+        self
+    }
+
+    /// How long a queued message waits before its priority is raised a level.
+    pub fn priority_aging(mut self, priority_aging: std::time::Duration) -> Self {
+        self.properties.priority_aging = Some(priority_aging);
+        self
+    }
+
+    /// Whether the progress of queued sweeps is broadcast as their measurements arrive.
+    pub fn stream_sweeps(mut self, stream_sweeps: bool) -> Self {
+        self.properties.stream_sweeps = stream_sweeps;
+        self
+    }
+
+    /// The line ending written after each command.
+    pub fn line_terminator(mut self, line_terminator: LineTerminator) -> Self {
+        self.properties.line_terminator = line_terminator;
+        self
+    }
+
+    /// Whether the link echoes every command back before the reply.
+    pub fn echo_enabled(mut self, echo_enabled: bool) -> Self {
+        self.properties.echo_enabled = echo_enabled;
+        self
+    }
+
+    /// How long to wait for the device to come back after a reset.
+    pub fn reset_timeout(mut self, reset_timeout: std::time::Duration) -> Self {
+        self.properties.reset_timeout = reset_timeout;
+        self
+    }
+
+    /// How long to wait after a command of the given name, such as `SetFrequency`, before
+    /// sending the next.
+    ///
+    /// Fails with `ValidationError::UnknownCommand` for a name that isn't in `Command::NAMES`,
+    /// which would otherwise never be waited after.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use minicircuit_commands::prelude::*;
+    ///
+    /// let properties = TargetProperties::builder()
+    ///     .settle_after("SetFrequency", Duration::from_millis(5))
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// let command: Command = SetFrequency::default().into();
+    /// assert_eq!(properties.settle_time(&command), Some(Duration::from_millis(5)));
+    ///
+    /// // A misspelled name is rejected rather than ignored.
+    /// assert!(TargetProperties::builder()
+    ///     .settle_after("SetFrequncy", Duration::from_millis(5))
+    ///     .is_err());
+    /// ```
+    pub fn settle_after(
+        mut self,
+        command: impl Into<String>,
+        settle: std::time::Duration,
+    ) -> Result<Self, ValidationError> {
+        let command = command.into();
+        if !Command::NAMES.contains(&command.as_str()) {
+            return Err(ValidationError::UnknownCommand { name: command });
+        }
+
+        self.properties.settle_times.insert(command, settle);
+        Ok(self)
+    }
+
+    pub fn build(self) -> TargetProperties {
+        self.properties
+    }
+}
+
+impl TargetProperties {
+    pub fn builder() -> TargetPropertiesBuilder {
+        TargetPropertiesBuilder::new()
+    }
+
+    /// How long to wait after the command before sending the next, see `settle_times`.
+    pub fn settle_time(&self, command: &Command) -> Option<std::time::Duration> {
+        self.settle_times.get(command.name()).copied()
+    }
+}
+
+/// The line ending a signal generator expects after each command, which differs between firmware.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineTerminator {
+    /// A carriage return, `\r`.
+    #[default]
+    Cr,
+    /// A line feed, `\n`.
+    Lf,
+    /// A carriage return followed by a line feed, `\r\n`.
+    CrLf,
+}
+impl LineTerminator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineTerminator::Cr => "\r",
+            LineTerminator::Lf => "\n",
+            LineTerminator::CrLf => "\r\n",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct VendorId {
     pub vendor_id: u16,
@@ -118,3 +581,66 @@ impl Default for ProductId {
         Self { product_id: 131 }
     }
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// The frequency and power range the signal generator is allowed to operate in.
+pub struct DeviceLimits {
+    /// The lowest frequency that can be requested (in MHz).
+    pub min_frequency: Frequency,
+    /// The highest frequency that can be requested (in MHz).
+    pub max_frequency: Frequency,
+    /// The highest power setpoint that can be requested.
+    pub max_power: Watt,
+}
+
+// Configured rather than measured, so the power limit is never NaN and equality is total.
+impl Eq for DeviceLimits {}
+
+impl DeviceLimits {
+    pub fn new(min_frequency: Frequency, max_frequency: Frequency, max_power: Watt) -> Self {
+        Self {
+            min_frequency,
+            max_frequency,
+            max_power,
+        }
+    }
+
+    /// Returns true if the frequency lies within the allowed band (inclusive).
+    pub fn frequency_in_range(&self, frequency: Frequency) -> bool {
+        frequency >= self.min_frequency && frequency <= self.max_frequency
+    }
+
+    /// Returns true if the power is non-negative and doesn't exceed the maximum setpoint.
+    pub fn power_in_range(&self, power: Watt) -> bool {
+        power.power >= 0. && power <= self.max_power
+    }
+}
+impl Default for DeviceLimits {
+    fn default() -> Self {
+        // The 2.4 - 2.5GHz ISM band covered by the ISC-2425 boards,
+        // capped at the rating of the ZHL-2425-250X+ amplifier.
+        Self {
+            min_frequency: Frequency::new(2400),
+            max_frequency: Frequency::new(2500),
+            max_power: Watt::new(250.),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// Describes how a power setpoint is stepped up to its target value.
+pub struct PowerRamp {
+    /// The increase in setpoint applied at each step.
+    pub step: Watt,
+    /// The time waited between consecutive steps.
+    pub interval: std::time::Duration,
+}
+
+// Configured rather than measured, so the step is never NaN and equality is total.
+impl Eq for PowerRamp {}
+
+impl PowerRamp {
+    pub fn new(step: Watt, interval: std::time::Duration) -> Self {
+        Self { step, interval }
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError, ValidationError},
     types::{Channel, Frequency, Percentage},
 };
 
@@ -31,24 +31,9 @@ impl TryFrom<String> for GetPWMDutyCycleResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let frequency: Frequency = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u16>()
-        {
-            Ok(value) => Frequency::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let duty_cycle: Percentage = match parts[10].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u8>()
-        {
-            Ok(value) => Percentage::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let frequency: Frequency = Frequency::new(parse_field("frequency", parts[2], &response)?);
+        let duty_cycle: Percentage =
+            Percentage::new(parse_field("duty_cycle", parts[10], &response)?);
 
         Ok(GetPWMDutyCycleResponse {
             frequency,
@@ -64,9 +49,9 @@ pub struct GetPWMDutyCycle {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPWMDutyCycle {
-    fn into(self) -> String {
-        format!("$DCG,{}", self.channel)
+impl From<&GetPWMDutyCycle> for String {
+    fn from(command: &GetPWMDutyCycle) -> String {
+        format!("$DCG,{}", command.channel)
     }
 }
 
@@ -87,7 +72,7 @@ impl Default for GetPWMDutyCycle {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetPWMDutyCycleResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -119,9 +104,9 @@ pub struct SetPWMDutyCycle {
     pub duty_cycle: Percentage,
 }
 
-impl Into<String> for SetPWMDutyCycle {
-    fn into(self) -> String {
-        format!("$DCS,{},{}", self.channel, self.duty_cycle)
+impl From<&SetPWMDutyCycle> for String {
+    fn from(command: &SetPWMDutyCycle) -> String {
+        format!("$DCS,{},{}", command.channel, command.duty_cycle)
     }
 }
 
@@ -133,6 +118,13 @@ impl SetPWMDutyCycle {
             duty_cycle,
         }
     }
+
+    /// Returns a handler to call the command, if the duty cycle lies between 0% and 100%.
+    pub fn try_new(channel: Channel, duty_cycle: Percentage) -> Result<Self, ValidationError> {
+        ValidationError::check_range("duty cycle", duty_cycle.percentage as f64, 0., 100.)?;
+
+        Ok(Self::new(channel, duty_cycle))
+    }
 }
 
 impl Default for SetPWMDutyCycle {
@@ -5,7 +5,7 @@ use crate::data_types::{
     types::{Channel, Frequency},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetPWMFrequencyResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -33,9 +33,9 @@ pub struct SetPWMFrequency {
     pub frequency: Frequency,
 }
 
-impl Into<String> for SetPWMFrequency {
-    fn into(self) -> String {
-        format!("$DCFS,{},{},0", self.channel, self.frequency)
+impl From<&SetPWMFrequency> for String {
+    fn from(command: &SetPWMFrequency) -> String {
+        format!("$DCFS,{},{},0", command.channel, command.frequency)
     }
 }
 
@@ -1,8 +1,94 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
-use crate::data_types::{errors::MWError, types::Channel};
+use crate::data_types::{
+    errors::{MWError, ValidationError},
+    types::Channel,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+/// A pulse of RF output made of timed enables, each `on_time` long and `off_time` apart.
+///
+/// The signal generator switches RF off by itself once a timed enable has run out, so only the
+/// first enable needs to reach it for the output to end up off again.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use minicircuit_commands::{data_types::types::Channel, pwm::timed_rf::TimedRfEnable};
+///
+/// let pulse = TimedRfEnable::new(Duration::from_millis(250), None, None).unwrap();
+/// let command = pulse.command(Channel::default());
+///
+/// // The duration goes over the wire in microseconds.
+/// assert_eq!(String::from(&command), "$ECST,1,1,250000");
+/// assert_eq!(command.on_time(), Duration::from_millis(250));
+/// ```
+pub struct TimedRfEnable {
+    /// How long RF output stays on for each enable.
+    pub on_time: Duration,
+    /// How long RF output stays off between enables, none by default.
+    pub off_time: Option<Duration>,
+    /// The number of enables, one by default.
+    pub cycles: Option<u32>,
+}
+
+impl TimedRfEnable {
+    /// The longest timed enable, as the duration is sent in microseconds as a `u32`.
+    pub const MAX_ON_TIME: Duration = Duration::from_micros(u32::MAX as u64);
+
+    /// Returns the pulse if the on time lies within 1µs - `MAX_ON_TIME` and
+    /// there is at least one cycle.
+    pub fn new(
+        on_time: Duration,
+        off_time: Option<Duration>,
+        cycles: Option<u32>,
+    ) -> Result<Self, ValidationError> {
+        let pulse = Self {
+            on_time,
+            off_time,
+            cycles,
+        };
+        pulse.validate()?;
+
+        Ok(pulse)
+    }
+
+    /// Checks the on time fits the command and there is at least one cycle.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        ValidationError::check_range(
+            "timed enable duration (µs)",
+            self.on_time.as_micros() as f64,
+            1.,
+            u32::MAX as f64,
+        )?;
+        ValidationError::check_range(
+            "timed enable cycles",
+            self.cycles() as f64,
+            1.,
+            u32::MAX as f64,
+        )
+    }
+
+    /// The number of enables, one if left unset.
+    pub fn cycles(&self) -> u32 {
+        self.cycles.unwrap_or(1)
+    }
+
+    /// How long RF output stays off between enables, zero if left unset.
+    pub fn off_time(&self) -> Duration {
+        self.off_time.unwrap_or_default()
+    }
+
+    /// The command for a single enable. The on time is capped at `MAX_ON_TIME`.
+    pub fn command(&self, channel: Channel) -> SetTimedRFEnable {
+        let duration = u32::try_from(self.on_time.as_micros()).unwrap_or(u32::MAX);
+        SetTimedRFEnable::new(channel, duration)
+    }
+}
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetTimedRFEnableResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -30,9 +116,9 @@ pub struct SetTimedRFEnable {
     pub duration: u32,
 }
 
-impl Into<String> for SetTimedRFEnable {
-    fn into(self) -> String {
-        format!("$ECST,{},1,{}", self.channel, self.duration)
+impl From<&SetTimedRFEnable> for String {
+    fn from(command: &SetTimedRFEnable) -> String {
+        format!("$ECST,{},1,{}", command.channel, command.duration)
     }
 }
 
@@ -41,6 +127,17 @@ impl SetTimedRFEnable {
     pub fn new(channel: Channel, duration: u32) -> Self {
         Self { channel, duration }
     }
+
+    /// Returns a handler to call the command, if the duration lies within
+    /// 1µs - `TimedRfEnable::MAX_ON_TIME`.
+    pub fn from_duration(channel: Channel, duration: Duration) -> Result<Self, ValidationError> {
+        Ok(TimedRfEnable::new(duration, None, None)?.command(channel))
+    }
+
+    /// How long RF output stays on.
+    pub fn on_time(&self) -> Duration {
+        Duration::from_micros(self.duration as u64)
+    }
 }
 
 impl Default for SetTimedRFEnable {
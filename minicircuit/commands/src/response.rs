@@ -1,6 +1,13 @@
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    data_types::errors::{MWError, ReadWriteError},
+    capabilities::CommandGroup,
+    command::{Command, Priority},
+    data_types::{
+        errors::{MWError, ReadWriteError, ReadWriteErrorKind},
+        measurement::{Measurement, Unit},
+        power::Power,
+    },
     prelude::{Frequency, Phase, Watt},
 };
 
@@ -65,9 +72,12 @@ use super::{
     },
 };
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 /// The response can consist of feedback from the signal generator for the given command,
 /// error from sending the command over serial connection, or error from the signal generator executing the command.
+///
+/// Equality compares measured values exactly, which suits responses built in code or replayed
+/// from a simulator but not readings from a real device.
 pub enum Response {
     GetPAPowerADCResponse(GetPAPowerADCResponse),
     GetPACurrentResponse(GetPACurrentResponse),
@@ -141,9 +151,428 @@ pub enum Response {
     SetZHLTriggerDelayResponse(SetZHLTriggerDelayResponse),
     ReadWriteError(ReadWriteError),
     MWError(MWError),
+    #[deprecated(
+        note = "Commands without a reply, such as `SetUartBaudRate`, are answered with `Response::Ack`."
+    )]
     SetUartBaudRate,
+    /// Answers a command the signal generator doesn't reply to once it has been written,
+    /// see `Command::expects_response`. Holds the command that was sent.
+    Ack(Command),
+    /// The device disappeared and the driver is attempting to reopen the port.
+    /// Holds the number of the attempt that is about to be made, starting at 1.
+    Reconnecting(u32),
+    /// The port was reopened after the device disappeared.
+    Reconnected,
+    /// The signal generator replied, but in a format the response couldn't be parsed from, such
+    /// as from firmware that lays a reply out differently. Holds the command, the reply as it
+    /// arrived and why it couldn't be parsed.
+    ///
+    /// Errors the signal generator reported, such as `ERR04`, remain a `Response::MWError`.
+    UnparsedReply {
+        command: Command,
+        raw: String,
+        error: MWError,
+    },
+    /// The signal generator answered `ERR06`, it was busy and didn't carry out the command.
+    ///
+    /// Unlike a `ReadWriteError` that timed out, the device did receive the command.
+    /// See `ReadWriteErrorKind` for how the queue handles each.
+    Busy,
+    /// A power setpoint outside of the device's limits was clamped to the nearest limit before
+    /// it was sent, see `TargetProperties::clamp_power`. Followed by the response to the setter.
+    PowerClamped {
+        requested: Power,
+        applied: Power,
+    },
+    /// The heartbeat started with `MiniCircuitDriver::start_watchdog` could no longer be queued
+    /// and has stopped, usually because the driver disconnected.
+    HeartbeatStopped,
+    /// Returned instead of sending the command when the driver runs in dry-run mode.
+    /// Holds the string that would have been written to the signal generator.
+    DryRun(String),
+    /// The unparsed reply to a `Command::Raw`.
+    Raw(String),
+    /// Answers a `Command::Barrier` once the commands queued ahead of it have been processed.
+    Barrier,
+    /// Returned instead of sending a command the connected controller doesn't support,
+    /// when `TargetProperties::reject_unsupported` is set.
+    Unsupported(CommandGroup),
+    /// A line the signal generator sent without being asked, such as a notification of an SOA trip.
+    ///
+    /// Only read when `TargetProperties::read_unsolicited` is set.
+    Unsolicited(String),
+    /// The subscriber fell behind and missed the given number of responses, which were dropped
+    /// from the broadcast channel before it could read them.
+    ///
+    /// Raise `TargetProperties::broadcast_capacity` if this happens regularly.
+    Lagged(u64),
+    /// No queued command was processed within the timeout of the dead-man switch started with
+    /// `MiniCircuitDriver::enable_rf_with_deadman`, so RF output was switched off.
+    ///
+    /// Broadcast after the response to the `SetRFOutput` that switched it off.
+    DeadmanTripped,
+    /// Another measurement of the queued sweep with the id `sweep_id` arrived, out of the `total`
+    /// it was asked for. The sweep's own response follows once it completes.
+    ///
+    /// Only sent when `TargetProperties::stream_sweeps` is set.
+    SweepProgress {
+        sweep_id: u64,
+        done: usize,
+        total: usize,
+    },
+}
+
+macro_rules! define_response_accessors {
+    (
+        $(
+            $accessor:ident => $variant:ident($inner:ty)
+        ),+ $(,)?
+    ) => {
+        impl Response {
+            $(
+                #[doc = concat!("Returns the contents if this is a `Response::", stringify!($variant), "`.")]
+                pub fn $accessor(&self) -> Option<&$inner> {
+                    match self {
+                        Response::$variant(inner) => Some(inner),
+                        _ => None,
+                    }
+                }
+            )+
+        }
+    }
+}
+
+define_response_accessors! {
+    as_papower_adc => GetPAPowerADCResponse(GetPAPowerADCResponse),
+    as_pa_current => GetPACurrentResponse(GetPACurrentResponse),
+    as_papower_dbm => GetPAPowerDBMResponse(GetPAPowerDBMResponse),
+    as_papower_watt => GetPAPowerWattResponse(GetPAPowerWattResponse),
+    as_frequency => GetFrequencyResponse(GetFrequencyResponse),
+    as_set_frequency => SetFrequencyResponse(Frequency),
+    as_rf_output => GetRFOutputResponse(GetRFOutputResponse),
+    as_set_rf_output => SetRFOutputResponse(bool),
+    as_phase => GetPhaseResponse(GetPhaseResponse),
+    as_set_phase => SetPhaseResponse(Phase),
+    as_papower_setpoint_dbm => GetPAPowerSetpointDBMResponse(GetPAPowerSetpointDBMResponse),
+    as_papower_setpoint_watt => GetPAPowerSetpointWattResponse(GetPAPowerSetpointWattResponse),
+    as_set_papower_setpoint_dbm => SetPAPowerSetpointDBMResponse(SetPAPowerSetpointDBMResponse),
+    as_set_papower_setpoint_watt => SetPAPowerSetpointWattResponse(Watt),
+    as_pa_temp => GetPATempResponse(GetPATempResponse),
+    as_pa_voltage => GetPAVoltageResponse(GetPAVoltageResponse),
+    as_dll_config => GetDLLConfigResponse(GetDLLConfigResponse),
+    as_set_dll_config => SetDLLConfigResponse(SetDLLConfigResponse),
+    as_dll_enabled => GetDLLEnabledResponse(GetDLLEnabledResponse),
+    as_set_dll_enabled => SetDLLEnabledResponse(SetDLLEnabledResponse),
+    as_perform_sweep_dbm => PerformSweepDBMResponse(PerformSweepDBMResponse),
+    as_perform_sweep_watt => PerformSweepWattResponse(PerformSweepWattResponse),
+    as_clear_errors => ClearErrorsResponse(ClearErrorsResponse),
+    as_pa_errors => GetPAErrorsResponse(GetPAErrorsResponse),
+    as_status => GetStatusResponse(GetStatusResponse),
+    as_identity => GetIdentityResponse(GetIdentityResponse),
+    as_isc_temp => GetISCTempResponse(GetISCTempResponse),
+    as_uptime => GetUptimeResponse(GetUptimeResponse),
+    as_version => GetVersionResponse(GetVersionResponse),
+    as_attenuation => GetAttenuationResponse(GetAttenuationResponse),
+    as_set_attenuation => SetAttenuationResponse(SetAttenuationResponse),
+    as_auto_gain_state => GetAutoGainStateResponse(GetAutoGainStateResponse),
+    as_set_auto_gain_state => SetAutoGainStateResponse(SetAutoGainStateResponse),
+    as_magnitude => GetMagnitudeResponse(GetMagnitudeResponse),
+    as_set_magnitude => SetMagnitudeResponse(SetMagnitudeResponse),
+    as_isc_power_output => GetISCPowerOutputResponse(GetISCPowerOutputResponse),
+    as_set_isc_power_output => SetISCPowerOutputResponse(SetISCPowerOutputResponse),
+    as_pwm_duty_cycle => GetPWMDutyCycleResponse(GetPWMDutyCycleResponse),
+    as_set_pwm_duty_cycle => SetPWMDutyCycleResponse(SetPWMDutyCycleResponse),
+    as_set_pwm_frequency => SetPWMFrequencyResponse(SetPWMFrequencyResponse),
+    as_set_timed_rf_enable => SetTimedRFEnableResponse(SetTimedRFEnableResponse),
+    as_soa_config => GetSOAConfigResponse(GetSOAConfigResponse),
+    as_set_soa_config => SetSOAConfigResponse(SetSOAConfigResponse),
+    as_soa_current_config => GetSOACurrentConfigResponse(GetSOACurrentConfigResponse),
+    as_set_soa_current_config => SetSOACurrentConfigResponse(SetSOACurrentConfigResponse),
+    as_soa_dissipation_config => GetSOADissipationConfigResponse(GetSOADissipationConfigResponse),
+    as_set_soa_dissipation_config => SetSOADissipationConfigResponse(SetSOADissipationConfigResponse),
+    as_soa_forward_power_limits => GetSOAForwardPowerLimitsResponse(GetSOAForwardPowerLimitsResponse),
+    as_set_soa_forward_power_limits => SetSOAForwardPowerLimitsResponse(SetSOAForwardPowerLimitsResponse),
+    as_set_soa_grace_timer => SetSOAGraceTimerResponse(SetSOAGraceTimerResponse),
+    as_soa_power_config => GetSOAPowerConfigResponse(GetSOAPowerConfigResponse),
+    as_set_soa_power_config => SetSOAPowerConfigResponse(SetSOAPowerConfigResponse),
+    as_soa_temp_config => GetSOATempConfigResponse(GetSOATempConfigResponse),
+    as_set_soa_temp_config => SetSOATempConfigResponse(SetSOATempConfigResponse),
+    as_soa_voltage_config => GetSOAVoltageConfigResponse(GetSOAVoltageConfigResponse),
+    as_set_soa_voltage_config => SetSOAVoltageConfigResponse(SetSOAVoltageConfigResponse),
+    as_set_soa_watchdog_config => SetSOAWatchdogConfigResponse(SetSOAWatchdogConfigResponse),
+    as_channel_id => GetChannelIDResponse(GetChannelIDResponse),
+    as_set_channel_id => SetChannelIDResponse(SetChannelIDResponse),
+    as_clock_source => GetClockSourceResponse(GetClockSourceResponse),
+    as_set_clock_source => SetClockSourceResponse(SetClockSourceResponse),
+    as_set_communication_interface => SetCommunicationInterfaceResponse(SetCommunicationInterfaceResponse),
+    as_power_max_dbm => GetPowerMaxDbmResponse(GetPowerMaxDbmResponse),
+    as_set_power_max_dbm => SetPowerMaxDbmResponse(SetPowerMaxDbmResponse),
+    as_power_min_dbm => GetPowerMinDbmResponse(GetPowerMinDbmResponse),
+    as_set_power_min_dbm => SetPowerMinDbmResponse(SetPowerMinDbmResponse),
+    as_power_offset => GetPowerOffsetResponse(GetPowerOffsetResponse),
+    as_set_power_offset => SetPowerOffsetResponse(SetPowerOffsetResponse),
+    as_reset_system => ResetSystemResponse(ResetSystemResponse),
+    as_set_zhl_trigger_delay => SetZHLTriggerDelayResponse(SetZHLTriggerDelayResponse),
+    as_read_write_error => ReadWriteError(ReadWriteError),
+    as_mw_error => MWError(MWError),
+    as_reconnecting => Reconnecting(u32),
+    as_dry_run => DryRun(String),
+    as_raw => Raw(String),
+    as_unsolicited => Unsolicited(String),
+    as_lagged => Lagged(u64),
+    as_unsupported => Unsupported(CommandGroup),
 }
 
+impl Response {
+    /// Returns true if the command failed, either while communicating with the
+    /// signal generator or while the signal generator was executing it.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Response::ReadWriteError(_)
+                | Response::MWError(_)
+                | Response::UnparsedReply { .. }
+                | Response::Busy
+                | Response::Unsupported(_)
+        )
+    }
+
+    /// The cause of the failure, for both communication errors and errors reported by the
+    /// signal generator. `None` if the command didn't fail in either way.
+    pub fn error_kind(&self) -> Option<ReadWriteErrorKind> {
+        match self {
+            Response::ReadWriteError(read_write_error) => Some(read_write_error.kind.clone()),
+            Response::MWError(mwerror) => Some(mwerror.clone().into()),
+            Response::UnparsedReply { .. } => Some(ReadWriteErrorKind::Parse),
+            Response::Busy => Some(ReadWriteErrorKind::Busy),
+            _ => None,
+        }
+    }
+
+    /// Flattens the measured values the response carries into one record each, such as for
+    /// writing to a time series database without matching on every response.
+    ///
+    /// Responses that don't carry a measurement, such as acknowledgements and errors, return
+    /// an empty list.
+    ///
+    /// ```
+    /// use minicircuit_commands::prelude::*;
+    ///
+    /// let response = Response::GetPAPowerWattResponse(
+    ///     GetPAPowerWattResponse::try_from("OK,1,250.0,3.5".to_string()).unwrap(),
+    /// );
+    ///
+    /// for measurement in response.to_measurements() {
+    ///     println!("{} = {} {}", measurement.name, measurement.value, measurement.unit);
+    /// }
+    /// assert_eq!(
+    ///     response.to_measurements()[1],
+    ///     Measurement::new("reflected_power", 3.5, Unit::Watt)
+    /// );
+    /// ```
+    pub fn to_measurements(&self) -> Vec<Measurement> {
+        match self {
+            Response::GetFrequencyResponse(r) => vec![Measurement::new(
+                "frequency",
+                r.frequency.frequency as f64,
+                Unit::Megahertz,
+            )],
+            Response::GetPhaseResponse(r) => {
+                vec![Measurement::new(
+                    "phase",
+                    r.phase.phase as f64,
+                    Unit::Degrees,
+                )]
+            }
+            Response::GetPAPowerWattResponse(r) => vec![
+                Measurement::new("forward_power", r.forward.power as f64, Unit::Watt),
+                Measurement::new("reflected_power", r.reflected.power as f64, Unit::Watt),
+            ],
+            Response::GetPAPowerDBMResponse(r) => vec![
+                Measurement::new("forward_power", r.forward.power as f64, Unit::Dbm),
+                Measurement::new("reflected_power", r.reflected.power as f64, Unit::Dbm),
+            ],
+            Response::GetPAPowerSetpointWattResponse(r) => vec![Measurement::new(
+                "power_setpoint",
+                r.power.power as f64,
+                Unit::Watt,
+            )],
+            Response::GetPAPowerSetpointDBMResponse(r) => vec![Measurement::new(
+                "power_setpoint",
+                r.power.power as f64,
+                Unit::Dbm,
+            )],
+            Response::GetPATempResponse(r) => vec![Measurement::new(
+                "pa_temperature",
+                r.temperature.temperature as f64,
+                Unit::Celsius,
+            )],
+            Response::GetISCTempResponse(r) => vec![Measurement::new(
+                "isc_temperature",
+                r.temperature.temperature as f64,
+                Unit::Celsius,
+            )],
+            Response::GetPACurrentResponse(r) => vec![Measurement::new(
+                "pa_current",
+                r.current.current as f64,
+                Unit::Ampere,
+            )],
+            Response::GetPAVoltageResponse(r) => vec![Measurement::new(
+                "pa_voltage",
+                r.voltage.voltage as f64,
+                Unit::Volt,
+            )],
+            Response::GetUptimeResponse(r) => vec![Measurement::new(
+                "uptime",
+                r.uptime.seconds as f64,
+                Unit::Second,
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Response {
+    /// Compares two responses, allowing measured values to differ by up to `tol`.
+    ///
+    /// The tolerance is applied in the unit of each field: watts or dBm for power, ADC counts for
+    /// raw power readings, amperes for current, volts for voltage, degrees Celsius for temperature,
+    /// dB for attenuation and the DLL threshold. Frequencies, channel ids, flags and every other
+    /// discrete field must match exactly, as must responses of different variants.
+    /// Sweeps are compared measurement by measurement, and must hold as many of them.
+    pub fn approx_eq(&self, other: &Response, tol: f64) -> bool {
+        let close = |a: f32, b: f32| (f64::from(a) - f64::from(b)).abs() <= tol;
+
+        match (self, other) {
+            (Response::GetPAPowerADCResponse(a), Response::GetPAPowerADCResponse(b)) => {
+                close(a.forward.power, b.forward.power)
+                    && close(a.reflected.power, b.reflected.power)
+            }
+            (Response::GetPACurrentResponse(a), Response::GetPACurrentResponse(b)) => {
+                close(a.current.current, b.current.current)
+            }
+            (Response::GetPAPowerDBMResponse(a), Response::GetPAPowerDBMResponse(b)) => {
+                close(a.forward.power, b.forward.power)
+                    && close(a.reflected.power, b.reflected.power)
+            }
+            (Response::GetPAPowerWattResponse(a), Response::GetPAPowerWattResponse(b)) => {
+                close(a.forward.power, b.forward.power)
+                    && close(a.reflected.power, b.reflected.power)
+            }
+            (
+                Response::GetPAPowerSetpointDBMResponse(a),
+                Response::GetPAPowerSetpointDBMResponse(b),
+            ) => close(a.power.power, b.power.power),
+            (
+                Response::GetPAPowerSetpointWattResponse(a),
+                Response::GetPAPowerSetpointWattResponse(b),
+            ) => close(a.power.power, b.power.power),
+            (
+                Response::SetPAPowerSetpointWattResponse(a),
+                Response::SetPAPowerSetpointWattResponse(b),
+            ) => close(a.power, b.power),
+            (Response::GetPATempResponse(a), Response::GetPATempResponse(b)) => close(
+                f32::from(a.temperature.temperature),
+                f32::from(b.temperature.temperature),
+            ),
+            (Response::GetISCTempResponse(a), Response::GetISCTempResponse(b)) => close(
+                f32::from(a.temperature.temperature),
+                f32::from(b.temperature.temperature),
+            ),
+            (Response::GetPAVoltageResponse(a), Response::GetPAVoltageResponse(b)) => {
+                close(a.voltage.voltage, b.voltage.voltage)
+            }
+            (Response::GetDLLConfigResponse(a), Response::GetDLLConfigResponse(b)) => {
+                let mut a = a.clone();
+                let threshold = b.threshold.threshold;
+                let within = close(a.threshold.threshold, threshold);
+                a.threshold.threshold = threshold;
+                within && &a == b
+            }
+            (Response::PerformSweepDBMResponse(a), Response::PerformSweepDBMResponse(b)) => {
+                a.measurement_frequency == b.measurement_frequency
+                    && close(a.forward_power.power, b.forward_power.power)
+                    && close(a.reflected_power.power, b.reflected_power.power)
+                    && a.expected_points == b.expected_points
+                    && a.points.len() == b.points.len()
+                    && a.points.iter().zip(&b.points).all(|(a, b)| {
+                        a.frequency == b.frequency
+                            && close(a.forward_power.power, b.forward_power.power)
+                            && close(a.reflected_power.power, b.reflected_power.power)
+                    })
+            }
+            (Response::PerformSweepWattResponse(a), Response::PerformSweepWattResponse(b)) => {
+                a.measurement_frequency == b.measurement_frequency
+                    && close(a.forward_power.power, b.forward_power.power)
+                    && close(a.reflected_power.power, b.reflected_power.power)
+                    && a.expected_points == b.expected_points
+                    && a.points.len() == b.points.len()
+                    && a.points.iter().zip(&b.points).all(|(a, b)| {
+                        a.frequency == b.frequency
+                            && close(a.forward_power.power, b.forward_power.power)
+                            && close(a.reflected_power.power, b.reflected_power.power)
+                    })
+            }
+            (Response::GetAttenuationResponse(a), Response::GetAttenuationResponse(b)) => {
+                close(a.attenuation.attenuation, b.attenuation.attenuation)
+            }
+            (Response::GetISCPowerOutputResponse(a), Response::GetISCPowerOutputResponse(b)) => {
+                close(a.power.power, b.power.power)
+            }
+            (
+                Response::GetSOACurrentConfigResponse(a),
+                Response::GetSOACurrentConfigResponse(b),
+            ) => {
+                close(a.high_current.current, b.high_current.current)
+                    && close(a.shutdown_current.current, b.shutdown_current.current)
+            }
+            (
+                Response::GetSOADissipationConfigResponse(a),
+                Response::GetSOADissipationConfigResponse(b),
+            ) => {
+                close(a.high_dissipation.power, b.high_dissipation.power)
+                    && close(a.shutdown_dissipation.power, b.shutdown_dissipation.power)
+            }
+            (
+                Response::GetSOAForwardPowerLimitsResponse(a),
+                Response::GetSOAForwardPowerLimitsResponse(b),
+            ) => {
+                close(a.high_forward_power.power, b.high_forward_power.power)
+                    && close(
+                        a.shutdown_forward_power.power,
+                        b.shutdown_forward_power.power,
+                    )
+            }
+            (Response::GetSOAPowerConfigResponse(a), Response::GetSOAPowerConfigResponse(b)) => {
+                close(a.high_reflection.power, b.high_reflection.power)
+                    && close(a.shutdown_reflection.power, b.shutdown_reflection.power)
+            }
+            (
+                Response::GetSOAVoltageConfigResponse(a),
+                Response::GetSOAVoltageConfigResponse(b),
+            ) => {
+                close(
+                    a.shutdown_min_voltage.voltage,
+                    b.shutdown_min_voltage.voltage,
+                ) && close(a.low_voltage.voltage, b.low_voltage.voltage)
+                    && close(a.high_voltage.voltage, b.high_voltage.voltage)
+                    && close(
+                        a.shutdown_max_voltage.voltage,
+                        b.shutdown_max_voltage.voltage,
+                    )
+            }
+            (Response::GetPowerMinDbmResponse(a), Response::GetPowerMinDbmResponse(b)) => {
+                close(a.min.power, b.min.power)
+            }
+            (Response::GetPowerMaxDbmResponse(a), Response::GetPowerMaxDbmResponse(b)) => {
+                close(a.max.power, b.max.power)
+            }
+            _ => self == other,
+        }
+    }
+}
+
+#[allow(deprecated)]
 impl Into<String> for Response {
     fn into(self) -> String {
         let response = match self {
@@ -159,15 +588,27 @@ impl Into<String> for Response {
                     set_frequency_response.frequency
                 )
             }
-            Response::ReadWriteError(read_write_error) => {
-                format!(
+            Response::ReadWriteError(read_write_error) => match read_write_error.retries {
+                0 => format!(
                     "An error occurred sending a command to the signal generator. \n{}",
                     read_write_error.description
-                )
-            }
+                ),
+                retries => format!(
+                    "An error occurred sending a command to the signal generator, even after {} retries. \n{}",
+                    retries, read_write_error.description
+                ),
+            },
             Response::MWError(mwerror) => {
                 format!("An error occurred executing a command. \n{}", mwerror)
             }
+            Response::UnparsedReply {
+                command,
+                raw,
+                error,
+            } => format!(
+                "The controller replied {:?} to {}, which couldn't be parsed. \n{}",
+                raw, command, error
+            ),
             Response::GetPAPowerADCResponse(get_papower_adcresponse) => {
                 format!(
                     "The forward PA power ADC is currently {}ADC and reflected is {}ADC.",
@@ -721,8 +1162,83 @@ impl Into<String> for Response {
             Response::SetUartBaudRate => {
                 format!("Updating UART baud rate command was successfully sent to the controller.")
             }
+            Response::Ack(command) => format!("Sent to the controller, which doesn't reply: {}", command),
+            Response::Reconnecting(attempt) => {
+                format!("Connection to the controller was lost. Reconnect attempt {}.", attempt)
+            }
+            Response::Reconnected => "Connection to the controller was restored.".to_string(),
+            Response::Busy => "The controller was busy and didn't carry out the command.".to_string(),
+            Response::PowerClamped { requested, applied } => format!(
+                "The power setpoint of {} was clamped to {} to stay within the limits of the controller.",
+                requested, applied
+            ),
+            Response::HeartbeatStopped => {
+                "The heartbeat to the controller has stopped.".to_string()
+            }
+            Response::DryRun(wire) => format!("Dry run, the command was not sent: {}", wire),
+            Response::Raw(reply) => format!("The controller replied: {}", reply),
+            Response::Barrier => "Every command queued ahead was processed.".to_string(),
+            Response::Unsupported(group) => {
+                format!("The connected controller doesn't support {} commands.", group)
+            }
+            Response::Unsolicited(line) => format!("The controller sent: {}", line),
+            Response::SweepProgress { done, total, .. } => {
+                format!("Received {} of {} sweep measurements.", done, total)
+            }
+            Response::DeadmanTripped => {
+                "No command was received in time, RF output was switched off.".to_string()
+            }
+            Response::Lagged(skipped) => {
+                format!("Fell behind and missed {} responses from the controller.", skipped)
+            }
         };
 
         response
     }
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// A response paired with the id of the queued message that produced it.
+///
+/// The id is the one returned when the message was enqueued, so callers with several
+/// commands in flight can tell which response belongs to which command.
+pub struct CorrelatedResponse {
+    pub id: u64,
+    pub response: Response,
+    /// The priority of the message that produced the response.
+    ///
+    /// `None` for notices from the driver itself, such as `Response::Reconnecting`.
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    /// The tag of the message that produced the response, if it was given one.
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+impl CorrelatedResponse {
+    /// Returns a response without a priority or tag, as used for notices from the driver.
+    pub fn new(id: u64, response: Response) -> Self {
+        Self {
+            id,
+            response,
+            priority: None,
+            tag: None,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: Option<String>) -> Self {
+        self.tag = tag;
+        self
+    }
+}
+
+impl From<CorrelatedResponse> for String {
+    fn from(correlated: CorrelatedResponse) -> Self {
+        correlated.response.into()
+    }
+}
@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-use crate::data_types::{errors::MWError, types::Channel};
+use crate::data_types::{
+    errors::{parse_field, MWError},
+    types::Channel,
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetSOAConfigResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -48,28 +51,28 @@ pub struct SetSOAConfig {
     pub dissipation_enabled: bool,
 }
 
-impl Into<String> for SetSOAConfig {
-    fn into(self) -> String {
-        let temp_enabled: u8 = match self.temp_enabled {
+impl From<&SetSOAConfig> for String {
+    fn from(command: &SetSOAConfig) -> String {
+        let temp_enabled: u8 = match command.temp_enabled {
             true => 1,
             false => 0,
         };
-        let reflection_enabled: u8 = match self.reflection_enabled {
+        let reflection_enabled: u8 = match command.reflection_enabled {
             true => 1,
             false => 0,
         };
-        let external_watchdog_enabled: u8 = match self.external_watchdog_enabled {
+        let external_watchdog_enabled: u8 = match command.external_watchdog_enabled {
             true => 1,
             false => 0,
         };
-        let dissipation_enabled: u8 = match self.dissipation_enabled {
+        let dissipation_enabled: u8 = match command.dissipation_enabled {
             true => 1,
             false => 0,
         };
 
         format!(
             "$SOA,{},{},1,{},{},{}",
-            self.channel,
+            command.channel,
             temp_enabled,
             reflection_enabled,
             external_watchdog_enabled,
@@ -143,44 +146,11 @@ impl TryFrom<String> for GetSOAConfigResponse {
         if temp_parts.len() != 2 || reflection_parts.len() != 2 || watchdog_parts.len() != 2 {
             return Err(Self::Error::FailedParseResponse);
         }
-        let temp_enabled: bool = match temp_parts[1].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u8>()
-        {
-            Ok(value) => match value {
-                1 => true,
-                _ => false,
-            },
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let reflection_enabled: bool = match reflection_parts[1].split('.').collect::<Vec<&str>>()
-            [0]
-        .trim()
-        .parse::<u8>()
-        {
-            Ok(value) => match value {
-                1 => true,
-                _ => false,
-            },
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let temp_enabled: bool = parse_field::<u8>("temp_enabled", temp_parts[1], &response)? == 1;
+        let reflection_enabled: bool =
+            parse_field::<u8>("reflection_enabled", reflection_parts[1], &response)? == 1;
         let external_watchdog_enabled: bool =
-            match watchdog_parts[1].split('.').collect::<Vec<&str>>()[0]
-                .trim()
-                .parse::<u8>()
-            {
-                Ok(value) => match value {
-                    1 => true,
-                    _ => false,
-                },
-                Err(_) => {
-                    return Err(Self::Error::FailedParseResponse);
-                }
-            };
+            parse_field::<u8>("external_watchdog_enabled", watchdog_parts[1], &response)? == 1;
 
         Ok(GetSOAConfigResponse {
             temp_enabled,
@@ -197,9 +167,9 @@ pub struct GetSOAConfig {
     pub channel: Channel,
 }
 
-impl Into<String> for GetSOAConfig {
-    fn into(self) -> String {
-        format!("$SOG,{}", self.channel)
+impl From<&GetSOAConfig> for String {
+    fn from(command: &GetSOAConfig) -> String {
+        format!("$SOG,{}", command.channel)
     }
 }
 
@@ -1,11 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Amperes, Channel},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetSOACurrentConfigResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -44,11 +44,11 @@ pub struct SetSOACurrentConfig {
     pub shutdown_current: Amperes,
 }
 
-impl Into<String> for SetSOACurrentConfig {
-    fn into(self) -> String {
+impl From<&SetSOACurrentConfig> for String {
+    fn from(command: &SetSOACurrentConfig) -> String {
         format!(
             "$SCS,{},{},{}",
-            self.channel, self.high_current, self.shutdown_current
+            command.channel, command.high_current, command.shutdown_current
         )
     }
 }
@@ -103,18 +103,9 @@ impl TryFrom<String> for GetSOACurrentConfigResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let high_current: Amperes = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Amperes::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let shutdown_current: Amperes = match parts[3].trim().parse::<f32>() {
-            Ok(value) => Amperes::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let high_current: Amperes = Amperes::new(parse_field("high_current", parts[2], &response)?);
+        let shutdown_current: Amperes =
+            Amperes::new(parse_field("shutdown_current", parts[3], &response)?);
 
         Ok(GetSOACurrentConfigResponse {
             high_current,
@@ -139,9 +130,9 @@ pub struct GetSOACurrentConfig {
     pub channel: Channel,
 }
 
-impl Into<String> for GetSOACurrentConfig {
-    fn into(self) -> String {
-        format!("$SCG,{}", self.channel)
+impl From<&GetSOACurrentConfig> for String {
+    fn from(command: &GetSOACurrentConfig) -> String {
+        format!("$SCG,{}", command.channel)
     }
 }
 
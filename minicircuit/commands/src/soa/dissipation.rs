@@ -1,11 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Watt},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetSOADissipationConfigResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -44,11 +44,11 @@ pub struct SetSOADissipationConfig {
     pub shutdown_dissipation: Watt,
 }
 
-impl Into<String> for SetSOADissipationConfig {
-    fn into(self) -> String {
+impl From<&SetSOADissipationConfig> for String {
+    fn from(command: &SetSOADissipationConfig) -> String {
         format!(
             "$SDS,{},{},{}",
-            self.channel, self.high_dissipation, self.shutdown_dissipation
+            command.channel, command.high_dissipation, command.shutdown_dissipation
         )
     }
 }
@@ -103,18 +103,10 @@ impl TryFrom<String> for GetSOADissipationConfigResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let high_dissipation: Watt = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Watt::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let shutdown_dissipation: Watt = match parts[3].trim().parse::<f32>() {
-            Ok(value) => Watt::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let high_dissipation: Watt =
+            Watt::new(parse_field("high_dissipation", parts[2], &response)?);
+        let shutdown_dissipation: Watt =
+            Watt::new(parse_field("shutdown_dissipation", parts[3], &response)?);
 
         Ok(GetSOADissipationConfigResponse {
             high_dissipation,
@@ -139,9 +131,9 @@ pub struct GetSOADissipationConfig {
     pub channel: Channel,
 }
 
-impl Into<String> for GetSOADissipationConfig {
-    fn into(self) -> String {
-        format!("$SDG,{}", self.channel)
+impl From<&GetSOADissipationConfig> for String {
+    fn from(command: &GetSOADissipationConfig) -> String {
+        format!("$SDG,{}", command.channel)
     }
 }
 
@@ -1,11 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Watt},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetSOAForwardPowerLimitsResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -43,11 +43,11 @@ pub struct SetSOAForwardPowerLimits {
     pub shutdown_forward_power: Watt,
 }
 
-impl Into<String> for SetSOAForwardPowerLimits {
-    fn into(self) -> String {
+impl From<&SetSOAForwardPowerLimits> for String {
+    fn from(command: &SetSOAForwardPowerLimits) -> String {
         format!(
             "$SFS,{},{},{}",
-            self.channel, self.high_forward_power, self.shutdown_forward_power
+            command.channel, command.high_forward_power, command.shutdown_forward_power
         )
     }
 }
@@ -102,18 +102,10 @@ impl TryFrom<String> for GetSOAForwardPowerLimitsResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let high_forward_power: Watt = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Watt::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let shutdown_forward_power: Watt = match parts[3].trim().parse::<f32>() {
-            Ok(value) => Watt::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let high_forward_power: Watt =
+            Watt::new(parse_field("high_forward_power", parts[2], &response)?);
+        let shutdown_forward_power: Watt =
+            Watt::new(parse_field("shutdown_forward_power", parts[3], &response)?);
 
         Ok(GetSOAForwardPowerLimitsResponse {
             high_forward_power,
@@ -137,9 +129,9 @@ pub struct GetSOAForwardPowerLimits {
     pub channel: Channel,
 }
 
-impl Into<String> for GetSOAForwardPowerLimits {
-    fn into(self) -> String {
-        format!("$SFG,{}", self.channel)
+impl From<&GetSOAForwardPowerLimits> for String {
+    fn from(command: &GetSOAForwardPowerLimits) -> String {
+        format!("$SFG,{}", command.channel)
     }
 }
 
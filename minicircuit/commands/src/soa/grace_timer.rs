@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::data_types::{errors::MWError, types::Channel};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetSOAGraceTimerResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -35,9 +35,9 @@ pub struct SetSOAGraceTimer {
     pub grace_period: u16,
 }
 
-impl Into<String> for SetSOAGraceTimer {
-    fn into(self) -> String {
-        format!("$SOAGS,{},{}", self.channel, self.grace_period)
+impl From<&SetSOAGraceTimer> for String {
+    fn from(command: &SetSOAGraceTimer) -> String {
+        format!("$SOAGS,{},{}", command.channel, command.grace_period)
     }
 }
 
@@ -1,11 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Dbm},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetSOAPowerConfigResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -45,11 +45,11 @@ pub struct SetSOAPowerConfig {
     pub shutdown_reflection: Dbm,
 }
 
-impl Into<String> for SetSOAPowerConfig {
-    fn into(self) -> String {
+impl From<&SetSOAPowerConfig> for String {
+    fn from(command: &SetSOAPowerConfig) -> String {
         format!(
             "$SPS,{},{},{}",
-            self.channel, self.high_reflection, self.shutdown_reflection
+            command.channel, command.high_reflection, command.shutdown_reflection
         )
     }
 }
@@ -106,18 +106,9 @@ impl TryFrom<String> for GetSOAPowerConfigResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let high_reflection: Dbm = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Dbm::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let shutdown_reflection: Dbm = match parts[3].trim().parse::<f32>() {
-            Ok(value) => Dbm::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let high_reflection: Dbm = Dbm::new(parse_field("high_reflection", parts[2], &response)?);
+        let shutdown_reflection: Dbm =
+            Dbm::new(parse_field("shutdown_reflection", parts[3], &response)?);
 
         Ok(GetSOAPowerConfigResponse {
             high_reflection,
@@ -133,9 +124,9 @@ pub struct GetSOAPowerConfig {
     pub channel: Channel,
 }
 
-impl Into<String> for GetSOAPowerConfig {
-    fn into(self) -> String {
-        format!("$SPG,{}", self.channel)
+impl From<&GetSOAPowerConfig> for String {
+    fn from(command: &GetSOAPowerConfig) -> String {
+        format!("$SPG,{}", command.channel)
     }
 }
 
@@ -1,11 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Temperature},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetSOATempConfigResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -46,11 +46,11 @@ pub struct SetSOATempConfig {
     pub shutdown_temp: Temperature,
 }
 
-impl Into<String> for SetSOATempConfig {
-    fn into(self) -> String {
+impl From<&SetSOATempConfig> for String {
+    fn from(command: &SetSOATempConfig) -> String {
         format!(
             "$STS,{},{},{}",
-            self.channel, self.high_temp, self.shutdown_temp
+            command.channel, command.high_temp, command.shutdown_temp
         )
     }
 }
@@ -107,24 +107,10 @@ impl TryFrom<String> for GetSOATempConfigResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let high_temp: Temperature = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u8>()
-        {
-            Ok(value) => Temperature::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let shutdown_temp: Temperature = match parts[3].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u8>()
-        {
-            Ok(value) => Temperature::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let high_temp: Temperature =
+            Temperature::new(parse_field("high_temp", parts[2], &response)?);
+        let shutdown_temp: Temperature =
+            Temperature::new(parse_field("shutdown_temp", parts[3], &response)?);
 
         Ok(GetSOATempConfigResponse {
             high_temp,
@@ -140,9 +126,9 @@ pub struct GetSOATempConfig {
     pub channel: Channel,
 }
 
-impl Into<String> for GetSOATempConfig {
-    fn into(self) -> String {
-        format!("$STG,{}", self.channel)
+impl From<&GetSOATempConfig> for String {
+    fn from(command: &GetSOATempConfig) -> String {
+        format!("$STG,{}", command.channel)
     }
 }
 
@@ -1,11 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Volts},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetSOAVoltageConfigResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -47,15 +47,15 @@ pub struct SetSOAVoltageConfig {
     pub shutdown_max_voltage: Volts,
 }
 
-impl Into<String> for SetSOAVoltageConfig {
-    fn into(self) -> String {
+impl From<&SetSOAVoltageConfig> for String {
+    fn from(command: &SetSOAVoltageConfig) -> String {
         format!(
             "$SVS,{},{},{},{},{}",
-            self.channel,
-            self.shutdown_min_voltage,
-            self.low_voltage,
-            self.high_voltage,
-            self.shutdown_max_voltage
+            command.channel,
+            command.shutdown_min_voltage,
+            command.low_voltage,
+            command.high_voltage,
+            command.shutdown_max_voltage
         )
     }
 }
@@ -132,30 +132,12 @@ impl TryFrom<String> for GetSOAVoltageConfigResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let shutdown_min_voltage: Volts = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Volts::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let low_voltage: Volts = match parts[3].trim().parse::<f32>() {
-            Ok(value) => Volts::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let high_voltage: Volts = match parts[4].trim().parse::<f32>() {
-            Ok(value) => Volts::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
-        let shutdown_max_voltage: Volts = match parts[5].trim().parse::<f32>() {
-            Ok(value) => Volts::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let shutdown_min_voltage: Volts =
+            Volts::new(parse_field("shutdown_min_voltage", parts[2], &response)?);
+        let low_voltage: Volts = Volts::new(parse_field("low_voltage", parts[3], &response)?);
+        let high_voltage: Volts = Volts::new(parse_field("high_voltage", parts[4], &response)?);
+        let shutdown_max_voltage: Volts =
+            Volts::new(parse_field("shutdown_max_voltage", parts[5], &response)?);
 
         Ok(GetSOAVoltageConfigResponse {
             shutdown_min_voltage,
@@ -173,9 +155,9 @@ pub struct GetSOAVoltageConfig {
     pub channel: Channel,
 }
 
-impl Into<String> for GetSOAVoltageConfig {
-    fn into(self) -> String {
-        format!("$SVG,{}", self.channel)
+impl From<&GetSOAVoltageConfig> for String {
+    fn from(command: &GetSOAVoltageConfig) -> String {
+        format!("$SVG,{}", command.channel)
     }
 }
 
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::data_types::{errors::MWError, types::Channel};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetSOAWatchdogConfigResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -59,9 +59,9 @@ pub struct SetSOAWatchdogConfig {
     pub enabled: bool,
 }
 
-impl Into<String> for SetSOAWatchdogConfig {
-    fn into(self) -> String {
-        format!("$SWES,{},{}", self.channel, self.enabled)
+impl From<&SetSOAWatchdogConfig> for String {
+    fn from(command: &SetSOAWatchdogConfig) -> String {
+        format!("$SWES,{},{}", command.channel, command.enabled)
     }
 }
 
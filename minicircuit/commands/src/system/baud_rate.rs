@@ -27,9 +27,9 @@ pub struct SetUartBaudRate {
     pub baud_rate: BaudRate,
 }
 
-impl Into<String> for SetUartBaudRate {
-    fn into(self) -> String {
-        format!("$UARTS,{},{}", self.channel, self.baud_rate)
+impl From<&SetUartBaudRate> for String {
+    fn from(command: &SetUartBaudRate) -> String {
+        format!("$UARTS,{},{}", command.channel, command.baud_rate)
     }
 }
 
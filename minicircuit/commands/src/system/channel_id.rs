@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-use crate::data_types::{errors::MWError, types::Channel};
+use crate::data_types::{
+    errors::{parse_field, MWError},
+    types::Channel,
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetChannelIDResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -35,9 +38,9 @@ pub struct SetChannelID {
     pub new_channel: Channel,
 }
 
-impl Into<String> for SetChannelID {
-    fn into(self) -> String {
-        format!("$CHANS,{},{}", self.channel, self.new_channel)
+impl From<&SetChannelID> for String {
+    fn from(command: &SetChannelID) -> String {
+        format!("$CHANS,{},{}", command.channel, command.new_channel)
     }
 }
 
@@ -83,15 +86,7 @@ impl TryFrom<String> for GetChannelIDResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let channel: Channel = match parts[1].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u8>()
-        {
-            Ok(value) => Channel::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let channel: Channel = Channel::new(parse_field("channel", parts[1], &response)?);
 
         Ok(GetChannelIDResponse { channel })
     }
@@ -101,8 +96,8 @@ impl TryFrom<String> for GetChannelIDResponse {
 /// Returns the channel number assigned to the ISC board.
 pub struct GetChannelID {}
 
-impl Into<String> for GetChannelID {
-    fn into(self) -> String {
+impl From<&GetChannelID> for String {
+    fn from(_command: &GetChannelID) -> String {
         "$CHANG".to_string()
     }
 }
@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-use crate::data_types::{errors::MWError, types::Channel};
+use crate::data_types::{
+    errors::{parse_field, MWError, ValidationError},
+    types::Channel,
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetClockSourceResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -35,10 +38,10 @@ pub struct SetClockSource {
     pub clock_source: ClockSource,
 }
 
-impl Into<String> for SetClockSource {
-    fn into(self) -> String {
-        let clock_source: u8 = self.clock_source.into();
-        format!("$CSS,{},{}", self.channel, clock_source)
+impl From<&SetClockSource> for String {
+    fn from(command: &SetClockSource) -> String {
+        let clock_source: u8 = command.clock_source.clone().into();
+        format!("$CSS,{},{}", command.channel, clock_source)
     }
 }
 
@@ -51,6 +54,21 @@ impl SetClockSource {
             clock_source,
         }
     }
+
+    /// Returns a handler to call the command, if the key is one of the clock sources the
+    /// manual lists.
+    ///
+    /// ```
+    /// use minicircuit_commands::prelude::*;
+    /// use minicircuit_commands::system::clock_source::{ClockSource, SetClockSource};
+    ///
+    /// let command = SetClockSource::try_new(Channel::default(), 2).unwrap();
+    /// assert_eq!(command.clock_source, ClockSource::Slave);
+    /// assert!(SetClockSource::try_new(Channel::default(), 4).is_err());
+    /// ```
+    pub fn try_new(channel: Channel, key: u8) -> Result<Self, ValidationError> {
+        Ok(Self::new(channel, ClockSource::try_from(key)?))
+    }
 }
 
 impl Default for SetClockSource {
@@ -87,20 +105,24 @@ impl TryFrom<String> for GetClockSourceResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let clock_source: ClockSource = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u8>()
-        {
-            Ok(value) => ClockSource::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        // A key the manual doesn't list would otherwise be reported as standalone.
+        let key: u8 = parse_field("clock_source", parts[2], &response)?;
+        let clock_source = ClockSource::try_from(key).map_err(|_| MWError::InvalidField {
+            field: "clock_source".to_string(),
+            raw: response.clone(),
+        })?;
 
         Ok(GetClockSourceResponse { clock_source })
     }
 }
 
+impl GetClockSourceResponse {
+    /// The clock source the board runs on.
+    pub fn source(&self) -> ClockSource {
+        self.clock_source.clone()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 /// Returns the clock source configuration of the ISC board.
 pub struct GetClockSource {
@@ -108,9 +130,9 @@ pub struct GetClockSource {
     pub channel: Channel,
 }
 
-impl Into<String> for GetClockSource {
-    fn into(self) -> String {
-        format!("$CSG,{}", self.channel)
+impl From<&GetClockSource> for String {
+    fn from(command: &GetClockSource) -> String {
+        format!("$CSG,{}", command.channel)
     }
 }
 
@@ -169,13 +191,34 @@ impl ClockSource {
     /// 1 => Master
     /// 2 => Slave
     /// 3 => SlaveInline
+    ///
+    /// Any other key falls back to Standalone, see `ClockSource::try_from` to reject it instead.
     pub fn new(key: u8) -> Self {
+        Self::try_from(key).unwrap_or(Self::Standalone)
+    }
+
+    /// Whether the board runs on the reference of another board rather than its own XCO.
+    pub fn uses_external_reference(&self) -> bool {
+        matches!(self, Self::Slave | Self::SlaveInline)
+    }
+}
+impl TryFrom<u8> for ClockSource {
+    type Error = ValidationError;
+
+    /// Converts the key used by `SetClockSource` and `GetClockSource`, failing for keys the
+    /// manual doesn't list.
+    fn try_from(key: u8) -> Result<Self, Self::Error> {
         match key {
-            0 => Self::Standalone,
-            1 => Self::Master,
-            2 => Self::Slave,
-            3 => Self::SlaveInline,
-            _ => Self::Standalone,
+            0 => Ok(Self::Standalone),
+            1 => Ok(Self::Master),
+            2 => Ok(Self::Slave),
+            3 => Ok(Self::SlaveInline),
+            _ => Err(ValidationError::OutOfRange {
+                parameter: "clock_source".to_string(),
+                value: key as f64,
+                min: 0.,
+                max: 3.,
+            }),
         }
     }
 }
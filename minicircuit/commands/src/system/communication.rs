@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::data_types::{errors::MWError, types::Channel};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetCommunicationInterfaceResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -37,10 +37,10 @@ pub struct SetCommunicationInterface {
     pub interface: Interface,
 }
 
-impl Into<String> for SetCommunicationInterface {
-    fn into(self) -> String {
-        let interface: u8 = self.interface.into();
-        format!("$COMS,{},{}", self.channel, interface)
+impl From<&SetCommunicationInterface> for String {
+    fn from(command: &SetCommunicationInterface) -> String {
+        let interface: u8 = command.interface.clone().into();
+        format!("$COMS,{},{}", command.channel, interface)
     }
 }
 
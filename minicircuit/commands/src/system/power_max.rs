@@ -1,11 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Dbm},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetPowerMaxDbmResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -35,9 +35,9 @@ pub struct SetPowerMaxDbm {
     pub max: Dbm,
 }
 
-impl Into<String> for SetPowerMaxDbm {
-    fn into(self) -> String {
-        format!("$PWRMDS,{},{}", self.channel, self.max)
+impl From<&SetPowerMaxDbm> for String {
+    fn from(command: &SetPowerMaxDbm) -> String {
+        format!("$PWRMDS,{},{}", command.channel, command.max)
     }
 }
 
@@ -84,12 +84,7 @@ impl TryFrom<String> for GetPowerMaxDbmResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let max: Dbm = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Dbm::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let max: Dbm = Dbm::new(parse_field("max", parts[2], &response)?);
 
         Ok(GetPowerMaxDbmResponse { max })
     }
@@ -102,9 +97,9 @@ pub struct GetPowerMaxDbm {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPowerMaxDbm {
-    fn into(self) -> String {
-        format!("$PWRMDG,{}", self.channel)
+impl From<&GetPowerMaxDbm> for String {
+    fn from(command: &GetPowerMaxDbm) -> String {
+        format!("$PWRMDG,{}", command.channel)
     }
 }
 
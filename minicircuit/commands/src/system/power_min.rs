@@ -1,11 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data_types::{
-    errors::MWError,
+    errors::{parse_field, MWError},
     types::{Channel, Dbm},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetPowerMinDbmResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -37,9 +37,9 @@ pub struct SetPowerMinDbm {
     pub min: Dbm,
 }
 
-impl Into<String> for SetPowerMinDbm {
-    fn into(self) -> String {
-        format!("$PWRMINDS,{},{}", self.channel, self.min)
+impl From<&SetPowerMinDbm> for String {
+    fn from(command: &SetPowerMinDbm) -> String {
+        format!("$PWRMINDS,{},{}", command.channel, command.min)
     }
 }
 
@@ -86,12 +86,7 @@ impl TryFrom<String> for GetPowerMinDbmResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let min: Dbm = match parts[2].trim().parse::<f32>() {
-            Ok(value) => Dbm::new(value),
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let min: Dbm = Dbm::new(parse_field("min", parts[2], &response)?);
 
         Ok(GetPowerMinDbmResponse { min })
     }
@@ -104,9 +99,9 @@ pub struct GetPowerMinDbm {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPowerMinDbm {
-    fn into(self) -> String {
-        format!("$PWRMINDG,{}", self.channel)
+impl From<&GetPowerMinDbm> for String {
+    fn from(command: &GetPowerMinDbm) -> String {
+        format!("$PWRMINDG,{}", command.channel)
     }
 }
 
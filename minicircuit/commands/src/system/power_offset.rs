@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-use crate::data_types::{errors::MWError, types::Channel};
+use crate::data_types::{
+    errors::{parse_field, MWError},
+    types::Channel,
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetPowerOffsetResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -46,9 +49,9 @@ pub struct SetPowerOffset {
     pub offset: u8,
 }
 
-impl Into<String> for SetPowerOffset {
-    fn into(self) -> String {
-        format!("$PODS,{},{}", self.channel, self.offset)
+impl From<&SetPowerOffset> for String {
+    fn from(command: &SetPowerOffset) -> String {
+        format!("$PODS,{},{}", command.channel, command.offset)
     }
 }
 
@@ -95,15 +98,7 @@ impl TryFrom<String> for GetPowerOffsetResponse {
             return Err(Self::Error::FailedParseResponse);
         }
 
-        let offset = match parts[2].split('.').collect::<Vec<&str>>()[0]
-            .trim()
-            .parse::<u8>()
-        {
-            Ok(value) => value,
-            Err(_) => {
-                return Err(Self::Error::FailedParseResponse);
-            }
-        };
+        let offset: u8 = parse_field("offset", parts[2], &response)?;
 
         Ok(GetPowerOffsetResponse { offset })
     }
@@ -116,9 +111,9 @@ pub struct GetPowerOffset {
     pub channel: Channel,
 }
 
-impl Into<String> for GetPowerOffset {
-    fn into(self) -> String {
-        format!("$PODG,{}", self.channel)
+impl From<&GetPowerOffset> for String {
+    fn from(command: &GetPowerOffset) -> String {
+        format!("$PODG,{}", command.channel)
     }
 }
 
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::data_types::{errors::MWError, types::Channel};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ResetSystemResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -32,9 +32,9 @@ pub struct ResetSystem {
     pub channel: Channel,
 }
 
-impl Into<String> for ResetSystem {
-    fn into(self) -> String {
-        format!("$RST,{}", self.channel)
+impl From<&ResetSystem> for String {
+    fn from(command: &ResetSystem) -> String {
+        format!("$RST,{}", command.channel)
     }
 }
 
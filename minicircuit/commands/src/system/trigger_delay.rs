@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::data_types::{errors::MWError, types::Channel};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetZHLTriggerDelayResponse {
     /// The result of the command (Ok/Err).
     pub result: Result<(), MWError>,
@@ -34,9 +34,9 @@ pub struct SetZHLTriggerDelay {
     pub delay: u16,
 }
 
-impl Into<String> for SetZHLTriggerDelay {
-    fn into(self) -> String {
-        format!("$ZHLDS,{},{}", self.channel, self.delay)
+impl From<&SetZHLTriggerDelay> for String {
+    fn from(command: &SetZHLTriggerDelay) -> String {
+        format!("$ZHLDS,{},{}", command.channel, command.delay)
     }
 }
 
@@ -0,0 +1,160 @@
+//! Throughput of the dispatch path against the mock transport, run with `cargo bench -p minicircuit_driver`.
+//!
+//! The queue loop is polled as often as tokio's timer allows, so the numbers mostly reflect the
+//! driver rather than `TargetProperties::queue_poll_interval`.
+
+use std::{collections::VecDeque, time::Duration};
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use tokio::runtime::{Builder, Runtime};
+
+use minicircuit_commands::prelude::*;
+use minicircuit_driver::{driver::MiniCircuitDriver, mock::MockTransport, sender::CommandSender};
+
+/// The number of commands queued at once by the mixed workload.
+const MIXED_BATCH: usize = 100;
+
+fn runtime() -> Runtime {
+    Builder::new_current_thread().enable_all().build().unwrap()
+}
+
+fn connect(runtime: &Runtime) -> (MiniCircuitDriver, CommandSender) {
+    let transport = MockTransport::builder()
+        .reply("$FCG,1", "OK,1,2450")
+        .reply("$FCS,1,2450", "OK")
+        .reply("$PTG,1", "OK,1,35")
+        .build();
+    let properties = TargetProperties::builder()
+        .queue_poll_interval(Duration::from_micros(1))
+        .build();
+
+    let _guard = runtime.enter();
+    let (driver, queue_tx, _response_tx) =
+        MiniCircuitDriver::from_transport(transport, properties).unwrap();
+
+    (driver, queue_tx)
+}
+
+fn getter(c: &mut Criterion) {
+    let runtime = runtime();
+    let (driver, _queue_tx) = connect(&runtime);
+
+    let mut group = c.benchmark_group("dispatch");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("getter", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(driver.send_await(GetFrequency::default(), Priority::Standard))
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn setter(c: &mut Criterion) {
+    let runtime = runtime();
+    let (driver, _queue_tx) = connect(&runtime);
+    let command = SetFrequency::new(Channel::default(), Frequency::new(2450));
+
+    let mut group = c.benchmark_group("dispatch");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("setter", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(driver.send_await(command.clone(), Priority::Standard))
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn mixed_priorities(c: &mut Criterion) {
+    let runtime = runtime();
+    let (driver, queue_tx) = connect(&runtime);
+    let priorities = [Priority::Low, Priority::Standard, Priority::High];
+
+    let mut group = c.benchmark_group("dispatch");
+    group.throughput(Throughput::Elements(MIXED_BATCH as u64));
+    group.bench_function("mixed_priorities", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                for index in 0..MIXED_BATCH {
+                    let priority = priorities[index % priorities.len()].clone();
+                    let message = match index % 3 {
+                        0 => Message::new(priority, GetFrequency::default()),
+                        1 => Message::new(priority, GetPATemp::default()),
+                        _ => Message::new(
+                            priority,
+                            SetFrequency::new(Channel::default(), Frequency::new(2450)),
+                        ),
+                    };
+                    queue_tx.send_wait(message).await.unwrap();
+                }
+
+                driver.flush().await.unwrap();
+            })
+        })
+    });
+    group.finish();
+}
+
+/// The order the queue loop takes messages in, comparing the FIFO per priority it keeps with
+/// sorting every batch, as it used to.
+fn queue_order(c: &mut Criterion) {
+    let priorities = [
+        Priority::Low,
+        Priority::Standard,
+        Priority::High,
+        Priority::Immediate,
+    ];
+
+    let mut group = c.benchmark_group("queue_order");
+    for depth in [100, 1_000, 10_000, 100_000] {
+        let queue: Vec<Message> = (0..depth)
+            .map(|index| {
+                Message::new(
+                    priorities[index % priorities.len()].clone(),
+                    GetFrequency::default(),
+                )
+            })
+            .collect();
+
+        group.throughput(Throughput::Elements(depth as u64));
+        group.bench_with_input(BenchmarkId::new("sort", depth), &queue, |b, queue| {
+            b.iter_batched(
+                || queue.clone(),
+                |mut queue| {
+                    queue.sort_by(|a, b| b.priority.cmp(&a.priority));
+                    queue.into_iter().for_each(drop)
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(
+            BenchmarkId::new("fifo_per_priority", depth),
+            &queue,
+            |b, queue| {
+                b.iter_batched(
+                    || queue.clone(),
+                    |queue| {
+                        // Mirrors `PriorityQueue`, which the crate keeps to itself.
+                        let mut levels: [VecDeque<Message>; 5] = Default::default();
+                        for message in queue {
+                            levels[message.priority.clone() as usize].push_back(message);
+                        }
+                        while let Some(message) =
+                            levels.iter_mut().rev().find_map(|level| level.pop_front())
+                        {
+                            drop(message);
+                        }
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, getter, setter, mixed_priorities, queue_order);
+criterion_main!(benches);
@@ -0,0 +1,326 @@
+use std::{collections::BTreeMap, net::ToSocketAddrs, path::Path, time::Duration};
+
+use serialport::Error;
+use tokio::runtime::{Builder, Runtime};
+use tokio_util::sync::CancellationToken;
+
+use minicircuit_commands::{
+    command::{Command, Priority},
+    data_types::{
+        power::{Power, PowerUnit},
+        types::{BaudRate, Frequency, Watt},
+    },
+    properties::TargetProperties,
+    response::Response,
+    system::clock_source::ClockSource,
+};
+
+use super::{
+    config::{ApplyReport, ConfigDiff, DeviceConfig},
+    connection::{DeviceMatcher, PortCandidate},
+    driver::MiniCircuitDriver,
+    error::{
+        ClockSourceError, ConnectError, PowerError, ReplayError, ResetError, ScriptError,
+        SendError, WaitError,
+    },
+    recorder::{self, ReplayTiming},
+    safety::{NotIdle, SafetyError},
+    script,
+    sender::SequenceFailure,
+    stats::{CommandLatency, CommandLogEntry, DriverStats},
+    telemetry::TelemetrySnapshot,
+    transport::Transport,
+};
+
+/// A `MiniCircuitDriver` for callers that don't run an async runtime.
+///
+/// The wrapper owns a current-thread runtime and blocks on the driver's async methods.
+/// The queue loop only makes progress while one of these calls is running, which is enough
+/// since every call waits for the responses it needs.
+///
+/// Must not be used from within an async context, as blocking there would stall the runtime.
+#[derive(Debug)]
+pub struct BlockingMiniCircuitDriver {
+    runtime: Runtime,
+    driver: MiniCircuitDriver,
+}
+
+impl BlockingMiniCircuitDriver {
+    pub fn new(properties: TargetProperties) -> std::io::Result<Self> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+
+        Ok(Self {
+            runtime,
+            driver: MiniCircuitDriver::new(properties),
+        })
+    }
+
+    /// Creates a driver that is already connected over the given transport,
+    /// see `MiniCircuitDriver::from_transport`.
+    ///
+    /// ```
+    /// use minicircuit_commands::prelude::*;
+    /// use minicircuit_driver::{blocking::BlockingMiniCircuitDriver, mock::MockTransport};
+    ///
+    /// let transport = MockTransport::builder()
+    ///     .reply("$FCG,1", "OK,1,2450")
+    ///     .build();
+    ///
+    /// let driver =
+    ///     BlockingMiniCircuitDriver::from_transport(transport, TargetProperties::default()).unwrap();
+    /// let response = driver.send(GetFrequency::default()).unwrap();
+    /// assert_eq!(response.as_frequency().unwrap().frequency, Frequency::new(2450));
+    /// ```
+    pub fn from_transport(
+        transport: impl Transport + 'static,
+        properties: TargetProperties,
+    ) -> std::io::Result<Self> {
+        let mut driver = Self::new(properties)?;
+        driver.connect_transport(transport)?;
+
+        Ok(driver)
+    }
+
+    /// The wrapped driver, for reading its properties or subscribing to responses.
+    pub fn driver(&self) -> &MiniCircuitDriver {
+        &self.driver
+    }
+
+    pub fn stats(&self) -> DriverStats {
+        self.driver.stats()
+    }
+
+    pub fn latency_report(&self) -> BTreeMap<&'static str, CommandLatency> {
+        self.driver.latency_report()
+    }
+
+    pub fn command_log(&self) -> Vec<CommandLogEntry> {
+        self.driver.command_log()
+    }
+
+    /// Autodetects the signal generator and connects to it, see `MiniCircuitDriver::connect`.
+    pub fn connect(&mut self) -> Result<(), ConnectError> {
+        // The queue loop is spawned onto the runtime, so it has to be entered first.
+        let _guard = self.runtime.enter();
+        self.driver.connect().map(|_| ())
+    }
+
+    /// Connects to the detected signal generator `select` picks, see `MiniCircuitDriver::connect_selecting`.
+    pub fn connect_selecting<F>(&mut self, select: F) -> Result<(), ConnectError>
+    where
+        F: FnOnce(&[PortCandidate]) -> Option<&PortCandidate>,
+    {
+        let _guard = self.runtime.enter();
+        self.driver.connect_selecting(select).map(|_| ())
+    }
+
+    /// Connects to the detected signal generator if it is the one expected,
+    /// see `MiniCircuitDriver::connect_expecting`.
+    pub fn connect_expecting(&mut self, expected: DeviceMatcher) -> Result<(), ConnectError> {
+        self.runtime
+            .block_on(self.driver.connect_expecting(expected))
+            .map(|_| ())
+    }
+
+    /// Connects to the port defined in the target properties, see `MiniCircuitDriver::port_connect`.
+    pub fn port_connect(&mut self) -> Result<(), ConnectError> {
+        let _guard = self.runtime.enter();
+        self.driver.port_connect().map(|_| ())
+    }
+
+    /// Connects to a signal generator exposed over TCP, see `MiniCircuitDriver::connect_tcp`.
+    pub fn connect_tcp(&mut self, addr: impl ToSocketAddrs) -> Result<(), Error> {
+        let _guard = self.runtime.enter();
+        self.driver.connect_tcp(addr).map(|_| ())
+    }
+
+    /// Connects over a transport that was opened elsewhere, see `MiniCircuitDriver::connect_transport`.
+    pub fn connect_transport(&mut self, transport: impl Transport + 'static) -> Result<(), Error> {
+        let _guard = self.runtime.enter();
+        self.driver.connect_transport(transport).map(|_| ())
+    }
+
+    /// Switches the device's UART and the port to a new baud rate,
+    /// see `MiniCircuitDriver::change_baud_rate`.
+    pub fn change_baud_rate(&mut self, baud_rate: BaudRate) -> Result<(), ConnectError> {
+        self.runtime
+            .block_on(self.driver.change_baud_rate(baud_rate))
+    }
+
+    /// Resets the signal generator and waits for it to come back,
+    /// see `MiniCircuitDriver::reset_and_reconnect`.
+    // Matches the error of the async method, which carries the refusing response.
+    #[allow(clippy::result_large_err)]
+    pub fn reset_and_reconnect(
+        &mut self,
+        config: Option<DeviceConfig>,
+    ) -> Result<Option<ApplyReport>, ResetError> {
+        self.runtime
+            .block_on(self.driver.reset_and_reconnect(config))
+    }
+
+    pub fn disconnect(&mut self) {
+        self.runtime.block_on(self.driver.disconnect());
+    }
+
+    /// Sends the command with standard priority and waits for its response.
+    pub fn send(&self, command: impl Into<Command>) -> Result<Response, SendError> {
+        self.send_with_priority(command, Priority::Standard)
+    }
+
+    /// Sends the command and waits for its response.
+    pub fn send_with_priority(
+        &self,
+        command: impl Into<Command>,
+        priority: Priority,
+    ) -> Result<Response, SendError> {
+        self.runtime
+            .block_on(self.driver.send_await(command, priority))
+    }
+
+    /// Like `send_with_priority`, but gives up once `cancel` is cancelled from another thread,
+    /// see `MiniCircuitDriver::send_await_cancellable`.
+    pub fn send_await_cancellable(
+        &self,
+        command: impl Into<Command>,
+        priority: Priority,
+        cancel: &CancellationToken,
+    ) -> Result<Response, SendError> {
+        self.runtime.block_on(
+            self.driver
+                .send_await_cancellable(command, priority, cancel),
+        )
+    }
+
+    /// Sends the commands as a single block and waits for all of their responses,
+    /// see `MiniCircuitDriver::send_sequence`.
+    pub fn send_sequence(
+        &self,
+        commands: Vec<Command>,
+        priority: Priority,
+        on_failure: SequenceFailure,
+    ) -> Result<Vec<Response>, SendError> {
+        self.runtime
+            .block_on(self.driver.send_sequence(commands, priority, on_failure))
+    }
+
+    /// Waits until every command queued so far has been processed, see `MiniCircuitDriver::flush`.
+    pub fn flush(&self) -> Result<(), SendError> {
+        self.runtime.block_on(self.driver.flush())
+    }
+
+    /// Polls the getter until the predicate holds, see `MiniCircuitDriver::wait_until`.
+    // Matches the error of the async method, which carries the last response.
+    #[allow(clippy::result_large_err)]
+    pub fn wait_until<F>(
+        &self,
+        command: impl Into<Command>,
+        predicate: F,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<Response, WaitError>
+    where
+        F: Fn(&Response) -> bool,
+    {
+        self.runtime.block_on(
+            self.driver
+                .wait_until(command, predicate, poll_interval, timeout),
+        )
+    }
+
+    /// Sets the frequency and power, then enables RF output, see `MiniCircuitDriver::start_output`.
+    // Matches the error of the async method, which carries the failing response.
+    #[allow(clippy::result_large_err)]
+    pub fn start_output(&self, frequency: Frequency, power: Watt) -> Result<(), SafetyError> {
+        self.runtime
+            .block_on(self.driver.start_output(frequency, power))
+    }
+
+    /// Pulses RF output with timed enables, see `MiniCircuitDriver::pulse_rf`.
+    // Matches the error of the async method, which carries the failing response.
+    #[allow(clippy::result_large_err)]
+    pub fn pulse_rf(
+        &self,
+        on: Duration,
+        off: Option<Duration>,
+        cycles: Option<u32>,
+    ) -> Result<(), SafetyError> {
+        self.runtime.block_on(self.driver.pulse_rf(on, off, cycles))
+    }
+
+    /// Checks that the signal generator is idle, see `MiniCircuitDriver::assert_idle`.
+    // Matches the error of the async method, which carries the failing responses.
+    #[allow(clippy::result_large_err)]
+    pub fn assert_idle(&self) -> Result<(), NotIdle> {
+        self.runtime.block_on(self.driver.assert_idle())
+    }
+
+    /// Drives the signal generator to its idle state, see `MiniCircuitDriver::force_idle`.
+    // Matches the error of the async method, which carries the failing responses.
+    #[allow(clippy::result_large_err)]
+    pub fn force_idle(&self) -> Result<(), NotIdle> {
+        self.runtime.block_on(self.driver.force_idle())
+    }
+
+    /// Sets the power setpoint in the unit it was given in, see `MiniCircuitDriver::set_power`.
+    // Matches the error of the async method, which carries the failing response.
+    #[allow(clippy::result_large_err)]
+    pub fn set_power(&self, power: Power) -> Result<(), PowerError> {
+        self.runtime.block_on(self.driver.set_power(power))
+    }
+
+    /// Reads the power setpoint in the given unit, see `MiniCircuitDriver::get_power`.
+    // Matches the error of the async method, which carries the failing response.
+    #[allow(clippy::result_large_err)]
+    pub fn get_power(&self, unit: PowerUnit) -> Result<Power, PowerError> {
+        self.runtime.block_on(self.driver.get_power(unit))
+    }
+
+    /// Switches the clock source, see `MiniCircuitDriver::set_clock_source`.
+    // Matches the error of the async method, which carries the failing response.
+    #[allow(clippy::result_large_err)]
+    pub fn set_clock_source(
+        &self,
+        source: ClockSource,
+        verify: bool,
+    ) -> Result<(), ClockSourceError> {
+        self.runtime
+            .block_on(self.driver.set_clock_source(source, verify))
+    }
+
+    /// Applies and reads back every setting of the config, see `MiniCircuitDriver::apply_config`.
+    pub fn apply_config(&self, config: &DeviceConfig) -> Result<ApplyReport, SendError> {
+        self.runtime.block_on(self.driver.apply_config(config))
+    }
+
+    /// Lists the settings of the device that differ from `golden`, see `MiniCircuitDriver::compare_to`.
+    pub fn compare_to(
+        &self,
+        golden: &DeviceConfig,
+        tolerance: f32,
+    ) -> Result<Vec<ConfigDiff>, SendError> {
+        self.runtime
+            .block_on(self.driver.compare_to(golden, tolerance))
+    }
+
+    /// Sends the messages of a script file in order, see `script::run_script`.
+    pub fn run_script(&self, path: impl AsRef<Path>) -> Result<Vec<Response>, ScriptError> {
+        self.runtime
+            .block_on(script::run_script(path, &self.driver))
+    }
+
+    /// Sends the messages of a recording again, see `recorder::replay`.
+    pub fn replay(
+        &self,
+        path: impl AsRef<Path>,
+        timing: ReplayTiming,
+    ) -> Result<Vec<Response>, ReplayError> {
+        self.runtime
+            .block_on(recorder::replay(path, &self.driver, timing))
+    }
+
+    /// Reads the main measurements in a single sequence, see `MiniCircuitDriver::read_telemetry`.
+    pub fn read_telemetry(&self) -> Result<TelemetrySnapshot, SendError> {
+        self.runtime.block_on(self.driver.read_telemetry())
+    }
+}
@@ -1,31 +1,179 @@
+use std::{
+    io::{Read, Write},
+    time::{Duration, Instant},
+};
+
+use minicircuit_commands::properties::LineTerminator;
 use serialport::{Error, ErrorKind, SerialPort};
 
 /// A function to send commands to the serial port and receive it's response.
+///
+/// The command is terminated with the default line terminator.
 pub fn write_read(port: &mut dyn SerialPort, tx: String) -> Result<String, Error> {
-    // Format the command to the ISC's standards.
-    let command = format!("{}\r\n", tx);
+    let timeout = port.timeout();
+    exchange(
+        port,
+        &mut Vec::new(),
+        tx,
+        LineTerminator::default(),
+        false,
+        timeout,
+    )
+}
 
-    if let Err(e) = port.write_all(command.as_bytes()) {
-        return Err(Error::new(
+/// Sends a command over any byte stream and reads back the line it responds with.
+///
+/// `buffer` holds what was received past the lines read so far, to be kept between calls on the
+/// same stream. Whatever is left in it from before the command is discarded, as the reply can
+/// only follow the command.
+///
+/// Gives up once `timeout` has elapsed without a complete line, even if bytes keep trickling in.
+/// With `echo` set, the command echoed back by the link is read and discarded first.
+pub(crate) fn exchange<T: Read + Write + ?Sized>(
+    stream: &mut T,
+    buffer: &mut Vec<u8>,
+    tx: String,
+    terminator: LineTerminator,
+    echo: bool,
+    timeout: Duration,
+) -> Result<String, Error> {
+    // The `\n` of a `\r\n` the previous reply ended with is left over as a matter of course.
+    if !buffer.iter().all(|byte| is_terminator(*byte)) {
+        tracing::debug!(
+            discarded = %String::from_utf8_lossy(buffer),
+            "Discarding what was received before the command."
+        );
+    }
+    buffer.clear();
+
+    if !echo {
+        write_line(stream, tx, terminator)?;
+        return read_line(stream, buffer, timeout);
+    }
+
+    // The echo and the reply may well arrive in a single read.
+    write_line(stream, tx.clone(), terminator)?;
+    let mut lines = read_lines(stream, buffer, 2, timeout)?;
+    let reply = lines.pop().unwrap_or_default();
+    check_echo(&tx, &lines[0]);
+
+    Ok(reply)
+}
+
+/// Writes a command over any byte stream without waiting for a response.
+pub(crate) fn write_line<T: Write + ?Sized>(
+    stream: &mut T,
+    tx: String,
+    terminator: LineTerminator,
+) -> Result<(), Error> {
+    tracing::trace!(wire = %tx, "Writing command.");
+
+    let command = format!("{}{}", tx, terminator.as_str());
+
+    stream.write_all(command.as_bytes()).map_err(|e| {
+        Error::new(
             ErrorKind::Io(e.kind()),
             format!("Failed to write to the port: {:?}", e),
-        ));
+        )
+    })
+}
+
+/// Reads the line a link that echoes commands sends back, for commands without a reply.
+pub(crate) fn discard_echo<T: Read + ?Sized>(
+    stream: &mut T,
+    buffer: &mut Vec<u8>,
+    tx: &str,
+    timeout: Duration,
+) -> Result<(), Error> {
+    let echo = read_line(stream, buffer, timeout)?;
+    check_echo(tx, &echo);
+
+    Ok(())
+}
+
+/// Warns about an echo that doesn't match the command.
+///
+/// The line is discarded either way, as the line before the reply is the echo, but a mismatch
+/// points at a link that garbles what it passes on.
+fn check_echo(tx: &str, echo: &str) {
+    if echo != tx.trim() {
+        tracing::warn!(
+            sent = %tx,
+            echo = %echo,
+            "The echo of the command doesn't match the command that was sent."
+        );
     }
+}
 
-    let mut buffer = String::new();
+/// Accumulates bytes until a complete, terminated line has been received.
+///
+/// A response may arrive split across any number of reads, so nothing is decoded until the
+/// whole line is in. A line ends at a `\r` or a `\n`, whichever the firmware sends, and the
+/// `\n` of a `\r\n` left over from a previous response is skipped.
+///
+/// Lines are served from `buffer` first, and whatever arrives past the line is kept in it for
+/// the next call, such as the measurements of a sweep that arrive along with its reply.
+pub(crate) fn read_line<T: Read + ?Sized>(
+    stream: &mut T,
+    buffer: &mut Vec<u8>,
+    timeout: Duration,
+) -> Result<String, Error> {
+    let mut lines = read_lines(stream, buffer, 1, timeout)?;
+    Ok(lines.pop().unwrap_or_default())
+}
+
+/// Like `read_line`, but reads `count` lines, all of them within `timeout`.
+fn read_lines<T: Read + ?Sized>(
+    stream: &mut T,
+    buffer: &mut Vec<u8>,
+    count: usize,
+    timeout: Duration,
+) -> Result<Vec<String>, Error> {
+    let deadline = Instant::now() + timeout;
+
+    let mut lines = Vec::with_capacity(count);
     let mut serial_buf: Vec<u8> = vec![0; 1000];
 
-    while !buffer.contains("\n") && !buffer.contains("\r") {
-        match port.read(serial_buf.as_mut_slice()) {
-            Ok(t) => {
-                buffer.push_str(&String::from_utf8_lossy(&serial_buf[..t]));
-            }
-            Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
+    loop {
+        while lines.len() < count {
+            let Some(line) = take_line(buffer) else {
+                break;
+            };
+            lines.push(line);
+        }
+        if lines.len() == count {
+            return Ok(lines);
+        }
+
+        if Instant::now() >= deadline {
+            tracing::debug!(
+                received = %String::from_utf8_lossy(buffer),
+                "Timed out before a complete response was received."
+            );
+            return Err(timed_out());
+        }
+
+        match stream.read(serial_buf.as_mut_slice()) {
+            // The other end closed the connection, so the rest of the response will never arrive.
+            Ok(0) => {
                 return Err(Error::new(
-                    ErrorKind::Io(std::io::ErrorKind::TimedOut),
-                    "System timedout while waiting for response from the controller.",
+                    ErrorKind::Io(std::io::ErrorKind::UnexpectedEof),
+                    "The connection was closed while waiting for response from the controller.",
                 ));
             }
+            Ok(t) => {
+                buffer.extend_from_slice(&serial_buf[..t]);
+            }
+            Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            // Sockets report an elapsed read timeout as `WouldBlock` on some platforms.
+            Err(ref e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+                ) =>
+            {
+                return Err(timed_out());
+            }
             Err(e) => {
                 return Err(Error::new(
                     ErrorKind::Io(e.kind()),
@@ -34,6 +182,67 @@ pub fn write_read(port: &mut dyn SerialPort, tx: String) -> Result<String, Error
             }
         }
     }
+}
+
+/// Takes the first complete line out of `buffer`, leaving anything after it.
+fn take_line(buffer: &mut Vec<u8>) -> Option<String> {
+    // Ignore a `\n` that trails the `\r` the previous response was cut off at.
+    let start = buffer
+        .iter()
+        .position(|byte| !is_terminator(*byte))
+        .unwrap_or(buffer.len());
+    let end = buffer[start..]
+        .iter()
+        .position(|byte| is_terminator(*byte))?;
+
+    let line = String::from_utf8_lossy(&buffer[start..start + end])
+        .trim()
+        .to_string();
+    tracing::trace!(response = %line, "Received response.");
+    buffer.drain(..start + end);
+
+    Some(line)
+}
+
+/// Reads the `pending` bytes already waiting on the stream, split into lines along with the
+/// lines already in `buffer`.
+///
+/// A line that is still arriving is read to its end, waiting at most `timeout` for it.
+pub(crate) fn read_pending<T: Read + ?Sized>(
+    stream: &mut T,
+    buffer: &mut Vec<u8>,
+    pending: usize,
+    timeout: Duration,
+) -> Result<Vec<String>, Error> {
+    let mut received: Vec<u8> = vec![0; pending];
+    if let Err(e) = stream.read_exact(&mut received) {
+        return Err(Error::new(
+            ErrorKind::Io(e.kind()),
+            format!("Failed to read from the port: {:?}", e),
+        ));
+    }
+    buffer.extend_from_slice(&received);
+
+    let mut lines = Vec::new();
+    while let Some(line) = take_line(buffer) {
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    if !buffer.iter().all(|byte| is_terminator(*byte)) {
+        lines.push(read_line(stream, buffer, timeout)?);
+    }
+
+    Ok(lines)
+}
+
+fn is_terminator(byte: u8) -> bool {
+    byte == b'\r' || byte == b'\n'
+}
 
-    Ok(buffer.trim().to_string())
+fn timed_out() -> Error {
+    Error::new(
+        ErrorKind::Io(std::io::ErrorKind::TimedOut),
+        "System timedout while waiting for response from the controller.",
+    )
 }
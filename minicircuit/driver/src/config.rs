@@ -0,0 +1,310 @@
+use std::{fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use minicircuit_commands::{
+    command::Command,
+    data_types::{
+        power::Power,
+        types::{Attenuation, Dbm, Frequency, Phase, Temperature, Watt},
+    },
+    response::Response,
+};
+
+/// The high (warning) and shutdown limit of a SOA measurement.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SoaLimits<T> {
+    pub high: T,
+    pub shutdown: T,
+}
+
+impl<T> SoaLimits<T> {
+    pub fn new(high: T, shutdown: T) -> Self {
+        Self { high, shutdown }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for SoaLimits<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (high), {} (shutdown)", self.high, self.shutdown)
+    }
+}
+
+/// The range the device accepts power setpoints in, as reported by `GetPowerMinDbm` and
+/// `GetPowerMaxDbm`, see `MiniCircuitDriver::power_limits`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PowerLimits {
+    pub min: Dbm,
+    pub max: Dbm,
+}
+
+impl PowerLimits {
+    pub fn new(min: Dbm, max: Dbm) -> Self {
+        Self { min, max }
+    }
+
+    /// Clamps the setpoint of a `SetPAPowerSetpointDBM` or `SetPAPowerSetpointWatt` to the
+    /// limits, returning the requested and applied power if it was out of range.
+    ///
+    /// ```
+    /// use minicircuit_commands::prelude::*;
+    /// use minicircuit_driver::config::PowerLimits;
+    ///
+    /// let limits = PowerLimits::new(Dbm::new(-30.), Dbm::new(47.));
+    /// let mut command: Command = SetPAPowerSetpointDBM::new(Channel::default(), Dbm::new(50.)).into();
+    ///
+    /// let (requested, applied) = limits.clamp(&mut command).unwrap();
+    /// assert_eq!((requested, applied), (Power::Dbm(50.), Power::Dbm(47.)));
+    /// assert_eq!(String::from(&command), "$PWRDS,1,47.0");
+    /// ```
+    pub fn clamp(&self, command: &mut Command) -> Option<(Power, Power)> {
+        match command {
+            Command::SetPAPowerSetpointDBM(setter) => {
+                let requested = setter.power.power;
+                let applied = clamp(requested, self.min.power, self.max.power)?;
+                setter.power = Dbm::new(applied);
+                Some((Power::Dbm(requested as f64), Power::Dbm(applied as f64)))
+            }
+            Command::SetPAPowerSetpointWatt(setter) => {
+                let min = Watt::from(self.min.clone()).power;
+                let max = Watt::from(self.max.clone()).power;
+                let requested = setter.power.power;
+                let applied = clamp(requested, min, max)?;
+                setter.power = Watt::new(applied);
+                Some((Power::Watt(requested as f64), Power::Watt(applied as f64)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The nearest value within the range, if the value lies outside of it.
+fn clamp(value: f32, min: f32, max: f32) -> Option<f32> {
+    // Unlike `f32::clamp` this doesn't panic should the device report the limits swapped.
+    let clamped = value.max(min).min(max);
+    (clamped != value).then_some(clamped)
+}
+
+/// The settings to bring a signal generator to, see `MiniCircuitDriver::apply_config`.
+///
+/// Settings left as `None` are not touched.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DeviceConfig {
+    pub frequency: Option<Frequency>,
+    /// The power setpoint.
+    pub power: Option<Watt>,
+    pub phase: Option<Phase>,
+    pub attenuation: Option<Attenuation>,
+    pub soa_temperature: Option<SoaLimits<Temperature>>,
+    pub soa_reflected_power: Option<SoaLimits<Dbm>>,
+    pub soa_dissipation: Option<SoaLimits<Watt>>,
+    pub dll_enabled: Option<bool>,
+}
+
+impl DeviceConfig {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Writes the configuration to a JSON file, such as one taken with
+    /// `MiniCircuitDriver::export_config`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_json()?)
+    }
+
+    /// Reads a configuration written with `save`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Ok(Self::from_json(&json)?)
+    }
+
+    /// Lists the settings that differ from `other`, taking this configuration as the expected
+    /// one, such as a golden unit to compare a unit under test against.
+    ///
+    /// Power and attenuation settings count as equal within `tolerance`, in their own unit.
+    /// A setting only one of the configurations holds differs.
+    ///
+    /// ```
+    /// use minicircuit_commands::prelude::*;
+    /// use minicircuit_driver::config::{DeviceConfig, Setting};
+    ///
+    /// let golden = DeviceConfig {
+    ///     frequency: Some(Frequency::new(2450)),
+    ///     power: Some(Watt::new(100.)),
+    ///     ..Default::default()
+    /// };
+    /// let unit = DeviceConfig {
+    ///     frequency: Some(Frequency::new(2440)),
+    ///     power: Some(Watt::new(100.04)),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let diffs = golden.diff(&unit, 0.1);
+    /// assert_eq!(diffs.len(), 1);
+    /// assert_eq!(diffs[0].setting, Setting::Frequency);
+    /// for diff in &diffs {
+    ///     println!("{}", diff);
+    /// }
+    /// ```
+    pub fn diff(&self, other: &DeviceConfig, tolerance: f32) -> Vec<ConfigDiff> {
+        let close = |a: f32, b: f32| (a - b).abs() <= tolerance;
+
+        let mut diffs = Vec::new();
+        diff_setting(
+            &mut diffs,
+            Setting::Frequency,
+            &self.frequency,
+            &other.frequency,
+            |a, b| a == b,
+        );
+        diff_setting(
+            &mut diffs,
+            Setting::Power,
+            &self.power,
+            &other.power,
+            |a, b| close(a.power, b.power),
+        );
+        diff_setting(
+            &mut diffs,
+            Setting::Phase,
+            &self.phase,
+            &other.phase,
+            |a, b| a == b,
+        );
+        diff_setting(
+            &mut diffs,
+            Setting::Attenuation,
+            &self.attenuation,
+            &other.attenuation,
+            |a, b| close(a.attenuation, b.attenuation),
+        );
+        diff_setting(
+            &mut diffs,
+            Setting::SoaTemperature,
+            &self.soa_temperature,
+            &other.soa_temperature,
+            |a, b| a == b,
+        );
+        diff_setting(
+            &mut diffs,
+            Setting::SoaReflectedPower,
+            &self.soa_reflected_power,
+            &other.soa_reflected_power,
+            |a, b| close(a.high.power, b.high.power) && close(a.shutdown.power, b.shutdown.power),
+        );
+        diff_setting(
+            &mut diffs,
+            Setting::SoaDissipation,
+            &self.soa_dissipation,
+            &other.soa_dissipation,
+            |a, b| close(a.high.power, b.high.power) && close(a.shutdown.power, b.shutdown.power),
+        );
+        diff_setting(
+            &mut diffs,
+            Setting::DllEnabled,
+            &self.dll_enabled,
+            &other.dll_enabled,
+            |a, b| a == b,
+        );
+
+        diffs
+    }
+}
+
+/// Adds the setting to the diffs unless both values are unset or equal.
+fn diff_setting<T: fmt::Display>(
+    diffs: &mut Vec<ConfigDiff>,
+    setting: Setting,
+    expected: &Option<T>,
+    actual: &Option<T>,
+    equal: impl Fn(&T, &T) -> bool,
+) {
+    let differs = match (expected, actual) {
+        (Some(expected), Some(actual)) => !equal(expected, actual),
+        (None, None) => false,
+        _ => true,
+    };
+
+    if differs {
+        diffs.push(ConfigDiff {
+            setting,
+            expected: expected.as_ref().map(ToString::to_string),
+            actual: actual.as_ref().map(ToString::to_string),
+        });
+    }
+}
+
+/// A setting that differs between two configurations, see `DeviceConfig::diff`.
+///
+/// The values are formatted with their unit, `None` where the configuration doesn't hold the
+/// setting. Displays as a line of a report, such as `Frequency: 2450MHz (expected), 2440MHz (actual)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDiff {
+    pub setting: Setting,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl fmt::Display for ConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unset = || "unset".to_string();
+        write!(
+            f,
+            "{:?}: {} (expected), {} (actual)",
+            self.setting,
+            self.expected.clone().unwrap_or_else(unset),
+            self.actual.clone().unwrap_or_else(unset)
+        )
+    }
+}
+
+/// One of the settings of a `DeviceConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Frequency,
+    Power,
+    Phase,
+    Attenuation,
+    SoaTemperature,
+    SoaReflectedPower,
+    SoaDissipation,
+    DllEnabled,
+}
+
+/// What happened when a single setting was applied.
+#[derive(Debug, Clone)]
+pub enum SettingOutcome {
+    /// The device accepted the setting and reports it back unchanged.
+    Applied,
+    /// The device accepted the setting but reports a different value, e.g. after rounding.
+    Mismatch { requested: String, reported: String },
+    /// The setter failed, carrying its response.
+    Rejected(Response),
+    /// The setter succeeded but the getter failed, carrying its response.
+    ReadbackFailed(Response),
+}
+
+/// The outcome of every setting sent by `MiniCircuitDriver::apply_config`.
+#[derive(Debug, Clone, Default)]
+pub struct ApplyReport {
+    /// The settings in the order they were applied.
+    pub outcomes: Vec<(Setting, SettingOutcome)>,
+}
+
+impl ApplyReport {
+    /// Whether every setting was applied and reported back unchanged.
+    pub fn is_clean(&self) -> bool {
+        self.issues().next().is_none()
+    }
+
+    /// The settings that weren't applied cleanly.
+    pub fn issues(&self) -> impl Iterator<Item = &(Setting, SettingOutcome)> {
+        self.outcomes
+            .iter()
+            .filter(|(_, outcome)| !matches!(outcome, SettingOutcome::Applied))
+    }
+}
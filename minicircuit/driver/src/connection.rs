@@ -1,6 +1,79 @@
-use serialport::{available_ports, Error, SerialPort, SerialPortInfo};
+use std::{
+    fmt,
+    net::SocketAddr,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
 
-use minicircuit_commands::properties::{ProductId, TargetProperties, VendorId};
+use serde::{Deserialize, Serialize};
+use serialport::{available_ports, ClearBuffer, Error, SerialPort, SerialPortType};
+
+use minicircuit_commands::{
+    information::{
+        identity::{GetIdentity, GetIdentityResponse},
+        version::{FirmwareVersion, GetVersionResponse},
+    },
+    properties::{ProductId, TargetProperties, VendorId},
+    response::Response,
+};
+
+use super::{
+    error::ConnectError,
+    executable::Executable,
+    transport::{SerialTransport, Transport},
+};
+
+/// The baud rates tried when the device doesn't answer at the configured one, most common first.
+pub const COMMON_BAUD_RATES: [u32; 6] = [115_200, 9_600, 19_200, 38_400, 57_600, 230_400];
+
+/// What the driver is connected to, see `MiniCircuitDriver::connection_info`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// The serial port that was opened, `None` when connected another way.
+    pub port_name: Option<String>,
+    /// The address connected to over TCP, `None` when connected another way.
+    pub address: Option<SocketAddr>,
+    /// The baud rate the serial port is open at.
+    pub baud_rate: u32,
+    /// The USB vendor id of the port, if it is a USB port.
+    pub vendor_id: Option<u16>,
+    /// The USB product id of the port, if it is a USB port.
+    pub product_id: Option<u16>,
+}
+
+/// A port that matched the vendor and product id during autodetection, see `autodetect_sg_port`.
+///
+/// The descriptor strings are read from the USB adapter and set whenever the OS reports them,
+/// so identical devices can be told apart by their serial number.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PortCandidate {
+    /// The name to open the port by, such as `/dev/ttyUSB0` or `COM3`.
+    pub port_name: String,
+    /// The USB vendor id of the port.
+    pub vendor_id: u16,
+    /// The USB product id of the port.
+    pub product_id: u16,
+    /// The serial number of the USB adapter.
+    pub serial_number: Option<String>,
+    /// The manufacturer string of the USB adapter.
+    pub manufacturer: Option<String>,
+    /// The product string of the USB adapter.
+    pub product: Option<String>,
+}
+
+/// The USB vendor and product id of the port, if it is a USB port that is still present.
+#[cfg(feature = "async")]
+pub(crate) fn usb_ids(port_name: &str) -> Option<(u16, u16)> {
+    available_ports()
+        .ok()?
+        .into_iter()
+        .find(|port| port.port_name == port_name)
+        .and_then(|port| match port.port_type {
+            SerialPortType::UsbPort(usb_info) => Some((usb_info.vid, usb_info.pid)),
+            _ => None,
+        })
+}
 
 /// Used for connecting directly to the supplied port in the target properties.
 ///
@@ -9,7 +82,7 @@ use minicircuit_commands::properties::{ProductId, TargetProperties, VendorId};
 /// Opens the port using the remaining target properties.
 pub fn open_port(target_properties: TargetProperties) -> Option<Box<dyn SerialPort>> {
     let Some(desired_port) = target_properties.port else {
-        eprintln!("Port required for direct connection.");
+        tracing::warn!("Port required for direct connection.");
         return None;
     };
 
@@ -25,7 +98,7 @@ pub fn open_port(target_properties: TargetProperties) -> Option<Box<dyn SerialPo
     {
         Ok(port) => Some(port),
         Err(e) => {
-            eprintln!("Failed to open port \"{}\". Error: {}", desired_port, e);
+            tracing::warn!(port = %desired_port, error = %e, "Failed to open port.");
             None
         }
     }
@@ -69,19 +142,161 @@ pub fn connect_to_signal_generator(
     {
         Ok(port) => Some(port),
         Err(e) => {
-            eprintln!(
-                "Failed to open port \"{}\". Error: {}",
-                first_signal_generator.port_name, e
+            tracing::warn!(
+                port = %first_signal_generator.port_name,
+                error = %e,
+                "Failed to open port."
             );
             None
         }
     }
 }
 
+/// Runs a blocking call to the OS on its own thread, failing with `NoDevice` once `timeout` has
+/// elapsed.
+///
+/// The call can't be cancelled, so one that never returns keeps its thread until the process exits.
+pub(crate) fn within<T, F>(timeout: Duration, what: &str, call: F) -> Result<T, Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+{
+    let (result_tx, result_rx) = mpsc::channel();
+    thread::spawn(move || {
+        // The caller may have given up already, in which case the result is dropped.
+        let _ = result_tx.send(call());
+    });
+
+    match result_rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(Error::new(
+            serialport::ErrorKind::NoDevice,
+            format!(
+                "{} did not finish within {:?}, is the device plugged in?",
+                what, timeout
+            ),
+        )),
+        Err(RecvTimeoutError::Disconnected) => Err(Error::new(
+            serialport::ErrorKind::Unknown,
+            format!("{} failed unexpectedly.", what),
+        )),
+    }
+}
+
+/// The time left until `deadline`, zero once it has passed.
+pub(crate) fn remaining(deadline: Instant) -> Duration {
+    deadline.saturating_duration_since(Instant::now())
+}
+
+/// Opens the serial port at the settings of the target properties, giving up after `timeout`.
+pub(crate) fn open_serial_port(
+    target_properties: &TargetProperties,
+    port_name: &str,
+    timeout: Duration,
+) -> Result<Box<dyn SerialPort>, Error> {
+    let builder = serialport::new(port_name, target_properties.baud_rate.clone().into())
+        .data_bits(target_properties.data_bits)
+        .parity(target_properties.parity)
+        .flow_control(target_properties.flow_control)
+        .stop_bits(target_properties.stop_bits)
+        .timeout(target_properties.connection_timeout);
+
+    within(timeout, "Opening the serial port", move || builder.open())
+}
+
+/// Reopens the signal generator after the connection was lost.
+///
+/// USB adapters can re-enumerate under a different name, so autodetection is tried first.
+/// If no matching device is detected, the previously used port name is opened instead.
+pub fn reopen_port(
+    target_properties: &TargetProperties,
+    last_port_name: &str,
+) -> Result<Box<dyn SerialPort>, Error> {
+    let deadline = Instant::now() + target_properties.connect_timeout;
+
+    let port_name = match autodetect_sg_port_within(
+        target_properties.vendor_id.clone(),
+        target_properties.product_id.clone(),
+        target_properties.connect_timeout,
+    ) {
+        // Prefer the same port if it is still there, so another identical device isn't picked up.
+        Ok(signal_generators) => signal_generators
+            .iter()
+            .find(|candidate| candidate.port_name == last_port_name)
+            .or(signal_generators.first())
+            .map_or_else(
+                || last_port_name.to_string(),
+                |candidate| candidate.port_name.clone(),
+            ),
+        Err(_) => last_port_name.to_string(),
+    };
+
+    open_serial_port(target_properties, &port_name, remaining(deadline))
+}
+
+/// Confirms the device answers a `GetIdentity` at the baud rate the port was opened with.
+///
+/// If it doesn't and probing is enabled in the target properties, the other common baud rates
+/// are tried so the error can suggest the one the device is set to.
+pub(crate) fn verify_baud_rate(
+    port: &mut SerialTransport,
+    target_properties: &TargetProperties,
+) -> Result<(), ConnectError> {
+    let configured: u32 = target_properties.baud_rate.clone().into();
+
+    // Drop anything the device sent before the port was opened.
+    let _ = port.port().clear(ClearBuffer::All);
+
+    if answers_identity(port) {
+        return Ok(());
+    }
+
+    if target_properties.probe_baud_rates {
+        for baud_rate in COMMON_BAUD_RATES {
+            if baud_rate == configured {
+                continue;
+            }
+
+            Transport::set_baud_rate(port, baud_rate)?;
+            if answers_identity(port) {
+                tracing::warn!(
+                    configured,
+                    suggested = baud_rate,
+                    "The device answered at a different baud rate than configured."
+                );
+                return Err(ConnectError::BaudMismatch {
+                    configured,
+                    suggested: baud_rate,
+                });
+            }
+        }
+    }
+
+    Err(ConnectError::Unresponsive {
+        baud_rate: configured,
+    })
+}
+
+/// Whether the device replies to a `GetIdentity` with something it could have meant.
+///
+/// An error code counts as an answer, garbage or silence from a mismatched baud rate doesn't.
+pub(crate) fn answers_identity(port: &mut dyn Transport) -> bool {
+    match GetIdentity::default().execute(port) {
+        Response::GetIdentityResponse(_) => true,
+        Response::MWError(e) => !e.is_parse_failure(),
+        _ => false,
+    }
+}
+
+/// Lists the ports that match the vendor id of the target properties, in the order the OS
+/// reports them.
+///
+/// Several identical devices can be attached at once, see `MiniCircuitDriver::connect_selecting`
+/// for picking one by its serial number.
 pub fn autodetect_sg_port(
     vendor_id: VendorId,
     product_id: ProductId,
-) -> Result<Vec<SerialPortInfo>, Error> {
+) -> Result<Vec<PortCandidate>, Error> {
     // Get a list of available coms ports.
     let available_ports = match available_ports() {
         Ok(ports) => ports,
@@ -96,34 +311,55 @@ pub fn autodetect_sg_port(
     // Return the ports that match the requested vendor and product ids.
     Ok(available_ports
         .into_iter()
-        .filter(|port| {
-            if let serialport::SerialPortType::UsbPort(usb_info) = &port.port_type {
-                let vendor_id: u16 = vendor_id.clone().into();
-                let _product_id: u16 = product_id.clone().into();
-
-                let Some(product) = usb_info.clone().product else {
-                    return false;
-                };
-
-                // The filter requirement for returning the port is that the product and vendor ids match the requested ids.
-                let matches_vid_pid = usb_info.vid == vendor_id;
-                let not_uart = !product.contains("UART");
-                let name_valid = if cfg!(target_os = "linux") || cfg!(target_os = "macos") {
-                    port.port_name.contains("tty")
-                } else if cfg!(target_os = "windows") {
-                    port.port_name.to_lowercase().contains("com")
-                } else {
-                    false
-                };
-
-                matches_vid_pid && not_uart && name_valid
+        .filter_map(|port| {
+            let SerialPortType::UsbPort(usb_info) = port.port_type else {
+                return None;
+            };
+
+            let vendor_id: u16 = vendor_id.clone().into();
+            let _product_id: u16 = product_id.clone().into();
+
+            let product = usb_info.product.as_ref()?;
+
+            // The filter requirement for returning the port is that the product and vendor ids match the requested ids.
+            let matches_vid_pid = usb_info.vid == vendor_id;
+            let not_uart = !product.contains("UART");
+            let name_valid = if cfg!(target_os = "linux") || cfg!(target_os = "macos") {
+                port.port_name.contains("tty")
+            } else if cfg!(target_os = "windows") {
+                port.port_name.to_lowercase().contains("com")
             } else {
                 false
+            };
+
+            if !(matches_vid_pid && not_uart && name_valid) {
+                return None;
             }
+
+            Some(PortCandidate {
+                port_name: port.port_name,
+                vendor_id: usb_info.vid,
+                product_id: usb_info.pid,
+                serial_number: usb_info.serial_number,
+                manufacturer: usb_info.manufacturer,
+                product: usb_info.product,
+            })
         })
         .collect())
 }
 
+/// Like `autodetect_sg_port`, but fails with `NoDevice` if listing the ports takes longer than
+/// `timeout`, as it can while a device is being unplugged.
+pub fn autodetect_sg_port_within(
+    vendor_id: VendorId,
+    product_id: ProductId,
+    timeout: Duration,
+) -> Result<Vec<PortCandidate>, Error> {
+    within(timeout, "Listing the serial ports", move || {
+        autodetect_sg_port(vendor_id, product_id)
+    })
+}
+
 pub fn print_available_ports() {
     // Get a list of available coms ports.
     let available_ports = match available_ports() {
@@ -133,3 +369,246 @@ pub fn print_available_ports() {
 
     println!("All available ports are {:#?}", available_ports)
 }
+
+/// A signal generator found by `list_minicircuit_devices`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// The port the device was found on, along with the descriptors of its USB adapter.
+    pub port: PortCandidate,
+    /// The identity the device reported, `None` if its port couldn't be opened or it didn't answer.
+    pub identity: Option<GetIdentityResponse>,
+}
+
+impl DeviceInfo {
+    pub fn port_name(&self) -> &str {
+        &self.port.port_name
+    }
+
+    /// The type of ISC board the device reported, such as `ISC-2425-25+`.
+    pub fn model(&self) -> Option<&str> {
+        self.identity
+            .as_ref()
+            .map(|identity| identity.isc_board.as_str())
+    }
+
+    /// The serial number of the board, which unlike that of the USB adapter is set by the device.
+    pub fn serial_number(&self) -> Option<&str> {
+        self.identity
+            .as_ref()
+            .map(|identity| identity.serial_number.as_str())
+    }
+}
+
+impl fmt::Display for DeviceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.identity {
+            Some(identity) => write!(
+                f,
+                "{}: {} {}, serial number {}",
+                self.port.port_name,
+                identity.manufacturer,
+                identity.isc_board,
+                identity.serial_number
+            ),
+            None => write!(f, "{}: did not answer", self.port.port_name),
+        }
+    }
+}
+
+/// What the connected device has to report to be the one expected, see
+/// `MiniCircuitDriver::connect_expecting`.
+///
+/// Each requirement is optional, a matcher without any matches every device that answers.
+///
+/// ```
+/// use minicircuit_driver::prelude::*;
+/// use minicircuit_commands::prelude::{FirmwareVersion, GetIdentityResponse, GetVersionResponse};
+///
+/// let expected = DeviceMatcher::new()
+///     .model("ISC-2425-25+")
+///     .serial_prefix("AB")
+///     .min_firmware(FirmwareVersion::new(1, 2, 0));
+///
+/// let identity = GetIdentityResponse::try_from("OK,1,MCL ISC-2425-25+,AB1234".to_string()).unwrap();
+/// let version =
+///     GetVersionResponse::try_from("OK,1,MCL,1,2,3,2023-01-01,12:00:00".to_string()).unwrap();
+/// assert!(expected.matches(Some(&identity), Some(&version)));
+///
+/// // A board that didn't report its firmware version can't be confirmed to be new enough.
+/// assert!(!expected.matches(Some(&identity), None));
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct DeviceMatcher {
+    /// The type of ISC board, such as `ISC-2425-25+`.
+    pub model: Option<String>,
+    /// What the serial number of the board starts with.
+    pub serial_prefix: Option<String>,
+    /// The oldest firmware version accepted.
+    pub min_firmware: Option<FirmwareVersion>,
+}
+
+impl DeviceMatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn serial_prefix(mut self, serial_prefix: impl Into<String>) -> Self {
+        self.serial_prefix = Some(serial_prefix.into());
+        self
+    }
+
+    pub fn min_firmware(mut self, min_firmware: FirmwareVersion) -> Self {
+        self.min_firmware = Some(min_firmware);
+        self
+    }
+
+    /// Whether the identity and version a device reported meet every requirement.
+    ///
+    /// A requirement on something the device didn't report isn't met.
+    pub fn matches(
+        &self,
+        identity: Option<&GetIdentityResponse>,
+        version: Option<&GetVersionResponse>,
+    ) -> bool {
+        if let Some(model) = &self.model {
+            if identity.map(|identity| identity.isc_board.as_str()) != Some(model.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(serial_prefix) = &self.serial_prefix {
+            if !identity.is_some_and(|identity| identity.serial_number.starts_with(serial_prefix)) {
+                return false;
+            }
+        }
+
+        if let Some(min_firmware) = self.min_firmware {
+            if !version.is_some_and(|version| version.firmware_at_least(min_firmware)) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl fmt::Display for DeviceMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut requirements = Vec::new();
+        if let Some(model) = &self.model {
+            requirements.push(format!("model {}", model));
+        }
+        if let Some(serial_prefix) = &self.serial_prefix {
+            requirements.push(format!("a serial number starting with {}", serial_prefix));
+        }
+        if let Some(min_firmware) = &self.min_firmware {
+            requirements.push(format!("firmware {} or newer", min_firmware));
+        }
+
+        match requirements.is_empty() {
+            true => write!(f, "any device"),
+            false => write!(f, "{}", requirements.join(", ")),
+        }
+    }
+}
+
+/// A device that didn't meet the requirements of a `DeviceMatcher`, see `ConnectError::WrongDevice`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeviceMismatch {
+    pub expected: DeviceMatcher,
+    /// The identity the device reported, `None` if it didn't answer with one.
+    pub identity: Option<GetIdentityResponse>,
+    /// The firmware version the device reported, `None` if it didn't answer with one.
+    pub firmware: Option<FirmwareVersion>,
+}
+
+impl fmt::Display for DeviceMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Expected {} but the device reported ", self.expected)?;
+        match &self.identity {
+            Some(identity) => write!(
+                f,
+                "{} with serial number {}",
+                identity.isc_board, identity.serial_number
+            )?,
+            None => write!(f, "no identity")?,
+        }
+        match &self.firmware {
+            Some(firmware) => write!(f, " on firmware {}.", firmware),
+            None => write!(f, " and no firmware version."),
+        }
+    }
+}
+
+/// Lists the signal generators matching the default target properties, asking each for its identity.
+///
+/// See `list_minicircuit_devices_with` for devices set up with other properties.
+pub fn list_minicircuit_devices() -> Vec<DeviceInfo> {
+    list_minicircuit_devices_with(&TargetProperties::default())
+}
+
+/// Lists the ports matching the vendor id of the target properties, briefly opening each at
+/// their settings to ask the device for its identity.
+///
+/// Ports that can't be listed are left out, ports that can't be opened are listed without an identity.
+/// Don't call it while a driver is connected, the port it holds can't be opened a second time.
+pub fn list_minicircuit_devices_with(target_properties: &TargetProperties) -> Vec<DeviceInfo> {
+    let Ok(signal_generators) = autodetect_sg_port_within(
+        target_properties.vendor_id.clone(),
+        target_properties.product_id.clone(),
+        target_properties.connect_timeout,
+    ) else {
+        return Vec::new();
+    };
+
+    signal_generators
+        .into_iter()
+        .map(|port| DeviceInfo {
+            identity: query_identity(target_properties, &port.port_name),
+            port,
+        })
+        .collect()
+}
+
+/// Prints the signal generators `list_minicircuit_devices` finds, one per line.
+pub fn print_minicircuit_devices() {
+    let devices = list_minicircuit_devices();
+
+    if devices.is_empty() {
+        println!("No MiniCircuit signal generators detected.");
+        return;
+    }
+
+    for device in devices {
+        println!("{}", device);
+    }
+}
+
+/// Opens the port just long enough to ask the device for its identity.
+fn query_identity(
+    target_properties: &TargetProperties,
+    port_name: &str,
+) -> Option<GetIdentityResponse> {
+    let port = open_serial_port(
+        target_properties,
+        port_name,
+        target_properties.connect_timeout,
+    )
+    .ok()?;
+    let mut port = SerialTransport::new(port)
+        .with_line_terminator(target_properties.line_terminator)
+        .with_echo(target_properties.echo_enabled);
+
+    // Drop anything the device sent before the port was opened.
+    let _ = port.port().clear(ClearBuffer::All);
+
+    match GetIdentity::default().execute(&mut port) {
+        Response::GetIdentityResponse(identity) => Some(identity),
+        _ => None,
+    }
+}
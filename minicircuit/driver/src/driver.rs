@@ -1,2343 +1,2580 @@
-use std::sync::Arc;
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    future::Future,
+    net::{SocketAddr, ToSocketAddrs},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
 
-use serialport::{Error, SerialPort};
+use serialport::Error;
 use tokio::{
     select,
     sync::{broadcast, oneshot, Mutex},
+    task::JoinHandle,
+    time::MissedTickBehavior,
 };
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tokio_util::sync::CancellationToken;
 
 use minicircuit_commands::{
-    basic::{
-        adc::GetPAPowerADCResponse,
-        current::GetPACurrentResponse,
-        forward_reflected::{GetPAPowerDBMResponse, GetPAPowerWattResponse},
-        frequency::GetFrequencyResponse,
-        output::GetRFOutputResponse,
-        phase::GetPhaseResponse,
-        setpoint::{
-            GetPAPowerSetpointDBMResponse, GetPAPowerSetpointWattResponse,
-            SetPAPowerSetpointDBMResponse,
-        },
-        temperature::GetPATempResponse,
-        voltage::GetPAVoltageResponse,
-    },
-    command::{Command, Message},
-    data_types::errors::ReadWriteError,
-    dll::{
-        config::{GetDLLConfigResponse, SetDLLConfigResponse},
-        enable::{GetDLLEnabledResponse, SetDLLEnabledResponse},
-        sweep::{PerformSweepDBMResponse, PerformSweepWattResponse},
-    },
+    capabilities::{Capabilities, CommandGroup},
+    command::{Command, Message, Priority},
+    dll::enable::{GetDLLEnabled, SetDLLEnabled},
     error::{
-        clear_errors::ClearErrorsResponse, pa::GetPAErrorsResponse, status::GetStatusResponse,
+        clear_errors::ClearErrors,
+        pa::GetPAErrors,
+        status::{GetStatusResponse, Status, StatusCode},
     },
-    information::{
-        identity::GetIdentityResponse, isc_temp::GetISCTempResponse, uptime::GetUptimeResponse,
-        version::GetVersionResponse,
+    manual::attenuation::{GetAttenuation, SetAttenuation},
+    prelude::{
+        BaudRate, Channel, Dbm, Frequency, GetFrequency, GetIdentity, GetPACurrent,
+        GetPAPowerSetpointDBM, GetPAPowerSetpointWatt, GetPAPowerWatt, GetPATemp, GetPAVoltage,
+        GetPhase, GetRFOutput, GetStatus, GetVersion, GetVersionResponse, Power, PowerUnit,
+        SetFrequency, SetPAPowerSetpointDBM, SetPAPowerSetpointWatt, SetPhase, SetRFOutput, Watt,
     },
-    manual::{
-        attenuation::{GetAttenuationResponse, SetAttenuationResponse},
-        auto_gain::{GetAutoGainStateResponse, SetAutoGainStateResponse},
-        magnitude::{GetMagnitudeResponse, SetMagnitudeResponse},
-        power::{GetISCPowerOutputResponse, SetISCPowerOutputResponse},
-    },
-    prelude::MWError,
     properties::*,
-    pwm::{
-        duty_cycle::{GetPWMDutyCycleResponse, SetPWMDutyCycleResponse},
-        frequency::SetPWMFrequencyResponse,
-        timed_rf::SetTimedRFEnableResponse,
-    },
-    response::Response,
+    pwm::timed_rf::TimedRfEnable,
+    response::{CorrelatedResponse, Response},
     soa::{
-        config::{GetSOAConfigResponse, SetSOAConfigResponse},
-        current::{GetSOACurrentConfigResponse, SetSOACurrentConfigResponse},
-        dissipation::{GetSOADissipationConfigResponse, SetSOADissipationConfigResponse},
-        forward_power::{GetSOAForwardPowerLimitsResponse, SetSOAForwardPowerLimitsResponse},
-        grace_timer::SetSOAGraceTimerResponse,
-        reflected_power::{GetSOAPowerConfigResponse, SetSOAPowerConfigResponse},
-        temperature::{GetSOATempConfigResponse, SetSOATempConfigResponse},
-        voltage::{GetSOAVoltageConfigResponse, SetSOAVoltageConfigResponse},
-        watchdog::SetSOAWatchdogConfigResponse,
+        dissipation::{GetSOADissipationConfig, SetSOADissipationConfig},
+        reflected_power::{GetSOAPowerConfig, SetSOAPowerConfig},
+        temperature::{GetSOATempConfig, SetSOATempConfig},
     },
     system::{
-        channel_id::{GetChannelIDResponse, SetChannelIDResponse},
-        clock_source::{GetClockSourceResponse, SetClockSourceResponse},
-        communication::SetCommunicationInterfaceResponse,
-        power_max::{GetPowerMaxDbmResponse, SetPowerMaxDbmResponse},
-        power_min::{GetPowerMinDbmResponse, SetPowerMinDbmResponse},
-        power_offset::{GetPowerOffsetResponse, SetPowerOffsetResponse},
-        system_reset::ResetSystemResponse,
-        trigger_delay::SetZHLTriggerDelayResponse,
+        baud_rate::SetUartBaudRate,
+        clock_source::{ClockSource, GetClockSource, SetClockSource},
+        power_max::GetPowerMaxDbm,
+        power_min::GetPowerMinDbm,
+        system_reset::ResetSystem,
     },
 };
 
-use super::{communication::write_read, connection::autodetect_sg_port};
+use super::{
+    config::{
+        ApplyReport, ConfigDiff, DeviceConfig, PowerLimits, Setting, SettingOutcome, SoaLimits,
+    },
+    connection::{
+        answers_identity, autodetect_sg_port_within, open_serial_port, remaining, reopen_port,
+        usb_ids, verify_baud_rate, ConnectionInfo, DeviceMatcher, DeviceMismatch, PortCandidate,
+    },
+    error::{ClockSourceError, ConnectError, PowerError, ResetError, SendError, WaitError},
+    executable::{response_wait, send_command_reporting, send_command_within, Executable},
+    guard::RfOutputGuard,
+    monitor::{spawn_monitor_loop, SoaEvent, SoaMonitor, SoaThreshold},
+    queue::PriorityQueue,
+    recorder::SentMessage,
+    safety::{IdleViolation, NotIdle, SafetyError},
+    sender::{
+        next_id, queue_channel, CommandSender, PendingIds, QueuedMessage, QueuedWork,
+        SequenceFailure,
+    },
+    stats::{CommandLatency, CommandLogEntry, DriverStats, StatsRecorder},
+    telemetry::{spawn_frame_loop, TelemetryFrame, TelemetryMetric, TelemetrySnapshot},
+    transport::{DryRunTransport, PacedTransport, SerialTransport, TcpTransport, Transport},
+    verify::{VerifiableSetter, VerifyError},
+    wire::{WireDirection, WireHook, WireTap},
+};
 
+/// The longest delay between two reconnect attempts.
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Talks to a signal generator through a command queue, served by a task of its own.
+///
+/// Every queued command, whoever sent it, goes through that one task, which holds the port
+/// for each exchange. Direct commands, such as those of `apply_config`, take the same port
+/// lock, so no two exchanges ever overlap.
+///
+/// The driver is `Send + Sync`, and cloning it is cheap: a clone shares the connection, the
+/// queue, the ids and the statistics, so tasks can each hold one and call `send_await`
+/// concurrently. Every response carries the id of its command, so each caller receives its
+/// own. Connecting, disconnecting, the watchdog and the dead-man switch belong to the driver
+/// they were started on, a clone neither sees nor stops them.
+///
+/// ```
+/// use minicircuit_commands::prelude::*;
+/// use minicircuit_driver::{driver::MiniCircuitDriver, mock::MockTransport};
+///
+/// #[tokio::main]
+/// async fn main() {
+/// #   fn assert_send_sync<T: Send + Sync>() {}
+/// #   assert_send_sync::<MiniCircuitDriver>();
+///     let transport = MockTransport::builder()
+///         .reply("$FCG,1", "OK,1,2450")
+///         .reply("$PTG,1", "OK,1,35")
+///         .build();
+///     let (driver, _, _) =
+///         MiniCircuitDriver::from_transport(transport, TargetProperties::default()).unwrap();
+///
+///     let frequency = tokio::spawn({
+///         let driver = driver.clone();
+///         async move {
+///             for _ in 0..20 {
+///                 let response = driver
+///                     .send_await(GetFrequency::default(), Priority::Standard)
+///                     .await
+///                     .unwrap();
+///                 assert_eq!(response.as_frequency().unwrap().frequency, Frequency::new(2450));
+///             }
+///         }
+///     });
+///     let temperature = tokio::spawn({
+///         let driver = driver.clone();
+///         async move {
+///             for _ in 0..20 {
+///                 let response = driver
+///                     .send_await(GetPATemp::default(), Priority::Standard)
+///                     .await
+///                     .unwrap();
+///                 assert_eq!(response.as_pa_temp().unwrap().temperature, Temperature::new(35));
+///             }
+///         }
+///     });
+///
+///     frequency.await.unwrap();
+///     temperature.await.unwrap();
+///     assert_eq!(driver.stats().processed, 40);
+/// }
+/// ```
 #[derive(Debug)]
 pub struct MiniCircuitDriver {
     pub properties: TargetProperties,
     pub queue_handle: Option<tokio::task::JoinHandle<()>>,
+    /// The channel responses are delivered on, available once connected.
+    pub response_tx: Option<broadcast::Sender<CorrelatedResponse>>,
+    /// The open connection to the signal generator, shared with the queue loop once connected.
+    port: Option<Arc<Mutex<Box<dyn Transport>>>>,
+    /// Signals the queue loop to exit.
+    shutdown_tx: Option<oneshot::Sender<()>>,
+    /// The handle to the command queue, available once connected.
+    queue_tx: Option<CommandSender>,
+    /// The source of the ids assigned to queued and directly executed commands.
+    next_id: Arc<AtomicU64>,
+    /// Activity of the queue loop and direct commands.
+    stats: StatsRecorder,
+    /// The task started by `start_watchdog()`, if it is running.
+    heartbeat: Option<JoinHandle<()>>,
+    /// The task started by `enable_rf_with_deadman()`, if it is running.
+    deadman: Option<JoinHandle<()>>,
+    /// The baud rate the port is open at, shared with the queue loop so a reconnect uses it too.
+    baud_rate: Arc<AtomicU32>,
+    /// What the connected device supports, detected when connecting and shared with clones.
+    capabilities: Arc<RwLock<Option<Capabilities>>>,
+    /// The power limits read when connecting, if power setpoints are clamped, shared with clones.
+    power_limits: Arc<RwLock<Option<PowerLimits>>>,
+    /// What the driver is connected to, set when connecting.
+    connection: Option<ConnectionInfo>,
+    /// Where the connection was opened, so `reset_and_reconnect()` can open it again.
+    endpoint: Option<Endpoint>,
+    /// Shown every command written and every line read, set with `on_wire()`.
+    wire_hook: Option<WireHook>,
+    /// Carries every message the queue loop takes up to send, see `subscribe_sent()`.
+    sent_tx: broadcast::Sender<SentMessage>,
+}
+
+impl Clone for MiniCircuitDriver {
+    /// Returns a handle onto the same connection, without the tasks started by this driver.
+    fn clone(&self) -> Self {
+        Self {
+            properties: self.properties.clone(),
+            queue_handle: None,
+            response_tx: self.response_tx.clone(),
+            port: self.port.clone(),
+            shutdown_tx: None,
+            queue_tx: self.queue_tx.clone(),
+            next_id: Arc::clone(&self.next_id),
+            stats: self.stats.clone(),
+            heartbeat: None,
+            deadman: None,
+            baud_rate: Arc::clone(&self.baud_rate),
+            capabilities: Arc::clone(&self.capabilities),
+            power_limits: Arc::clone(&self.power_limits),
+            connection: self.connection.clone(),
+            endpoint: self.endpoint.clone(),
+            wire_hook: self.wire_hook.clone(),
+            sent_tx: self.sent_tx.clone(),
+        }
+    }
 }
 
 impl MiniCircuitDriver {
     pub fn new(properties: TargetProperties) -> Self {
         Self {
-            properties,
             queue_handle: None,
+            response_tx: None,
+            port: None,
+            shutdown_tx: None,
+            queue_tx: None,
+            next_id: Arc::new(AtomicU64::new(0)),
+            stats: StatsRecorder::new(properties.command_log_capacity.unwrap_or(0)),
+            heartbeat: None,
+            deadman: None,
+            wire_hook: None,
+            capabilities: Arc::new(RwLock::new(None)),
+            power_limits: Arc::new(RwLock::new(None)),
+            connection: None,
+            endpoint: None,
+            baud_rate: Arc::new(AtomicU32::new(properties.baud_rate.clone().into())),
+            // Outlives every connection, so a recorder keeps recording across reconnects.
+            sent_tx: broadcast::channel(properties.broadcast_capacity.max(1)).0,
+            properties,
         }
     }
 
-    pub fn connect(
-        &mut self,
-    ) -> Result<
-        (
-            tokio::sync::mpsc::UnboundedSender<Message>,
-            broadcast::Sender<Response>,
-        ),
-        Error,
-    > {
-        let properties_clone = self.properties.clone();
+    /// Returns a snapshot of how many commands were processed, how many failed,
+    /// how many are waiting and when the most recent one completed.
+    pub fn stats(&self) -> DriverStats {
+        self.stats.stats()
+    }
 
-        // Try to get a list of ports that match the vendor and product ids
-        let signal_generators =
-            match autodetect_sg_port(properties_clone.vendor_id, properties_clone.product_id) {
-                Ok(list_of_sg) => list_of_sg,
-                Err(e) => {
-                    // If autodetection fails and we have a specified port, try to use that instead
-                    if let Some(port_name) = &properties_clone.port {
-                        println!(
-                            "Autodetection failed: {}. Falling back to specified port: {}",
-                            e, port_name
-                        );
-                        return self.port_connect();
-                    } else {
-                        return Err(e);
-                    }
-                }
-            };
+    /// How long each type of command took to be answered, keyed by `Command::name()`.
+    ///
+    /// The same as `stats().latency`, for watching which commands are slow or drifting
+    /// without copying the rest of the stats.
+    pub fn latency_report(&self) -> BTreeMap<&'static str, CommandLatency> {
+        self.stats.latency_report()
+    }
 
-        // Verify a port was detected.
-        if signal_generators.is_empty() {
-            // If no ports were detected but we have a specified port, try to use that instead
-            if let Some(port_name) = &properties_clone.port {
-                println!("No devices detected matching defined properties. Falling back to specified port: {}", port_name);
-                return self.port_connect();
-            } else {
-                return Err(Error::new(
-                    serialport::ErrorKind::NoDevice,
-                    "Unable to detect device matching defined properties.",
-                ));
-            }
-        }
+    /// What the connected device supports, read from its version and identity when connecting.
+    ///
+    /// `None` before connecting and in dry-run mode.
+    pub fn capabilities(&self) -> Option<Capabilities> {
+        read_shared(&self.capabilities)
+    }
 
-        // Connect to the first port that matches the requirements.
-        let first_signal_generator = &signal_generators[0];
+    /// The power limits read from the device when connecting, which power setpoints are
+    /// clamped to.
+    ///
+    /// `None` unless `TargetProperties::clamp_power` is set, as well as before connecting,
+    /// in dry-run mode and if the device didn't report its limits.
+    pub fn power_limits(&self) -> Option<PowerLimits> {
+        read_shared(&self.power_limits)
+    }
 
-        // Open a serial connection with the detected port at the requested settings.
-        let port = match serialport::new(
-            &first_signal_generator.port_name,
-            properties_clone.baud_rate.into(),
-        )
-        .data_bits(properties_clone.data_bits)
-        .parity(properties_clone.parity)
-        .flow_control(properties_clone.flow_control)
-        .stop_bits(properties_clone.stop_bits)
-        .timeout(properties_clone.connection_timeout)
-        .open()
-        {
-            Ok(port) => port,
-            Err(e) => {
-                return Err(e);
-            }
-        };
+    /// The serial port the driver opened, which `connect()` may have picked by autodetection.
+    ///
+    /// `None` before connecting and when connected over TCP or in dry-run mode.
+    pub fn port_name(&self) -> Option<&str> {
+        self.connection.as_ref()?.port_name.as_deref()
+    }
 
-        // Wrap `port` in `Arc<Mutex<T>>` so it can be shared across threads.
-        let port = Arc::new(Mutex::new(port));
+    /// The port or address the driver is connected to, along with its baud rate and USB ids.
+    ///
+    /// `None` before connecting and after disconnecting.
+    pub fn connection_info(&self) -> Option<ConnectionInfo> {
+        let mut connection = self.connection.clone()?;
+        // Follows `change_baud_rate()`.
+        connection.baud_rate = self.baud_rate.load(Ordering::Relaxed);
+        Some(connection)
+    }
 
-        // Create a channel that will be used by the driver to deliver responses from the commands back to the caller.
-        let (channel_tx, _channel_rx) = broadcast::channel::<Response>(100);
-        // Create a queue that can be used by the driver for receiving commands.
-        let (queue_tx, queue_rx) = tokio::sync::mpsc::unbounded_channel::<Message>();
+    /// Returns the most recent commands along with their responses, oldest first.
+    ///
+    /// Empty unless a command log capacity is set in the target properties.
+    pub fn command_log(&self) -> Vec<CommandLogEntry> {
+        self.stats.command_log()
+    }
 
-        // Clone Arc pointers for the thread.
-        let port_clone = Arc::clone(&port);
+    /// Stops the queue loop and releases the serial port.
+    ///
+    /// The loop is given up to the connection timeout to finish the command it is processing
+    /// before it is aborted. Once the loop has exited the port is closed, so a following
+    /// `connect()` can reopen the same port.
+    ///
+    /// The response channel closes once every sender is dropped. Drop the sender returned by
+    /// `connect()` as well for subscribers to receive `RecvError::Closed`.
+    pub async fn disconnect(&mut self) {
+        self.stop_watchdog();
+        self.stop_deadman();
+
+        // Ask the loop to stop after the current batch of commands.
+        if let Some(shutdown_tx) = self.shutdown_tx.take() {
+            let _ = shutdown_tx.send(());
+        }
+
+        if let Some(mut queue_handle) = self.queue_handle.take() {
+            let timeout = self.properties.connection_timeout;
+            if tokio::time::timeout(timeout, &mut queue_handle)
+                .await
+                .is_err()
+            {
+                // The loop is stuck on the port - abort it and wait for the task to be dropped.
+                queue_handle.abort();
+                let _ = queue_handle.await;
+            }
+        }
 
-        // Spawn a thread for handling commands in the queue.
-        // Store the handle so the thread doesn't get dropped.
-        self.queue_handle = Some(spawn_queue_loop(queue_rx, port_clone, channel_tx.clone()));
+        // Dropping the last references closes the port and the driver's side of the channel.
+        self.queue_tx = None;
+        self.port = None;
+        self.response_tx = None;
+        self.connection = None;
+        self.endpoint = None;
+    }
 
-        // Return the queue sender and response sender (to be subscribed to).
-        Ok((queue_tx, channel_tx))
+    /// Enqueues the command and waits for its response.
+    ///
+    /// Only the response carrying the id of this command is returned, responses to other
+    /// commands are ignored. Fails with `SendError::Timeout` if the response doesn't arrive
+    /// within the response timeout of the target properties, or the longer default timeout of
+    /// slow commands such as sweeps, see `Command::default_timeout`.
+    pub async fn send_await(
+        &self,
+        command: impl Into<Command>,
+        priority: Priority,
+    ) -> Result<Response, SendError> {
+        self.send_await_until(Message::new(priority, command), None)
+            .await
     }
 
-    pub fn port_connect(
-        &mut self,
-    ) -> Result<
-        (
-            tokio::sync::mpsc::UnboundedSender<Message>,
-            broadcast::Sender<Response>,
-        ),
-        Error,
-    > {
-        let properties_clone = self.properties.clone();
+    /// Like `send_await`, but sends the message as it is, keeping its tag, timeout and channel.
+    ///
+    /// A message timeout longer than the response timeout of the target properties is waited
+    /// for in full.
+    pub async fn send_message_await(&self, message: Message) -> Result<Response, SendError> {
+        self.send_await_until(message, None).await
+    }
 
-        let Some(port_name) = properties_clone.port else {
-            return Err(Error::new(serialport::ErrorKind::InvalidInput, "A port must be defined in order to connect to it. Please add a port to the target properties."));
+    /// Like `send_await`, but gives up with `SendError::Cancelled` once `cancel` is cancelled,
+    /// such as by a shutdown signal.
+    ///
+    /// The command is withdrawn from the queue if it hasn't been sent yet. If it has, its
+    /// response is still broadcast under its own id, which doesn't affect any other command.
+    pub async fn send_await_cancellable(
+        &self,
+        command: impl Into<Command>,
+        priority: Priority,
+        cancel: &CancellationToken,
+    ) -> Result<Response, SendError> {
+        self.send_await_until(Message::new(priority, command), Some(cancel))
+            .await
+    }
+
+    async fn send_await_until(
+        &self,
+        message: Message,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Response, SendError> {
+        let (Some(queue_tx), Some(response_tx)) = (&self.queue_tx, &self.response_tx) else {
+            return Err(SendError::NotConnected);
         };
 
-        // Open a serial connection with the detected port at the requested settings.
-        let port = match serialport::new(port_name.clone(), properties_clone.baud_rate.into())
-            .data_bits(properties_clone.data_bits)
-            .parity(properties_clone.parity)
-            .flow_control(properties_clone.flow_control)
-            .stop_bits(properties_clone.stop_bits)
-            .timeout(properties_clone.connection_timeout)
-            .open()
-        {
-            Ok(port) => port,
-            Err(e) => {
-                return Err(e);
+        // Subscribe before sending so the response can't be missed.
+        let mut response_rx = response_tx.subscribe();
+
+        let timeout = response_wait(
+            &message.command,
+            message.timeout,
+            self.properties.response_timeout,
+        );
+        let id = queue_tx.send_wait(message).await?;
+
+        let wait = response_for(&mut response_rx, id);
+
+        let cancelled = async {
+            match cancel {
+                Some(cancel) => cancel.cancelled().await,
+                None => std::future::pending().await,
             }
         };
 
-        // Wrap `port` in `Arc<Mutex<T>>` so it can be shared across threads.
-        let port = Arc::new(Mutex::new(port));
-
-        // Create a channel that will be used by the driver to deliver responses from the commands back to the caller.
-        let (channel_tx, _channel_rx) = broadcast::channel::<Response>(100);
-        // Create a queue that can be used by the driver for receiving commands.
-        let (queue_tx, queue_rx) = tokio::sync::mpsc::unbounded_channel::<Message>();
+        select! {
+            result = tokio::time::timeout(timeout, wait) => match result {
+                Ok(result) => result,
+                Err(_) => Err(SendError::Timeout(timeout)),
+            },
+            _ = cancelled => {
+                queue_tx.cancel(id);
+                Err(SendError::Cancelled)
+            }
+        }
+    }
 
-        // Clone Arc pointers for the thread.
-        let port_clone = Arc::clone(&port);
+    /// Waits until every command queued before the call has been processed.
+    ///
+    /// Queues a `Command::Barrier` at the lowest priority, which the queue loop answers without
+    /// sending anything once it gets to it. Commands of a higher priority queued after the call
+    /// may still be processed first. There's no timeout, as the wait depends on how much was queued.
+    pub async fn flush(&self) -> Result<(), SendError> {
+        let (Some(queue_tx), Some(response_tx)) = (&self.queue_tx, &self.response_tx) else {
+            return Err(SendError::NotConnected);
+        };
 
-        // Spawn a thread for handling commands in the queue.
-        // Store the handle so the thread doesn't get dropped
-        self.queue_handle = Some(spawn_queue_loop(queue_rx, port_clone, channel_tx.clone()));
+        // Subscribe before sending so the response can't be missed.
+        let mut response_rx = response_tx.subscribe();
 
-        // Return the queue sender and response sender.
-        Ok((queue_tx, channel_tx))
+        let id = queue_tx
+            .send_wait(Message::new(Priority::Low, Command::Barrier))
+            .await?;
+        response_for(&mut response_rx, id).await.map(|_| ())
     }
-}
 
-fn spawn_queue_loop(
-    mut queue_rx: tokio::sync::mpsc::UnboundedReceiver<Message>,
-    port: Arc<tokio::sync::Mutex<Box<dyn SerialPort>>>,
-    channel_tx: tokio::sync::broadcast::Sender<Response>,
-) -> tokio::task::JoinHandle<()> {
-    tokio::spawn(async move {
+    /// Sends the getter every `poll_interval` until `predicate` holds for its response,
+    /// then returns that response.
+    ///
+    /// The getter is queued at standard priority, so other commands are serviced while waiting.
+    /// The timeout is checked between polls, a getter that is already in flight is awaited.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use minicircuit_commands::prelude::*;
+    /// use minicircuit_driver::prelude::*;
+    ///
+    /// # async fn cool_down(driver: &MiniCircuitDriver) -> Result<(), Box<dyn std::error::Error>> {
+    /// // Wait for the PA to cool below 40 degC before enabling RF again.
+    /// driver
+    ///     .wait_until(
+    ///         GetPATemp::default(),
+    ///         |response| {
+    ///             response
+    ///                 .as_pa_temp()
+    ///                 .is_some_and(|pa| pa.temperature < Temperature::new(40))
+    ///         },
+    ///         Duration::from_secs(1),
+    ///         Duration::from_secs(120),
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_until<F>(
+        &self,
+        command: impl Into<Command>,
+        predicate: F,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<Response, WaitError>
+    where
+        F: Fn(&Response) -> bool,
+    {
+        let command = command.into();
+        let deadline = Instant::now() + timeout;
+
         loop {
-            // Define a vector for the queue so that it can be manipulated freely.
-            let mut queue = Vec::new();
-            while let Ok(msg) = queue_rx.try_recv() {
-                queue.push(msg.clone());
+            let response = self.send_await(command.clone(), Priority::Standard).await?;
+            if predicate(&response) {
+                return Ok(response);
             }
 
-            // Sort the messages in the queue by priority.
-            queue.sort_by(|a, b| b.priority.cmp(&a.priority));
-
-            // Loop through the messages in the queue.
-            for message in queue {
-                // Send the command to the controller and wait for the response.
-                let response = {
-                    let mut port = port.lock().await;
-                    send_command(message.command, &mut **port)
-                };
-
-                // Return the response to the caller.
-                let _ = channel_tx.send(response);
+            let left = remaining(deadline);
+            if left.is_zero() {
+                return Err(WaitError::Timeout {
+                    timeout,
+                    last: response,
+                });
             }
 
-            // Await in order to allow abort
-            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            // Poll once more at the deadline rather than sleeping past it.
+            tokio::time::sleep(poll_interval.min(left)).await;
         }
-    })
-}
-
-#[allow(deprecated)]
-fn send_command(command: Command, port: &mut dyn SerialPort) -> Response {
-    match command {
-        Command::GetPAPowerADC(get_papower_adc) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_papower_adc.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPAPowerADCResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetPAPowerADCResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetPAPowerADC(get_papower_adc), e.description);
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+    /// Enqueues the commands as a single block and waits for all of their responses.
+    ///
+    /// The queue loop sends the commands back to back, no other message is serviced in between.
+    /// The responses are returned in the order of the commands and are also broadcast to
+    /// subscribers, all carrying the same id.
+    ///
+    /// If a command fails, `on_failure` decides whether the remaining commands are still sent.
+    /// When aborting, the returned responses end with the one that failed. An empty sequence
+    /// isn't queued at all.
+    pub async fn send_sequence(
+        &self,
+        commands: Vec<Command>,
+        priority: Priority,
+        on_failure: SequenceFailure,
+    ) -> Result<Vec<Response>, SendError> {
+        let Some(queue_tx) = &self.queue_tx else {
+            return Err(SendError::NotConnected);
+        };
+        // There's nothing to wait for, and no time to wait for it in.
+        if commands.is_empty() {
+            return Ok(Vec::new());
         }
-        Command::GetPACurrent(get_pacurrent) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_pacurrent.clone().into();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPACurrentResponse, _> = sg_response.try_into();
+        // Every command in the sequence gets the time it is allowed when sent on its own.
+        let timeout = commands
+            .iter()
+            .map(|command| response_wait(command, None, self.properties.response_timeout))
+            .fold(Duration::ZERO, Duration::saturating_add);
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetPACurrentResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetPACurrent(get_pacurrent), e.description);
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        let (_id, reply_rx) = queue_tx
+            .send_sequence(commands, priority, on_failure)
+            .await?;
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+        match tokio::time::timeout(timeout, reply_rx).await {
+            Ok(Ok(responses)) => Ok(responses),
+            // The queue loop stopped before finishing the sequence.
+            Ok(Err(_)) => Err(SendError::QueueClosed),
+            Err(_) => Err(SendError::Timeout(timeout)),
         }
-        Command::GetPAPowerDBM(get_papower_dbm) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_papower_dbm.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPAPowerDBMResponse, _> = sg_response.try_into();
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetPAPowerDBMResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetPAPowerDBM(get_papower_dbm), e.description);
+    /// Spawns a task that queues a `GetStatus` at `Priority::High` every `interval`, so the
+    /// controller keeps hearing from the host without the caller running its own loop.
+    ///
+    /// A heartbeat that is already running is replaced. If a heartbeat can't be queued because
+    /// the queue loop has stopped, the task ends and `Response::HeartbeatStopped` is broadcast.
+    ///
+    /// Must be called from within a tokio runtime after connecting.
+    /// Fails with `SendError::ZeroInterval` for a zero `interval`, leaving a running heartbeat as it is.
+    pub fn start_watchdog(&mut self, interval: Duration) -> Result<(), SendError> {
+        if interval.is_zero() {
+            return Err(SendError::ZeroInterval);
+        }
+
+        let (Some(queue_tx), Some(response_tx)) = (&self.queue_tx, &self.response_tx) else {
+            return Err(SendError::NotConnected);
+        };
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        let queue_tx = queue_tx.clone();
+        let response_tx = response_tx.clone();
+        let id_counter = Arc::clone(&self.next_id);
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetPAPowerWatt(get_papower_watt) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_papower_watt.clone().into();
+        self.stop_watchdog();
+        self.heartbeat = Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPAPowerWattResponse, _> = sg_response.try_into();
+            loop {
+                ticker.tick().await;
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetPAPowerWattResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
+                let heartbeat = Message::new(Priority::High, GetStatus::default());
+                if let Err(e) = queue_tx.send_wait(heartbeat).await {
+                    tracing::warn!(error = %e, "The heartbeat could not be queued, stopping it.");
+                    let _ = response_tx.send(CorrelatedResponse::new(
+                        next_id(&id_counter),
+                        Response::HeartbeatStopped,
+                    ));
+                    break;
                 }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetPAPowerWatt(get_papower_watt),
-                        e.description,
-                    );
+            }
+        }));
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        Ok(())
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+    /// Stops the heartbeat started by `start_watchdog()`, if it is running.
+    pub fn stop_watchdog(&mut self) {
+        if let Some(heartbeat) = self.heartbeat.take() {
+            heartbeat.abort();
         }
-        Command::GetFrequency(get_frequency) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_frequency.clone().into();
+    }
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetFrequencyResponse, _> = sg_response.try_into();
+    /// Enables RF output and spawns a dead-man switch that switches it off again once no queued
+    /// command has been processed for `timeout`.
+    ///
+    /// Every response of a queued command restarts the timer, so the caller keeps the output on by
+    /// keeping commands flowing, such as with `start_watchdog`. When the switch trips, the response
+    /// to the `SetRFOutput` is broadcast followed by `Response::DeadmanTripped`, and the task ends.
+    /// A switch that is already running is replaced.
+    ///
+    /// Unlike `SetSOAWatchdogConfig` this runs on the host, so it also covers firmware without a
+    /// watchdog, but not a host that stops running altogether.
+    ///
+    /// Must be called from within a tokio runtime after connecting.
+    pub async fn enable_rf_with_deadman(&mut self, timeout: Duration) -> Result<(), SafetyError> {
+        let (Some(port), Some(response_tx)) = (&self.port, &self.response_tx) else {
+            return Err(SafetyError::NotConnected);
+        };
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetFrequencyResponse(formatted_response)
+        let port = Arc::clone(port);
+        let response_tx = response_tx.clone();
+        let id_counter = Arc::clone(&self.next_id);
+
+        // Subscribe before enabling so no activity in between is missed.
+        let mut response_rx = response_tx.subscribe();
+
+        self.stop_deadman();
+
+        let response = self
+            .execute(
+                &port,
+                Command::SetRFOutput(SetRFOutput::new(Channel::default(), true)),
+            )
+            .await;
+        if response.is_error() {
+            // Leave the output off, as it may have been enabled without the reply arriving.
+            let disable = Command::SetRFOutput(SetRFOutput::new(Channel::default(), false));
+            self.execute(&port, disable).await;
+            return Err(SafetyError::EnableRf(response));
+        }
+
+        self.deadman = Some(tokio::spawn(async move {
+            let deadline = tokio::time::sleep(timeout);
+            tokio::pin!(deadline);
+
+            loop {
+                select! {
+                    _ = &mut deadline => break,
+                    received = response_rx.recv() => match received {
+                        // Notices from the driver itself don't mean a command went through.
+                        Ok(correlated) if correlated.priority.is_none() => {}
+                        // Skipped responses were commands going through all the same.
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                            deadline.as_mut().reset(tokio::time::Instant::now() + timeout);
                         }
-                        Err(e) => Response::MWError(e),
-                    }
+                        // The driver disconnected, there's nothing left to switch off.
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    },
                 }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetFrequency(get_frequency), e.description);
+            }
 
-                    Response::ReadWriteError(error_response)
-                }
+            tracing::warn!(
+                ?timeout,
+                "No command was processed in time, switching RF output off."
+            );
+            let response = {
+                let mut port = port.lock().await;
+                Command::SetRFOutput(SetRFOutput::new(Channel::default(), false))
+                    .execute(&mut **port)
             };
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetFrequency(set_frequency) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_frequency.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    if sg_response.contains("ERR") {
-                        let e: MWError = sg_response.into();
-                        Response::MWError(e)
-                    } else {
-                        Response::SetFrequencyResponse(set_frequency.frequency)
-                    }
-                    // let parse_result: Result<SetFrequencyResponse, _> = sg_response.try_into();
-                    //
-                    // match parse_result {
-                    //     Ok(formatted_response) => {
-                    //         Response::SetFrequencyResponse(formatted_response)
-                    //     }
-                    //     Err(e) => Response::MWError(e),
-                    // }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::SetFrequency(set_frequency), e.description);
+            let _ = response_tx.send(CorrelatedResponse::new(next_id(&id_counter), response));
+            let _ = response_tx.send(CorrelatedResponse::new(
+                next_id(&id_counter),
+                Response::DeadmanTripped,
+            ));
+        }));
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        Ok(())
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+    /// Stops the dead-man switch started by `enable_rf_with_deadman()`, leaving RF output as it is.
+    pub fn stop_deadman(&mut self) {
+        if let Some(deadman) = self.deadman.take() {
+            deadman.abort();
         }
-        Command::GetRFOutput(get_rfoutput) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_rfoutput.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetRFOutputResponse, _> = sg_response.try_into();
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => Response::GetRFOutputResponse(formatted_response),
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetRFOutput(get_rfoutput), e.description);
+    /// Switches the device's UART to `baud_rate` and reopens the port at the new rate,
+    /// updating the target properties to match.
+    ///
+    /// The port is held for the whole switch, so no queued or direct command is sent while the
+    /// two sides disagree. The new rate is confirmed with a `GetIdentity`. If the device still
+    /// answers at the previous rate, the port is switched back and `ConnectError::BaudMismatch`
+    /// suggests the previous rate. If it answers at neither, the port is left at the new rate.
+    ///
+    /// The device only applies the baud rate to its UART, so this has no use over USB.
+    pub async fn change_baud_rate(&mut self, baud_rate: BaudRate) -> Result<(), ConnectError> {
+        let Some(port) = &self.port else {
+            return Err(Error::new(
+                serialport::ErrorKind::NoDevice,
+                "The driver must be connected before the baud rate can be changed.",
+            )
+            .into());
+        };
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        let port = Arc::clone(port);
+        let previous: u32 = self.properties.baud_rate.clone().into();
+        let requested: u32 = baud_rate.clone().into();
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetRFOutput(set_rfoutput) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_rfoutput.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    if sg_response.contains("ERR") {
-                        let e: MWError = sg_response.into();
-                        Response::MWError(e)
-                    } else {
-                        Response::SetRFOutputResponse(set_rfoutput.enabled)
-                    }
+        let mut port = port.lock().await;
 
-                    // let parse_result: Result<SetRFOutputResponse, _> = sg_response.try_into();
-                    //
-                    // match parse_result {
-                    //     Ok(formatted_response) => {
-                    //         Response::SetRFOutputResponse(formatted_response, set_rfoutput.enabled)
-                    //     }
-                    //     Err(e) => Response::MWError(e),
-                    // }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::SetRFOutput(set_rfoutput), e.description);
+        // The command has no reply, the device switches rates once it has been received.
+        let command = SetUartBaudRate::new(Channel::default(), baud_rate.clone());
+        send_command_within(command.into(), &mut **port, None);
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        port.set_baud_rate(requested)?;
+        if !answers_identity(&mut **port) {
+            port.set_baud_rate(previous)?;
+            if answers_identity(&mut **port) {
+                return Err(ConnectError::BaudMismatch {
+                    configured: requested,
+                    suggested: previous,
+                });
+            }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+            port.set_baud_rate(requested)?;
+            self.set_baud_rate(baud_rate);
+            return Err(ConnectError::Unresponsive {
+                baud_rate: requested,
+            });
         }
-        Command::GetPhase(get_phase) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_phase.clone().into();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPhaseResponse, _> = sg_response.try_into();
+        self.set_baud_rate(baud_rate);
+        Ok(())
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => Response::GetPhaseResponse(formatted_response),
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetPhase(get_phase), e.description);
+    /// Resets the signal generator, waits for it to come back and optionally reapplies `config`.
+    ///
+    /// The port is held from the reset until the device answers again, so queued commands wait
+    /// rather than fail, and the senders and subscribers of the connection keep working. A serial
+    /// port or TCP connection is reopened, as the device drops off the bus while it reboots.
+    /// A transport handed to `connect_transport()` is kept and polled instead.
+    ///
+    /// The device is polled with a `GetIdentity`, starting after the reconnect backoff and
+    /// doubling the wait after every attempt, until the reset timeout of the target properties
+    /// has passed. The report of the reapplied config is returned, if one was given.
+    pub async fn reset_and_reconnect(
+        &mut self,
+        config: Option<DeviceConfig>,
+    ) -> Result<Option<ApplyReport>, ResetError> {
+        let (Some(port), Some(endpoint)) = (&self.port, &self.endpoint) else {
+            return Err(ResetError::NotConnected);
+        };
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        let port = Arc::clone(port);
+        let reset_timeout = self.properties.reset_timeout;
+        let deadline = Instant::now() + reset_timeout;
+
+        {
+            let mut port = port.lock().await;
+
+            let response = send_command_within(ResetSystem::default().into(), &mut **port, None);
+            // The device may reboot before finishing its reply, so only an outright refusal counts.
+            if let Response::MWError(_) = response {
+                return Err(ResetError::Rejected(response));
+            }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetPhase(set_phase) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_phase.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    if sg_response.contains("ERR") {
-                        let e: MWError = sg_response.into();
-                        Response::MWError(e)
-                    } else {
-                        Response::SetPhaseResponse(set_phase.phase)
+            if !matches!(endpoint, Endpoint::DryRun) {
+                // The old connection is kept until a new one opens, so a device that never comes
+                // back leaves a lost connection behind for `auto_reconnect` to pick up.
+                let reopenable = !matches!(endpoint, Endpoint::Custom);
+                let mut backoff = self.properties.reconnect_backoff;
+                loop {
+                    if Instant::now() >= deadline {
+                        return Err(ResetError::Timeout(reset_timeout));
                     }
+                    tokio::time::sleep(backoff.min(remaining(deadline))).await;
+                    backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
 
-                    // let parse_result: Result<SetPhaseResponse, _> = sg_response.try_into();
-                    //
-                    // match parse_result {
-                    //     Ok(formatted_response) => Response::SetPhaseResponse(formatted_response),
-                    //     Err(e) => Response::MWError(e),
-                    // }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::SetPhase(set_phase), e.description);
+                    if reopenable {
+                        let Ok(reopened) = reopen(endpoint, &self.properties) else {
+                            continue;
+                        };
+                        *port = wrap_transport(reopened, &self.properties, self.wire_hook.as_ref());
+                    }
 
-                    Response::ReadWriteError(error_response)
+                    if answers_identity(&mut **port) {
+                        tracing::info!("The controller is back after the reset.");
+                        break;
+                    }
                 }
-            };
+            }
+        }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+        match config {
+            Some(config) => Ok(Some(self.apply_config(&config).await?)),
+            None => Ok(None),
         }
-        Command::GetPAPowerSetpointDBM(get_papower_setpoint_dbm) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_papower_setpoint_dbm.clone().into();
+    }
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPAPowerSetpointDBMResponse, _> =
-                        sg_response.try_into();
+    fn set_baud_rate(&mut self, baud_rate: BaudRate) {
+        self.baud_rate
+            .store(baud_rate.clone().into(), Ordering::Relaxed);
+        self.properties.baud_rate = baud_rate;
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetPAPowerSetpointDBMResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetPAPowerSetpointDBM(get_papower_setpoint_dbm),
-                        e.description,
-                    );
+    /// Sets the frequency and power, then enables RF output as a single guarded operation.
+    ///
+    /// Both values are validated against the device limits in the target properties before
+    /// anything is sent. If a power ramp is configured, the setpoint is stepped up from its
+    /// current value to `power`.
+    /// RF is only enabled once the board reports no active faults, and the enable is confirmed
+    /// by reading the output state back.
+    ///
+    /// If any step fails, RF output is switched off and the failing step is returned.
+    pub async fn start_output(&self, frequency: Frequency, power: Watt) -> Result<(), SafetyError> {
+        let limits = &self.properties.limits;
+        if !limits.frequency_in_range(frequency) {
+            return Err(SafetyError::FrequencyOutOfRange {
+                requested: frequency,
+                limits: limits.clone(),
+            });
+        }
+        if !limits.power_in_range(power) {
+            return Err(SafetyError::PowerOutOfRange {
+                requested: power,
+                limits: limits.clone(),
+            });
+        }
+
+        let Some(port) = &self.port else {
+            return Err(SafetyError::NotConnected);
+        };
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        let result = self.enable_output(port, frequency, power).await;
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+        if result.is_err() {
+            // Leave the output off regardless of which step failed.
+            let disable = Command::SetRFOutput(SetRFOutput::new(Channel::default(), false));
+            self.execute(port, disable).await;
         }
-        Command::GetPAPowerSetpointWatt(get_papower_setpoint_watt) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_papower_setpoint_watt.clone().into();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPAPowerSetpointWattResponse, _> =
-                        sg_response.try_into();
+        result
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetPAPowerSetpointWattResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetPAPowerSetpointWatt(get_papower_setpoint_watt),
-                        e.description,
-                    );
+    /// Enables RF output and returns a guard that switches it off again when dropped.
+    ///
+    /// Unlike `start_output`, the frequency and power are left as they are. See `RfOutputGuard`
+    /// for how the output is switched off when the guard is dropped.
+    pub async fn enable_rf_guarded(&self) -> Result<RfOutputGuard, SafetyError> {
+        let Some(port) = &self.port else {
+            return Err(SafetyError::NotConnected);
+        };
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        // Create the guard first so the output is switched off again if enabling fails halfway.
+        let guard = RfOutputGuard::new(Arc::clone(port));
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+        let response = self
+            .execute(
+                port,
+                Command::SetRFOutput(SetRFOutput::new(Channel::default(), true)),
+            )
+            .await;
+        if response.is_error() {
+            return Err(SafetyError::EnableRf(response));
         }
-        Command::SetPAPowerSetpointDBM(set_papower_setpoint_dbm) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_papower_setpoint_dbm.clone().into();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetPAPowerSetpointDBMResponse, _> =
-                        sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetPAPowerSetpointDBMResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetPAPowerSetpointDBM(set_papower_setpoint_dbm),
-                        e.description,
-                    );
+        Ok(guard)
+    }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    /// Pulses RF output `cycles` times, on for `on` and off for `off` in between,
+    /// then returns once the last pulse has run out.
+    ///
+    /// Each pulse is a `SetTimedRFEnable`, so the signal generator switches RF off by itself
+    /// even if the driver goes away mid-pulse. The time between pulses is kept by the driver
+    /// and is therefore approximate. If a pulse is rejected, RF output is switched off and the
+    /// response returned.
+    pub async fn pulse_rf(
+        &self,
+        on: Duration,
+        off: Option<Duration>,
+        cycles: Option<u32>,
+    ) -> Result<(), SafetyError> {
+        let pulse = TimedRfEnable::new(on, off, cycles).map_err(SafetyError::InvalidPulse)?;
+
+        let Some(port) = &self.port else {
+            return Err(SafetyError::NotConnected);
+        };
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetPAPowerSetpointWatt(set_papower_setpoint_watt) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_papower_setpoint_watt.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    if sg_response.contains("ERR") {
-                        let e: MWError = sg_response.into();
-                        Response::MWError(e)
-                    } else {
-                        Response::SetPAPowerSetpointWattResponse(set_papower_setpoint_watt.power)
-                    }
-                    // let parse_result: Result<SetPAPowerSetpointWattResponse, _> =
-                    //     sg_response.try_into();
-                    //
-                    // match parse_result {
-                    //     Ok(formatted_response) => {
-                    //         Response::SetPAPowerSetpointWattResponse(formatted_response)
-                    //     }
-                    //     Err(e) => Response::MWError(e),
-                    // }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetPAPowerSetpointWatt(set_papower_setpoint_watt),
-                        e.description,
-                    );
+        for cycle in 0..pulse.cycles() {
+            if cycle > 0 {
+                tokio::time::sleep(pulse.off_time()).await;
+            }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+            let enable = Command::SetTimedRFEnable(pulse.command(Channel::default()));
+            let response = self.execute(port, enable).await;
+            if response.is_error() {
+                let disable = Command::SetRFOutput(SetRFOutput::new(Channel::default(), false));
+                self.execute(port, disable).await;
+                return Err(SafetyError::TimedEnable(response));
+            }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+            tokio::time::sleep(pulse.on_time).await;
         }
-        Command::GetPATemp(get_patemp) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_patemp.clone().into();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPATempResponse, _> = sg_response.try_into();
+        Ok(())
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => Response::GetPATempResponse(formatted_response),
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetPATemp(get_patemp), e.description);
+    /// Checks that the signal generator is in a known-safe idle state: RF output off,
+    /// a power setpoint of zero, no active faults on the board and no alarms on the PA.
+    ///
+    /// Every reading is taken regardless of the others, so the error lists everything that
+    /// doesn't match, including readings that failed.
+    pub async fn assert_idle(&self) -> Result<(), NotIdle> {
+        let Some(port) = &self.port else {
+            return Err(NotIdle::NotConnected);
+        };
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        let mut violations = Vec::new();
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+        match self
+            .execute(
+                port,
+                Command::GetRFOutput(GetRFOutput::new(Channel::default())),
+            )
+            .await
+        {
+            Response::GetRFOutputResponse(output) if output.enabled => {
+                violations.push(IdleViolation::RfEnabled)
+            }
+            Response::GetRFOutputResponse(_) => {}
+            response => violations.push(IdleViolation::Unreadable(response)),
         }
-        Command::GetPAVoltage(get_pavoltage) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_pavoltage.clone().into();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPAVoltageResponse, _> = sg_response.try_into();
+        match self
+            .execute(
+                port,
+                Command::GetPAPowerSetpointWatt(GetPAPowerSetpointWatt::new(Channel::default())),
+            )
+            .await
+        {
+            Response::GetPAPowerSetpointWattResponse(setpoint) if setpoint.power.power > 0. => {
+                violations.push(IdleViolation::PowerSetpoint(setpoint.power))
+            }
+            Response::GetPAPowerSetpointWattResponse(_) => {}
+            response => violations.push(IdleViolation::Unreadable(response)),
+        }
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetPAVoltageResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
+        match self
+            .execute(port, Command::GetStatus(GetStatus::new(Channel::default())))
+            .await
+        {
+            Response::GetStatusResponse(status) => {
+                let faults = active_faults(status);
+                if !faults.is_empty() {
+                    violations.push(IdleViolation::ActiveFaults(faults));
                 }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetPAVoltage(get_pavoltage), e.description);
+            }
+            response => violations.push(IdleViolation::Unreadable(response)),
+        }
 
-                    Response::ReadWriteError(error_response)
+        #[allow(deprecated)]
+        let pa_errors = Command::GetPAErrors(GetPAErrors::new(Channel::default()));
+        match self.execute(port, pa_errors).await {
+            Response::GetPAErrorsResponse(errors) => {
+                let alarms = errors.errors();
+                if !alarms.is_empty() {
+                    violations.push(IdleViolation::PaAlarms(alarms));
                 }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+            }
+            // The ISC-2425-25+ doesn't implement the command, its status above covers the PA.
+            Response::MWError(_) => {}
+            response => violations.push(IdleViolation::Unreadable(response)),
         }
-        Command::GetDLLConfig(get_dllconfig) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_dllconfig.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetDLLConfigResponse, _> = sg_response.try_into();
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetDLLConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetDLLConfig(get_dllconfig), e.description);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(NotIdle::Violations(violations))
+        }
+    }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    /// Switches RF output off, sets the power setpoint to zero and clears the errors,
+    /// then confirms the idle state with `assert_idle`.
+    ///
+    /// Faults that are still present after clearing the errors are reported in the error.
+    pub async fn force_idle(&self) -> Result<(), NotIdle> {
+        let Some(port) = &self.port else {
+            return Err(NotIdle::NotConnected);
+        };
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+        // Failures are left to the read back, which reports whatever didn't take effect.
+        let commands = [
+            Command::SetRFOutput(SetRFOutput::new(Channel::default(), false)),
+            Command::SetPAPowerSetpointWatt(SetPAPowerSetpointWatt::new(
+                Channel::default(),
+                Watt::new(0.),
+            )),
+            Command::ClearErrors(ClearErrors::new(Channel::default())),
+        ];
+        for command in commands {
+            self.execute(port, command).await;
         }
-        Command::SetDLLConfig(set_dllconfig) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_dllconfig.clone().into();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetDLLConfigResponse, _> = sg_response.try_into();
+        self.assert_idle().await
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetDLLConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::SetDLLConfig(set_dllconfig), e.description);
+    /// Brings the signal generator to the given configuration, reading back every setting.
+    ///
+    /// For each setting that is `Some`, the setter is sent followed by the matching getter.
+    /// A failing setting doesn't stop the others, the report lists the outcome of each one.
+    pub async fn apply_config(&self, config: &DeviceConfig) -> Result<ApplyReport, SendError> {
+        let Some(port) = &self.port else {
+            return Err(SendError::NotConnected);
+        };
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        let channel = Channel::default;
+        let mut report = ApplyReport::default();
+
+        if let Some(frequency) = config.frequency {
+            let outcome = self
+                .apply_setting(
+                    port,
+                    Command::SetFrequency(SetFrequency::new(channel(), frequency)),
+                    Command::GetFrequency(GetFrequency::new(channel())),
+                    frequency,
+                    |response| response.as_frequency().map(|get| get.frequency),
+                )
+                .await;
+            report.outcomes.push((Setting::Frequency, outcome));
+        }
+
+        if let Some(power) = config.power {
+            let outcome = self
+                .apply_setting(
+                    port,
+                    Command::SetPAPowerSetpointWatt(SetPAPowerSetpointWatt::new(channel(), power)),
+                    Command::GetPAPowerSetpointWatt(GetPAPowerSetpointWatt::new(channel())),
+                    power,
+                    |response| response.as_papower_setpoint_watt().map(|get| get.power),
+                )
+                .await;
+            report.outcomes.push((Setting::Power, outcome));
+        }
+
+        if let Some(phase) = config.phase {
+            let outcome = self
+                .apply_setting(
+                    port,
+                    Command::SetPhase(SetPhase::new(channel(), phase)),
+                    Command::GetPhase(GetPhase::new(channel())),
+                    phase,
+                    |response| response.as_phase().map(|get| get.phase),
+                )
+                .await;
+            report.outcomes.push((Setting::Phase, outcome));
+        }
+
+        if let Some(attenuation) = &config.attenuation {
+            let outcome = self
+                .apply_setting(
+                    port,
+                    Command::SetAttenuation(SetAttenuation::new(channel(), attenuation.clone())),
+                    Command::GetAttenuation(GetAttenuation::new(channel())),
+                    attenuation.clone(),
+                    |response| response.as_attenuation().map(|get| get.attenuation.clone()),
+                )
+                .await;
+            report.outcomes.push((Setting::Attenuation, outcome));
+        }
+
+        if let Some(limits) = &config.soa_temperature {
+            let outcome = self
+                .apply_setting(
+                    port,
+                    Command::SetSOATempConfig(SetSOATempConfig::new(
+                        channel(),
+                        limits.high,
+                        limits.shutdown,
+                    )),
+                    Command::GetSOATempConfig(GetSOATempConfig::new(channel())),
+                    limits.clone(),
+                    |response| {
+                        response
+                            .as_soa_temp_config()
+                            .map(|get| SoaLimits::new(get.high_temp, get.shutdown_temp))
+                    },
+                )
+                .await;
+            report.outcomes.push((Setting::SoaTemperature, outcome));
+        }
+
+        if let Some(limits) = &config.soa_reflected_power {
+            let outcome = self
+                .apply_setting(
+                    port,
+                    Command::SetSOAPowerConfig(SetSOAPowerConfig::new(
+                        channel(),
+                        limits.high.clone(),
+                        limits.shutdown.clone(),
+                    )),
+                    Command::GetSOAPowerConfig(GetSOAPowerConfig::new(channel())),
+                    limits.clone(),
+                    |response| {
+                        response.as_soa_power_config().map(|get| {
+                            SoaLimits::new(
+                                get.high_reflection.clone(),
+                                get.shutdown_reflection.clone(),
+                            )
+                        })
+                    },
+                )
+                .await;
+            report.outcomes.push((Setting::SoaReflectedPower, outcome));
+        }
+
+        if let Some(limits) = &config.soa_dissipation {
+            let outcome = self
+                .apply_setting(
+                    port,
+                    Command::SetSOADissipationConfig(SetSOADissipationConfig::new(
+                        channel(),
+                        limits.high,
+                        limits.shutdown,
+                    )),
+                    Command::GetSOADissipationConfig(GetSOADissipationConfig::new(channel())),
+                    limits.clone(),
+                    |response| {
+                        response.as_soa_dissipation_config().map(|get| {
+                            SoaLimits::new(get.high_dissipation, get.shutdown_dissipation)
+                        })
+                    },
+                )
+                .await;
+            report.outcomes.push((Setting::SoaDissipation, outcome));
+        }
+
+        if let Some(enabled) = config.dll_enabled {
+            let outcome = self
+                .apply_setting(
+                    port,
+                    Command::SetDLLEnabled(SetDLLEnabled::new(channel(), enabled)),
+                    Command::GetDLLEnabled(GetDLLEnabled::new(channel())),
+                    enabled,
+                    |response| response.as_dll_enabled().map(|get| get.enabled),
+                )
+                .await;
+            report.outcomes.push((Setting::DllEnabled, outcome));
+        }
+
+        Ok(report)
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetDLLEnabled(get_dllenabled) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_dllenabled.clone().into();
+    /// Reads back every setting a `DeviceConfig` holds, to be restored later with `import_config`
+    /// on this or another signal generator.
+    ///
+    /// Settings whose getter fails are left as `None`, so importing the snapshot doesn't touch them.
+    pub async fn export_config(&self) -> Result<DeviceConfig, SendError> {
+        let Some(port) = &self.port else {
+            return Err(SendError::NotConnected);
+        };
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetDLLEnabledResponse, _> = sg_response.try_into();
+        let channel = Channel::default;
+
+        let frequency = self
+            .execute(port, Command::GetFrequency(GetFrequency::new(channel())))
+            .await;
+        let power = self
+            .execute(
+                port,
+                Command::GetPAPowerSetpointWatt(GetPAPowerSetpointWatt::new(channel())),
+            )
+            .await;
+        let phase = self
+            .execute(port, Command::GetPhase(GetPhase::new(channel())))
+            .await;
+        let attenuation = self
+            .execute(
+                port,
+                Command::GetAttenuation(GetAttenuation::new(channel())),
+            )
+            .await;
+        let soa_temperature = self
+            .execute(
+                port,
+                Command::GetSOATempConfig(GetSOATempConfig::new(channel())),
+            )
+            .await;
+        let soa_reflected_power = self
+            .execute(
+                port,
+                Command::GetSOAPowerConfig(GetSOAPowerConfig::new(channel())),
+            )
+            .await;
+        let soa_dissipation = self
+            .execute(
+                port,
+                Command::GetSOADissipationConfig(GetSOADissipationConfig::new(channel())),
+            )
+            .await;
+        let dll_enabled = self
+            .execute(port, Command::GetDLLEnabled(GetDLLEnabled::new(channel())))
+            .await;
+
+        Ok(DeviceConfig {
+            frequency: frequency.as_frequency().map(|get| get.frequency),
+            power: power.as_papower_setpoint_watt().map(|get| get.power),
+            phase: phase.as_phase().map(|get| get.phase),
+            attenuation: attenuation
+                .as_attenuation()
+                .map(|get| get.attenuation.clone()),
+            soa_temperature: soa_temperature
+                .as_soa_temp_config()
+                .map(|get| SoaLimits::new(get.high_temp, get.shutdown_temp)),
+            soa_reflected_power: soa_reflected_power.as_soa_power_config().map(|get| {
+                SoaLimits::new(get.high_reflection.clone(), get.shutdown_reflection.clone())
+            }),
+            soa_dissipation: soa_dissipation
+                .as_soa_dissipation_config()
+                .map(|get| SoaLimits::new(get.high_dissipation, get.shutdown_dissipation)),
+            dll_enabled: dll_enabled.as_dll_enabled().map(|get| get.enabled),
+        })
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetDLLEnabledResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetDLLEnabled(get_dllenabled), e.description);
+    /// Reads back the settings of the device and lists those that differ from `golden`, see
+    /// `DeviceConfig::diff` for how `tolerance` applies.
+    pub async fn compare_to(
+        &self,
+        golden: &DeviceConfig,
+        tolerance: f32,
+    ) -> Result<Vec<ConfigDiff>, SendError> {
+        let config = self.export_config().await?;
+        Ok(golden.diff(&config, tolerance))
+    }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    /// Restores a snapshot taken with `export_config`, verifying every setting as `apply_config` does.
+    pub async fn import_config(&self, config: &DeviceConfig) -> Result<ApplyReport, SendError> {
+        self.apply_config(config).await
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetDLLEnabled(set_dllenabled) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_dllenabled.clone().into();
+    /// Sends the setter, reads the value back with the matching getter and checks the device
+    /// reports exactly what was requested.
+    ///
+    /// On success the value the device reports is returned.
+    pub async fn set_verified<S: VerifiableSetter>(
+        &self,
+        setter: S,
+    ) -> Result<S::Value, VerifyError<S::Value>> {
+        self.set_verified_within(setter, |requested, reported| requested == reported)
+            .await
+    }
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetDLLEnabledResponse, _> = sg_response.try_into();
+    /// Like `set_verified`, but `accept` decides whether the reported value is close enough to
+    /// the requested one, e.g. when the device rounds to its own resolution.
+    pub async fn set_verified_within<S: VerifiableSetter>(
+        &self,
+        setter: S,
+        accept: impl Fn(&S::Value, &S::Value) -> bool,
+    ) -> Result<S::Value, VerifyError<S::Value>> {
+        let Some(port) = &self.port else {
+            return Err(VerifyError::NotConnected);
+        };
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetDLLEnabledResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::SetDLLEnabled(set_dllenabled), e.description);
+        let requested = setter.requested();
+        let getter = setter.getter();
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        let response = self.execute(port, setter.into()).await;
+        if response.is_error() {
+            return Err(VerifyError::Rejected(response));
+        }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+        let response = self.execute(port, getter).await;
+        match S::reported(&response) {
+            Some(reported) if accept(&requested, &reported) => Ok(reported),
+            Some(reported) => Err(VerifyError::Mismatch {
+                requested,
+                reported,
+            }),
+            None => Err(VerifyError::ReadbackFailed(response)),
         }
-        Command::PerformSweepDBM(perform_sweep_dbm) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = perform_sweep_dbm.clone().into();
+    }
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<PerformSweepDBMResponse, _> = sg_response.try_into();
+    /// Sets the frequency and confirms the device reports it within `tolerance_mhz` of the
+    /// requested value, returning the frequency the device reports.
+    pub async fn set_frequency_verified(
+        &self,
+        frequency: Frequency,
+        tolerance_mhz: u16,
+    ) -> Result<Frequency, VerifyError<Frequency>> {
+        self.set_verified_within(
+            SetFrequency::new(Channel::default(), frequency),
+            |requested, reported| requested.frequency.abs_diff(reported.frequency) <= tolerance_mhz,
+        )
+        .await
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::PerformSweepDBMResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::PerformSweepDBM(perform_sweep_dbm),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    /// Sets the power setpoint in whichever unit the power was given in, sending
+    /// `SetPAPowerSetpointWatt` or `SetPAPowerSetpointDBM` accordingly.
+    ///
+    /// The power is checked against the device limits of the target properties first.
+    pub async fn set_power(&self, power: Power) -> Result<(), PowerError> {
+        let limits = &self.properties.limits;
+        let command: Command = match power {
+            Power::Watt(watt) => SetPAPowerSetpointWatt::try_new_within(
+                Channel::default(),
+                Watt::new(watt as f32),
+                limits,
+            )?
+            .into(),
+            Power::Dbm(dbm) => SetPAPowerSetpointDBM::try_new_within(
+                Channel::default(),
+                Dbm::new(dbm as f32),
+                limits,
+            )?
+            .into(),
+        };
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+        let response = self.send_await(command, Priority::Standard).await?;
+        if response.is_error() {
+            return Err(PowerError::Rejected(response));
         }
-        Command::PerformSweepWatt(perform_sweep_watt) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = perform_sweep_watt.clone().into();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<PerformSweepWattResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::PerformSweepWattResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::PerformSweepWatt(perform_sweep_watt),
-                        e.description,
-                    );
+        Ok(())
+    }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    /// Reads the power setpoint in the given unit, sending `GetPAPowerSetpointWatt` or
+    /// `GetPAPowerSetpointDBM` accordingly.
+    ///
+    /// ```no_run
+    /// use minicircuit_commands::prelude::*;
+    /// use minicircuit_driver::prelude::*;
+    ///
+    /// # async fn example(driver: &MiniCircuitDriver) -> Result<(), PowerError> {
+    /// driver.set_power(Power::from_dbm(47.0)).await?;
+    ///
+    /// let setpoint = driver.get_power(PowerUnit::Watt).await?;
+    /// println!("Setpoint: {:.1}W", setpoint.watt());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_power(&self, unit: PowerUnit) -> Result<Power, PowerError> {
+        let command: Command = match unit {
+            PowerUnit::Watt => GetPAPowerSetpointWatt::new(Channel::default()).into(),
+            PowerUnit::Dbm => GetPAPowerSetpointDBM::new(Channel::default()).into(),
+        };
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+        let response = self.send_await(command, Priority::Standard).await?;
+        match response {
+            Response::GetPAPowerSetpointWattResponse(setpoint) => Ok(setpoint.power.into()),
+            Response::GetPAPowerSetpointDBMResponse(setpoint) => Ok(setpoint.power.into()),
+            response => Err(PowerError::Rejected(response)),
         }
-        Command::ClearErrors(clear_errors) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = clear_errors.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<ClearErrorsResponse, _> = sg_response.try_into();
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => Response::ClearErrorsResponse(formatted_response),
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::ClearErrors(clear_errors), e.description);
+    /// Switches the clock source, such as to the external reference of a master board in a
+    /// coherent multi-channel system.
+    ///
+    /// With `verify` set and a source that runs on the external reference, the board is asked
+    /// for its clock source and status afterwards. `GetStatus` has no bit of its own for the
+    /// lock of the reference, so the check is that the board reports the source back and no
+    /// active fault, failing with `ClockSourceError::NotLocked` otherwise.
+    ///
+    /// ```
+    /// use minicircuit_commands::{prelude::*, system::clock_source::ClockSource};
+    /// use minicircuit_driver::{driver::MiniCircuitDriver, error::ClockSourceError, mock::MockTransport};
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() {
+    ///     let transport = MockTransport::builder()
+    ///         .reply("$CSS,1,2", "OK")
+    ///         .reply("$CSG,1", "OK,1,2")
+    ///         // An unspecified error raised after switching.
+    ///         .reply("$ST,1", "OK,1,0,1")
+    ///         .build();
+    ///     let (driver, _, _) =
+    ///         MiniCircuitDriver::from_transport(transport, TargetProperties::default()).unwrap();
+    ///
+    ///     let result = driver.set_clock_source(ClockSource::Slave, true).await;
+    ///     assert!(matches!(result, Err(ClockSourceError::NotLocked(_))));
+    /// }
+    /// ```
+    pub async fn set_clock_source(
+        &self,
+        source: ClockSource,
+        verify: bool,
+    ) -> Result<(), ClockSourceError> {
+        let setter = SetClockSource::new(Channel::default(), source.clone());
+        let response = self.send_await(setter, Priority::Standard).await?;
+        if response.is_error() {
+            return Err(ClockSourceError::Rejected(response));
+        }
+
+        if !verify || !source.uses_external_reference() {
+            return Ok(());
+        }
+
+        let getter = GetClockSource::new(Channel::default());
+        match self.send_await(getter, Priority::Standard).await? {
+            Response::GetClockSourceResponse(reported) if reported.source() == source => {}
+            Response::GetClockSourceResponse(reported) => {
+                return Err(ClockSourceError::Mismatch {
+                    requested: source,
+                    reported: reported.source(),
+                })
+            }
+            response => return Err(ClockSourceError::Rejected(response)),
+        }
 
-                    Response::ReadWriteError(error_response)
+        let status = GetStatus::new(Channel::default());
+        match self.send_await(status, Priority::Standard).await? {
+            Response::GetStatusResponse(status) => {
+                let faults = active_faults(status);
+                if faults.is_empty() {
+                    Ok(())
+                } else {
+                    Err(ClockSourceError::NotLocked(faults))
                 }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+            }
+            response => Err(ClockSourceError::Rejected(response)),
         }
-        Command::GetPAErrors(get_paerrors) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_paerrors.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPAErrorsResponse, _> = sg_response.try_into();
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => Response::GetPAErrorsResponse(formatted_response),
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetPAErrors(get_paerrors), e.description);
+    /// Sends the setter, then compares what the getter reports with the requested value.
+    async fn apply_setting<T: PartialEq + Display>(
+        &self,
+        port: &Mutex<Box<dyn Transport>>,
+        setter: Command,
+        getter: Command,
+        requested: T,
+        reported: impl Fn(&Response) -> Option<T>,
+    ) -> SettingOutcome {
+        let response = self.execute(port, setter).await;
+        if response.is_error() {
+            return SettingOutcome::Rejected(response);
+        }
+
+        let response = self.execute(port, getter).await;
+        match reported(&response) {
+            Some(reported) if reported == requested => SettingOutcome::Applied,
+            Some(reported) => SettingOutcome::Mismatch {
+                requested: requested.to_string(),
+                reported: reported.to_string(),
+            },
+            None => SettingOutcome::ReadbackFailed(response),
+        }
+    }
 
-                    Response::ReadWriteError(error_response)
+    async fn enable_output(
+        &self,
+        port: &Mutex<Box<dyn Transport>>,
+        frequency: Frequency,
+        power: Watt,
+    ) -> Result<(), SafetyError> {
+        let response = self
+            .execute(
+                port,
+                Command::SetFrequency(SetFrequency::new(Channel::default(), frequency)),
+            )
+            .await;
+        if response.is_error() {
+            return Err(SafetyError::SetFrequency(response));
+        }
+
+        // Build the list of setpoints to step through on the way to the requested power.
+        let mut setpoints = Vec::new();
+        if let Some(ramp) = &self.properties.power_ramp {
+            if ramp.step.power > 0. {
+                // Step up from where the setpoint is now. If it can't be read, stepping up from
+                // nothing is the cautious choice.
+                let current = self
+                    .execute(
+                        port,
+                        Command::GetPAPowerSetpointWatt(GetPAPowerSetpointWatt::new(
+                            Channel::default(),
+                        )),
+                    )
+                    .await;
+                let current = current
+                    .as_papower_setpoint_watt()
+                    .map_or(0., |setpoint| setpoint.power.power.max(0.));
+
+                let mut setpoint = current + ramp.step.power;
+                while setpoint < power.power {
+                    setpoints.push(Watt::new(setpoint));
+                    setpoint += ramp.step.power;
                 }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+            }
         }
-        Command::GetStatus(get_status) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_status.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetStatusResponse, _> = sg_response.try_into();
+        setpoints.push(power);
+
+        let last = setpoints.len() - 1;
+        for (index, setpoint) in setpoints.into_iter().enumerate() {
+            let response = self
+                .execute(
+                    port,
+                    Command::SetPAPowerSetpointWatt(SetPAPowerSetpointWatt::new(
+                        Channel::default(),
+                        setpoint,
+                    )),
+                )
+                .await;
+            if response.is_error() {
+                return Err(SafetyError::SetPower(response));
+            }
 
-                    match parse_result {
-                        Ok(formatted_response) => Response::GetStatusResponse(formatted_response),
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetStatus(get_status), e.description);
+            if let (Some(ramp), true) = (&self.properties.power_ramp, index < last) {
+                tokio::time::sleep(ramp.interval).await;
+            }
+        }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        // Refuse to enable RF while the board reports a fault.
+        let response = self
+            .execute(port, Command::GetStatus(GetStatus::new(Channel::default())))
+            .await;
+        let Response::GetStatusResponse(status) = response else {
+            return Err(SafetyError::StatusQuery(response));
+        };
+        let faults = active_faults(status);
+        if !faults.is_empty() {
+            return Err(SafetyError::Precondition(faults));
+        }
+
+        let response = self
+            .execute(
+                port,
+                Command::SetRFOutput(SetRFOutput::new(Channel::default(), true)),
+            )
+            .await;
+        if response.is_error() {
+            return Err(SafetyError::EnableRf(response));
+        }
+
+        // Confirm the board actually turned the output on.
+        let response = self
+            .execute(
+                port,
+                Command::GetRFOutput(GetRFOutput::new(Channel::default())),
+            )
+            .await;
+        match &response {
+            Response::GetRFOutputResponse(output) if output.enabled => Ok(()),
+            _ => Err(SafetyError::Readback(response)),
+        }
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+    /// Sends a command directly, bypassing the queue, and delivers the response to subscribers.
+    async fn execute(&self, port: &Mutex<Box<dyn Transport>>, mut command: Command) -> Response {
+        if let Some(notice) = clamp_power(&mut command, self.power_limits().as_ref()) {
+            if let Some(response_tx) = &self.response_tx {
+                let _ = response_tx.send(CorrelatedResponse::new(next_id(&self.next_id), notice));
+            }
         }
-        Command::GetIdentity(get_identity) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_identity.clone().into();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetIdentityResponse, _> = sg_response.try_into();
+        let response = {
+            let mut port = port.lock().await;
+            if let Some(response_tx) = &self.response_tx {
+                publish_unsolicited(&mut **port, &self.properties, response_tx, &self.next_id);
+            }
+
+            if self.properties.dry_run {
+                Response::DryRun(String::from(&command))
+            } else if let Some(rejected) =
+                reject_unsupported(&self.properties, self.capabilities().as_ref(), &command)
+            {
+                rejected
+            } else {
+                let started = Instant::now();
+                let response = send_command_within(command.clone(), &mut **port, None);
+                self.stats.record_latency(&command, started.elapsed());
 
-                    match parse_result {
-                        Ok(formatted_response) => Response::GetIdentityResponse(formatted_response),
-                        Err(e) => Response::MWError(e),
-                    }
+                // The port stays locked, so the next command waits for the device to settle.
+                if let Some(settle) = settle_time(&self.properties, &command, &response) {
+                    tokio::time::sleep(settle).await;
                 }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetIdentity(get_identity), e.description);
+                response
+            }
+        };
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        let id = next_id(&self.next_id);
+        self.stats.record(id, command, &response);
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+        // Direct commands skip the queue, which makes them as urgent as it gets.
+        if let Some(response_tx) = &self.response_tx {
+            let _ = response_tx.send(
+                CorrelatedResponse::new(id, response.clone()).with_priority(Priority::Immediate),
+            );
         }
-        Command::GetISCTemp(get_isctemp) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_isctemp.clone().into();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetISCTempResponse, _> = sg_response.try_into();
+        response
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => Response::GetISCTempResponse(formatted_response),
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetISCTemp(get_isctemp), e.description);
+    /// Reads the frequency, forward and reflected power, PA temperature, current and voltage
+    /// as one sequence, so no other command is serviced in between.
+    ///
+    /// A getter that fails doesn't stop the others, its field holds the failing response.
+    pub async fn read_telemetry(&self) -> Result<TelemetrySnapshot, SendError> {
+        let commands = vec![
+            GetFrequency::default().into(),
+            GetPAPowerWatt::default().into(),
+            GetPATemp::default().into(),
+            GetPACurrent::default().into(),
+            GetPAVoltage::default().into(),
+        ];
+
+        let responses = self
+            .send_sequence(commands, Priority::Standard, SequenceFailure::Continue)
+            .await?;
+
+        // Every getter is answered when the sequence continues past failures.
+        let responses: [Response; 5] = responses.try_into().map_err(|_| SendError::QueueClosed)?;
+
+        Ok(TelemetrySnapshot::from_responses(responses))
+    }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    /// Coalesces responses into evenly spaced frames holding the latest value of each requested metric.
+    ///
+    /// A frame is emitted every `rate` regardless of when the underlying getters complete.
+    /// The driver doesn't poll on its own - the caller keeps queueing the relevant getters.
+    /// Values that weren't refreshed since the previous frame are marked as stale.
+    ///
+    /// Must be called from within a tokio runtime after connecting.
+    pub fn telemetry_frames(
+        &self,
+        rate: Duration,
+        metrics: Vec<TelemetryMetric>,
+    ) -> Result<impl Stream<Item = TelemetryFrame>, Error> {
+        let Some(response_tx) = &self.response_tx else {
+            return Err(Error::new(
+                serialport::ErrorKind::NoDevice,
+                "The driver must be connected before telemetry frames can be produced.",
+            ));
+        };
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetUptime(get_uptime) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_uptime.clone().into();
+        Ok(spawn_frame_loop(response_tx.subscribe(), rate, metrics))
+    }
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetUptimeResponse, _> = sg_response.try_into();
+    /// Watches the measured values for the thresholds and yields an event whenever one trips or
+    /// clears, rather than on every sample.
+    ///
+    /// A threshold trips above its limit and clears below its limit minus its hysteresis, so a
+    /// value hovering around the limit, such as in marginal thermal conditions, doesn't flap.
+    /// Like `telemetry_frames`, the driver doesn't poll on its own - the caller keeps queueing
+    /// the relevant getters. Acting on an event, such as switching RF output off, is left to
+    /// the caller.
+    ///
+    /// Must be called from within a tokio runtime after connecting.
+    pub fn monitor_soa(
+        &self,
+        thresholds: Vec<SoaThreshold>,
+    ) -> Result<impl Stream<Item = SoaEvent>, Error> {
+        let Some(response_tx) = &self.response_tx else {
+            return Err(Error::new(
+                serialport::ErrorKind::NoDevice,
+                "The driver must be connected before the SOA can be monitored.",
+            ));
+        };
+
+        Ok(spawn_monitor_loop(
+            response_tx.subscribe(),
+            SoaMonitor::new(thresholds),
+        ))
+    }
+
+    /// Subscribes to the responses matching `predicate` only, such as those of `Priority::Immediate`
+    /// messages or of messages carrying a certain tag.
+    ///
+    /// If the stream isn't read fast enough to keep up with the broadcast channel, it yields a
+    /// `Response::Lagged` with the number of responses that were missed.
+    ///
+    /// Must be called from within a tokio runtime after connecting.
+    pub fn subscribe_filtered<F>(
+        &self,
+        predicate: F,
+    ) -> Result<impl Stream<Item = CorrelatedResponse>, Error>
+    where
+        F: Fn(&CorrelatedResponse) -> bool + Send + 'static,
+    {
+        let Some(response_tx) = &self.response_tx else {
+            return Err(Error::new(
+                serialport::ErrorKind::NoDevice,
+                "The driver must be connected before responses can be subscribed to.",
+            ));
+        };
 
-                    match parse_result {
-                        Ok(formatted_response) => Response::GetUptimeResponse(formatted_response),
-                        Err(e) => Response::MWError(e),
+        let mut response_rx = response_tx.subscribe();
+        let (filtered_tx, filtered_rx) = tokio::sync::mpsc::channel(16);
+        let id_counter = Arc::clone(&self.next_id);
+
+        tokio::spawn(async move {
+            loop {
+                match response_rx.recv().await {
+                    Ok(correlated) if predicate(&correlated) => {
+                        // The subscriber dropped the stream.
+                        if filtered_tx.send(correlated).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) => continue,
+                    // Passed on regardless of the predicate, as any of the skipped responses may have matched.
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "Filtered subscriber fell behind.");
+                        let notice = CorrelatedResponse::new(
+                            next_id(&id_counter),
+                            Response::Lagged(skipped),
+                        );
+                        if filtered_tx.send(notice).await.is_err() {
+                            break;
+                        }
                     }
+                    Err(broadcast::error::RecvError::Closed) => break,
                 }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetUptime(get_uptime), e.description);
+            }
+        });
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        Ok(ReceiverStream::new(filtered_rx))
+    }
+
+    /// Connects to the first detected signal generator, or to the port of the target properties
+    /// if none is detected.
+    ///
+    /// Once the port is open, the device is asked for its identity to confirm it answers at the
+    /// configured baud rate. See `ConnectError` for how a mismatch is reported.
+    ///
+    /// Listing and opening the ports fails with `NoDevice` once the connect timeout of the target
+    /// properties has elapsed, rather than stalling on an unplugged device.
+    pub fn connect(
+        &mut self,
+    ) -> Result<(CommandSender, broadcast::Sender<CorrelatedResponse>), ConnectError> {
+        self.connect_selecting(|candidates| candidates.first())
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+    /// Like `connect()`, but lets `select` pick which of the detected signal generators to
+    /// connect to, such as by the serial number of its USB adapter.
+    ///
+    /// When `select` picks none of them, the port of the target properties is used instead,
+    /// as when no signal generator is detected.
+    ///
+    /// ```no_run
+    /// use minicircuit_driver::prelude::*;
+    ///
+    /// let mut driver = MiniCircuitDriver::new(TargetProperties::default());
+    /// let (queue_tx, response_tx) = driver
+    ///     .connect_selecting(|candidates| {
+    ///         candidates
+    ///             .iter()
+    ///             .find(|candidate| candidate.serial_number.as_deref() == Some("A10KX3RS"))
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn connect_selecting<F>(
+        &mut self,
+        select: F,
+    ) -> Result<(CommandSender, broadcast::Sender<CorrelatedResponse>), ConnectError>
+    where
+        F: FnOnce(&[PortCandidate]) -> Option<&PortCandidate>,
+    {
+        // Nothing is sent in dry-run mode, so there's no need for a device.
+        if self.properties.dry_run {
+            return Ok(self.start(Box::new(DryRunTransport), Endpoint::DryRun)?);
         }
-        Command::GetVersion(get_version) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_version.clone().into();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetVersionResponse, _> = sg_response.try_into();
+        let properties_clone = self.properties.clone();
+        let deadline = Instant::now() + properties_clone.connect_timeout;
 
-                    match parse_result {
-                        Ok(formatted_response) => Response::GetVersionResponse(formatted_response),
-                        Err(e) => Response::MWError(e),
-                    }
+        // Try to get a list of ports that match the vendor and product ids
+        let signal_generators = match autodetect_sg_port_within(
+            properties_clone.vendor_id.clone(),
+            properties_clone.product_id.clone(),
+            properties_clone.connect_timeout,
+        ) {
+            Ok(list_of_sg) => list_of_sg,
+            Err(e) => {
+                // If autodetection fails and we have a specified port, try to use that instead
+                if let Some(port_name) = &properties_clone.port {
+                    tracing::warn!(
+                        error = %e,
+                        port = %port_name,
+                        "Autodetection failed, falling back to the specified port."
+                    );
+                    return self.port_connect_before(deadline);
+                } else {
+                    return Err(e.into());
                 }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetVersion(get_version), e.description);
+            }
+        };
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        // Verify a port was detected and selected.
+        let Some(signal_generator) = select(&signal_generators) else {
+            // If no ports were detected but we have a specified port, try to use that instead
+            if let Some(port_name) = &properties_clone.port {
+                tracing::warn!(
+                    port = %port_name,
+                    "No device was detected and selected matching defined properties, falling back to the specified port."
+                );
+                return self.port_connect_before(deadline);
+            } else {
+                return Err(Error::new(
+                    serialport::ErrorKind::NoDevice,
+                    "Unable to detect and select a device matching defined properties.",
+                )
+                .into());
+            }
+        };
+        let port_name = signal_generator.port_name.clone();
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetAttenuation(get_attenuation) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_attenuation.clone().into();
+        // Open a serial connection with the detected port at the requested settings.
+        let port = open_serial_port(&properties_clone, &port_name, remaining(deadline))?;
+        let mut port = SerialTransport::new(port)
+            .with_line_terminator(self.properties.line_terminator)
+            .with_echo(self.properties.echo_enabled);
+        verify_baud_rate(&mut port, &self.properties)?;
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetAttenuationResponse, _> = sg_response.try_into();
+        Ok(self.start(Box::new(port), Endpoint::Serial(port_name))?)
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetAttenuationResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetAttenuation(get_attenuation),
-                        e.description,
-                    );
+    /// Like `connect()`, but confirms the device is the one expected before returning, such as
+    /// to avoid driving the wrong instrument on a bench with several of them.
+    ///
+    /// The device is asked for its identity and firmware version. If they don't meet every
+    /// requirement of `expected`, the driver disconnects and `ConnectError::WrongDevice` is
+    /// returned. Nothing is checked in dry-run mode, as there is no device to ask.
+    ///
+    /// ```no_run
+    /// use minicircuit_driver::prelude::*;
+    ///
+    /// # async fn example() {
+    /// let mut driver = MiniCircuitDriver::new(TargetProperties::default());
+    /// let expected = DeviceMatcher::new()
+    ///     .model("ISC-2425-25+")
+    ///     .serial_prefix("AB12");
+    /// let (queue_tx, response_tx) = driver.connect_expecting(expected).await.unwrap();
+    /// # }
+    /// ```
+    pub async fn connect_expecting(
+        &mut self,
+        expected: DeviceMatcher,
+    ) -> Result<(CommandSender, broadcast::Sender<CorrelatedResponse>), ConnectError> {
+        let channels = self.connect()?;
+        if self.properties.dry_run {
+            return Ok(channels);
+        }
+
+        // A device that can't be asked can't be confirmed either.
+        let identity = self
+            .send_await(GetIdentity::default(), Priority::High)
+            .await
+            .ok();
+        let version = self
+            .send_await(GetVersion::default(), Priority::High)
+            .await
+            .ok();
+        let identity = identity.as_ref().and_then(Response::as_identity);
+        let version = version.as_ref().and_then(Response::as_version);
+
+        if expected.matches(identity, version) {
+            return Ok(channels);
+        }
+
+        let error = ConnectError::WrongDevice(Box::new(DeviceMismatch {
+            expected,
+            identity: identity.cloned(),
+            firmware: version.and_then(GetVersionResponse::version),
+        }));
+        tracing::warn!(%error, "Disconnecting from an unexpected device.");
+        self.disconnect().await;
+
+        Err(error)
+    }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    /// Connects to the port of the target properties, confirming the device answers at the
+    /// configured baud rate like `connect()`.
+    pub fn port_connect(
+        &mut self,
+    ) -> Result<(CommandSender, broadcast::Sender<CorrelatedResponse>), ConnectError> {
+        self.port_connect_before(Instant::now() + self.properties.connect_timeout)
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetAttenuation(set_attenuation) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_attenuation.clone().into();
+    /// Connects to the port of the target properties, failing with `NoDevice` if it can't be
+    /// opened before `deadline`.
+    fn port_connect_before(
+        &mut self,
+        deadline: Instant,
+    ) -> Result<(CommandSender, broadcast::Sender<CorrelatedResponse>), ConnectError> {
+        let properties_clone = self.properties.clone();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetAttenuationResponse, _> = sg_response.try_into();
+        let Some(port_name) = properties_clone.port.clone() else {
+            return Err(Error::new(serialport::ErrorKind::InvalidInput, "A port must be defined in order to connect to it. Please add a port to the target properties.").into());
+        };
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetAttenuationResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetAttenuation(set_attenuation),
-                        e.description,
-                    );
+        // Open a serial connection with the detected port at the requested settings.
+        let port = open_serial_port(&properties_clone, &port_name, remaining(deadline))?;
+        let mut port = SerialTransport::new(port)
+            .with_line_terminator(self.properties.line_terminator)
+            .with_echo(self.properties.echo_enabled);
+        verify_baud_rate(&mut port, &self.properties)?;
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        Ok(self.start(Box::new(port), Endpoint::Serial(port_name))?)
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetAutoGainState(get_auto_gain_state) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_auto_gain_state.clone().into();
+    /// Connects to a signal generator exposed over TCP, such as one behind an Ethernet-to-serial bridge.
+    ///
+    /// The connection timeout of the target properties is used both to connect and to wait for each response.
+    pub fn connect_tcp(
+        &mut self,
+        addr: impl ToSocketAddrs,
+    ) -> Result<(CommandSender, broadcast::Sender<CorrelatedResponse>), Error> {
+        let Some(addr) = addr.to_socket_addrs()?.next() else {
+            return Err(Error::new(
+                serialport::ErrorKind::InvalidInput,
+                "The address did not resolve to any socket address.",
+            ));
+        };
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetAutoGainStateResponse, _> = sg_response.try_into();
+        let transport = TcpTransport::connect(addr, self.properties.connection_timeout)?
+            .with_line_terminator(self.properties.line_terminator)
+            .with_echo(self.properties.echo_enabled);
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetAutoGainStateResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetAutoGainState(get_auto_gain_state),
-                        e.description,
-                    );
+        self.start(Box::new(transport), Endpoint::Tcp(addr))
+    }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    /// Connects over a transport that was opened elsewhere, such as a simulator running in-process.
+    ///
+    /// The transport can't be reopened, so it isn't replaced if the device disappears.
+    pub fn connect_transport(
+        &mut self,
+        transport: impl Transport + 'static,
+    ) -> Result<(CommandSender, broadcast::Sender<CorrelatedResponse>), Error> {
+        self.start(Box::new(transport), Endpoint::Custom)
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetAutoGainState(set_auto_gain_state) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_auto_gain_state.clone().into();
+    /// Creates a driver that is already connected over the given transport, bypassing port
+    /// detection and opening, e.g. to test against a loopback or mock port without hardware.
+    ///
+    /// An opened `Box<dyn SerialPort>` can be passed as `SerialTransport::from(port)`.
+    /// Like `connect_transport()`, the transport isn't replaced if the device disappears.
+    /// Must be called from within a tokio runtime, as the queue loop is spawned onto it.
+    pub fn from_transport(
+        transport: impl Transport + 'static,
+        properties: TargetProperties,
+    ) -> Result<(Self, CommandSender, broadcast::Sender<CorrelatedResponse>), Error> {
+        let mut driver = Self::new(properties);
+        let (queue_tx, response_tx) = driver.connect_transport(transport)?;
+
+        Ok((driver, queue_tx, response_tx))
+    }
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetAutoGainStateResponse, _> = sg_response.try_into();
+    /// Calls `hook` with the bytes of every command written to the signal generator and every
+    /// line read back, to diagnose protocol mismatches such as with new firmware.
+    ///
+    /// The hook runs while the connection is held, so it should return quickly. It applies from
+    /// the next connect onwards and survives reconnects.
+    ///
+    /// ```
+    /// use minicircuit_driver::{driver::MiniCircuitDriver, wire::WireDirection};
+    /// use minicircuit_commands::properties::TargetProperties;
+    ///
+    /// let mut driver = MiniCircuitDriver::new(TargetProperties::default());
+    /// driver.on_wire(|direction, bytes| match direction {
+    ///     WireDirection::Sent => eprintln!("> {:?}", String::from_utf8_lossy(bytes)),
+    ///     WireDirection::Received => eprintln!("< {:?}", String::from_utf8_lossy(bytes)),
+    /// });
+    /// ```
+    pub fn on_wire<F>(&mut self, hook: F)
+    where
+        F: Fn(WireDirection, &[u8]) + Send + Sync + 'static,
+    {
+        self.wire_hook = Some(WireHook::new(hook));
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetAutoGainStateResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetAutoGainState(set_auto_gain_state),
-                        e.description,
-                    );
+    /// Subscribes to the messages the queue loop takes up to send, such as to record them with
+    /// a `CommandRecorder`.
+    ///
+    /// Each message is delivered once when it is first sent, not again for retries. Commands
+    /// of a sequence are delivered as messages of their own. Unlike the responses, the channel
+    /// is available before connecting and carries on across reconnects.
+    pub fn subscribe_sent(&self) -> broadcast::Receiver<SentMessage> {
+        self.sent_tx.subscribe()
+    }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    /// Removes the hook set with `on_wire()`, from the next connect onwards.
+    pub fn clear_wire_hook(&mut self) {
+        self.wire_hook = None;
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetMagnitude(get_magnitude) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_magnitude.clone().into();
+    /// Starts the queue loop on a freshly opened connection.
+    fn start(
+        &mut self,
+        port: Box<dyn Transport>,
+        endpoint: Endpoint,
+    ) -> Result<(CommandSender, broadcast::Sender<CorrelatedResponse>), Error> {
+        let mut port = wrap_transport(port, &self.properties, self.wire_hook.as_ref());
+
+        // Nothing is sent in dry-run mode, so there's nothing to detect.
+        let capabilities = match endpoint {
+            Endpoint::DryRun => None,
+            _ => Some(detect_capabilities(&mut *port)),
+        };
+        let power_limits = match endpoint {
+            Endpoint::DryRun => None,
+            _ if self.properties.clamp_power => read_power_limits(&mut *port),
+            _ => None,
+        };
+        // Written in place, so clones of the driver see them too.
+        write_shared(&self.capabilities, capabilities);
+        write_shared(&self.power_limits, power_limits);
+        self.connection = Some(connection_info(&endpoint, &self.properties));
+        self.endpoint = Some(endpoint.clone());
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetMagnitudeResponse, _> = sg_response.try_into();
+        // Wrap `port` in `Arc<Mutex<T>>` so it can be shared across threads.
+        let port = Arc::new(Mutex::new(port));
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetMagnitudeResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetMagnitude(get_magnitude), e.description);
+        // Create a channel that will be used by the driver to deliver responses from the commands back to the caller.
+        // `broadcast::channel` panics on a capacity of zero.
+        let (channel_tx, _channel_rx) =
+            broadcast::channel::<CorrelatedResponse>(self.properties.broadcast_capacity.max(1));
+        // Create a queue that can be used by the driver for receiving commands.
+        let (queue_tx, queue_rx, slots) = queue_channel(self.properties.max_queue_depth);
+        let pending = PendingIds::default();
+        let queue_tx =
+            CommandSender::new(queue_tx, slots, Arc::clone(&self.next_id), pending.clone());
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+        // Clone Arc pointers for the thread.
+        let port_clone = Arc::clone(&port);
+        // Create the signal used by `disconnect()` to stop the thread.
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetMagnitude(set_magnitude) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_magnitude.clone().into();
+        // Spawn a thread for handling commands in the queue.
+        // Store the handle so the thread doesn't get dropped.
+        self.baud_rate
+            .store(self.properties.baud_rate.clone().into(), Ordering::Relaxed);
+        let context = QueueContext {
+            properties: self.properties.clone(),
+            baud_rate: Arc::clone(&self.baud_rate),
+            capabilities: self.capabilities(),
+            power_limits: self.power_limits(),
+            endpoint,
+            channel_tx: channel_tx.clone(),
+            id_counter: Arc::clone(&self.next_id),
+            stats: self.stats.clone(),
+            wire_hook: self.wire_hook.clone(),
+            sent_tx: self.sent_tx.clone(),
+            pending,
+        };
+        self.queue_handle = Some(spawn_queue_loop(queue_rx, port_clone, shutdown_rx, context));
+        self.shutdown_tx = Some(shutdown_tx);
+        self.response_tx = Some(channel_tx.clone());
+        self.port = Some(port);
+        self.queue_tx = Some(queue_tx.clone());
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetMagnitudeResponse, _> = sg_response.try_into();
+        // Return the queue sender and response sender (to be subscribed to).
+        Ok((queue_tx, channel_tx))
+    }
+}
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetMagnitudeResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::SetMagnitude(set_magnitude), e.description);
+/// Adds what the driver was configured with on top of a freshly opened connection.
+fn wrap_transport(
+    mut port: Box<dyn Transport>,
+    properties: &TargetProperties,
+    wire_hook: Option<&WireHook>,
+) -> Box<dyn Transport> {
+    if let Some(hook) = wire_hook {
+        port = Box::new(WireTap::new(port, hook.clone(), properties.line_terminator));
+    }
+    if let Some(interval) = properties.min_command_interval {
+        port = Box::new(PacedTransport::new(port, interval));
+    }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    port
+}
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetISCPowerOutput(get_iscpower_output) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_iscpower_output.clone().into();
+/// Where a connection was opened, so it can be opened again after the device is lost.
+#[derive(Debug, Clone)]
+enum Endpoint {
+    /// The serial port that was opened, used as a fallback when autodetection fails.
+    Serial(String),
+    Tcp(SocketAddr),
+    /// No connection was opened because the driver is in dry-run mode.
+    DryRun,
+    /// The transport was handed to `connect_transport()`.
+    Custom,
+}
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetISCPowerOutputResponse, _> = sg_response.try_into();
+fn connection_info(endpoint: &Endpoint, properties: &TargetProperties) -> ConnectionInfo {
+    let (port_name, address) = match endpoint {
+        Endpoint::Serial(port_name) => (Some(port_name.clone()), None),
+        Endpoint::Tcp(addr) => (None, Some(*addr)),
+        Endpoint::DryRun | Endpoint::Custom => (None, None),
+    };
+    let ids = port_name.as_deref().and_then(usb_ids);
+
+    ConnectionInfo {
+        port_name,
+        address,
+        baud_rate: properties.baud_rate.clone().into(),
+        vendor_id: ids.map(|(vendor_id, _)| vendor_id),
+        product_id: ids.map(|(_, product_id)| product_id),
+    }
+}
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetISCPowerOutputResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetISCPowerOutput(get_iscpower_output),
-                        e.description,
-                    );
+/// State shared by the queue loop and the helpers it calls.
+struct QueueContext {
+    properties: TargetProperties,
+    /// The baud rate to reopen a serial port at, kept up to date by `change_baud_rate()`.
+    baud_rate: Arc<AtomicU32>,
+    capabilities: Option<Capabilities>,
+    power_limits: Option<PowerLimits>,
+    endpoint: Endpoint,
+    channel_tx: broadcast::Sender<CorrelatedResponse>,
+    id_counter: Arc<AtomicU64>,
+    stats: StatsRecorder,
+    /// Wraps a reopened connection so the hook keeps seeing the wire.
+    wire_hook: Option<WireHook>,
+    sent_tx: broadcast::Sender<SentMessage>,
+    /// The entries still waiting in the queue, any withdrawn through `CommandSender::cancel` are skipped.
+    pending: PendingIds,
+}
 
-                    Response::ReadWriteError(error_response)
+fn spawn_queue_loop(
+    mut queue_rx: tokio::sync::mpsc::Receiver<QueuedMessage>,
+    port: Arc<tokio::sync::Mutex<Box<dyn Transport>>>,
+    shutdown_rx: oneshot::Receiver<()>,
+    context: QueueContext,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        // Only an explicit `disconnect()` stops the loop. Dropping the driver leaves it running.
+        let shutdown = async move {
+            if shutdown_rx.await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        };
+        tokio::pin!(shutdown);
+
+        // Only ticks while idle when unsolicited lines are read, see `publish_unsolicited`.
+        let mut unsolicited_poll = tokio::time::interval(context.properties.queue_poll_interval);
+        unsolicited_poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        // Outlives a single wake-up, so whatever arrives while sending is ordered with the rest.
+        let mut queue = PriorityQueue::new().with_aging(context.properties.priority_aging);
+
+        'queue: loop {
+            // Sleep until a message arrives rather than polling an empty queue.
+            select! {
+                _ = &mut shutdown => break 'queue,
+                msg = queue_rx.recv() => match msg {
+                    Some(msg) => queue.push(msg),
+                    // Every queue sender was dropped so nothing more can arrive.
+                    None => break 'queue,
+                },
+                _ = unsolicited_poll.tick(), if context.properties.read_unsolicited => {
+                    publish_unsolicited(
+                        &mut **port.lock().await,
+                        &context.properties,
+                        &context.channel_tx,
+                        &context.id_counter,
+                    );
+                    continue 'queue;
                 }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetISCPowerOutput(set_iscpower_output) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_iscpower_output.clone().into();
+            }
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetISCPowerOutputResponse, _> = sg_response.try_into();
+            // Take the message of the highest priority, those of equal priority in the order
+            // they were sent in. Anything that arrived meanwhile is taken in before each one,
+            // so an urgent message doesn't wait for the ones queued ahead of it.
+            loop {
+                while let Ok(msg) = queue_rx.try_recv() {
+                    queue.push(msg);
+                }
+                let Some(queued) = queue.pop() else {
+                    break;
+                };
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetISCPowerOutputResponse(formatted_response)
+                context.stats.set_queue_length(queue.len());
+
+                // Withdrawn while it was waiting, so nobody is after its response.
+                if !context.pending.dispatch(queued.id) {
+                    continue;
+                }
+
+                // Hold the port for the whole entry so a sequence can't be interleaved with direct commands.
+                let mut port = port.lock().await;
+
+                // Anything waiting now arrived unasked and would be mistaken for the next reply.
+                publish_unsolicited(
+                    &mut **port,
+                    &context.properties,
+                    &context.channel_tx,
+                    &context.id_counter,
+                );
+
+                match queued.work {
+                    QueuedWork::Command(command, timeout) => {
+                        let response = execute_queued(
+                            &mut port,
+                            (queued.id, &queued.priority, &queued.tag),
+                            command,
+                            timeout,
+                            &context,
+                            &mut shutdown,
+                        )
+                        .await;
+                        if response.is_none() {
+                            break 'queue;
                         }
-                        Err(e) => Response::MWError(e),
                     }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetISCPowerOutput(set_iscpower_output),
-                        e.description,
-                    );
+                    QueuedWork::Sequence {
+                        commands,
+                        on_failure,
+                        reply_tx,
+                    } => {
+                        let mut responses = Vec::with_capacity(commands.len());
+
+                        for command in commands {
+                            let Some(response) = execute_queued(
+                                &mut port,
+                                (queued.id, &queued.priority, &queued.tag),
+                                command,
+                                None,
+                                &context,
+                                &mut shutdown,
+                            )
+                            .await
+                            else {
+                                break 'queue;
+                            };
+
+                            let failed = response.is_error();
+                            responses.push(response);
+                            if failed && on_failure == SequenceFailure::Abort {
+                                break;
+                            }
+                        }
 
-                    Response::ReadWriteError(error_response)
+                        let _ = reply_tx.send(responses);
+                    }
                 }
-            };
+            }
+            context.stats.set_queue_length(queue_rx.len());
+
+            // Await in order to allow abort, exiting if a shutdown was requested.
+            select! {
+                _ = &mut shutdown => break 'queue,
+                _ = tokio::time::sleep(context.properties.queue_poll_interval) => {}
+            }
+        }
+    })
+}
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+/// Waits for the response carrying the given id, ignoring responses to other commands.
+async fn response_for(
+    response_rx: &mut broadcast::Receiver<CorrelatedResponse>,
+    id: u64,
+) -> Result<Response, SendError> {
+    loop {
+        match response_rx.recv().await {
+            Ok(correlated) if correlated.id == id => return Ok(correlated.response),
+            Ok(_) => continue,
+            // The response may have been among the skipped ones, a timeout covers that case.
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(skipped, "Fell behind while waiting for a response.");
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => return Err(SendError::ChannelClosed),
         }
-        Command::GetPWMDutyCycle(get_pwmduty_cycle) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_pwmduty_cycle.clone().into();
+    }
+}
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPWMDutyCycleResponse, _> = sg_response.try_into();
+/// Sends a queued command, reconnecting and sending it again if the device disappeared.
+///
+/// The response is recorded and broadcast to subscribers with the id, priority and tag of the message.
+/// Returns `None` if a shutdown was requested while reconnecting.
+async fn execute_queued<F: Future<Output = ()>>(
+    port: &mut Box<dyn Transport>,
+    (id, priority, tag): (u64, &Priority, &Option<String>),
+    mut command: Command,
+    timeout: Option<Duration>,
+    context: &QueueContext,
+    shutdown: &mut Pin<&mut F>,
+) -> Option<Response> {
+    if let Some(notice) = clamp_power(&mut command, context.power_limits.as_ref()) {
+        let _ = context.channel_tx.send(CorrelatedResponse::new(
+            next_id(&context.id_counter),
+            notice,
+        ));
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetPWMDutyCycleResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetPWMDutyCycle(get_pwmduty_cycle),
-                        e.description,
-                    );
+    // Cloning the command is only worth it if anyone is listening.
+    if context.sent_tx.receiver_count() > 0 && !matches!(command, Command::Barrier) {
+        let mut message = Message::new(priority.clone(), command.clone());
+        message.timeout = timeout;
+        message.tag = tag.clone();
+        let _ = context.sent_tx.send(SentMessage::new(message));
+    }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    let mut retries = 0;
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+    let mut response = loop {
+        // A barrier only marks its place in the queue, there's nothing to send.
+        if matches!(command, Command::Barrier) {
+            break Response::Barrier;
+        }
+        if context.properties.dry_run {
+            break Response::DryRun(String::from(&command));
+        }
+        if let Some(rejected) =
+            reject_unsupported(&context.properties, context.capabilities.as_ref(), &command)
+        {
+            break rejected;
         }
-        Command::SetPWMDutyCycle(set_pwmduty_cycle) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_pwmduty_cycle.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetPWMDutyCycleResponse, _> = sg_response.try_into();
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetPWMDutyCycleResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetPWMDutyCycle(set_pwmduty_cycle),
-                        e.description,
-                    );
+        // Send the command to the controller and wait for the response.
+        let started = Instant::now();
+        let mut on_progress = |done, total| {
+            if !context.properties.stream_sweeps {
+                return;
+            }
 
-                    Response::ReadWriteError(error_response)
-                }
+            let progress = Response::SweepProgress {
+                sweep_id: id,
+                done,
+                total,
             };
+            let _ = context.channel_tx.send(
+                CorrelatedResponse::new(next_id(&context.id_counter), progress)
+                    .with_priority(priority.clone())
+                    .with_tag(tag.clone()),
+            );
+        };
+        let response = tracing::debug_span!("queued", id).in_scope(|| {
+            send_command_reporting(command.clone(), &mut **port, timeout, &mut on_progress)
+        });
+        context.stats.record_latency(&command, started.elapsed());
+
+        // Only a lost connection is worth reconnecting for, not a slow or failing device.
+        let device_lost = matches!(response, Response::ReadWriteError(_)) && !port.is_connected();
+
+        // A hiccup on a live connection is retried, a lost device is left to reconnecting.
+        if !device_lost && should_retry(&context.properties, &command, &response, retries) {
+            // A busy device is only waited on briefly, it'll likely be free again soon.
+            let backoff = match response {
+                Response::Busy => context.properties.busy_retry_delay,
+                _ => context
+                    .properties
+                    .retry_backoff
+                    .saturating_mul(2u32.saturating_pow(retries)),
+            };
+            retries += 1;
+            tracing::debug!(id, retries, ?backoff, "Retrying the command.");
+
+            select! {
+                _ = &mut *shutdown => return None,
+                _ = tokio::time::sleep(backoff) => continue,
+            }
+        }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+        if !context.properties.auto_reconnect || !device_lost {
+            break response;
         }
-        Command::SetPWMFrequency(set_pwmfrequency) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_pwmfrequency.clone().into();
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetPWMFrequencyResponse, _> = sg_response.try_into();
+        // The port stays locked while reconnecting so direct commands wait for the new one.
+        select! {
+            _ = &mut *shutdown => return None,
+            new_port = reconnect(context) => {
+                *port = new_port;
+            }
+        }
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetPWMFrequencyResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetPWMFrequency(set_pwmfrequency),
-                        e.description,
-                    );
+        // The command is sent again on the new port rather than being lost.
+    };
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    if let Response::ReadWriteError(read_write_error) = &mut response {
+        read_write_error.retries = retries;
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetTimedRFEnable(set_timed_rfenable) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_timed_rfenable.clone().into();
+    let settle = settle_time(&context.properties, &command, &response);
+    context.stats.record(id, command, &response);
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetTimedRFEnableResponse, _> = sg_response.try_into();
+    // Return the response to the caller.
+    let _ = context.channel_tx.send(
+        CorrelatedResponse::new(id, response.clone())
+            .with_priority(priority.clone())
+            .with_tag(tag.clone()),
+    );
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetTimedRFEnableResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetTimedRFEnable(set_timed_rfenable),
-                        e.description,
-                    );
+    // The port stays locked, so the next command waits for the device to settle.
+    if let Some(settle) = settle {
+        select! {
+            _ = &mut *shutdown => return None,
+            _ = tokio::time::sleep(settle) => {}
+        }
+    }
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+    Some(response)
+}
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetSOAConfig(get_soaconfig) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_soaconfig.clone().into();
+/// How long to wait after the command before sending the next, see `TargetProperties::settle_times`.
+fn settle_time(
+    properties: &TargetProperties,
+    command: &Command,
+    response: &Response,
+) -> Option<Duration> {
+    // Nothing was carried out that needs to settle.
+    if properties.dry_run || response.is_error() {
+        return None;
+    }
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetSOAConfigResponse, _> = sg_response.try_into();
+    properties.settle_time(command)
+}
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetSOAConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetSOAConfig(get_soaconfig), e.description);
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetSOAConfig(set_soaconfig) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_soaconfig.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetSOAConfigResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetSOAConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::SetSOAConfig(set_soaconfig), e.description);
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetSOACurrentConfig(get_soacurrent_config) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_soacurrent_config.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetSOACurrentConfigResponse, _> =
-                        sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetSOACurrentConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetSOACurrentConfig(get_soacurrent_config),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetSOACurrentConfig(set_soacurrent_config) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_soacurrent_config.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetSOACurrentConfigResponse, _> =
-                        sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetSOACurrentConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetSOACurrentConfig(set_soacurrent_config),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetSOADissipationConfig(get_soadissipation_config) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_soadissipation_config.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetSOADissipationConfigResponse, _> =
-                        sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetSOADissipationConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetSOADissipationConfig(get_soadissipation_config),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetSOADissipationConfig(set_soadissipation_config) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_soadissipation_config.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetSOADissipationConfigResponse, _> =
-                        sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetSOADissipationConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetSOADissipationConfig(set_soadissipation_config),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetSOAForwardPowerLimits(get_soaforward_power_limits) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_soaforward_power_limits.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetSOAForwardPowerLimitsResponse, _> =
-                        sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetSOAForwardPowerLimitsResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetSOAForwardPowerLimits(get_soaforward_power_limits),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetSOAForwardPowerLimits(set_soaforward_power_limits) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_soaforward_power_limits.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetSOAForwardPowerLimitsResponse, _> =
-                        sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetSOAForwardPowerLimitsResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetSOAForwardPowerLimits(set_soaforward_power_limits),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetSOAGraceTimer(set_soagrace_timer) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_soagrace_timer.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetSOAGraceTimerResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetSOAGraceTimerResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetSOAGraceTimer(set_soagrace_timer),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetSOAPowerConfig(get_soapower_config) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_soapower_config.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetSOAPowerConfigResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetSOAPowerConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetSOAPowerConfig(get_soapower_config),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetSOAPowerConfig(set_soapower_config) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_soapower_config.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetSOAPowerConfigResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetSOAPowerConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetSOAPowerConfig(set_soapower_config),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetSOATempConfig(get_soatemp_config) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_soatemp_config.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetSOATempConfigResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetSOATempConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetSOATempConfig(get_soatemp_config),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetSOATempConfig(set_soatemp_config) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_soatemp_config.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetSOATempConfigResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetSOATempConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetSOATempConfig(set_soatemp_config),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetSOAVoltageConfig(get_soavoltage_config) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_soavoltage_config.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetSOAVoltageConfigResponse, _> =
-                        sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetSOAVoltageConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetSOAVoltageConfig(get_soavoltage_config),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetSOAVoltageConfig(set_soavoltage_config) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_soavoltage_config.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetSOAVoltageConfigResponse, _> =
-                        sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetSOAVoltageConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetSOAVoltageConfig(set_soavoltage_config),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetSOAWatchdogConfig(set_soawatchdog_config) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_soawatchdog_config.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetSOAWatchdogConfigResponse, _> =
-                        sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetSOAWatchdogConfigResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetSOAWatchdogConfig(set_soawatchdog_config),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetUartBaudRate(set_uart_baud_rate) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_uart_baud_rate.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                // This command doesn't have a response from the signal generator.
-                Ok(_) => Response::SetUartBaudRate,
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetUartBaudRate(set_uart_baud_rate),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetChannelID(get_channel_id) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_channel_id.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetChannelIDResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetChannelIDResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::GetChannelID(get_channel_id), e.description);
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetChannelID(set_channel_id) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_channel_id.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetChannelIDResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetChannelIDResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::SetChannelID(set_channel_id), e.description);
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetClockSource(get_clock_source) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_clock_source.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetClockSourceResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetClockSourceResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetClockSource(get_clock_source),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetClockSource(set_clock_source) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_clock_source.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetClockSourceResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetClockSourceResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetClockSource(set_clock_source),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetCommunicationInterface(set_communication_interface) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_communication_interface.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetCommunicationInterfaceResponse, _> =
-                        sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetCommunicationInterfaceResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetCommunicationInterface(set_communication_interface),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::GetPowerMaxDbm(get_power_max_dbm) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_power_max_dbm.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPowerMaxDbmResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetPowerMaxDbmResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetPowerMaxDbm(get_power_max_dbm),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
+/// Broadcasts the lines the device sent without being asked, if they are being read.
+///
+/// Only called while the port is locked and no command is in flight, so a reply is never taken.
+fn publish_unsolicited(
+    port: &mut dyn Transport,
+    properties: &TargetProperties,
+    channel_tx: &broadcast::Sender<CorrelatedResponse>,
+    id_counter: &AtomicU64,
+) {
+    if !properties.read_unsolicited {
+        return;
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+    match port.read_unsolicited() {
+        Ok(lines) => {
+            for line in lines {
+                tracing::debug!(%line, "Received an unsolicited line.");
+                let _ = channel_tx.send(CorrelatedResponse::new(
+                    next_id(id_counter),
+                    Response::Unsolicited(line),
+                ));
+            }
         }
-        Command::SetPowerMaxDbm(set_power_max_dbm) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_power_max_dbm.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetPowerMaxDbmResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetPowerMaxDbmResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetPowerMaxDbm(set_power_max_dbm),
-                        e.description,
-                    );
+        Err(e) => tracing::warn!(error = %e, "Failed to read unsolicited lines."),
+    }
+}
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+/// Whether a failed command should be sent again, given how often it was already retried.
+///
+/// A busy device didn't carry out the command, so setters are resent too. A communication
+/// error may have lost only the response, so setters are resent only if `retry_setters` is set.
+fn should_retry(
+    properties: &TargetProperties,
+    command: &Command,
+    response: &Response,
+    retries: u32,
+) -> bool {
+    if retries >= properties.max_retries {
+        return false;
+    }
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+    match response {
+        Response::Busy => true,
+        Response::ReadWriteError(error) if error.kind.is_transient() => {
+            command.is_query() || properties.retry_setters
         }
-        Command::GetPowerMinDbm(get_power_min_dbm) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_power_min_dbm.clone().into();
+        _ => false,
+    }
+}
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPowerMinDbmResponse, _> = sg_response.try_into();
+/// A copy of the value shared between the driver and its clones.
+fn read_shared<T: Clone>(shared: &RwLock<T>) -> T {
+    // A panic while holding the lock leaves the value as consistent as it was.
+    shared.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetPowerMinDbmResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetPowerMinDbm(get_power_min_dbm),
-                        e.description,
-                    );
+/// Replaces the value shared between the driver and its clones.
+fn write_shared<T>(shared: &RwLock<T>, value: T) {
+    *shared.write().unwrap_or_else(|e| e.into_inner()) = value;
+}
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+/// Reads the range the device accepts power setpoints in.
+///
+/// A device that doesn't report both limits isn't clamped for, it still refuses setpoints
+/// outside of them itself.
+fn read_power_limits(port: &mut dyn Transport) -> Option<PowerLimits> {
+    let min = send_command_within(GetPowerMinDbm::default().into(), port, None);
+    let max = send_command_within(GetPowerMaxDbm::default().into(), port, None);
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+    match (min.as_power_min_dbm(), max.as_power_max_dbm()) {
+        (Some(min), Some(max)) => {
+            let limits = PowerLimits::new(min.min.clone(), max.max.clone());
+            tracing::debug!(?limits, "Read the power limits of the device.");
+            Some(limits)
         }
-        Command::SetPowerMinDbm(set_power_min_dbm) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_power_min_dbm.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetPowerMinDbmResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetPowerMinDbmResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetPowerMinDbm(set_power_min_dbm),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
+        _ => {
+            tracing::warn!("The device didn't report its power limits, setpoints aren't clamped.");
+            None
         }
-        Command::GetPowerOffset(get_power_offset) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = get_power_offset.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<GetPowerOffsetResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::GetPowerOffsetResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::GetPowerOffset(get_power_offset),
-                        e.description,
-                    );
-
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetPowerOffset(set_power_offset) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_power_offset.clone().into();
-
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetPowerOffsetResponse, _> = sg_response.try_into();
-
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetPowerOffsetResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetPowerOffset(set_power_offset),
-                        e.description,
-                    );
+    }
+}
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+/// Clamps a power setpoint to the limits read when connecting, returning the notice to
+/// broadcast if it was out of range.
+fn clamp_power(command: &mut Command, limits: Option<&PowerLimits>) -> Option<Response> {
+    let (requested, applied) = limits?.clamp(command)?;
+    tracing::warn!(%requested, %applied, "Clamped a power setpoint to the limits of the device.");
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::ResetSystem(reset_system) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = reset_system.clone().into();
+    Some(Response::PowerClamped { requested, applied })
+}
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<ResetSystemResponse, _> = sg_response.try_into();
+/// Reads the firmware version and identity to find out what the device supports.
+///
+/// A device that doesn't answer is assumed to support everything.
+fn detect_capabilities(port: &mut dyn Transport) -> Capabilities {
+    let version = send_command_within(GetVersion::default().into(), port, None);
+    let identity = send_command_within(GetIdentity::default().into(), port, None);
 
-                    match parse_result {
-                        Ok(formatted_response) => Response::ResetSystemResponse(formatted_response),
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response =
-                        ReadWriteError::new(Command::ResetSystem(reset_system), e.description);
+    let capabilities = Capabilities::new(version.as_version(), identity.as_identity());
+    tracing::debug!(?capabilities, "Detected the capabilities of the device.");
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
-
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
-        Command::SetZHLTriggerDelay(set_zhltrigger_delay) => {
-            // Convert the command into a string (required format to be sent to the signal generator).
-            let command: String = set_zhltrigger_delay.clone().into();
+    capabilities
+}
 
-            // Collect the resulting response of sending the command.
-            let command_response: Response = match write_read(port, command) {
-                Ok(sg_response) => {
-                    let parse_result: Result<SetZHLTriggerDelayResponse, _> =
-                        sg_response.try_into();
+/// The response to return instead of sending the command, if the device doesn't support it
+/// and rejecting unsupported commands is enabled.
+fn reject_unsupported(
+    properties: &TargetProperties,
+    capabilities: Option<&Capabilities>,
+    command: &Command,
+) -> Option<Response> {
+    if !properties.reject_unsupported {
+        return None;
+    }
 
-                    match parse_result {
-                        Ok(formatted_response) => {
-                            Response::SetZHLTriggerDelayResponse(formatted_response)
-                        }
-                        Err(e) => Response::MWError(e),
-                    }
-                }
-                // Return the command (for backtracking the source of issue) and the error description
-                Err(e) => {
-                    let error_response = ReadWriteError::new(
-                        Command::SetZHLTriggerDelay(set_zhltrigger_delay),
-                        e.description,
-                    );
+    let group = CommandGroup::of(command)?;
+    match capabilities {
+        Some(capabilities) if !capabilities.supports(group) => Some(Response::Unsupported(group)),
+        _ => None,
+    }
+}
 
-                    Response::ReadWriteError(error_response)
-                }
-            };
+/// Keeps trying to reopen the connection with exponential backoff until it succeeds.
+///
+/// Every attempt is reported to subscribers, as is the eventual success.
+async fn reconnect(context: &QueueContext) -> Box<dyn Transport> {
+    let QueueContext {
+        properties,
+        endpoint,
+        channel_tx,
+        id_counter,
+        ..
+    } = context;
+    let mut backoff = properties.reconnect_backoff;
+    let mut attempt: u32 = 1;
+
+    loop {
+        tokio::time::sleep(backoff).await;
+
+        tracing::warn!(
+            attempt,
+            "Connection to the controller was lost, reconnecting."
+        );
+        let _ = channel_tx.send(CorrelatedResponse::new(
+            next_id(id_counter),
+            Response::Reconnecting(attempt),
+        ));
+
+        // Reopen at the rate set by `change_baud_rate()` rather than the one connected with.
+        let mut reopen_properties = properties.clone();
+        reopen_properties.baud_rate = BaudRate::new(context.baud_rate.load(Ordering::Relaxed));
+
+        if let Ok(port) = reopen(endpoint, &reopen_properties) {
+            let port = wrap_transport(port, properties, context.wire_hook.as_ref());
+
+            tracing::info!(attempt, "Connection to the controller was restored.");
+            let _ = channel_tx.send(CorrelatedResponse::new(
+                next_id(id_counter),
+                Response::Reconnected,
+            ));
+            return port;
+        }
+
+        // Cap the backoff so a long outage is still noticed reasonably quickly once resolved.
+        backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+        attempt = attempt.saturating_add(1);
+    }
+}
 
-            // Directly return the response to the caller rather than sending it to a queue.
-            command_response
-        }
+/// Opens the connection to the endpoint again, at the settings of the properties.
+fn reopen(endpoint: &Endpoint, properties: &TargetProperties) -> Result<Box<dyn Transport>, Error> {
+    match endpoint {
+        Endpoint::Serial(port_name) => reopen_port(properties, port_name).map(|port| {
+            let port = SerialTransport::new(port)
+                .with_line_terminator(properties.line_terminator)
+                .with_echo(properties.echo_enabled);
+            Box::new(port) as Box<dyn Transport>
+        }),
+        Endpoint::Tcp(addr) => {
+            TcpTransport::connect(*addr, properties.connection_timeout).map(|transport| {
+                let transport = transport
+                    .with_line_terminator(properties.line_terminator)
+                    .with_echo(properties.echo_enabled);
+                Box::new(transport) as Box<dyn Transport>
+            })
+        }
+        Endpoint::DryRun => Ok(Box::new(DryRunTransport) as Box<dyn Transport>),
+        Endpoint::Custom => Err(Error::new(
+            serialport::ErrorKind::NoDevice,
+            "A transport handed to the driver can't be reopened.",
+        )),
     }
 }
+
+/// The statuses of the board that are faults, leaving out the ones reported during normal operation.
+fn active_faults(status: GetStatusResponse) -> Vec<Status> {
+    let ok = [
+        Status::from(StatusCode::SystemOk),
+        Status::from(StatusCode::ResetDetected),
+    ];
+    status
+        .status_codes
+        .into_iter()
+        .filter(|status| !ok.contains(status))
+        .collect()
+}
@@ -0,0 +1,396 @@
+use std::{error::Error, fmt, io, time::Duration};
+
+use minicircuit_commands::{
+    data_types::errors::ValidationError, error::status::Status, response::Response,
+    system::clock_source::ClockSource,
+};
+
+use super::connection::DeviceMismatch;
+
+/// Errors that can occur while sending a command and waiting for its response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendError {
+    /// The driver has not been connected to a signal generator.
+    NotConnected,
+    /// The queue loop has stopped so the command could not be enqueued.
+    QueueClosed,
+    /// The queue has reached its maximum depth. Retry once the device has caught up.
+    QueueFull,
+    /// The response channel closed before the response arrived.
+    ChannelClosed,
+    /// No response arrived within the allowed time. The command may still be executed.
+    Timeout(Duration),
+    /// The wait was cancelled. The command was withdrawn unless it had already been sent.
+    Cancelled,
+    /// A periodic task was asked to run with a zero interval.
+    ZeroInterval,
+}
+
+impl Error for SendError {}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConnected => write!(f, "The driver is not connected to a signal generator."),
+            Self::QueueClosed => write!(f, "The command queue is no longer running."),
+            Self::QueueFull => write!(f, "The command queue is full."),
+            Self::ChannelClosed => write!(
+                f,
+                "The response channel closed before a response was received."
+            ),
+            Self::Timeout(timeout) => {
+                write!(f, "No response was received within {:?}.", timeout)
+            }
+            Self::Cancelled => write!(f, "The wait for the response was cancelled."),
+            Self::ZeroInterval => write!(f, "The interval must be longer than zero."),
+        }
+    }
+}
+
+/// Errors that can occur while connecting to a signal generator over a serial port.
+#[derive(Debug)]
+pub enum ConnectError {
+    /// No matching port was detected or the port could not be opened.
+    Port(serialport::Error),
+    /// The device didn't answer at the configured baud rate but did at `suggested`.
+    ///
+    /// Only returned when `TargetProperties::probe_baud_rates` is set.
+    BaudMismatch { configured: u32, suggested: u32 },
+    /// The port opened but the device didn't answer a `GetIdentity` at the given baud rate.
+    Unresponsive { baud_rate: u32 },
+    /// The device isn't the one expected, see `MiniCircuitDriver::connect_expecting`.
+    WrongDevice(Box<DeviceMismatch>),
+}
+
+impl Error for ConnectError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Port(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Port(e) => write!(f, "{}", e),
+            Self::BaudMismatch {
+                configured,
+                suggested,
+            } => write!(
+                f,
+                "The device didn't answer at {} baud but did at {} baud. Set the baud rate of the target properties to {}.",
+                configured, suggested, suggested
+            ),
+            Self::Unresponsive { baud_rate } => write!(
+                f,
+                "The port opened but the device didn't answer at {} baud.",
+                baud_rate
+            ),
+            Self::WrongDevice(mismatch) => write!(f, "{}", mismatch),
+        }
+    }
+}
+
+impl From<serialport::Error> for ConnectError {
+    fn from(e: serialport::Error) -> Self {
+        Self::Port(e)
+    }
+}
+
+/// Errors that can occur while resetting the signal generator with `reset_and_reconnect()`.
+#[derive(Debug, Clone)]
+pub enum ResetError {
+    /// The driver has not been connected to a signal generator.
+    NotConnected,
+    /// The signal generator refused the reset, so it wasn't restarted.
+    Rejected(Response),
+    /// The device didn't answer a `GetIdentity` again within the reset timeout.
+    Timeout(Duration),
+    /// The device came back but the config couldn't be reapplied.
+    ApplyConfig(SendError),
+}
+
+impl Error for ResetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::ApplyConfig(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ResetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConnected => write!(f, "The driver is not connected to a signal generator."),
+            Self::Rejected(response) => {
+                let description: String = response.clone().into();
+                write!(f, "The signal generator refused the reset: {}", description)
+            }
+            Self::Timeout(timeout) => write!(
+                f,
+                "The signal generator didn't come back within {:?} of the reset.",
+                timeout
+            ),
+            Self::ApplyConfig(e) => {
+                write!(f, "Failed to reapply the config after the reset: {}", e)
+            }
+        }
+    }
+}
+
+impl From<SendError> for ResetError {
+    fn from(e: SendError) -> Self {
+        Self::ApplyConfig(e)
+    }
+}
+
+/// Errors that can occur while polling a getter with `wait_until()`.
+#[derive(Debug, Clone)]
+pub enum WaitError {
+    /// The getter couldn't be sent or its response didn't arrive.
+    Send(SendError),
+    /// The condition didn't hold within the timeout, carrying the last response of the getter.
+    Timeout { timeout: Duration, last: Response },
+}
+
+impl Error for WaitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Send(e) => Some(e),
+            Self::Timeout { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for WaitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Send(e) => write!(f, "Failed to poll the signal generator: {}", e),
+            Self::Timeout { timeout, last } => {
+                let description: String = last.clone().into();
+                write!(
+                    f,
+                    "The condition didn't hold within {:?}, the last response was: {}",
+                    timeout, description
+                )
+            }
+        }
+    }
+}
+
+impl From<SendError> for WaitError {
+    fn from(e: SendError) -> Self {
+        Self::Send(e)
+    }
+}
+
+/// Errors that can occur while setting or reading the power setpoint with `set_power()` or
+/// `get_power()`.
+#[derive(Debug, Clone)]
+pub enum PowerError {
+    /// The command couldn't be sent or its response didn't arrive.
+    Send(SendError),
+    /// The power lies outside of the device limits.
+    Invalid(ValidationError),
+    /// The signal generator refused the setpoint or didn't report it, carrying its response.
+    Rejected(Response),
+}
+
+impl Error for PowerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Send(e) => Some(e),
+            Self::Invalid(e) => Some(e),
+            Self::Rejected(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for PowerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Send(e) => write!(f, "Failed to reach the signal generator: {}", e),
+            Self::Invalid(e) => write!(f, "Invalid power setpoint: {}", e),
+            Self::Rejected(response) => {
+                let description: String = response.clone().into();
+                write!(f, "The power setpoint failed: {}", description)
+            }
+        }
+    }
+}
+
+impl From<SendError> for PowerError {
+    fn from(e: SendError) -> Self {
+        Self::Send(e)
+    }
+}
+
+impl From<ValidationError> for PowerError {
+    fn from(e: ValidationError) -> Self {
+        Self::Invalid(e)
+    }
+}
+
+/// Errors that can occur while switching the clock source with `MiniCircuitDriver::set_clock_source`.
+#[derive(Debug)]
+pub enum ClockSourceError {
+    /// A command couldn't be sent or its response didn't arrive.
+    Send(SendError),
+    /// The signal generator refused a command or didn't report its clock source or status,
+    /// carrying the response.
+    Rejected(Response),
+    /// The board reports another clock source than the one that was set.
+    Mismatch {
+        requested: ClockSource,
+        reported: ClockSource,
+    },
+    /// The board reported active faults after switching to the external reference.
+    NotLocked(Vec<Status>),
+}
+
+impl Error for ClockSourceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Send(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ClockSourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Send(e) => write!(f, "Failed to reach the signal generator: {}", e),
+            Self::Rejected(response) => {
+                let description: String = response.clone().into();
+                write!(f, "Switching the clock source failed: {}", description)
+            }
+            Self::Mismatch {
+                requested,
+                reported,
+            } => write!(
+                f,
+                "The clock source was set to {:?} but the board reports {:?}.",
+                requested, reported
+            ),
+            Self::NotLocked(statuses) => {
+                let statuses: Vec<&str> = statuses
+                    .iter()
+                    .map(|status| status.status.as_str())
+                    .collect();
+                write!(
+                    f,
+                    "The external reference doesn't appear to be locked, the board reported: {}",
+                    statuses.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl From<SendError> for ClockSourceError {
+    fn from(e: SendError) -> Self {
+        Self::Send(e)
+    }
+}
+
+/// Errors that can occur while sending a recording again with `replay()`.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The recording couldn't be read.
+    Io(io::Error),
+    /// A message couldn't be sent or its response didn't arrive.
+    Send(SendError),
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Send(e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to read the recording: {}", e),
+            Self::Send(e) => write!(f, "Failed to replay the recording: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<SendError> for ReplayError {
+    fn from(e: SendError) -> Self {
+        Self::Send(e)
+    }
+}
+
+/// Errors that can occur while reading or running a script with `run_script()`.
+#[derive(Debug)]
+pub enum ScriptError {
+    /// The script file couldn't be read.
+    Io(io::Error),
+    /// The JSON script doesn't hold a list of messages.
+    Json(serde_json::Error),
+    /// The TOML script doesn't hold a `messages` array of messages.
+    Toml(toml::de::Error),
+    /// A message couldn't be sent or its response didn't arrive.
+    Send(SendError),
+}
+
+impl Error for ScriptError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Json(e) => Some(e),
+            Self::Toml(e) => Some(e),
+            Self::Send(e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to read the script: {}", e),
+            Self::Json(e) => write!(f, "Failed to parse the JSON script: {}", e),
+            Self::Toml(e) => write!(f, "Failed to parse the TOML script: {}", e),
+            Self::Send(e) => write!(f, "Failed to run the script: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for ScriptError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ScriptError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<toml::de::Error> for ScriptError {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Toml(e)
+    }
+}
+
+impl From<SendError> for ScriptError {
+    fn from(e: SendError) -> Self {
+        Self::Send(e)
+    }
+}
@@ -0,0 +1,704 @@
+use std::time::{Duration, Instant};
+
+use minicircuit_commands::{
+    basic::{
+        adc::GetPAPowerADCResponse,
+        current::GetPACurrentResponse,
+        forward_reflected::{GetPAPowerDBMResponse, GetPAPowerWattResponse},
+        frequency::GetFrequencyResponse,
+        output::GetRFOutputResponse,
+        phase::GetPhaseResponse,
+        setpoint::{
+            GetPAPowerSetpointDBMResponse, GetPAPowerSetpointWattResponse,
+            SetPAPowerSetpointDBMResponse,
+        },
+        temperature::GetPATempResponse,
+        voltage::GetPAVoltageResponse,
+    },
+    command::*,
+    data_types::errors::ReadWriteError,
+    dll::{
+        config::{GetDLLConfigResponse, SetDLLConfigResponse},
+        enable::{GetDLLEnabledResponse, SetDLLEnabledResponse},
+        sweep::{PerformSweepDBMResponse, PerformSweepWattResponse},
+    },
+    error::{
+        clear_errors::ClearErrorsResponse, pa::GetPAErrorsResponse, status::GetStatusResponse,
+    },
+    information::{
+        identity::GetIdentityResponse, isc_temp::GetISCTempResponse, uptime::GetUptimeResponse,
+        version::GetVersionResponse,
+    },
+    manual::{
+        attenuation::{GetAttenuationResponse, SetAttenuationResponse},
+        auto_gain::{GetAutoGainStateResponse, SetAutoGainStateResponse},
+        magnitude::{GetMagnitudeResponse, SetMagnitudeResponse},
+        power::{GetISCPowerOutputResponse, SetISCPowerOutputResponse},
+    },
+    prelude::MWError,
+    pwm::{
+        duty_cycle::{GetPWMDutyCycleResponse, SetPWMDutyCycleResponse},
+        frequency::SetPWMFrequencyResponse,
+        timed_rf::SetTimedRFEnableResponse,
+    },
+    response::Response,
+    soa::{
+        config::{GetSOAConfigResponse, SetSOAConfigResponse},
+        current::{GetSOACurrentConfigResponse, SetSOACurrentConfigResponse},
+        dissipation::{GetSOADissipationConfigResponse, SetSOADissipationConfigResponse},
+        forward_power::{GetSOAForwardPowerLimitsResponse, SetSOAForwardPowerLimitsResponse},
+        grace_timer::SetSOAGraceTimerResponse,
+        reflected_power::{GetSOAPowerConfigResponse, SetSOAPowerConfigResponse},
+        temperature::{GetSOATempConfigResponse, SetSOATempConfigResponse},
+        voltage::{GetSOAVoltageConfigResponse, SetSOAVoltageConfigResponse},
+        watchdog::SetSOAWatchdogConfigResponse,
+    },
+    system::{
+        channel_id::{GetChannelIDResponse, SetChannelIDResponse},
+        clock_source::{GetClockSourceResponse, SetClockSourceResponse},
+        communication::SetCommunicationInterfaceResponse,
+        power_max::{GetPowerMaxDbmResponse, SetPowerMaxDbmResponse},
+        power_min::{GetPowerMinDbmResponse, SetPowerMinDbmResponse},
+        power_offset::{GetPowerOffsetResponse, SetPowerOffsetResponse},
+        system_reset::ResetSystemResponse,
+        trigger_delay::SetZHLTriggerDelayResponse,
+    },
+};
+
+use super::transport::Transport;
+
+/// A command that can be sent to the signal generator on its own.
+pub trait Executable {
+    /// Sends the command over the transport and interprets the reply as a `Response`.
+    ///
+    /// Communication failures are returned as a `ReadWriteError` holding the command,
+    /// and errors reported by the signal generator as an `MWError`.
+    fn execute(self, port: &mut dyn Transport) -> Response;
+}
+
+/// # Wire format
+///
+/// Every command is written as the exact string the signal generator expects, and every
+/// reply the signal generator sends is parsed into the response of the same name.
+/// The table below is the protocol as implemented, checked in both directions.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use minicircuit_commands::{command::{Command, SetChannelID}, prelude::*};
+/// use minicircuit_driver::{executable::Executable, mock::MockTransport};
+///
+/// // The command, the string written for it and a reply the signal generator could send.
+/// let protocol: Vec<(Command, &str, &str)> = vec![
+///     (Command::GetPAPowerADC(Default::default()), "$PAG,1", "OK,1,1024,12"),
+///     (Command::GetPACurrent(Default::default()), "$PIG,1", "OK,1,3.2"),
+///     (Command::GetPAPowerDBM(Default::default()), "$PPDG,1", "OK,1,47.5,30.1"),
+///     (Command::GetPAPowerWatt(Default::default()), "$PPG,1", "OK,1,56.2,1.0"),
+///     (Command::GetFrequency(Default::default()), "$FCG,1", "OK,1,2450"),
+///     (Command::SetFrequency(Default::default()), "$FCS,1,2450", "OK"),
+///     (Command::GetRFOutput(Default::default()), "$ECG,1", "OK,1,1"),
+///     (Command::SetRFOutput(Default::default()), "$ECS,1,0", "OK"),
+///     (Command::GetPhase(Default::default()), "$PCG,1", "OK,1,90"),
+///     (Command::SetPhase(Default::default()), "$PCS,1,0", "OK"),
+///     (Command::GetPAPowerSetpointDBM(Default::default()), "$PWRDG,1", "OK,1,50.0"),
+///     (Command::GetPAPowerSetpointWatt(Default::default()), "$PWRG,1", "OK,1,25.0"),
+///     (Command::SetPAPowerSetpointDBM(Default::default()), "$PWRDS,1,50.0", "OK"),
+///     (Command::SetPAPowerSetpointWatt(Default::default()), "$PWRS,1,25.0", "OK"),
+///     (Command::GetPATemp(Default::default()), "$PTG,1", "OK,1,35"),
+///     (Command::GetPAVoltage(Default::default()), "$PVG,1", "OK,1,28.1"),
+///     (Command::GetDLLConfig(Default::default()), "$DLCG,1", "OK,1,2400,2500,2410,5,0.5,25"),
+///     (Command::SetDLLConfig(Default::default()), "$DLCS,1,2400,2500,2410,5,0.5,25", "OK"),
+///     (Command::GetDLLEnabled(Default::default()), "$DLEG,1", "OK,1,0"),
+///     (Command::SetDLLEnabled(Default::default()), "$DLES,1,0", "OK"),
+///     (Command::PerformSweepDBM(Default::default()), "$SWPD,1,2400,2500,10,10.0,1", "OK,1,2400,10.0,-5.0"),
+///     (Command::PerformSweepWatt(Default::default()), "$SWP,1,2400,2500,10,100.0,1", "OK,1,2400,100.0,2.0"),
+///     (Command::ClearErrors(Default::default()), "$ERRC,1", "OK"),
+///     (Command::GetPAErrors(Default::default()), "$PSG,1", "OK,1,0"),
+///     (Command::GetStatus(Default::default()), "$ST,1", "OK,1,0,0"),
+///     (Command::GetIdentity(Default::default()), "$IDN,1", "OK,1,MCL ISC-2425-25+,12345"),
+///     (Command::GetISCTemp(Default::default()), "$TCG,1", "OK,1,30"),
+///     (Command::GetUptime(Default::default()), "$RTG,1", "OK,1,3600"),
+///     (Command::GetVersion(Default::default()), "$VER,1", "OK,1,MCL,2,1,0,2023-01-01,12:00:00"),
+///     (Command::GetAttenuation(Default::default()), "$GCG,1", "OK,1,7.25"),
+///     (Command::SetAttenuation(Default::default()), "$GCS,1,7.0", "OK"),
+///     (Command::GetAutoGainState(Default::default()), "$AGEG,1", "OK,1,1"),
+///     (Command::SetAutoGainState(Default::default()), "$AGES,1,1", "OK"),
+///     (Command::GetMagnitude(Default::default()), "$MCG,1", "OK,1,75"),
+///     (Command::SetMagnitude(Default::default()), "$MCS,1,75", "OK"),
+///     (Command::GetISCPowerOutput(Default::default()), "$PWRSGDG,1", "OK,1,20.0"),
+///     (Command::SetISCPowerOutput(Default::default()), "$PWRSGDS,1,20.0", "OK"),
+///     (Command::GetPWMDutyCycle(Default::default()), "$DCG,1", "OK,1,1200,0,0,0,0,0,0,0,100"),
+///     (Command::SetPWMDutyCycle(Default::default()), "$DCS,1,100", "OK"),
+///     (Command::SetPWMFrequency(Default::default()), "$DCFS,1,1200,0", "OK"),
+///     (Command::SetTimedRFEnable(Default::default()), "$ECST,1,1,5000000", "OK"),
+///     (Command::GetSOAConfig(Default::default()), "$SOG,1", "OK temp:1 reflection:1 watchdog:0"),
+///     (Command::SetSOAConfig(Default::default()), "$SOA,1,1,1,1,1,1", "OK"),
+///     (Command::GetSOACurrentConfig(Default::default()), "$SCG,1", "OK,1,5.5,6.0"),
+///     (Command::SetSOACurrentConfig(Default::default()), "$SCS,1,5.5,6.0", "OK"),
+///     (Command::GetSOADissipationConfig(Default::default()), "$SDG,1", "OK,1,200.0,250.0"),
+///     (Command::SetSOADissipationConfig(Default::default()), "$SDS,1,0.0,0.0", "OK"),
+///     (Command::GetSOAForwardPowerLimits(Default::default()), "$SFG,1", "OK,1,55.0,65.0"),
+///     (Command::SetSOAForwardPowerLimits(Default::default()), "$SFS,1,55.0,65.0", "OK"),
+///     (Command::SetSOAGraceTimer(Default::default()), "$SOAGS,1,500", "OK"),
+///     (Command::GetSOAPowerConfig(Default::default()), "$SPG,1", "OK,1,47.2,54.0"),
+///     (Command::SetSOAPowerConfig(Default::default()), "$SPS,1,47.2,54.0", "OK"),
+///     (Command::GetSOATempConfig(Default::default()), "$STG,1", "OK,1,55,65"),
+///     (Command::SetSOATempConfig(Default::default()), "$STS,1,55,65", "OK"),
+///     (Command::GetSOAVoltageConfig(Default::default()), "$SVG,1", "OK 1 24.0 26.0 30.0 32.0"),
+///     (Command::SetSOAVoltageConfig(Default::default()), "$SVS,1,24.0,26.0,30.0,32.0", "OK"),
+///     (Command::SetSOAWatchdogConfig(Default::default()), "$SWES,1,true", "OK"),
+///     (Command::GetChannelID(Default::default()), "$CHANG", "OK,1"),
+///     (Command::SetChannelID(SetChannelID::new(Channel::new(1), Channel::new(2))), "$CHANS,1,2", "OK"),
+///     (Command::GetClockSource(Default::default()), "$CSG,1", "OK,1,0"),
+///     (Command::SetClockSource(Default::default()), "$CSS,1,0", "OK"),
+///     (Command::SetCommunicationInterface(Default::default()), "$COMS,1,2", "OK"),
+///     (Command::GetPowerMaxDbm(Default::default()), "$PWRMDG,1", "OK,1,47.1"),
+///     (Command::SetPowerMaxDbm(Default::default()), "$PWRMDS,1,47.1", "OK"),
+///     (Command::GetPowerMinDbm(Default::default()), "$PWRMINDG,1", "OK,1,-30.0"),
+///     (Command::SetPowerMinDbm(Default::default()), "$PWRMINDS,1,-30.0", "OK"),
+///     (Command::GetPowerOffset(Default::default()), "$PODG,1", "OK,1,0"),
+///     (Command::SetPowerOffset(Default::default()), "$PODS,1,0", "OK"),
+///     (Command::ResetSystem(Default::default()), "$RST,1", "OK"),
+///     (Command::SetZHLTriggerDelay(Default::default()), "$ZHLDS,1,30", "OK"),
+/// ];
+///
+/// for (command, wire, reply) in protocol {
+///     let name = format!("{:?}", command);
+///     let name = name.split('(').next().unwrap();
+///     assert_eq!(String::from(&command), wire, "{name} is written differently");
+///
+///     let mut transport = MockTransport::builder().reply(wire, reply).build();
+///     let response = command.execute(&mut transport);
+///     let parsed = format!("{:?}", response);
+///     let parsed = parsed.split('(').next().unwrap().trim_end_matches("Response");
+///     assert!(!response.is_error(), "{name} failed to parse {reply:?}: {response:?}");
+///     assert_eq!(parsed, name, "{name} was answered with the wrong response");
+/// }
+/// ```
+///
+/// Commands the signal generator doesn't reply to are only written, and answered with
+/// `Response::Ack` rather than waiting out the timeout.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use minicircuit_commands::{command::Command, response::Response};
+/// use minicircuit_driver::{executable::Executable, mock::MockTransport};
+///
+/// // Nothing is set up to reply, so waiting for a reply would fail.
+/// let mut transport = MockTransport::builder().build();
+///
+/// let response = Command::SetUartBaudRate(Default::default()).execute(&mut transport);
+/// assert!(matches!(response, Response::Ack(Command::SetUartBaudRate(_))));
+/// assert_eq!(transport.writes(), vec!["$UARTS,1,115200"]);
+/// ```
+///
+/// Values that aren't whole or positive keep their sign and fraction on the wire,
+/// while the frequency, a whole number of MHz, drops the fraction of a reply.
+///
+/// ```
+/// use minicircuit_commands::{command::Command, manual::attenuation::SetAttenuation, prelude::*};
+/// use minicircuit_driver::{executable::Executable, mock::MockTransport};
+///
+/// let negative_dbm = SetPAPowerSetpointDBM::new(Channel::default(), Dbm::new(-3.5));
+/// assert_eq!(String::from(Command::from(negative_dbm)), "$PWRDS,1,-3.5");
+///
+/// let fractional_watt = SetPAPowerSetpointWatt::new(Channel::default(), Watt::new(12.5));
+/// assert_eq!(String::from(Command::from(fractional_watt)), "$PWRS,1,12.5");
+///
+/// let fractional_attenuation = SetAttenuation::new(Channel::default(), Attenuation::new(7.25));
+/// assert_eq!(String::from(Command::from(fractional_attenuation)), "$GCS,1,7.25");
+///
+/// let mut transport = MockTransport::builder()
+///     .reply("$FCG,1", "OK,1,2450.7")
+///     .reply("$PPDG,1", "OK,1,47.5,-3.5")
+///     .build();
+///
+/// let response = GetFrequency::default().execute(&mut transport);
+/// assert_eq!(response.as_frequency().unwrap().frequency, Frequency::new(2450));
+///
+/// let response = GetPAPowerDBM::default().execute(&mut transport);
+/// let powers = response.as_papower_dbm().unwrap();
+/// assert_eq!(powers.forward, Dbm::new(47.5));
+/// assert_eq!(powers.reflected, Dbm::new(-3.5));
+/// ```
+#[allow(deprecated)]
+impl Executable for Command {
+    fn execute(self, port: &mut dyn Transport) -> Response {
+        if !self.expects_response() {
+            return acknowledge(self, port);
+        }
+
+        match self {
+            Command::GetPAPowerADC(command) => command.execute(port),
+            Command::GetPACurrent(command) => command.execute(port),
+            Command::GetPAPowerDBM(command) => command.execute(port),
+            Command::GetPAPowerWatt(command) => command.execute(port),
+            Command::GetFrequency(command) => command.execute(port),
+            Command::SetFrequency(command) => command.execute(port),
+            Command::GetRFOutput(command) => command.execute(port),
+            Command::SetRFOutput(command) => command.execute(port),
+            Command::GetPhase(command) => command.execute(port),
+            Command::SetPhase(command) => command.execute(port),
+            Command::GetPAPowerSetpointDBM(command) => command.execute(port),
+            Command::GetPAPowerSetpointWatt(command) => command.execute(port),
+            Command::SetPAPowerSetpointDBM(command) => command.execute(port),
+            Command::SetPAPowerSetpointWatt(command) => command.execute(port),
+            Command::GetPATemp(command) => command.execute(port),
+            Command::GetPAVoltage(command) => command.execute(port),
+            Command::GetDLLConfig(command) => command.execute(port),
+            Command::SetDLLConfig(command) => command.execute(port),
+            Command::GetDLLEnabled(command) => command.execute(port),
+            Command::SetDLLEnabled(command) => command.execute(port),
+            Command::PerformSweepDBM(command) => command.execute(port),
+            Command::PerformSweepWatt(command) => command.execute(port),
+            Command::ClearErrors(command) => command.execute(port),
+            Command::GetPAErrors(command) => command.execute(port),
+            Command::GetStatus(command) => command.execute(port),
+            Command::GetIdentity(command) => command.execute(port),
+            Command::GetISCTemp(command) => command.execute(port),
+            Command::GetUptime(command) => command.execute(port),
+            Command::GetVersion(command) => command.execute(port),
+            Command::GetAttenuation(command) => command.execute(port),
+            Command::SetAttenuation(command) => command.execute(port),
+            Command::GetAutoGainState(command) => command.execute(port),
+            Command::SetAutoGainState(command) => command.execute(port),
+            Command::GetMagnitude(command) => command.execute(port),
+            Command::SetMagnitude(command) => command.execute(port),
+            Command::GetISCPowerOutput(command) => command.execute(port),
+            Command::SetISCPowerOutput(command) => command.execute(port),
+            Command::GetPWMDutyCycle(command) => command.execute(port),
+            Command::SetPWMDutyCycle(command) => command.execute(port),
+            Command::SetPWMFrequency(command) => command.execute(port),
+            Command::SetTimedRFEnable(command) => command.execute(port),
+            Command::GetSOAConfig(command) => command.execute(port),
+            Command::SetSOAConfig(command) => command.execute(port),
+            Command::GetSOACurrentConfig(command) => command.execute(port),
+            Command::SetSOACurrentConfig(command) => command.execute(port),
+            Command::GetSOADissipationConfig(command) => command.execute(port),
+            Command::SetSOADissipationConfig(command) => command.execute(port),
+            Command::GetSOAForwardPowerLimits(command) => command.execute(port),
+            Command::SetSOAForwardPowerLimits(command) => command.execute(port),
+            Command::SetSOAGraceTimer(command) => command.execute(port),
+            Command::GetSOAPowerConfig(command) => command.execute(port),
+            Command::SetSOAPowerConfig(command) => command.execute(port),
+            Command::GetSOATempConfig(command) => command.execute(port),
+            Command::SetSOATempConfig(command) => command.execute(port),
+            Command::GetSOAVoltageConfig(command) => command.execute(port),
+            Command::SetSOAVoltageConfig(command) => command.execute(port),
+            Command::SetSOAWatchdogConfig(command) => command.execute(port),
+            Command::SetUartBaudRate(command) => command.execute(port),
+            Command::GetChannelID(command) => command.execute(port),
+            Command::SetChannelID(command) => command.execute(port),
+            Command::GetClockSource(command) => command.execute(port),
+            Command::SetClockSource(command) => command.execute(port),
+            Command::SetCommunicationInterface(command) => command.execute(port),
+            Command::GetPowerMaxDbm(command) => command.execute(port),
+            Command::SetPowerMaxDbm(command) => command.execute(port),
+            Command::GetPowerMinDbm(command) => command.execute(port),
+            Command::SetPowerMinDbm(command) => command.execute(port),
+            Command::GetPowerOffset(command) => command.execute(port),
+            Command::SetPowerOffset(command) => command.execute(port),
+            Command::ResetSystem(command) => command.execute(port),
+            Command::SetZHLTriggerDelay(command) => command.execute(port),
+            // Nothing goes over the wire, the queue loop answers it when it gets to it.
+            Command::Barrier => Response::Barrier,
+            // Passed through as is, the reply isn't parsed.
+            Command::Raw(wire) => exchange(wire, port, Command::Raw, |_, sg_response| {
+                Response::Raw(sg_response)
+            }),
+        }
+    }
+}
+
+/// Sends the command and hands a successful reply to `on_reply`.
+///
+/// `wrap` turns the command back into a `Command` so a failed exchange can be traced to its source.
+/// The wire string is written from a borrow, so the command is never cloned.
+fn exchange<C>(
+    command: C,
+    port: &mut dyn Transport,
+    wrap: fn(C) -> Command,
+    on_reply: impl FnOnce(C, String) -> Response,
+) -> Response
+where
+    for<'a> &'a C: Into<String>,
+{
+    // Convert the command into a string (required format to be sent to the signal generator).
+    let tx: String = (&command).into();
+
+    match port.write_read(tx) {
+        Ok(sg_response) => on_reply(command, sg_response),
+        // Return the command (for backtracking the source of issue) and the error description
+        Err(e) => Response::ReadWriteError(ReadWriteError::from_port_error(wrap(command), e)),
+    }
+}
+
+/// Keeps the reply along with the command when it couldn't be parsed, so what the device said
+/// can still be inspected. Errors the device reported are returned as they are.
+fn unparsed(command: Command, raw: String, error: MWError) -> Response {
+    if error.is_parse_failure() {
+        Response::UnparsedReply {
+            command,
+            raw,
+            error,
+        }
+    } else {
+        Response::MWError(error)
+    }
+}
+
+/// Commands whose reply is parsed into a response type of the same name.
+macro_rules! parsed_reply {
+    ($($command:ident => $response:ident),* $(,)?) => {
+        $(
+            #[allow(deprecated)]
+            impl Executable for $command {
+                fn execute(self, port: &mut dyn Transport) -> Response {
+                    exchange(self, port, Command::$command, |command, sg_response| {
+                        let parse_result: Result<$response, _> = sg_response.clone().try_into();
+
+                        match parse_result {
+                            Ok(formatted_response) => Response::$response(formatted_response),
+                            Err(e) => unparsed(Command::$command(command), sg_response, e),
+                        }
+                    })
+                }
+            }
+        )*
+    };
+}
+
+/// A command the device answers with a line for each of its measurements.
+trait Sweep {
+    /// Like `Executable::execute`, but calls `on_progress` with how many of the measurements have
+    /// arrived, and how many were asked for, once the reply is in and after each further line.
+    fn execute_reporting(
+        self,
+        port: &mut dyn Transport,
+        on_progress: &mut dyn FnMut(usize, usize),
+    ) -> Response;
+}
+
+/// Sweeps, whose response is told how many measurements were asked for so a cut off reply can be
+/// told apart from a complete one. The measurements that follow the reply are read along with it.
+macro_rules! sweep_reply {
+    ($($command:ident => $response:ident),* $(,)?) => {
+        $(
+            impl Executable for $command {
+                fn execute(self, port: &mut dyn Transport) -> Response {
+                    self.execute_reporting(port, &mut |_, _| {})
+                }
+            }
+
+            impl Sweep for $command {
+                fn execute_reporting(
+                    self,
+                    port: &mut dyn Transport,
+                    on_progress: &mut dyn FnMut(usize, usize),
+                ) -> Response {
+                    let mut response = exchange(self, port, Command::$command, |command, sg_response| {
+                        let parse_result: Result<$response, _> = sg_response.clone().try_into();
+
+                        match parse_result {
+                            Ok(mut formatted_response) => {
+                                formatted_response.expected_points = command.point_count();
+                                Response::$response(formatted_response)
+                            }
+                            Err(e) => unparsed(Command::$command(command), sg_response, e),
+                        }
+                    });
+                    read_sweep_lines(port, &mut response, on_progress);
+
+                    response
+                }
+            }
+        )*
+    };
+}
+
+/// Reads the measurements of a sweep that follow its reply, until as many as were asked for
+/// have arrived.
+///
+/// A sweep that stops short keeps the measurements that did arrive, see
+/// `PerformSweepDBMResponse::points` for telling it apart from a complete one.
+fn read_sweep_lines(
+    port: &mut dyn Transport,
+    response: &mut Response,
+    on_progress: &mut dyn FnMut(usize, usize),
+) {
+    let total = match response {
+        Response::PerformSweepDBMResponse(sweep) => sweep.expected_points,
+        Response::PerformSweepWattResponse(sweep) => sweep.expected_points,
+        _ => None,
+    };
+    let Some(total) = total else {
+        return;
+    };
+
+    loop {
+        let done = match response {
+            Response::PerformSweepDBMResponse(sweep) => sweep.points.len(),
+            Response::PerformSweepWattResponse(sweep) => sweep.points.len(),
+            _ => return,
+        };
+        on_progress(done, total);
+
+        if done >= total {
+            return;
+        }
+
+        let line = match port.read_line() {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!(done, total, error = %e, "The sweep stopped short.");
+                return;
+            }
+        };
+
+        let pushed = match response {
+            Response::PerformSweepDBMResponse(sweep) => sweep.push_line(&line),
+            Response::PerformSweepWattResponse(sweep) => sweep.push_line(&line),
+            _ => return,
+        };
+        if let Err(e) = pushed {
+            *response = Response::MWError(e);
+            return;
+        }
+    }
+}
+
+/// Setters whose reply only signals success, so the response echoes the value that was set.
+macro_rules! echoed_reply {
+    ($($command:ident => $response:ident($field:ident)),* $(,)?) => {
+        $(
+            #[allow(deprecated)]
+            impl Executable for $command {
+                fn execute(self, port: &mut dyn Transport) -> Response {
+                    exchange(self, port, Command::$command, |command, sg_response| {
+                        if sg_response.contains("ERR") {
+                            let e: MWError = sg_response.into();
+                            Response::MWError(e)
+                        } else {
+                            Response::$response(command.$field)
+                        }
+                    })
+                }
+            }
+        )*
+    };
+}
+
+sweep_reply! {
+    PerformSweepDBM => PerformSweepDBMResponse,
+    PerformSweepWatt => PerformSweepWattResponse,
+}
+
+parsed_reply! {
+    GetPAPowerADC => GetPAPowerADCResponse,
+    GetPACurrent => GetPACurrentResponse,
+    GetPAPowerDBM => GetPAPowerDBMResponse,
+    GetPAPowerWatt => GetPAPowerWattResponse,
+    GetFrequency => GetFrequencyResponse,
+    GetRFOutput => GetRFOutputResponse,
+    GetPhase => GetPhaseResponse,
+    GetPAPowerSetpointDBM => GetPAPowerSetpointDBMResponse,
+    GetPAPowerSetpointWatt => GetPAPowerSetpointWattResponse,
+    SetPAPowerSetpointDBM => SetPAPowerSetpointDBMResponse,
+    GetPATemp => GetPATempResponse,
+    GetPAVoltage => GetPAVoltageResponse,
+    GetDLLConfig => GetDLLConfigResponse,
+    SetDLLConfig => SetDLLConfigResponse,
+    GetDLLEnabled => GetDLLEnabledResponse,
+    SetDLLEnabled => SetDLLEnabledResponse,
+    ClearErrors => ClearErrorsResponse,
+    GetPAErrors => GetPAErrorsResponse,
+    GetStatus => GetStatusResponse,
+    GetIdentity => GetIdentityResponse,
+    GetISCTemp => GetISCTempResponse,
+    GetUptime => GetUptimeResponse,
+    GetVersion => GetVersionResponse,
+    GetAttenuation => GetAttenuationResponse,
+    SetAttenuation => SetAttenuationResponse,
+    GetAutoGainState => GetAutoGainStateResponse,
+    SetAutoGainState => SetAutoGainStateResponse,
+    GetMagnitude => GetMagnitudeResponse,
+    SetMagnitude => SetMagnitudeResponse,
+    GetISCPowerOutput => GetISCPowerOutputResponse,
+    SetISCPowerOutput => SetISCPowerOutputResponse,
+    GetPWMDutyCycle => GetPWMDutyCycleResponse,
+    SetPWMDutyCycle => SetPWMDutyCycleResponse,
+    SetPWMFrequency => SetPWMFrequencyResponse,
+    SetTimedRFEnable => SetTimedRFEnableResponse,
+    GetSOAConfig => GetSOAConfigResponse,
+    SetSOAConfig => SetSOAConfigResponse,
+    GetSOACurrentConfig => GetSOACurrentConfigResponse,
+    SetSOACurrentConfig => SetSOACurrentConfigResponse,
+    GetSOADissipationConfig => GetSOADissipationConfigResponse,
+    SetSOADissipationConfig => SetSOADissipationConfigResponse,
+    GetSOAForwardPowerLimits => GetSOAForwardPowerLimitsResponse,
+    SetSOAForwardPowerLimits => SetSOAForwardPowerLimitsResponse,
+    SetSOAGraceTimer => SetSOAGraceTimerResponse,
+    GetSOAPowerConfig => GetSOAPowerConfigResponse,
+    SetSOAPowerConfig => SetSOAPowerConfigResponse,
+    GetSOATempConfig => GetSOATempConfigResponse,
+    SetSOATempConfig => SetSOATempConfigResponse,
+    GetSOAVoltageConfig => GetSOAVoltageConfigResponse,
+    SetSOAVoltageConfig => SetSOAVoltageConfigResponse,
+    SetSOAWatchdogConfig => SetSOAWatchdogConfigResponse,
+    GetChannelID => GetChannelIDResponse,
+    SetChannelID => SetChannelIDResponse,
+    GetClockSource => GetClockSourceResponse,
+    SetClockSource => SetClockSourceResponse,
+    SetCommunicationInterface => SetCommunicationInterfaceResponse,
+    GetPowerMaxDbm => GetPowerMaxDbmResponse,
+    SetPowerMaxDbm => SetPowerMaxDbmResponse,
+    GetPowerMinDbm => GetPowerMinDbmResponse,
+    SetPowerMinDbm => SetPowerMinDbmResponse,
+    GetPowerOffset => GetPowerOffsetResponse,
+    SetPowerOffset => SetPowerOffsetResponse,
+    ResetSystem => ResetSystemResponse,
+    SetZHLTriggerDelay => SetZHLTriggerDelayResponse,
+}
+
+echoed_reply! {
+    SetFrequency => SetFrequencyResponse(frequency),
+    SetRFOutput => SetRFOutputResponse(enabled),
+    SetPhase => SetPhaseResponse(phase),
+    SetPAPowerSetpointWatt => SetPAPowerSetpointWattResponse(power),
+}
+
+#[allow(deprecated)]
+impl Executable for SetUartBaudRate {
+    fn execute(self, port: &mut dyn Transport) -> Response {
+        // This command doesn't have a response from the signal generator.
+        acknowledge(Command::SetUartBaudRate(self), port)
+    }
+}
+
+/// Writes a command the signal generator doesn't reply to, see `Command::expects_response`.
+fn acknowledge(command: Command, port: &mut dyn Transport) -> Response {
+    match port.write(String::from(&command)) {
+        Ok(()) => Response::Ack(command),
+        Err(e) => Response::ReadWriteError(ReadWriteError::from_port_error(command, e)),
+    }
+}
+
+/// Sends a command, allowing it `timeout` to respond instead of the transport's own timeout.
+///
+/// Without an override, commands known to respond slowly are still given their default timeout.
+///
+/// Every command is traced in its own span, recording the wire string, the latency and the outcome.
+pub(crate) fn send_command_within(
+    command: Command,
+    port: &mut dyn Transport,
+    timeout: Option<Duration>,
+) -> Response {
+    send_command_reporting(command, port, timeout, &mut |_, _| {})
+}
+
+/// Like `send_command_within`, but calls `on_progress` with how many of the measurements of a
+/// sweep have arrived, and how many were asked for, as they are read.
+pub(crate) fn send_command_reporting(
+    command: Command,
+    port: &mut dyn Transport,
+    timeout: Option<Duration>,
+    on_progress: &mut dyn FnMut(usize, usize),
+) -> Response {
+    let span = tracing::debug_span!(
+        "command",
+        command = %command,
+        wire = tracing::field::Empty,
+        latency_us = tracing::field::Empty,
+        outcome = tracing::field::Empty,
+    );
+    let _entered = span.enter();
+
+    // Building the wire string costs an allocation, so only do it when it'll be recorded.
+    if !span.is_disabled() {
+        span.record("wire", String::from(&command).as_str());
+    }
+
+    let started = Instant::now();
+    let response = exchange_within(command, port, timeout, on_progress);
+    let latency = started.elapsed();
+
+    span.record("latency_us", latency.as_micros() as u64);
+    span.record("outcome", outcome(&response));
+    if response.is_error() {
+        tracing::warn!(response = ?response, "Command failed.");
+    }
+
+    response
+}
+
+fn exchange_within(
+    command: Command,
+    port: &mut dyn Transport,
+    timeout: Option<Duration>,
+    on_progress: &mut dyn FnMut(usize, usize),
+) -> Response {
+    let previous = port.timeout();
+    let timeout = timeout.or_else(|| {
+        command
+            .default_timeout()
+            .map(|default| default.max(previous))
+    });
+
+    let Some(timeout) = timeout.filter(|timeout| *timeout != previous) else {
+        return busy_or(execute_reporting(command, port, on_progress));
+    };
+
+    if let Err(e) = port.set_timeout(timeout) {
+        return Response::ReadWriteError(ReadWriteError::from_port_error(command, e));
+    }
+    let response = execute_reporting(command, port, on_progress);
+    // Restore the timeout for the commands that follow.
+    let _ = port.set_timeout(previous);
+
+    busy_or(response)
+}
+
+fn execute_reporting(
+    command: Command,
+    port: &mut dyn Transport,
+    on_progress: &mut dyn FnMut(usize, usize),
+) -> Response {
+    match command {
+        Command::PerformSweepDBM(command) => command.execute_reporting(port, on_progress),
+        Command::PerformSweepWatt(command) => command.execute_reporting(port, on_progress),
+        command => command.execute(port),
+    }
+}
+
+/// How long a caller waits for the response to `command`, sent with the message timeout
+/// `timeout`, so it doesn't give up on an exchange that is still allowed to take longer.
+///
+/// Without an override the command's default timeout applies, as when it is sent. Neither waits
+/// less than the response timeout of the target properties.
+pub(crate) fn response_wait(
+    command: &Command,
+    timeout: Option<Duration>,
+    response_timeout: Duration,
+) -> Duration {
+    timeout
+        .or_else(|| command.default_timeout())
+        .map_or(response_timeout, |timeout| timeout.max(response_timeout))
+}
+
+/// Reports `ERR06` as `Response::Busy`, which is retried unlike the other device errors.
+fn busy_or(response: Response) -> Response {
+    match response {
+        Response::MWError(MWError::SystemBusy) => Response::Busy,
+        response => response,
+    }
+}
+
+/// A short description of how a command went, for tracing.
+fn outcome(response: &Response) -> &'static str {
+    match response {
+        Response::ReadWriteError(_) => "read_write_error",
+        Response::MWError(_) => "device_error",
+        Response::UnparsedReply { .. } => "unparsed_reply",
+        Response::Busy => "busy",
+        _ => "ok",
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use tokio::{runtime::Handle, sync::Mutex};
+
+use minicircuit_commands::{
+    command::Command,
+    prelude::{Channel, SetRFOutput},
+    response::Response,
+};
+
+use super::{executable::Executable, transport::Transport};
+
+/// Keeps RF output enabled for as long as it is alive and switches it off when dropped,
+/// including when the owning code panics and unwinds.
+///
+/// `Drop` can't be async, so the disable command is sent in whichever way doesn't deadlock:
+/// - If the connection is free, the command is written on the spot, blocking for a single exchange.
+/// - If the connection is busy and a tokio runtime is running, a task is spawned that sends the
+///   command as soon as the connection is released. It won't run if the runtime shuts down first.
+/// - Otherwise the guard blocks until the connection is released and then sends the command.
+///
+/// Prefer `disable()` where possible, which waits for the signal generator to acknowledge.
+#[derive(Debug)]
+#[must_use = "RF output is switched off as soon as the guard is dropped"]
+pub struct RfOutputGuard {
+    port: Arc<Mutex<Box<dyn Transport>>>,
+    /// Cleared once RF output has been dealt with, so dropping does nothing.
+    armed: bool,
+}
+
+impl RfOutputGuard {
+    pub(crate) fn new(port: Arc<Mutex<Box<dyn Transport>>>) -> Self {
+        Self { port, armed: true }
+    }
+
+    /// Switches RF output off and returns the response of the signal generator.
+    pub async fn disable(mut self) -> Response {
+        self.armed = false;
+
+        let mut port = self.port.lock().await;
+        disable_command().execute(&mut **port)
+    }
+
+    /// Leaves RF output enabled once the guard is gone.
+    pub fn release(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for RfOutputGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+
+        if let Ok(mut port) = self.port.try_lock() {
+            disable_command().execute(&mut **port);
+            return;
+        }
+
+        match Handle::try_current() {
+            Ok(handle) => {
+                let port = Arc::clone(&self.port);
+                handle.spawn(async move {
+                    let mut port = port.lock().await;
+                    disable_command().execute(&mut **port);
+                });
+            }
+            Err(_) => {
+                let mut port = self.port.blocking_lock();
+                disable_command().execute(&mut **port);
+            }
+        }
+    }
+}
+
+fn disable_command() -> Command {
+    Command::SetRFOutput(SetRFOutput::new(Channel::default(), false))
+}
@@ -1,3 +1,59 @@
+//! Drivers for the MiniCircuits ISC signal generators.
+//!
+//! What gets compiled is chosen with cargo features:
+//!
+//! | Feature | Default | Provides |
+//! |---------|---------|----------|
+//! | `async` | yes | `MiniCircuitDriver` and everything built on it, such as the blocking wrapper, the registry and the telemetry and SOA monitoring helpers. Pulls in tokio. |
+//! | `sync` | no | `SyncMiniCircuitDriver`, which queues commands on a std thread without tokio. |
+//!
+//! The transports, connection helpers, mocks and error types are always available.
+//! The command and response types live in `minicircuit_commands`, which depends on neither.
+//!
+//! ```toml
+//! minicircuit_driver = { version = "0.1", default-features = false, features = ["sync"] }
+//! ```
+
+// Without either driver, the helpers they share are left unused.
+#![cfg_attr(
+    not(any(feature = "async", feature = "sync")),
+    allow(dead_code, unused_imports)
+)]
+
+#[cfg(feature = "async")]
+pub mod blocking;
 pub mod communication;
+pub mod config;
 pub mod connection;
+#[cfg(feature = "async")]
 pub mod driver;
+pub mod error;
+pub mod executable;
+#[cfg(feature = "async")]
+pub mod guard;
+#[cfg(feature = "async")]
+pub mod logger;
+pub mod mock;
+#[cfg(feature = "async")]
+pub mod monitor;
+pub mod prelude;
+mod queue;
+#[cfg(feature = "async")]
+pub mod recorder;
+#[cfg(feature = "async")]
+pub mod registry;
+pub mod safety;
+#[cfg(feature = "async")]
+pub mod script;
+#[cfg(feature = "async")]
+pub mod sender;
+#[cfg(feature = "async")]
+pub mod stats;
+#[cfg(feature = "sync")]
+pub mod sync;
+#[cfg(feature = "async")]
+pub mod telemetry;
+pub mod transport;
+pub mod verify;
+#[cfg(feature = "async")]
+pub mod wire;
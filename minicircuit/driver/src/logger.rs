@@ -0,0 +1,381 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use tokio::{
+    select,
+    sync::{broadcast, oneshot},
+    task::JoinHandle,
+    time::MissedTickBehavior,
+};
+
+use minicircuit_commands::response::CorrelatedResponse;
+
+use super::telemetry::TelemetryMetric;
+
+/// The layout of the telemetry log file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Comma separated values with a header row and a column per metric.
+    Csv,
+    /// One JSON object per line with a field per metric.
+    JsonLines,
+}
+
+/// When the log file is moved aside and a new one is started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Keep writing to the same file.
+    Never,
+    /// Rotate once the file has grown to the given number of bytes.
+    Size(u64),
+    /// Rotate once the file has been written to for the given time.
+    Interval(Duration),
+}
+
+/// Configures and starts a `TelemetryLogger`.
+#[derive(Debug, Clone)]
+pub struct TelemetryLoggerBuilder {
+    path: PathBuf,
+    format: LogFormat,
+    metrics: Vec<TelemetryMetric>,
+    include_errors: bool,
+    flush_interval: Duration,
+    rotation: Rotation,
+}
+
+impl TelemetryLoggerBuilder {
+    /// Starts from a CSV log of every metric, flushed every second and never rotated.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            format: LogFormat::Csv,
+            metrics: TelemetryMetric::ALL.to_vec(),
+            include_errors: false,
+            flush_interval: Duration::from_secs(1),
+            rotation: Rotation::Never,
+        }
+    }
+
+    pub fn format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Only records responses carrying at least one of the given metrics.
+    pub fn metrics(mut self, metrics: impl IntoIterator<Item = TelemetryMetric>) -> Self {
+        self.metrics = metrics.into_iter().collect();
+        self
+    }
+
+    /// Also records failed commands, with the error description in an `error` field.
+    pub fn include_errors(mut self, include_errors: bool) -> Self {
+        self.include_errors = include_errors;
+        self
+    }
+
+    /// How often buffered records are written to disk, must be longer than zero.
+    pub fn flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Opens the log file and starts recording the responses received on `response_rx`.
+    ///
+    /// Subscribe with `MiniCircuitDriver::response_tx` once the driver is connected.
+    /// Fails with `io::ErrorKind::InvalidInput` for a zero flush interval, before the file is opened.
+    pub fn spawn(
+        self,
+        response_rx: broadcast::Receiver<CorrelatedResponse>,
+    ) -> io::Result<TelemetryLogger> {
+        if self.flush_interval.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The flush interval must be longer than zero.",
+            ));
+        }
+
+        let writer = LogWriter::open(self)?;
+        let (stop_tx, stop_rx) = oneshot::channel();
+
+        let handle = tokio::spawn(record(writer, response_rx, stop_rx));
+
+        Ok(TelemetryLogger { handle, stop_tx })
+    }
+}
+
+/// Appends timestamped responses from the driver to a CSV or JSON lines file.
+///
+/// Recording continues until `stop()` is called or the driver's response channel closes.
+#[derive(Debug)]
+pub struct TelemetryLogger {
+    handle: JoinHandle<io::Result<()>>,
+    stop_tx: oneshot::Sender<()>,
+}
+
+impl TelemetryLogger {
+    pub fn builder(path: impl Into<PathBuf>) -> TelemetryLoggerBuilder {
+        TelemetryLoggerBuilder::new(path)
+    }
+
+    /// Stops recording, flushes the file and returns the first error that stopped the logger, if any.
+    pub async fn stop(self) -> io::Result<()> {
+        let _ = self.stop_tx.send(());
+
+        match self.handle.await {
+            Ok(result) => result,
+            Err(e) => Err(io::Error::other(e)),
+        }
+    }
+}
+
+async fn record(
+    mut writer: LogWriter,
+    mut response_rx: broadcast::Receiver<CorrelatedResponse>,
+    mut stop_rx: oneshot::Receiver<()>,
+) -> io::Result<()> {
+    let mut ticker = tokio::time::interval(writer.config.flush_interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        select! {
+            _ = &mut stop_rx => break,
+            _ = ticker.tick() => writer.flush()?,
+            received = response_rx.recv() => match received {
+                Ok(correlated) => writer.write(&correlated)?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        skipped,
+                        "Telemetry logger fell behind, responses were not recorded."
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+
+    writer.flush()
+}
+
+struct LogWriter {
+    config: TelemetryLoggerBuilder,
+    file: BufWriter<File>,
+    /// Bytes in the current file, used for size based rotation.
+    written: u64,
+    opened_at: Instant,
+}
+
+impl LogWriter {
+    /// Appends to the file at the configured path, if there is one.
+    ///
+    /// A CSV file with other columns than configured is moved aside as if it were rotated,
+    /// rather than appending rows that don't line up with its header.
+    fn open(config: TelemetryLoggerBuilder) -> io::Result<Self> {
+        if config.format == LogFormat::Csv && !starts_with(&config.path, &csv_header(&config))? {
+            fs::rename(&config.path, rotated_path(&config.path, rotation_stamp()))?;
+        }
+
+        let (file, written) = open_file(&config.path)?;
+
+        let mut writer = Self {
+            config,
+            file,
+            written,
+            opened_at: Instant::now(),
+        };
+        if writer.written == 0 {
+            writer.write_header()?;
+        }
+
+        Ok(writer)
+    }
+
+    fn write(&mut self, correlated: &CorrelatedResponse) -> io::Result<()> {
+        let values: Vec<(TelemetryMetric, f64)> = TelemetryMetric::extract(&correlated.response)
+            .into_iter()
+            .filter(|(metric, _)| self.config.metrics.contains(metric))
+            .collect();
+
+        let error = match &correlated.response {
+            response if self.config.include_errors && response.is_error() => {
+                let description: String = response.clone().into();
+                Some(description)
+            }
+            _ => None,
+        };
+
+        if values.is_empty() && error.is_none() {
+            return Ok(());
+        }
+
+        self.rotate_if_due()?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+
+        let line = match self.config.format {
+            LogFormat::Csv => self.csv_row(timestamp, correlated.id, &values, error),
+            LogFormat::JsonLines => json_line(timestamp, correlated.id, &values, error),
+        };
+
+        self.file.write_all(line.as_bytes())?;
+        self.written += line.len() as u64;
+
+        Ok(())
+    }
+
+    fn csv_row(
+        &self,
+        timestamp: f64,
+        id: u64,
+        values: &[(TelemetryMetric, f64)],
+        error: Option<String>,
+    ) -> String {
+        let mut columns = vec![format!("{:.3}", timestamp), id.to_string()];
+
+        for metric in &self.config.metrics {
+            let value = values
+                .iter()
+                .find(|(recorded, _)| recorded == metric)
+                .map(|(_, value)| value.to_string())
+                .unwrap_or_default();
+            columns.push(value);
+        }
+
+        if self.config.include_errors {
+            // Quote the description since it may contain commas.
+            columns.push(match error {
+                Some(error) => format!("\"{}\"", error.replace('"', "\"\"")),
+                None => String::new(),
+            });
+        }
+
+        format!("{}\n", columns.join(","))
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if self.config.format != LogFormat::Csv {
+            return Ok(());
+        }
+
+        let header = csv_header(&self.config);
+        self.file.write_all(header.as_bytes())?;
+        self.written += header.len() as u64;
+
+        Ok(())
+    }
+
+    fn rotate_if_due(&mut self) -> io::Result<()> {
+        let due = match self.config.rotation {
+            Rotation::Never => false,
+            Rotation::Size(max_bytes) => self.written >= max_bytes,
+            Rotation::Interval(interval) => self.opened_at.elapsed() >= interval,
+        };
+        if !due {
+            return Ok(());
+        }
+
+        self.file.flush()?;
+
+        // Move the full file aside, named after the moment it was rotated.
+        fs::rename(
+            &self.config.path,
+            rotated_path(&self.config.path, rotation_stamp()),
+        )?;
+
+        let (file, written) = open_file(&self.config.path)?;
+        self.file = file;
+        self.written = written;
+        self.opened_at = Instant::now();
+
+        self.write_header()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// The header row of a CSV log, naming a column per configured metric.
+fn csv_header(config: &TelemetryLoggerBuilder) -> String {
+    let mut columns = vec!["timestamp", "id"];
+    columns.extend(config.metrics.iter().map(|metric| metric.name()));
+    if config.include_errors {
+        columns.push("error");
+    }
+
+    format!("{}\n", columns.join(","))
+}
+
+/// Whether the file at `path` starts with the line `header`, or is missing or empty.
+fn starts_with(path: &Path, header: &str) -> io::Result<bool> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(true),
+        Err(e) => return Err(e),
+    };
+
+    let mut first_line = String::new();
+    BufReader::new(file).read_line(&mut first_line)?;
+
+    Ok(first_line.is_empty() || first_line == header)
+}
+
+fn open_file(path: &Path) -> io::Result<(BufWriter<File>, u64)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let written = file.metadata()?.len();
+
+    Ok((BufWriter::new(file), written))
+}
+
+/// The moment a file is moved aside, in milliseconds since the Unix epoch.
+fn rotation_stamp() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+/// Inserts the rotation time before the extension, `telemetry.csv` becomes `telemetry.<millis>.csv`.
+fn rotated_path(path: &Path, rotated_at: u128) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, rotated_at, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, rotated_at),
+    };
+
+    path.with_file_name(name)
+}
+
+fn json_line(
+    timestamp: f64,
+    id: u64,
+    values: &[(TelemetryMetric, f64)],
+    error: Option<String>,
+) -> String {
+    let mut object = serde_json::Map::new();
+    object.insert("timestamp".to_string(), timestamp.into());
+    object.insert("id".to_string(), id.into());
+    for (metric, value) in values {
+        object.insert(metric.name().to_string(), (*value).into());
+    }
+    if let Some(error) = error {
+        object.insert("error".to_string(), error.into());
+    }
+
+    format!("{}\n", serde_json::Value::Object(object))
+}
@@ -0,0 +1,343 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Debug},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use serialport::{Error, ErrorKind};
+
+use super::transport::Transport;
+
+/// Decides which written commands an expectation answers.
+#[derive(Clone)]
+pub enum RequestMatcher {
+    /// The command must equal the string, such as `$FCG,1`.
+    Exact(String),
+    /// The command must start with the string, such as `$FCS`.
+    Prefix(String),
+    /// The command must satisfy the predicate, which can wrap a regex.
+    Predicate(Arc<dyn Fn(&str) -> bool + Send + Sync>),
+}
+
+impl RequestMatcher {
+    pub fn matches(&self, tx: &str) -> bool {
+        match self {
+            RequestMatcher::Exact(request) => tx == request,
+            RequestMatcher::Prefix(prefix) => tx.starts_with(prefix.as_str()),
+            RequestMatcher::Predicate(predicate) => predicate(tx),
+        }
+    }
+}
+
+impl Debug for RequestMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestMatcher::Exact(request) => f.debug_tuple("Exact").field(request).finish(),
+            RequestMatcher::Prefix(prefix) => f.debug_tuple("Prefix").field(prefix).finish(),
+            RequestMatcher::Predicate(_) => f.write_str("Predicate"),
+        }
+    }
+}
+
+/// How the mock answers a matched command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockReply {
+    /// Responds with the line, without its terminator, such as `OK,1,2450`.
+    Line(String),
+    /// Responds with the first line, leaving the others for `read_line`, such as for a streamed sweep.
+    Lines(Vec<String>),
+    /// Fails the exchange as a real port would, such as with `ErrorKind::Io(TimedOut)`.
+    Error(ErrorKind, String),
+}
+
+impl MockReply {
+    /// The reply to the exchange, and the lines that follow it.
+    fn to_result(&self) -> (Result<String, Error>, VecDeque<String>) {
+        match self {
+            MockReply::Line(line) => (Ok(line.clone()), VecDeque::new()),
+            MockReply::Lines(lines) => {
+                let mut lines: VecDeque<String> = lines.iter().cloned().collect();
+                match lines.pop_front() {
+                    Some(first) => (Ok(first), lines),
+                    None => (
+                        Err(timed_out("The mock was given no lines to reply with.")),
+                        lines,
+                    ),
+                }
+            }
+            MockReply::Error(kind, description) => (
+                Err(Error::new(*kind, description.as_str())),
+                VecDeque::new(),
+            ),
+        }
+    }
+}
+
+fn timed_out(description: &str) -> Error {
+    Error::new(ErrorKind::Io(std::io::ErrorKind::TimedOut), description)
+}
+
+#[derive(Debug)]
+struct MockState {
+    expectations: Vec<(RequestMatcher, MockReply)>,
+    unmatched: MockReply,
+    writes: Vec<String>,
+    unsolicited: Vec<String>,
+    /// The lines left over from the last `MockReply::Lines`.
+    following: VecDeque<String>,
+    connected: bool,
+    timeout: Duration,
+}
+
+/// Registers the replies of a `MockTransport`.
+#[derive(Debug)]
+pub struct MockTransportBuilder {
+    state: MockState,
+}
+
+impl MockTransportBuilder {
+    /// Starts from a mock that times out on every command.
+    pub fn new() -> Self {
+        Self {
+            state: MockState {
+                expectations: Vec::new(),
+                unmatched: MockReply::Error(
+                    ErrorKind::Io(std::io::ErrorKind::TimedOut),
+                    "No reply is registered for the command.".to_string(),
+                ),
+                writes: Vec::new(),
+                unsolicited: Vec::new(),
+                following: VecDeque::new(),
+                connected: true,
+                timeout: Duration::from_secs(1),
+            },
+        }
+    }
+
+    /// Answers the commands matching `matcher` with `reply`.
+    ///
+    /// Expectations are checked in the order they were registered and the first match wins.
+    pub fn expect(mut self, matcher: RequestMatcher, reply: MockReply) -> Self {
+        self.state.expectations.push((matcher, reply));
+        self
+    }
+
+    /// Answers the command equal to `request` with the line `response`.
+    pub fn reply(self, request: impl Into<String>, response: impl Into<String>) -> Self {
+        self.expect(
+            RequestMatcher::Exact(request.into()),
+            MockReply::Line(response.into()),
+        )
+    }
+
+    /// Answers the command equal to `request` with several lines, the first as the reply and the
+    /// others to be read with `read_line`.
+    ///
+    /// ```
+    /// use minicircuit_commands::{dll::sweep::PerformSweepWatt, prelude::*};
+    /// use minicircuit_driver::{executable::Executable, mock::MockTransport};
+    ///
+    /// // A sweep is answered with a line for each of its measurements.
+    /// let sweep = PerformSweepWatt::new(
+    ///     Channel::default(),
+    ///     Frequency::new(2400),
+    ///     Frequency::new(2420),
+    ///     Frequency::new(10),
+    ///     Watt::new(100.0),
+    /// );
+    /// let mut transport = MockTransport::builder()
+    ///     .reply_lines(
+    ///         "$SWP,1,2400,2420,10,100.0,1",
+    ///         ["OK,1,2400,100.0,8.0", "OK,1,2410,100.0,2.0", "OK,1,2420,100.0,5.0"],
+    ///     )
+    ///     .build();
+    ///
+    /// let response = sweep.execute(&mut transport);
+    /// let points = response.as_perform_sweep_watt().unwrap().points().unwrap();
+    /// assert_eq!(points.len(), 3);
+    /// ```
+    pub fn reply_lines<I, S>(self, request: impl Into<String>, lines: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.expect(
+            RequestMatcher::Exact(request.into()),
+            MockReply::Lines(lines.into_iter().map(Into::into).collect()),
+        )
+    }
+
+    /// Answers every command starting with `prefix` with the line `response`.
+    pub fn reply_prefix(self, prefix: impl Into<String>, response: impl Into<String>) -> Self {
+        self.expect(
+            RequestMatcher::Prefix(prefix.into()),
+            MockReply::Line(response.into()),
+        )
+    }
+
+    /// Answers every command satisfying `predicate` with the line `response`.
+    pub fn reply_when<F>(self, predicate: F, response: impl Into<String>) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.expect(
+            RequestMatcher::Predicate(Arc::new(predicate)),
+            MockReply::Line(response.into()),
+        )
+    }
+
+    /// Fails the command equal to `request` with an error of the given kind.
+    pub fn fail(self, request: impl Into<String>, kind: ErrorKind) -> Self {
+        self.expect(
+            RequestMatcher::Exact(request.into()),
+            MockReply::Error(kind, "The mock was told to fail the command.".to_string()),
+        )
+    }
+
+    /// How commands without a matching expectation are answered, a timeout by default.
+    pub fn unmatched(mut self, reply: MockReply) -> Self {
+        self.state.unmatched = reply;
+        self
+    }
+
+    /// The timeout the mock reports, it never actually waits.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.state.timeout = timeout;
+        self
+    }
+
+    pub fn build(self) -> MockTransport {
+        MockTransport {
+            state: Arc::new(Mutex::new(self.state)),
+        }
+    }
+}
+
+impl Default for MockTransportBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Answers commands from a table of canned replies instead of a signal generator,
+/// recording every command written to it.
+///
+/// Clones share the same table and record, so a clone can be kept for assertions
+/// after the original has been handed to the code under test.
+///
+/// ```
+/// use minicircuit_commands::prelude::*;
+/// use minicircuit_driver::{executable::Executable, mock::MockTransport};
+///
+/// let mut transport = MockTransport::builder()
+///     .reply("$FCG,1", "OK,1,2450")
+///     .build();
+/// let handle = transport.clone();
+///
+/// let response = GetFrequency::default().execute(&mut transport);
+///
+/// assert_eq!(response.as_frequency().unwrap().frequency, Frequency::new(2450));
+/// assert_eq!(handle.writes(), vec!["$FCG,1".to_string()]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransport {
+    pub fn builder() -> MockTransportBuilder {
+        MockTransportBuilder::new()
+    }
+
+    /// Every command written so far, oldest first, without line terminators.
+    pub fn writes(&self) -> Vec<String> {
+        self.state().writes.clone()
+    }
+
+    /// Forgets the commands written so far.
+    pub fn clear_writes(&self) {
+        self.state().writes.clear();
+    }
+
+    /// Queues a line as if the device sent it without being asked, see `read_unsolicited`.
+    pub fn push_unsolicited(&self, line: impl Into<String>) {
+        self.state().unsolicited.push(line.into());
+    }
+
+    /// Simulates the device disappearing, or coming back.
+    ///
+    /// While disconnected every command fails as if the port was gone.
+    pub fn set_connected(&self, connected: bool) {
+        self.state().connected = connected;
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        // A panic while holding the lock leaves the state as consistent as it was.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Transport for MockTransport {
+    fn write_read(&mut self, tx: String) -> Result<String, Error> {
+        let mut state = self.state();
+
+        if !state.connected {
+            return Err(Error::new(
+                ErrorKind::NoDevice,
+                "The mock device is disconnected.",
+            ));
+        }
+
+        let (reply, following) = state
+            .expectations
+            .iter()
+            .find(|(matcher, _)| matcher.matches(&tx))
+            .map(|(_, reply)| reply.to_result())
+            .unwrap_or_else(|| state.unmatched.to_result());
+
+        state.writes.push(tx);
+        state.following = following;
+
+        reply
+    }
+
+    // The device doesn't reply, so the command is only recorded.
+    fn write(&mut self, tx: String) -> Result<(), Error> {
+        let mut state = self.state();
+
+        if !state.connected {
+            return Err(Error::new(
+                ErrorKind::NoDevice,
+                "The mock device is disconnected.",
+            ));
+        }
+
+        state.writes.push(tx);
+        Ok(())
+    }
+
+    fn is_connected(&mut self) -> bool {
+        self.state().connected
+    }
+
+    fn timeout(&self) -> Duration {
+        self.state().timeout
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<(), Error> {
+        self.state().timeout = timeout;
+        Ok(())
+    }
+
+    fn read_unsolicited(&mut self) -> Result<Vec<String>, Error> {
+        Ok(std::mem::take(&mut self.state().unsolicited))
+    }
+
+    fn read_line(&mut self) -> Result<String, Error> {
+        self.state()
+            .following
+            .pop_front()
+            .ok_or_else(|| timed_out("The reply has no more lines."))
+    }
+}
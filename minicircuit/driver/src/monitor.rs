@@ -0,0 +1,146 @@
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+
+use minicircuit_commands::response::CorrelatedResponse;
+
+use super::telemetry::TelemetryMetric;
+
+/// A host-side limit on a measured metric, see `MiniCircuitDriver::monitor_soa`.
+///
+/// The threshold trips once the value rises above `limit` and only clears again once it falls
+/// below `limit - hysteresis`, so a value hovering around the limit doesn't flap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoaThreshold {
+    pub metric: TelemetryMetric,
+    /// The value above which the threshold trips.
+    pub limit: f64,
+    /// How far below the limit the value must fall before the threshold clears.
+    pub hysteresis: f64,
+}
+
+impl SoaThreshold {
+    pub fn new(metric: TelemetryMetric, limit: f64, hysteresis: f64) -> Self {
+        Self {
+            metric,
+            limit,
+            hysteresis: hysteresis.max(0.),
+        }
+    }
+}
+
+/// A change in the state of a monitored threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SoaEvent {
+    /// The value rose above the limit of the threshold.
+    Tripped { threshold: SoaThreshold, value: f64 },
+    /// The value fell below the limit minus the hysteresis of a tripped threshold.
+    Cleared { threshold: SoaThreshold, value: f64 },
+}
+
+/// Tracks which thresholds are tripped, reporting only the samples that change their state.
+///
+/// ```
+/// use minicircuit_driver::{
+///     monitor::{SoaEvent, SoaMonitor, SoaThreshold},
+///     telemetry::TelemetryMetric,
+/// };
+///
+/// let threshold = SoaThreshold::new(TelemetryMetric::PATemperature, 60., 5.);
+/// let mut monitor = SoaMonitor::new(vec![threshold]);
+///
+/// let events: Vec<SoaEvent> = [59., 61., 59., 61., 54.]
+///     .into_iter()
+///     .flat_map(|value| monitor.update(TelemetryMetric::PATemperature, value))
+///     .collect();
+///
+/// // Hovering around the limit trips once, and clears only once the value drops below 55.
+/// assert_eq!(
+///     events,
+///     vec![
+///         SoaEvent::Tripped { threshold, value: 61. },
+///         SoaEvent::Cleared { threshold, value: 54. },
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct SoaMonitor {
+    /// Every threshold along with whether it is tripped.
+    thresholds: Vec<(SoaThreshold, bool)>,
+}
+
+impl SoaMonitor {
+    pub fn new(thresholds: Vec<SoaThreshold>) -> Self {
+        Self {
+            thresholds: thresholds
+                .into_iter()
+                .map(|threshold| (threshold, false))
+                .collect(),
+        }
+    }
+
+    /// Compares a sample with the thresholds on its metric, returning the changes of state.
+    pub fn update(&mut self, metric: TelemetryMetric, value: f64) -> Vec<SoaEvent> {
+        let mut events = Vec::new();
+
+        for (threshold, tripped) in &mut self.thresholds {
+            if threshold.metric != metric {
+                continue;
+            }
+
+            if !*tripped && value > threshold.limit {
+                *tripped = true;
+                events.push(SoaEvent::Tripped {
+                    threshold: *threshold,
+                    value,
+                });
+            } else if *tripped && value < threshold.limit - threshold.hysteresis {
+                *tripped = false;
+                events.push(SoaEvent::Cleared {
+                    threshold: *threshold,
+                    value,
+                });
+            }
+        }
+
+        events
+    }
+
+    /// Whether any threshold on the metric is tripped.
+    pub fn is_tripped(&self, metric: TelemetryMetric) -> bool {
+        self.thresholds
+            .iter()
+            .any(|(threshold, tripped)| threshold.metric == metric && *tripped)
+    }
+}
+
+/// Spawns the task that feeds responses through a `SoaMonitor` and returns its events as a stream.
+///
+/// The task exits once the stream is dropped or the response channel closes.
+pub(crate) fn spawn_monitor_loop(
+    mut response_rx: broadcast::Receiver<CorrelatedResponse>,
+    mut monitor: SoaMonitor,
+) -> impl Stream<Item = SoaEvent> {
+    let (event_tx, event_rx) = mpsc::channel::<SoaEvent>(16);
+
+    tokio::spawn(async move {
+        loop {
+            let correlated = match response_rx.recv().await {
+                Ok(correlated) => correlated,
+                // The next sample of the metric brings the state up to date.
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            for (metric, value) in TelemetryMetric::extract(&correlated.response) {
+                for event in monitor.update(metric, value) {
+                    // The stream was dropped by the caller.
+                    if event_tx.send(event).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    });
+
+    ReceiverStream::new(event_rx)
+}
@@ -0,0 +1,49 @@
+//! Prelude module for minicircuit_driver
+//!
+//! This module re-exports the driver along with the command and response types
+//! needed to talk to a signal generator, allowing users to import them all at once:
+//!
+//! ```
+//! use minicircuit_driver::prelude::*;
+//! ```
+//!
+//! Individual command structs are left to `minicircuit_commands::prelude`.
+
+// Driver types
+#[cfg(feature = "async")]
+pub use crate::blocking::BlockingMiniCircuitDriver;
+pub use crate::config::{ApplyReport, ConfigDiff, DeviceConfig, PowerLimits, SoaLimits};
+#[cfg(feature = "async")]
+pub use crate::driver::MiniCircuitDriver;
+pub use crate::error::{
+    ClockSourceError, ConnectError, PowerError, ReplayError, ResetError, ScriptError, SendError,
+    WaitError,
+};
+#[cfg(feature = "async")]
+pub use crate::guard::RfOutputGuard;
+#[cfg(feature = "async")]
+pub use crate::monitor::{SoaEvent, SoaThreshold};
+pub use crate::safety::{IdleViolation, NotIdle, SafetyError};
+#[cfg(feature = "async")]
+pub use crate::sender::{CommandSender, SequenceFailure};
+#[cfg(feature = "sync")]
+pub use crate::sync::SyncMiniCircuitDriver;
+#[cfg(feature = "async")]
+pub use crate::telemetry::TelemetrySnapshot;
+pub use crate::verify::{VerifiableSetter, VerifyError};
+#[cfg(feature = "async")]
+pub use tokio_util::sync::CancellationToken;
+
+// Connection helpers
+pub use crate::connection::{
+    autodetect_sg_port, autodetect_sg_port_within, list_minicircuit_devices,
+    list_minicircuit_devices_with, print_available_ports, print_minicircuit_devices,
+    ConnectionInfo, DeviceInfo, DeviceMatcher, DeviceMismatch, PortCandidate,
+};
+
+// Command, response and property types
+pub use minicircuit_commands::capabilities::{Capabilities, CommandGroup};
+pub use minicircuit_commands::command::{Command, Message, Priority};
+pub use minicircuit_commands::data_types::types::{Channel, Dbm, Frequency, Phase, Watt};
+pub use minicircuit_commands::properties::TargetProperties;
+pub use minicircuit_commands::response::{CorrelatedResponse, Response};
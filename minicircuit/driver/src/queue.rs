@@ -0,0 +1,116 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use minicircuit_commands::command::Priority;
+
+/// A message that waits in a `PriorityQueue`.
+pub(crate) trait Prioritized {
+    fn priority(&self) -> &Priority;
+}
+
+/// The messages waiting to be sent, kept in a FIFO per priority.
+///
+/// Messages of a higher priority are taken first, and messages of equal priority in the order
+/// they were pushed. Pushing and taking don't depend on how many messages are waiting, unlike
+/// sorting the whole queue whenever something arrives.
+///
+/// With aging, a message rises a level for every `aging` it has waited, up to `High`, and the
+/// oldest message is taken of those on the same level. `Immediate` and `Termination` are never
+/// overtaken, see `TargetProperties::priority_aging`.
+#[derive(Debug)]
+pub(crate) struct PriorityQueue<T> {
+    /// One FIFO per priority, from `Low` to `Termination`, along with when each was pushed.
+    levels: [VecDeque<(Instant, T)>; 5],
+    len: usize,
+    aging: Option<Duration>,
+}
+
+impl<T: Prioritized> PriorityQueue<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            levels: Default::default(),
+            len: 0,
+            aging: None,
+        }
+    }
+
+    /// Raises the priority of waiting messages a level for every `aging` they have waited.
+    pub(crate) fn with_aging(mut self, aging: Option<Duration>) -> Self {
+        self.aging = aging.filter(|aging| !aging.is_zero());
+        self
+    }
+
+    pub(crate) fn push(&mut self, item: T) {
+        self.levels[level(item.priority())].push_back((Instant::now(), item));
+        self.len += 1;
+    }
+
+    /// Takes the oldest message of the highest priority waiting, after aging.
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        let index = match self.aging {
+            None => self.levels.iter().rposition(|level| !level.is_empty())?,
+            Some(aging) => self.aged_level(aging)?,
+        };
+
+        let (_, item) = self.levels[index].pop_front()?;
+        self.len -= 1;
+
+        Some(item)
+    }
+
+    /// Only the async driver reports the length of its queue.
+    #[cfg(feature = "async")]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The level whose oldest message goes next.
+    ///
+    /// The front of each FIFO has waited longest, so only the fronts need comparing.
+    fn aged_level(&self, aging: Duration) -> Option<usize> {
+        let now = Instant::now();
+
+        self.levels
+            .iter()
+            .enumerate()
+            .filter_map(|(index, level)| {
+                let (pushed, _) = level.front()?;
+                let waited = now.saturating_duration_since(*pushed);
+                Some((aged(index, waited, aging), *pushed, index))
+            })
+            // The highest level, and of those the one pushed first.
+            .max_by(|(a_level, a_pushed, _), (b_level, b_pushed, _)| {
+                a_level.cmp(b_level).then(b_pushed.cmp(a_pushed))
+            })
+            .map(|(_, _, index)| index)
+    }
+}
+
+impl<T: Prioritized> Default for PriorityQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn level(priority: &Priority) -> usize {
+    match priority {
+        Priority::Low => 0,
+        Priority::Standard => 1,
+        Priority::High => 2,
+        Priority::Immediate => 3,
+        Priority::Termination => 4,
+    }
+}
+
+/// The level a message pushed at `index` has risen to after waiting, which stops at `High`.
+fn aged(index: usize, waited: Duration, aging: Duration) -> usize {
+    let high = level(&Priority::High);
+    if index >= high {
+        return index;
+    }
+
+    let steps = (waited.as_nanos() / aging.as_nanos()).min(high as u128) as usize;
+    (index + steps).min(high)
+}
@@ -0,0 +1,189 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    select,
+    sync::{broadcast, oneshot},
+    task::JoinHandle,
+};
+
+use minicircuit_commands::{command::Message, response::Response};
+
+use super::{driver::MiniCircuitDriver, error::ReplayError};
+
+/// A message as the queue loop took it up to send, see `MiniCircuitDriver::subscribe_sent`.
+///
+/// The command is addressed to its channel already, so the message carries no channel.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SentMessage {
+    pub timestamp: SystemTime,
+    pub message: Message,
+}
+
+impl SentMessage {
+    pub fn new(message: Message) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            message,
+        }
+    }
+}
+
+/// Writes every message the driver sends to a file, one JSON object per line, for `replay()`
+/// to send again later.
+///
+/// Recording continues until `stop()` is called or the driver is dropped. Pairs with the
+/// `TelemetryLogger`, which records what came back.
+///
+/// ```
+/// use minicircuit_commands::prelude::*;
+/// use minicircuit_driver::{
+///     driver::MiniCircuitDriver,
+///     mock::MockTransport,
+///     recorder::{replay, CommandRecorder, ReplayTiming},
+/// };
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() {
+///     let path = std::env::temp_dir().join("minicircuit_recorder_doctest.jsonl");
+///
+///     let transport = MockTransport::builder().reply("$FCS,1,2450", "OK").build();
+///     let (driver, _, _) =
+///         MiniCircuitDriver::from_transport(transport, TargetProperties::default()).unwrap();
+///
+///     let recorder = CommandRecorder::spawn(&path, driver.subscribe_sent()).unwrap();
+///     let command = SetFrequency::new(Channel::default(), Frequency::new(2450));
+///     driver.send_await(command, Priority::Standard).await.unwrap();
+///     recorder.stop().await.unwrap();
+///
+///     // Send the session again to another device.
+///     let transport = MockTransport::builder().reply("$FCS,1,2450", "OK").build();
+///     let handle = transport.clone();
+///     let (driver, _, _) =
+///         MiniCircuitDriver::from_transport(transport, TargetProperties::default()).unwrap();
+///     handle.clear_writes();
+///
+///     replay(&path, &driver, ReplayTiming::Recorded).await.unwrap();
+///     assert_eq!(handle.writes(), vec!["$FCS,1,2450"]);
+/// #   std::fs::remove_file(&path).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CommandRecorder {
+    handle: JoinHandle<io::Result<()>>,
+    stop_tx: oneshot::Sender<()>,
+}
+
+impl CommandRecorder {
+    /// Creates the file, replacing any previous recording, and starts writing the messages
+    /// received on `sent_rx` to it.
+    pub fn spawn(
+        path: impl AsRef<Path>,
+        sent_rx: broadcast::Receiver<SentMessage>,
+    ) -> io::Result<Self> {
+        let writer = BufWriter::new(File::create(path)?);
+        let (stop_tx, stop_rx) = oneshot::channel();
+
+        let handle = tokio::spawn(record(writer, sent_rx, stop_rx));
+
+        Ok(Self { handle, stop_tx })
+    }
+
+    /// Stops recording, flushes the file and returns the first error that stopped the recorder, if any.
+    pub async fn stop(self) -> io::Result<()> {
+        let _ = self.stop_tx.send(());
+
+        match self.handle.await {
+            Ok(result) => result,
+            Err(e) => Err(io::Error::other(e)),
+        }
+    }
+}
+
+async fn record(
+    mut writer: BufWriter<File>,
+    mut sent_rx: broadcast::Receiver<SentMessage>,
+    mut stop_rx: oneshot::Receiver<()>,
+) -> io::Result<()> {
+    loop {
+        select! {
+            _ = &mut stop_rx => break,
+            received = sent_rx.recv() => match received {
+                Ok(sent) => {
+                    serde_json::to_writer(&mut writer, &sent)?;
+                    writeln!(writer)?;
+                    // A session is recorded to reproduce a problem, which may well end in a crash.
+                    writer.flush()?;
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        skipped,
+                        "Command recorder fell behind, messages were not recorded."
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+
+    writer.flush()
+}
+
+/// How `replay()` spaces the messages it sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayTiming {
+    /// Sends every message as long after the first as it was sent in the recording.
+    Recorded,
+    /// Sends every message as soon as the previous one was answered.
+    Immediate,
+}
+
+/// Reads the messages written by a `CommandRecorder`.
+pub fn read_recording(path: impl AsRef<Path>) -> io::Result<Vec<SentMessage>> {
+    let reader = BufReader::new(File::open(path)?);
+
+    let mut recording = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        recording.push(serde_json::from_str(&line).map_err(io::Error::other)?);
+    }
+
+    Ok(recording)
+}
+
+/// Sends the messages of a recording to the driver with their priority, tag and timeout,
+/// returning the responses in the order of the messages.
+///
+/// Every message is awaited before the next is sent, so the order matches the recording.
+/// A failing command doesn't stop the replay, but a message that can't be sent does.
+pub async fn replay(
+    path: impl AsRef<Path>,
+    driver: &MiniCircuitDriver,
+    timing: ReplayTiming,
+) -> Result<Vec<Response>, ReplayError> {
+    let recording = read_recording(path)?;
+
+    let started = tokio::time::Instant::now();
+    let first = recording.first().map(|sent| sent.timestamp);
+
+    let mut responses = Vec::with_capacity(recording.len());
+    for sent in recording {
+        if let (ReplayTiming::Recorded, Some(first)) = (timing, first) {
+            // A clock that went backwards while recording sends the message straight away.
+            let offset = sent.timestamp.duration_since(first).unwrap_or_default();
+            tokio::time::sleep_until(started + offset).await;
+        }
+
+        responses.push(driver.send_message_await(sent.message).await?);
+    }
+
+    Ok(responses)
+}
@@ -0,0 +1,144 @@
+use serialport::Error;
+
+use minicircuit_commands::{
+    command::{Command, GetChannelID, GetIdentity, Priority},
+    data_types::types::Channel,
+    properties::TargetProperties,
+    response::Response,
+};
+
+use super::{
+    connection::autodetect_sg_port_within,
+    driver::MiniCircuitDriver,
+    error::{ConnectError, SendError},
+};
+
+/// A signal generator connected through a `DeviceRegistry`.
+#[derive(Debug)]
+pub struct RegisteredDevice {
+    pub port_name: String,
+    /// The channel id reported by the device, if it answered the query.
+    pub channel_id: Option<u8>,
+    /// The serial number reported by the device, if it answered the query.
+    pub serial_number: Option<String>,
+    /// The driver for this device, with its own queue loop and response channel.
+    pub driver: MiniCircuitDriver,
+}
+
+/// Connects to every signal generator matching the target properties at once.
+///
+/// Devices can be looked up by the channel id or the serial number they report.
+#[derive(Debug, Default)]
+pub struct DeviceRegistry {
+    devices: Vec<RegisteredDevice>,
+    /// Ports that matched the target properties but could not be opened.
+    failures: Vec<(String, ConnectError)>,
+}
+
+impl DeviceRegistry {
+    /// Connects to every port matching the vendor and product id of the target properties,
+    /// then asks each device for its channel id and identity.
+    ///
+    /// A port that fails to open doesn't stop the others from connecting, see `failures()`.
+    pub async fn connect_all(properties: TargetProperties) -> Result<Self, Error> {
+        let signal_generators = autodetect_sg_port_within(
+            properties.vendor_id.clone(),
+            properties.product_id.clone(),
+            properties.connect_timeout,
+        )?;
+
+        let mut registry = Self::default();
+
+        for signal_generator in signal_generators {
+            let port_name = signal_generator.port_name;
+
+            let mut device_properties = properties.clone();
+            device_properties.port = Some(port_name.clone());
+
+            let mut driver = MiniCircuitDriver::new(device_properties);
+            if let Err(e) = driver.port_connect() {
+                registry.failures.push((port_name, e));
+                continue;
+            }
+
+            let channel_id = match driver
+                .send_await(GetChannelID::default(), Priority::High)
+                .await
+            {
+                Ok(Response::GetChannelIDResponse(response)) => Some(response.channel.channel_id),
+                _ => None,
+            };
+            let serial_number = match driver
+                .send_await(GetIdentity::default(), Priority::High)
+                .await
+            {
+                Ok(Response::GetIdentityResponse(response)) => Some(response.serial_number),
+                _ => None,
+            };
+
+            registry.devices.push(RegisteredDevice {
+                port_name,
+                channel_id,
+                serial_number,
+                driver,
+            });
+        }
+
+        Ok(registry)
+    }
+
+    pub fn devices(&self) -> &[RegisteredDevice] {
+        &self.devices
+    }
+
+    /// The ports that matched but could not be opened, along with the reason.
+    pub fn failures(&self) -> &[(String, ConnectError)] {
+        &self.failures
+    }
+
+    /// The driver of the device reporting the given channel id.
+    pub fn device(&self, channel: Channel) -> Option<&MiniCircuitDriver> {
+        self.devices
+            .iter()
+            .find(|device| device.channel_id == Some(channel.channel_id))
+            .map(|device| &device.driver)
+    }
+
+    /// The driver of the device reporting the given serial number.
+    pub fn device_by_serial_number(&self, serial_number: &str) -> Option<&MiniCircuitDriver> {
+        self.devices
+            .iter()
+            .find(|device| device.serial_number.as_deref() == Some(serial_number))
+            .map(|device| &device.driver)
+    }
+
+    /// Sends the command to every device and waits for each response.
+    ///
+    /// The responses are returned in the same order as `devices()`.
+    pub async fn broadcast(
+        &self,
+        command: impl Into<Command>,
+        priority: Priority,
+    ) -> Vec<Result<Response, SendError>> {
+        let command = command.into();
+        let mut responses = Vec::with_capacity(self.devices.len());
+
+        for device in &self.devices {
+            responses.push(
+                device
+                    .driver
+                    .send_await(command.clone(), priority.clone())
+                    .await,
+            );
+        }
+
+        responses
+    }
+
+    /// Disconnects every device.
+    pub async fn disconnect_all(&mut self) {
+        for device in &mut self.devices {
+            device.driver.disconnect().await;
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::{error::Error, fmt};
+
+use minicircuit_commands::{
+    data_types::{
+        errors::ValidationError,
+        types::{Frequency, Watt},
+    },
+    error::{pa::AlarmCause, status::Status},
+    properties::DeviceLimits,
+    response::Response,
+};
+
+/// Describes the step of a guarded RF enable that failed.
+///
+/// `NotConnected`, `FrequencyOutOfRange`, `PowerOutOfRange` and `InvalidPulse` are returned
+/// before anything is sent, leaving the output as it was. Whenever one of the others is
+/// returned the driver has already attempted to switch RF output off.
+#[derive(Debug, Clone)]
+pub enum SafetyError {
+    /// The driver has not been connected to a signal generator.
+    NotConnected,
+    /// The requested frequency lies outside of the configured device limits.
+    FrequencyOutOfRange {
+        requested: Frequency,
+        limits: DeviceLimits,
+    },
+    /// The requested power lies outside of the configured device limits.
+    PowerOutOfRange {
+        requested: Watt,
+        limits: DeviceLimits,
+    },
+    /// The signal generator rejected or failed to acknowledge the frequency.
+    SetFrequency(Response),
+    /// The signal generator rejected or failed to acknowledge the power setpoint.
+    SetPower(Response),
+    /// The status of the ISC board could not be read.
+    StatusQuery(Response),
+    /// The ISC board reported one or more active faults.
+    Precondition(Vec<Status>),
+    /// The signal generator rejected or failed to acknowledge the RF enable.
+    EnableRf(Response),
+    /// RF output was enabled but the read back did not confirm it.
+    Readback(Response),
+    /// The timing of an RF pulse can't be expressed as timed enables.
+    InvalidPulse(ValidationError),
+    /// The signal generator rejected or failed to acknowledge a timed enable.
+    TimedEnable(Response),
+}
+
+impl Error for SafetyError {}
+
+impl fmt::Display for SafetyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConnected => write!(f, "The driver is not connected to a signal generator."),
+            Self::FrequencyOutOfRange { requested, limits } => write!(
+                f,
+                "Frequency of {}MHz is outside of the allowed range ({}MHz - {}MHz).",
+                requested.frequency, limits.min_frequency.frequency, limits.max_frequency.frequency
+            ),
+            Self::PowerOutOfRange { requested, limits } => write!(
+                f,
+                "Power of {}W is outside of the allowed range (0W - {}W).",
+                requested.power, limits.max_power.power
+            ),
+            Self::SetFrequency(response) => {
+                write!(f, "Failed to set the frequency: {}", describe(response))
+            }
+            Self::SetPower(response) => {
+                write!(
+                    f,
+                    "Failed to set the power setpoint: {}",
+                    describe(response)
+                )
+            }
+            Self::StatusQuery(response) => {
+                write!(f, "Failed to read the board status: {}", describe(response))
+            }
+            Self::Precondition(statuses) => {
+                let statuses: Vec<&str> = statuses
+                    .iter()
+                    .map(|status| status.status.as_str())
+                    .collect();
+                write!(
+                    f,
+                    "The board reported active faults: {}.",
+                    statuses.join(", ")
+                )
+            }
+            Self::EnableRf(response) => {
+                write!(f, "Failed to enable RF output: {}", describe(response))
+            }
+            Self::Readback(response) => write!(
+                f,
+                "RF output could not be confirmed as enabled: {}",
+                describe(response)
+            ),
+            Self::InvalidPulse(error) => write!(f, "Invalid RF pulse: {}", error),
+            Self::TimedEnable(response) => {
+                write!(f, "Failed to start a timed enable: {}", describe(response))
+            }
+        }
+    }
+}
+
+/// A reading that doesn't match the idle state, as found by `MiniCircuitDriver::assert_idle`.
+#[derive(Debug, Clone)]
+pub enum IdleViolation {
+    /// RF output is enabled.
+    RfEnabled,
+    /// The power setpoint is above zero.
+    PowerSetpoint(Watt),
+    /// The ISC board reported one or more active faults.
+    ActiveFaults(Vec<Status>),
+    /// The PA raised one or more alarms.
+    PaAlarms(Vec<AlarmCause>),
+    /// A reading failed, so that part of the idle state could not be confirmed.
+    Unreadable(Response),
+}
+
+impl fmt::Display for IdleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RfEnabled => write!(f, "RF output is enabled"),
+            Self::PowerSetpoint(power) => write!(f, "the power setpoint is {}W", power.power),
+            Self::ActiveFaults(statuses) => {
+                let statuses: Vec<&str> = statuses
+                    .iter()
+                    .map(|status| status.status.as_str())
+                    .collect();
+                write!(
+                    f,
+                    "the board reported active faults: {}",
+                    statuses.join(", ")
+                )
+            }
+            Self::PaAlarms(alarms) => write!(f, "the PA raised alarms: {:?}", alarms),
+            Self::Unreadable(response) => {
+                write!(f, "a reading failed: {}", describe(response))
+            }
+        }
+    }
+}
+
+/// Why the signal generator isn't in a known-safe idle state, see `MiniCircuitDriver::assert_idle`.
+#[derive(Debug, Clone)]
+pub enum NotIdle {
+    /// The driver has not been connected to a signal generator.
+    NotConnected,
+    /// Every reading that doesn't match the idle state.
+    Violations(Vec<IdleViolation>),
+}
+
+impl Error for NotIdle {}
+
+impl fmt::Display for NotIdle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConnected => write!(f, "The driver is not connected to a signal generator."),
+            Self::Violations(violations) => {
+                let violations: Vec<String> = violations.iter().map(ToString::to_string).collect();
+                write!(
+                    f,
+                    "The signal generator is not idle: {}.",
+                    violations.join("; ")
+                )
+            }
+        }
+    }
+}
+
+fn describe(response: &Response) -> String {
+    response.clone().into()
+}
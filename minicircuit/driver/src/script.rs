@@ -0,0 +1,121 @@
+//! Command sequences read from a JSON or TOML file, to script a device without writing Rust.
+//!
+//! A script is a list of `Message`s, sent in the order they are listed. Each message has a
+//! `priority` and a `command`, the command written as an object holding the fields of the
+//! command under its name. `timeout`, `tag` and `channel` may be left out.
+//!
+//! In JSON the script is a plain list:
+//!
+//! ```json
+//! [
+//!     {
+//!         "priority": "Standard",
+//!         "command": { "SetFrequency": { "channel": { "channel_id": 1 }, "frequency": { "frequency": 2450 } } }
+//!     },
+//!     {
+//!         "priority": "Standard",
+//!         "command": { "GetPATemp": { "channel": { "channel_id": 1 } } },
+//!         "tag": "temperature"
+//!     }
+//! ]
+//! ```
+//!
+//! TOML can't hold a bare list, so the messages go in a `messages` array of tables:
+//!
+//! ```toml
+//! [[messages]]
+//! priority = "Standard"
+//! command = { SetFrequency = { channel = { channel_id = 1 }, frequency = { frequency = 2450 } } }
+//!
+//! [[messages]]
+//! priority = "Standard"
+//! command = { GetPATemp = { channel = { channel_id = 1 } } }
+//! tag = "temperature"
+//! ```
+//!
+//! A `timeout` is written as `{ "secs": 5, "nanos": 0 }`. The example crate ships a script in
+//! both formats along with a binary to run it.
+
+use std::{fs, path::Path};
+
+use serde::Deserialize;
+
+use minicircuit_commands::{command::Message, response::Response};
+
+use super::{driver::MiniCircuitDriver, error::ScriptError};
+
+/// The file format of a script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptFormat {
+    Json,
+    Toml,
+}
+
+impl ScriptFormat {
+    /// Picks the format from the file extension, JSON unless the extension is `.toml`.
+    pub fn of(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// The layout of a TOML script.
+#[derive(Deserialize)]
+struct TomlScript {
+    messages: Vec<Message>,
+}
+
+/// Reads the messages of a script in the given format.
+///
+/// ```
+/// use minicircuit_commands::prelude::*;
+/// use minicircuit_driver::script::{parse_script, ScriptFormat};
+///
+/// let script = r#"
+///     [[messages]]
+///     priority = "High"
+///     command = { SetRFOutput = { channel = { channel_id = 1 }, enabled = false } }
+/// "#;
+///
+/// let messages = parse_script(script, ScriptFormat::Toml).unwrap();
+/// assert_eq!(messages[0].priority, Priority::High);
+/// assert_eq!(String::from(&messages[0].command), "$ECS,1,0");
+/// ```
+pub fn parse_script(script: &str, format: ScriptFormat) -> Result<Vec<Message>, ScriptError> {
+    match format {
+        ScriptFormat::Json => Ok(serde_json::from_str(script)?),
+        ScriptFormat::Toml => Ok(toml::from_str::<TomlScript>(script)?.messages),
+    }
+}
+
+/// Reads the messages of a script file, in the format of its extension.
+pub fn load_script(path: impl AsRef<Path>) -> Result<Vec<Message>, ScriptError> {
+    let script = fs::read_to_string(&path)?;
+    parse_script(&script, ScriptFormat::of(path))
+}
+
+/// Sends the messages of a script file in order, printing each response as it arrives.
+///
+/// Every message is awaited before the next is sent. A failing command doesn't stop the
+/// script, but a message that can't be sent does. The responses are returned in the order
+/// of the messages.
+pub async fn run_script(
+    path: impl AsRef<Path>,
+    driver: &MiniCircuitDriver,
+) -> Result<Vec<Response>, ScriptError> {
+    let messages = load_script(path)?;
+
+    let mut responses = Vec::with_capacity(messages.len());
+    for message in messages {
+        let command = message.command.to_string();
+        let response = driver.send_message_await(message).await?;
+
+        let description: String = response.clone().into();
+        println!("{}: {}", command, description);
+        responses.push(response);
+    }
+
+    Ok(responses)
+}
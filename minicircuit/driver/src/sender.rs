@@ -0,0 +1,333 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::Duration,
+};
+
+use tokio::{
+    select,
+    sync::{
+        mpsc::{self, error::TrySendError},
+        oneshot, OwnedSemaphorePermit, Semaphore, TryAcquireError,
+    },
+};
+
+use minicircuit_commands::{
+    command::{Command, Message, Priority},
+    response::Response,
+};
+
+use super::{error::SendError, queue::Prioritized};
+
+/// Decides what happens to the rest of a sequence when one of its commands fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceFailure {
+    /// Send the remaining commands regardless.
+    Continue,
+    /// Skip the remaining commands. The responses end with the one that failed.
+    Abort,
+}
+
+/// The work a queued entry asks the queue loop to carry out.
+pub(crate) enum QueuedWork {
+    /// A single command and the timeout it overrides, its response is broadcast to subscribers.
+    Command(Command, Option<Duration>),
+    /// Commands that are sent back to back without any other message in between.
+    ///
+    /// Every response is broadcast to subscribers and the collected responses are
+    /// returned through `reply_tx` once the sequence is done.
+    Sequence {
+        commands: Vec<Command>,
+        on_failure: SequenceFailure,
+        reply_tx: oneshot::Sender<Vec<Response>>,
+    },
+}
+
+/// An entry waiting in the queue along with its id.
+pub(crate) struct QueuedMessage {
+    pub(crate) id: u64,
+    pub(crate) priority: Priority,
+    /// The tag of the message, returned on every response it produces.
+    pub(crate) tag: Option<String>,
+    pub(crate) work: QueuedWork,
+    /// Counts the entry against the maximum depth until it is dropped, once it has been carried
+    /// out or withdrawn, rather than only while it waits in the channel.
+    _slot: OwnedSemaphorePermit,
+}
+
+impl Prioritized for QueuedMessage {
+    fn priority(&self) -> &Priority {
+        &self.priority
+    }
+}
+
+/// Handle used to place messages in the driver's command queue.
+///
+/// Every message is assigned a unique, monotonically increasing id when it is enqueued.
+/// The same id is attached to the response broadcast once the command has been executed.
+///
+/// Messages of a higher priority are sent first, and messages of the same priority in the
+/// order they were enqueued:
+///
+/// ```
+/// use minicircuit_commands::prelude::*;
+/// use minicircuit_driver::{driver::MiniCircuitDriver, mock::MockTransport};
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() {
+///     let transport = MockTransport::builder()
+///         .reply("$FCS,1,2400", "OK")
+///         .reply("$FCS,1,2450", "OK")
+///         .reply("$ECS,1,0", "OK")
+///         .build();
+///     let handle = transport.clone();
+///
+///     let (driver, queue_tx, _response_tx) =
+///         MiniCircuitDriver::from_transport(transport, TargetProperties::default()).unwrap();
+///     handle.clear_writes();
+///
+///     // Nothing is sent until the queue loop gets to run, so all three wait together.
+///     for frequency in [2400, 2450] {
+///         let command = SetFrequency::new(Channel::default(), Frequency::new(frequency));
+///         queue_tx.send(Message::new(Priority::Standard, command)).unwrap();
+///     }
+///     let disable = SetRFOutput::new(Channel::default(), false);
+///     queue_tx.send(Message::new(Priority::High, disable)).unwrap();
+///
+///     driver.flush().await.unwrap();
+///     assert_eq!(handle.writes(), vec!["$ECS,1,0", "$FCS,1,2400", "$FCS,1,2450"]);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommandSender {
+    queue_tx: mpsc::Sender<QueuedMessage>,
+    /// A permit for every entry the queue may hold, see `queue_channel`.
+    slots: Arc<Semaphore>,
+    next_id: Arc<AtomicU64>,
+    pending: PendingIds,
+}
+
+impl CommandSender {
+    pub(crate) fn new(
+        queue_tx: mpsc::Sender<QueuedMessage>,
+        slots: Arc<Semaphore>,
+        next_id: Arc<AtomicU64>,
+        pending: PendingIds,
+    ) -> Self {
+        Self {
+            queue_tx,
+            slots,
+            next_id,
+            pending,
+        }
+    }
+
+    /// Withdraws the message with the given id if the queue loop hasn't taken it up yet.
+    ///
+    /// Returns whether it was withdrawn. A message that was already sent still has its response
+    /// broadcast under its own id, so the responses to other messages aren't affected either way.
+    pub fn cancel(&self, id: u64) -> bool {
+        self.pending.withdraw(id)
+    }
+
+    /// Adds the message to the queue and returns the id its response will carry.
+    ///
+    /// Never waits. Fails with `SendError::QueueFull` if the queue has reached its maximum depth,
+    /// so the caller can slow down, or with `SendError::QueueClosed` if the queue loop has stopped.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use minicircuit_commands::prelude::*;
+    /// use minicircuit_driver::{driver::MiniCircuitDriver, error::SendError, mock::MockTransport};
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() {
+    ///     let transport = MockTransport::builder().reply_prefix("$FCS", "OK").build();
+    ///     let properties = TargetProperties::builder()
+    ///         .max_queue_depth(2)
+    ///         .settle_after("SetFrequency", Duration::from_millis(200))
+    ///         .unwrap()
+    ///         .build();
+    ///     let (_driver, queue_tx, _response_tx) =
+    ///         MiniCircuitDriver::from_transport(transport, properties).unwrap();
+    ///
+    ///     for _ in 0..2 {
+    ///         queue_tx
+    ///             .send(Message::new(Priority::Standard, SetFrequency::default()))
+    ///             .unwrap();
+    ///     }
+    ///     // The queue loop takes both up and waits for the first to settle, still holding them.
+    ///     tokio::time::sleep(Duration::from_millis(50)).await;
+    ///
+    ///     let third = queue_tx.send(Message::new(Priority::Standard, SetFrequency::default()));
+    ///     assert!(matches!(third, Err(SendError::QueueFull)));
+    /// }
+    /// ```
+    pub fn send(&self, message: Message) -> Result<u64, SendError> {
+        if self.queue_tx.is_closed() {
+            return Err(SendError::QueueClosed);
+        }
+        let slot = match Arc::clone(&self.slots).try_acquire_owned() {
+            Ok(slot) => slot,
+            Err(TryAcquireError::NoPermits) => return Err(SendError::QueueFull),
+            Err(TryAcquireError::Closed) => return Err(SendError::QueueClosed),
+        };
+
+        let command = message.addressed_command();
+        let queued = self.queued(
+            message.priority,
+            message.tag,
+            QueuedWork::Command(command, message.timeout),
+            slot,
+        );
+        let id = queued.id;
+
+        let result = match self.queue_tx.try_send(queued) {
+            Ok(()) => return Ok(id),
+            Err(TrySendError::Full(_)) => Err(SendError::QueueFull),
+            Err(TrySendError::Closed(_)) => Err(SendError::QueueClosed),
+        };
+        self.pending.withdraw(id);
+
+        result
+    }
+
+    /// Adds the message to the queue, waiting for room if the queue is full,
+    /// and returns the id its response will carry.
+    pub async fn send_wait(&self, message: Message) -> Result<u64, SendError> {
+        let slot = self.wait_for_slot().await?;
+        let command = message.addressed_command();
+        let queued = self.queued(
+            message.priority,
+            message.tag,
+            QueuedWork::Command(command, message.timeout),
+            slot,
+        );
+
+        self.enqueue(queued).await
+    }
+
+    /// Adds the sequence to the queue as a single entry, waiting for room if the queue is full.
+    ///
+    /// Returns the id every response of the sequence will carry, and the receiver
+    /// the collected responses are delivered on.
+    pub(crate) async fn send_sequence(
+        &self,
+        commands: Vec<Command>,
+        priority: Priority,
+        on_failure: SequenceFailure,
+    ) -> Result<(u64, oneshot::Receiver<Vec<Response>>), SendError> {
+        let slot = self.wait_for_slot().await?;
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let queued = self.queued(
+            priority,
+            None,
+            QueuedWork::Sequence {
+                commands,
+                on_failure,
+                reply_tx,
+            },
+            slot,
+        );
+
+        let id = self.enqueue(queued).await?;
+
+        Ok((id, reply_rx))
+    }
+
+    fn queued(
+        &self,
+        priority: Priority,
+        tag: Option<String>,
+        work: QueuedWork,
+        slot: OwnedSemaphorePermit,
+    ) -> QueuedMessage {
+        let id = next_id(&self.next_id);
+        self.pending.insert(id);
+
+        QueuedMessage {
+            id,
+            priority,
+            tag,
+            work,
+            _slot: slot,
+        }
+    }
+
+    /// Waits until the queue has room for another entry.
+    async fn wait_for_slot(&self) -> Result<OwnedSemaphorePermit, SendError> {
+        // The entries still held are dropped along with the queue loop, but nothing frees up
+        // room once it has stopped with the queue empty.
+        select! {
+            slot = Arc::clone(&self.slots).acquire_owned() => {
+                slot.map_err(|_| SendError::QueueClosed)
+            }
+            _ = self.queue_tx.closed() => Err(SendError::QueueClosed),
+        }
+    }
+
+    async fn enqueue(&self, queued: QueuedMessage) -> Result<u64, SendError> {
+        let id = queued.id;
+
+        match self.queue_tx.send(queued).await {
+            Ok(()) => Ok(id),
+            Err(_) => {
+                self.pending.withdraw(id);
+                Err(SendError::QueueClosed)
+            }
+        }
+    }
+}
+
+/// The ids of the entries waiting in the queue, so one can be withdrawn before it is sent.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PendingIds(Arc<Mutex<HashSet<u64>>>);
+
+impl PendingIds {
+    fn insert(&self, id: u64) {
+        self.ids().insert(id);
+    }
+
+    fn withdraw(&self, id: u64) -> bool {
+        self.ids().remove(&id)
+    }
+
+    /// Claims the entry for sending, returning `false` if it was withdrawn meanwhile.
+    pub(crate) fn dispatch(&self, id: u64) -> bool {
+        self.ids().remove(&id)
+    }
+
+    fn ids(&self) -> MutexGuard<'_, HashSet<u64>> {
+        // The set stays consistent even if a holder panicked, every operation is a single call.
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Creates the command queue, limited to `max_depth` messages that haven't been carried out yet.
+///
+/// Every entry holds one of the returned permits from being queued until it has been carried
+/// out, so the entries the queue loop has taken in from the channel still count against the
+/// depth. Without a maximum depth the queue is effectively unbounded.
+pub(crate) fn queue_channel(
+    max_depth: Option<usize>,
+) -> (
+    mpsc::Sender<QueuedMessage>,
+    mpsc::Receiver<QueuedMessage>,
+    Arc<Semaphore>,
+) {
+    let capacity = max_depth
+        .unwrap_or(Semaphore::MAX_PERMITS)
+        .clamp(1, Semaphore::MAX_PERMITS);
+
+    let (queue_tx, queue_rx) = mpsc::channel(capacity);
+    (queue_tx, queue_rx, Arc::new(Semaphore::new(capacity)))
+}
+
+/// Reserves the next id from the shared counter.
+pub(crate) fn next_id(counter: &AtomicU64) -> u64 {
+    counter.fetch_add(1, Ordering::Relaxed)
+}
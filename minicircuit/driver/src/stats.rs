@@ -0,0 +1,151 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use minicircuit_commands::{command::Command, response::Response};
+
+/// A snapshot of the activity of the driver since it was created.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DriverStats {
+    /// The number of commands sent to the signal generator.
+    pub processed: u64,
+    /// The number of commands that failed, either during communication or on the signal generator.
+    pub errors: u64,
+    /// The number of queued messages that haven't been sent yet.
+    pub queue_length: usize,
+    /// The moment the most recent command completed.
+    pub last_command_at: Option<SystemTime>,
+    /// How long the device took to reply, keyed by `Command::name()`.
+    pub latency: BTreeMap<&'static str, CommandLatency>,
+}
+
+/// The round-trip times of every exchange of one type of command with the signal generator.
+///
+/// Each attempt is timed from writing the command to reading its reply, so retries count
+/// separately and an exchange that timed out counts as taking the whole timeout.
+/// Dry runs and commands rejected before being sent aren't timed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CommandLatency {
+    /// The number of exchanges timed.
+    pub count: u64,
+    pub min: Duration,
+    pub max: Duration,
+    /// The most recent exchange, which creeping above the mean hints at a failing cable or a busy device.
+    pub last: Duration,
+    /// The sum of every exchange, see `mean()`.
+    pub total: Duration,
+}
+
+impl CommandLatency {
+    /// The average round-trip time, zero before anything was timed.
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        self.total.div_f64(self.count as f64)
+    }
+
+    fn record(&mut self, latency: Duration) {
+        if self.count == 0 || latency < self.min {
+            self.min = latency;
+        }
+        self.max = self.max.max(latency);
+        self.last = latency;
+        self.total = self.total.saturating_add(latency);
+        self.count += 1;
+    }
+}
+
+/// A command that was sent to the signal generator along with its outcome.
+#[derive(Debug, Clone)]
+pub struct CommandLogEntry {
+    /// The id the response was broadcast with.
+    pub id: u64,
+    pub command: Command,
+    pub response: Response,
+    /// The moment the command completed.
+    pub timestamp: SystemTime,
+}
+
+#[derive(Debug, Default)]
+struct StatsState {
+    stats: DriverStats,
+    log: VecDeque<CommandLogEntry>,
+}
+
+/// Shared handle used by the driver and its queue loop to record activity.
+#[derive(Debug, Clone)]
+pub(crate) struct StatsRecorder {
+    state: Arc<Mutex<StatsState>>,
+    /// The number of entries kept in the command log, the log is disabled at 0.
+    log_capacity: usize,
+}
+
+impl StatsRecorder {
+    pub(crate) fn new(log_capacity: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(StatsState::default())),
+            log_capacity,
+        }
+    }
+
+    /// Records a completed command, dropping the oldest log entry once the log is full.
+    pub(crate) fn record(&self, id: u64, command: Command, response: &Response) {
+        let timestamp = SystemTime::now();
+        let mut state = self.lock();
+
+        state.stats.processed += 1;
+        if response.is_error() {
+            state.stats.errors += 1;
+        }
+        state.stats.last_command_at = Some(timestamp);
+
+        if self.log_capacity == 0 {
+            return;
+        }
+        if state.log.len() == self.log_capacity {
+            state.log.pop_front();
+        }
+        state.log.push_back(CommandLogEntry {
+            id,
+            command,
+            response: response.clone(),
+            timestamp,
+        });
+    }
+
+    /// Adds the round-trip time of one exchange of the command with the signal generator.
+    pub(crate) fn record_latency(&self, command: &Command, latency: Duration) {
+        self.lock()
+            .stats
+            .latency
+            .entry(command.name())
+            .or_default()
+            .record(latency);
+    }
+
+    pub(crate) fn set_queue_length(&self, queue_length: usize) {
+        self.lock().stats.queue_length = queue_length;
+    }
+
+    pub(crate) fn stats(&self) -> DriverStats {
+        self.lock().stats.clone()
+    }
+
+    pub(crate) fn latency_report(&self) -> BTreeMap<&'static str, CommandLatency> {
+        self.lock().stats.latency.clone()
+    }
+
+    pub(crate) fn command_log(&self) -> Vec<CommandLogEntry> {
+        self.lock().log.iter().cloned().collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StatsState> {
+        // The state stays consistent even if a holder panicked, so keep using it.
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
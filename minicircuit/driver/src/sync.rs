@@ -0,0 +1,372 @@
+use std::{
+    net::ToSocketAddrs,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use serialport::Error;
+
+use minicircuit_commands::{
+    command::{Command, Message, Priority},
+    properties::TargetProperties,
+    response::{CorrelatedResponse, Response},
+};
+
+use super::{
+    connection::{
+        autodetect_sg_port_within, open_serial_port, remaining, verify_baud_rate, PortCandidate,
+    },
+    error::{ConnectError, SendError},
+    executable::{response_wait, send_command_within},
+    queue::{Prioritized, PriorityQueue},
+    transport::{DryRunTransport, PacedTransport, SerialTransport, TcpTransport, Transport},
+};
+
+/// A message waiting for the worker thread, along with where its response goes.
+struct SyncQueued {
+    id: u64,
+    priority: Priority,
+    tag: Option<String>,
+    command: Command,
+    timeout: Option<Duration>,
+    /// Set by `send_await`, which takes the response instead of the response receiver.
+    reply_tx: Option<mpsc::Sender<Response>>,
+}
+
+/// A driver that sends commands from a thread of its own, for callers that can't pull in tokio.
+///
+/// Available with the `sync` feature. Messages are queued through a `std::sync::mpsc` channel
+/// and the worker thread sends whatever is waiting in order of priority, like the queue loop of
+/// `MiniCircuitDriver`. Responses arrive on the receiver returned when connecting.
+///
+/// Only the queue is provided. The device isn't reopened when it disappears, and neither
+/// statistics nor unsolicited lines are collected.
+///
+/// ```
+/// use minicircuit_commands::prelude::*;
+/// use minicircuit_driver::{mock::MockTransport, sync::SyncMiniCircuitDriver};
+///
+/// let transport = MockTransport::builder()
+///     .reply("$FCG,1", "OK,1,2450")
+///     .build();
+///
+/// let mut driver = SyncMiniCircuitDriver::new(TargetProperties::default());
+/// let responses = driver.connect_transport(transport);
+///
+/// driver
+///     .send(Message::new(Priority::Standard, GetFrequency::default()))
+///     .unwrap();
+/// let response = responses.recv().unwrap().response;
+/// assert_eq!(response.as_frequency().unwrap().frequency, Frequency::new(2450));
+///
+/// let response = driver
+///     .send_await(GetFrequency::default(), Priority::High)
+///     .unwrap();
+/// assert_eq!(response.as_frequency().unwrap().frequency, Frequency::new(2450));
+///
+/// driver.disconnect();
+/// ```
+#[derive(Debug)]
+pub struct SyncMiniCircuitDriver {
+    properties: TargetProperties,
+    queue_tx: Option<mpsc::Sender<SyncQueued>>,
+    worker: Option<JoinHandle<()>>,
+    next_id: Arc<AtomicU64>,
+    /// The messages sent but not yet carried out by the worker thread, to enforce the maximum queue depth.
+    pending: Arc<AtomicUsize>,
+}
+
+impl SyncMiniCircuitDriver {
+    pub fn new(properties: TargetProperties) -> Self {
+        Self {
+            properties,
+            queue_tx: None,
+            worker: None,
+            next_id: Arc::new(AtomicU64::new(0)),
+            pending: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Connects to the first detected signal generator, or to the port of the target properties
+    /// if none is detected, see `MiniCircuitDriver::connect`.
+    pub fn connect(&mut self) -> Result<mpsc::Receiver<CorrelatedResponse>, ConnectError> {
+        self.connect_selecting(|candidates| candidates.first())
+    }
+
+    /// Like `connect()`, but lets `select` pick which of the detected signal generators to
+    /// connect to, see `MiniCircuitDriver::connect_selecting`.
+    pub fn connect_selecting<F>(
+        &mut self,
+        select: F,
+    ) -> Result<mpsc::Receiver<CorrelatedResponse>, ConnectError>
+    where
+        F: FnOnce(&[PortCandidate]) -> Option<&PortCandidate>,
+    {
+        // Nothing is sent in dry-run mode, so there's no need for a device.
+        if self.properties.dry_run {
+            return Ok(self.start(Box::new(DryRunTransport)));
+        }
+
+        let deadline = Instant::now() + self.properties.connect_timeout;
+
+        let signal_generators = autodetect_sg_port_within(
+            self.properties.vendor_id.clone(),
+            self.properties.product_id.clone(),
+            self.properties.connect_timeout,
+        );
+        let port_name = match signal_generators {
+            Ok(signal_generators) => select(&signal_generators)
+                .map(|candidate| candidate.port_name.clone())
+                .or_else(|| self.properties.port.clone()),
+            Err(e) => match &self.properties.port {
+                Some(port_name) => Some(port_name.clone()),
+                None => return Err(e.into()),
+            },
+        };
+
+        let Some(port_name) = port_name else {
+            return Err(Error::new(
+                serialport::ErrorKind::NoDevice,
+                "Unable to detect and select a device matching defined properties.",
+            )
+            .into());
+        };
+
+        self.open(&port_name, deadline)
+    }
+
+    /// Connects to the port of the target properties, see `MiniCircuitDriver::port_connect`.
+    pub fn port_connect(&mut self) -> Result<mpsc::Receiver<CorrelatedResponse>, ConnectError> {
+        let Some(port_name) = self.properties.port.clone() else {
+            return Err(Error::new(
+                serialport::ErrorKind::InvalidInput,
+                "A port must be defined in order to connect to it. Please add a port to the target properties.",
+            )
+            .into());
+        };
+
+        self.open(&port_name, Instant::now() + self.properties.connect_timeout)
+    }
+
+    /// Connects to a signal generator exposed over TCP, see `MiniCircuitDriver::connect_tcp`.
+    pub fn connect_tcp(
+        &mut self,
+        addr: impl ToSocketAddrs,
+    ) -> Result<mpsc::Receiver<CorrelatedResponse>, Error> {
+        let Some(addr) = addr.to_socket_addrs()?.next() else {
+            return Err(Error::new(
+                serialport::ErrorKind::InvalidInput,
+                "The address did not resolve to any socket address.",
+            ));
+        };
+
+        let transport = TcpTransport::connect(addr, self.properties.connection_timeout)?
+            .with_line_terminator(self.properties.line_terminator)
+            .with_echo(self.properties.echo_enabled);
+
+        Ok(self.start(Box::new(transport)))
+    }
+
+    /// Connects over a transport that was opened elsewhere, such as a mock.
+    pub fn connect_transport(
+        &mut self,
+        transport: impl Transport + 'static,
+    ) -> mpsc::Receiver<CorrelatedResponse> {
+        self.start(Box::new(transport))
+    }
+
+    /// Adds the message to the queue and returns the id its response will carry.
+    ///
+    /// Fails with `SendError::QueueFull` if the queue has reached its maximum depth.
+    pub fn send(&self, message: Message) -> Result<u64, SendError> {
+        let command = message.addressed_command();
+        self.enqueue(
+            message.priority,
+            message.tag,
+            command,
+            message.timeout,
+            None,
+        )
+    }
+
+    /// Enqueues the command and waits for its response.
+    ///
+    /// The response is returned here rather than delivered on the response receiver.
+    /// Fails with `SendError::Timeout` if it doesn't arrive within the response timeout of the
+    /// target properties, or the longer default timeout of slow commands such as sweeps.
+    pub fn send_await(
+        &self,
+        command: impl Into<Command>,
+        priority: Priority,
+    ) -> Result<Response, SendError> {
+        let command = command.into();
+        let timeout = response_wait(&command, None, self.properties.response_timeout);
+
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.enqueue(priority, None, command, None, Some(reply_tx))?;
+
+        match reply_rx.recv_timeout(timeout) {
+            Ok(response) => Ok(response),
+            Err(RecvTimeoutError::Timeout) => Err(SendError::Timeout(timeout)),
+            Err(RecvTimeoutError::Disconnected) => Err(SendError::ChannelClosed),
+        }
+    }
+
+    /// Stops the worker thread once it has sent the messages already queued, then closes the port.
+    pub fn disconnect(&mut self) {
+        // The worker stops once the queue is closed and drained.
+        self.queue_tx = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    fn open(
+        &mut self,
+        port_name: &str,
+        deadline: Instant,
+    ) -> Result<mpsc::Receiver<CorrelatedResponse>, ConnectError> {
+        let port = open_serial_port(&self.properties, port_name, remaining(deadline))?;
+        let mut port = SerialTransport::new(port)
+            .with_line_terminator(self.properties.line_terminator)
+            .with_echo(self.properties.echo_enabled);
+        verify_baud_rate(&mut port, &self.properties)?;
+
+        Ok(self.start(Box::new(port)))
+    }
+
+    fn start(&mut self, mut port: Box<dyn Transport>) -> mpsc::Receiver<CorrelatedResponse> {
+        // A previous connection would otherwise keep its worker thread.
+        self.disconnect();
+
+        if let Some(interval) = self.properties.min_command_interval {
+            port = Box::new(PacedTransport::new(port, interval));
+        }
+
+        let (queue_tx, queue_rx) = mpsc::channel();
+        let (response_tx, response_rx) = mpsc::channel();
+
+        let properties = self.properties.clone();
+        let pending = Arc::clone(&self.pending);
+        self.worker = Some(thread::spawn(move || {
+            run_queue(port, queue_rx, response_tx, pending, properties)
+        }));
+        self.queue_tx = Some(queue_tx);
+
+        response_rx
+    }
+
+    fn enqueue(
+        &self,
+        priority: Priority,
+        tag: Option<String>,
+        command: Command,
+        timeout: Option<Duration>,
+        reply_tx: Option<mpsc::Sender<Response>>,
+    ) -> Result<u64, SendError> {
+        let Some(queue_tx) = &self.queue_tx else {
+            return Err(SendError::NotConnected);
+        };
+
+        let pending = self.pending.fetch_add(1, Ordering::Relaxed);
+        if self
+            .properties
+            .max_queue_depth
+            .is_some_and(|max_depth| pending >= max_depth.max(1))
+        {
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+            return Err(SendError::QueueFull);
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let queued = SyncQueued {
+            id,
+            priority,
+            tag,
+            command,
+            timeout,
+            reply_tx,
+        };
+
+        match queue_tx.send(queued) {
+            Ok(()) => Ok(id),
+            Err(_) => {
+                self.pending.fetch_sub(1, Ordering::Relaxed);
+                Err(SendError::QueueClosed)
+            }
+        }
+    }
+}
+
+impl Prioritized for SyncQueued {
+    fn priority(&self) -> &Priority {
+        &self.priority
+    }
+}
+
+impl Drop for SyncMiniCircuitDriver {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+/// Sends whatever is waiting in order of priority until the queue is closed.
+fn run_queue(
+    mut port: Box<dyn Transport>,
+    queue_rx: mpsc::Receiver<SyncQueued>,
+    response_tx: mpsc::Sender<CorrelatedResponse>,
+    pending: Arc<AtomicUsize>,
+    properties: TargetProperties,
+) {
+    let mut queue = PriorityQueue::new().with_aging(properties.priority_aging);
+
+    // Sleep until a message arrives rather than polling an empty queue.
+    while let Ok(first) = queue_rx.recv() {
+        queue.push(first);
+
+        // Take in anything that arrived before each message, then send the one of the highest
+        // priority. Messages of equal priority keep the order they were sent in.
+        loop {
+            for queued in queue_rx.try_iter() {
+                queue.push(queued);
+            }
+            let Some(queued) = queue.pop() else {
+                break;
+            };
+
+            let response = if properties.dry_run {
+                Response::DryRun(String::from(&queued.command))
+            } else {
+                let settle = properties.settle_time(&queued.command);
+                let response = send_command_within(queued.command, &mut *port, queued.timeout);
+
+                // Nothing else is sent until the device has settled, see `settle_times`.
+                if let Some(settle) = settle.filter(|_| !response.is_error()) {
+                    thread::sleep(settle);
+                }
+                response
+            };
+
+            match queued.reply_tx {
+                Some(reply_tx) => {
+                    let _ = reply_tx.send(response);
+                }
+                None => {
+                    // Nobody may be listening, which doesn't stop the other messages.
+                    let _ = response_tx.send(
+                        CorrelatedResponse::new(queued.id, response)
+                            .with_priority(queued.priority)
+                            .with_tag(queued.tag),
+                    );
+                }
+            }
+            // Only now does the message stop counting against the maximum queue depth.
+            pending.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    select,
+    sync::{broadcast, mpsc},
+    time::MissedTickBehavior,
+};
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+
+use minicircuit_commands::{
+    data_types::types::{Amperes, Frequency, Temperature, Volts, Watt},
+    response::{CorrelatedResponse, Response},
+};
+
+/// A single measured quantity that can be tracked in a telemetry frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TelemetryMetric {
+    /// RF output frequency in MHz (`GetFrequency`).
+    Frequency,
+    /// RF output phase in degrees (`GetPhase`).
+    Phase,
+    /// PA forward power in watts (`GetPAPowerWatt`).
+    ForwardPowerWatt,
+    /// PA reflected power in watts (`GetPAPowerWatt`).
+    ReflectedPowerWatt,
+    /// PA forward power in dBm (`GetPAPowerDBM`).
+    ForwardPowerDbm,
+    /// PA reflected power in dBm (`GetPAPowerDBM`).
+    ReflectedPowerDbm,
+    /// PA temperature in degC (`GetPATemp`).
+    PATemperature,
+    /// ISC board microcontroller temperature in degC (`GetISCTemp`).
+    ISCTemperature,
+    /// PA DC current in amps (`GetPACurrent`).
+    PACurrent,
+    /// PA DC voltage in volts (`GetPAVoltage`).
+    PAVoltage,
+}
+
+impl TelemetryMetric {
+    /// Every metric that can be extracted from a response.
+    pub const ALL: [TelemetryMetric; 10] = [
+        TelemetryMetric::Frequency,
+        TelemetryMetric::Phase,
+        TelemetryMetric::ForwardPowerWatt,
+        TelemetryMetric::ReflectedPowerWatt,
+        TelemetryMetric::ForwardPowerDbm,
+        TelemetryMetric::ReflectedPowerDbm,
+        TelemetryMetric::PATemperature,
+        TelemetryMetric::ISCTemperature,
+        TelemetryMetric::PACurrent,
+        TelemetryMetric::PAVoltage,
+    ];
+
+    /// A short snake case name for the metric, used as a column or field name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TelemetryMetric::Frequency => "frequency_mhz",
+            TelemetryMetric::Phase => "phase_deg",
+            TelemetryMetric::ForwardPowerWatt => "forward_power_w",
+            TelemetryMetric::ReflectedPowerWatt => "reflected_power_w",
+            TelemetryMetric::ForwardPowerDbm => "forward_power_dbm",
+            TelemetryMetric::ReflectedPowerDbm => "reflected_power_dbm",
+            TelemetryMetric::PATemperature => "pa_temperature_c",
+            TelemetryMetric::ISCTemperature => "isc_temperature_c",
+            TelemetryMetric::PACurrent => "pa_current_a",
+            TelemetryMetric::PAVoltage => "pa_voltage_v",
+        }
+    }
+
+    /// Extracts every metric carried by the response along with its value.
+    ///
+    /// Responses that don't carry a measurement return an empty list.
+    pub fn extract(response: &Response) -> Vec<(TelemetryMetric, f64)> {
+        match response {
+            Response::GetFrequencyResponse(r) => {
+                vec![(TelemetryMetric::Frequency, r.frequency.frequency as f64)]
+            }
+            Response::GetPhaseResponse(r) => vec![(TelemetryMetric::Phase, r.phase.phase as f64)],
+            Response::GetPAPowerWattResponse(r) => vec![
+                (TelemetryMetric::ForwardPowerWatt, r.forward.power as f64),
+                (
+                    TelemetryMetric::ReflectedPowerWatt,
+                    r.reflected.power as f64,
+                ),
+            ],
+            Response::GetPAPowerDBMResponse(r) => vec![
+                (TelemetryMetric::ForwardPowerDbm, r.forward.power as f64),
+                (TelemetryMetric::ReflectedPowerDbm, r.reflected.power as f64),
+            ],
+            Response::GetPATempResponse(r) => vec![(
+                TelemetryMetric::PATemperature,
+                r.temperature.temperature as f64,
+            )],
+            Response::GetISCTempResponse(r) => vec![(
+                TelemetryMetric::ISCTemperature,
+                r.temperature.temperature as f64,
+            )],
+            Response::GetPACurrentResponse(r) => {
+                vec![(TelemetryMetric::PACurrent, r.current.current as f64)]
+            }
+            Response::GetPAVoltageResponse(r) => {
+                vec![(TelemetryMetric::PAVoltage, r.voltage.voltage as f64)]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The latest known value of a metric at the moment a frame was emitted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryValue {
+    pub metric: TelemetryMetric,
+    /// The most recent value, or `None` if no response for this metric has been seen yet.
+    pub value: Option<f64>,
+    /// True when the value was not refreshed since the previous frame.
+    pub stale: bool,
+    /// Time elapsed between receiving the value and emitting the frame.
+    pub age: Option<Duration>,
+}
+
+/// A fixed-rate snapshot of the most recent value of each configured metric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryFrame {
+    /// Increments by one for every emitted frame, starting at 0.
+    pub sequence: u64,
+    /// The moment the frame was emitted.
+    pub timestamp: Instant,
+    /// One entry per configured metric, in the order they were requested.
+    pub values: Vec<TelemetryValue>,
+}
+
+impl TelemetryFrame {
+    /// Returns the entry for the requested metric, if it was configured.
+    pub fn get(&self, metric: TelemetryMetric) -> Option<&TelemetryValue> {
+        self.values.iter().find(|value| value.metric == metric)
+    }
+}
+
+/// The measurements read by `MiniCircuitDriver::read_telemetry` in a single sequence.
+///
+/// A measurement whose getter failed, or was answered with an unexpected response,
+/// holds that response as its error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetrySnapshot {
+    /// The moment the last response of the sequence arrived.
+    pub captured_at: Instant,
+    pub frequency: Result<Frequency, Response>,
+    pub forward_power: Result<Watt, Response>,
+    pub reflected_power: Result<Watt, Response>,
+    pub pa_temperature: Result<Temperature, Response>,
+    pub pa_current: Result<Amperes, Response>,
+    pub pa_voltage: Result<Volts, Response>,
+}
+
+impl TelemetrySnapshot {
+    /// Assembles the snapshot from the responses to `GetFrequency`, `GetPAPowerWatt`,
+    /// `GetPATemp`, `GetPACurrent` and `GetPAVoltage`, in that order.
+    pub(crate) fn from_responses(responses: [Response; 5]) -> Self {
+        let [frequency, power, pa_temperature, pa_current, pa_voltage] = responses;
+
+        Self {
+            captured_at: Instant::now(),
+            frequency: frequency
+                .as_frequency()
+                .map(|r| r.frequency)
+                .ok_or_else(|| frequency.clone()),
+            forward_power: power
+                .as_papower_watt()
+                .map(|r| r.forward)
+                .ok_or_else(|| power.clone()),
+            reflected_power: power
+                .as_papower_watt()
+                .map(|r| r.reflected)
+                .ok_or_else(|| power.clone()),
+            pa_temperature: pa_temperature
+                .as_pa_temp()
+                .map(|r| r.temperature)
+                .ok_or_else(|| pa_temperature.clone()),
+            pa_current: pa_current
+                .as_pa_current()
+                .map(|r| r.current.clone())
+                .ok_or_else(|| pa_current.clone()),
+            pa_voltage: pa_voltage
+                .as_pa_voltage()
+                .map(|r| r.voltage.clone())
+                .ok_or_else(|| pa_voltage.clone()),
+        }
+    }
+}
+
+/// Spawns the task that coalesces responses into frames and returns the frames as a stream.
+///
+/// The task exits once the stream is dropped or the response channel closes.
+pub(crate) fn spawn_frame_loop(
+    mut response_rx: broadcast::Receiver<CorrelatedResponse>,
+    rate: Duration,
+    metrics: Vec<TelemetryMetric>,
+) -> impl Stream<Item = TelemetryFrame> {
+    let (frame_tx, frame_rx) = mpsc::channel::<TelemetryFrame>(16);
+
+    tokio::spawn(async move {
+        // Latest value per metric along with when it was received.
+        let mut latest: HashMap<TelemetryMetric, (f64, Instant)> = HashMap::new();
+
+        let mut ticker = tokio::time::interval(rate);
+        // A late tick shouldn't be followed by a burst of frames; the DAQ expects even spacing.
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        let mut sequence: u64 = 0;
+        let mut previous_tick: Option<Instant> = None;
+
+        loop {
+            select! {
+                _ = ticker.tick() => {
+                    let now = Instant::now();
+
+                    let values = metrics
+                        .iter()
+                        .map(|metric| match latest.get(metric) {
+                            Some((value, received)) => TelemetryValue {
+                                metric: *metric,
+                                value: Some(*value),
+                                stale: previous_tick.is_some_and(|tick| *received < tick),
+                                age: Some(now.duration_since(*received)),
+                            },
+                            None => TelemetryValue {
+                                metric: *metric,
+                                value: None,
+                                stale: true,
+                                age: None,
+                            },
+                        })
+                        .collect();
+
+                    let frame = TelemetryFrame {
+                        sequence,
+                        timestamp: now,
+                        values,
+                    };
+
+                    // The stream was dropped by the caller.
+                    if frame_tx.send(frame).await.is_err() {
+                        break;
+                    }
+
+                    sequence += 1;
+                    previous_tick = Some(now);
+                }
+                received = response_rx.recv() => {
+                    match received {
+                        Ok(correlated) => {
+                            let now = Instant::now();
+                            for (metric, value) in TelemetryMetric::extract(&correlated.response) {
+                                latest.insert(metric, (value, now));
+                            }
+                        }
+                        // Missing a few responses only delays the refresh of a value.
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            }
+        }
+    });
+
+    ReceiverStream::new(frame_rx)
+}
@@ -0,0 +1,570 @@
+use std::{
+    fmt::Debug,
+    net::{SocketAddr, TcpStream},
+    time::{Duration, Instant},
+};
+
+use minicircuit_commands::properties::LineTerminator;
+use serialport::{ClearBuffer, Error, ErrorKind, SerialPort};
+
+use super::communication::{discard_echo, exchange, read_line, read_pending, write_line};
+
+/// A connection to a signal generator that commands can be exchanged over.
+pub trait Transport: Send + Debug {
+    /// Sends a command and returns the line the signal generator responded with.
+    fn write_read(&mut self, tx: String) -> Result<String, Error>;
+
+    /// Sends a command the signal generator doesn't reply to, without waiting for a reply.
+    ///
+    /// Transports that can only exchange commands wait for a reply and discard it, treating a
+    /// missing one as sent.
+    fn write(&mut self, tx: String) -> Result<(), Error> {
+        match self.write_read(tx) {
+            Err(Error {
+                kind: ErrorKind::Io(std::io::ErrorKind::TimedOut),
+                ..
+            }) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    /// Returns `false` once the device on the other end is known to be gone.
+    fn is_connected(&mut self) -> bool;
+
+    /// The time allowed for a response to arrive.
+    fn timeout(&self) -> Duration;
+
+    /// Changes the time allowed for a response to arrive.
+    fn set_timeout(&mut self, timeout: Duration) -> Result<(), Error>;
+
+    /// Changes the baud rate, discarding anything received at the previous rate.
+    ///
+    /// Transports without a baud rate of their own, such as TCP, ignore it.
+    fn set_baud_rate(&mut self, _baud_rate: u32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Returns the lines the device sent without being asked, without waiting for more.
+    ///
+    /// Transports that can't tell whether anything is waiting return nothing.
+    fn read_unsolicited(&mut self) -> Result<Vec<String>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Waits for another line after a reply, for commands the device answers with several lines.
+    ///
+    /// Lines that arrived along with the reply are returned without waiting. Transports that only
+    /// ever deliver a single line per reply time out straight away.
+    ///
+    /// ```
+    /// use std::{io::{BufRead, BufReader, Write}, net::TcpListener, time::Duration};
+    ///
+    /// use minicircuit_driver::transport::{TcpTransport, Transport};
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let addr = listener.local_addr().unwrap();
+    ///
+    /// // A device that sends every line of a sweep in one go.
+    /// std::thread::spawn(move || {
+    ///     let (stream, _) = listener.accept().unwrap();
+    ///     let mut reader = BufReader::new(stream.try_clone().unwrap());
+    ///     reader.read_until(b'\r', &mut Vec::new()).unwrap();
+    ///     (&stream)
+    ///         .write_all(b"OK,1,2400,100.0,8.0\rOK,1,2410,100.0,2.0\r")
+    ///         .unwrap();
+    /// });
+    ///
+    /// let mut transport = TcpTransport::connect(addr, Duration::from_secs(1)).unwrap();
+    /// let sweep = "$SWP,1,2400,2410,10,100.0,1".to_string();
+    /// assert_eq!(transport.write_read(sweep).unwrap(), "OK,1,2400,100.0,8.0");
+    /// assert_eq!(transport.read_line().unwrap(), "OK,1,2410,100.0,2.0");
+    /// ```
+    fn read_line(&mut self) -> Result<String, Error> {
+        Err(Error::new(
+            ErrorKind::Io(std::io::ErrorKind::TimedOut),
+            "The transport doesn't deliver more than one line per reply.",
+        ))
+    }
+}
+
+/// Writes each command with the default line terminator, see `SerialTransport` to pick another.
+impl Transport for Box<dyn SerialPort> {
+    fn write_read(&mut self, tx: String) -> Result<String, Error> {
+        let timeout = self.timeout();
+        exchange(
+            &mut **self,
+            &mut Vec::new(),
+            tx,
+            LineTerminator::default(),
+            false,
+            timeout,
+        )
+    }
+
+    fn write(&mut self, tx: String) -> Result<(), Error> {
+        write_line(&mut **self, tx, LineTerminator::default())
+    }
+
+    fn is_connected(&mut self) -> bool {
+        // A failed port query means the device itself is gone rather than slow to respond.
+        self.bytes_to_read().is_ok()
+    }
+
+    fn timeout(&self) -> Duration {
+        SerialPort::timeout(&**self)
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<(), Error> {
+        SerialPort::set_timeout(&mut **self, timeout)
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), Error> {
+        SerialPort::set_baud_rate(&mut **self, baud_rate)?;
+        SerialPort::clear(&**self, ClearBuffer::All)
+    }
+
+    fn read_unsolicited(&mut self) -> Result<Vec<String>, Error> {
+        let pending = self.bytes_to_read()? as usize;
+        if pending == 0 {
+            return Ok(Vec::new());
+        }
+
+        let timeout = SerialPort::timeout(&**self);
+        read_pending(&mut **self, &mut Vec::new(), pending, timeout)
+    }
+
+    /// The bare port has nowhere to keep what arrives past the line, wrap it in a
+    /// `SerialTransport` for commands that reply with several lines.
+    fn read_line(&mut self) -> Result<String, Error> {
+        let timeout = SerialPort::timeout(&**self);
+        read_line(&mut **self, &mut Vec::new(), timeout)
+    }
+}
+
+/// A signal generator reached over a serial port, writing commands with the line terminator its
+/// firmware expects.
+#[derive(Debug)]
+pub struct SerialTransport {
+    port: Box<dyn SerialPort>,
+    terminator: LineTerminator,
+    /// Set when the link echoes each command back before the reply.
+    echo: bool,
+    /// What was received past the last line read, such as further lines of a reply.
+    buffer: Vec<u8>,
+}
+
+impl SerialTransport {
+    /// Wraps an opened port, writing commands with the default line terminator.
+    pub fn new(port: Box<dyn SerialPort>) -> Self {
+        Self {
+            port,
+            terminator: LineTerminator::default(),
+            echo: false,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Writes commands with the given line terminator instead.
+    pub fn with_line_terminator(mut self, terminator: LineTerminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// Discards the echo of each command before reading the reply, see
+    /// `TargetProperties::echo_enabled`.
+    pub fn with_echo(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+
+    /// The serial port commands are exchanged over.
+    pub fn port(&self) -> &dyn SerialPort {
+        &*self.port
+    }
+}
+
+impl From<Box<dyn SerialPort>> for SerialTransport {
+    fn from(port: Box<dyn SerialPort>) -> Self {
+        Self::new(port)
+    }
+}
+
+impl Transport for SerialTransport {
+    fn write_read(&mut self, tx: String) -> Result<String, Error> {
+        let timeout = SerialPort::timeout(&*self.port);
+        exchange(
+            &mut *self.port,
+            &mut self.buffer,
+            tx,
+            self.terminator,
+            self.echo,
+            timeout,
+        )
+    }
+
+    fn write(&mut self, tx: String) -> Result<(), Error> {
+        if !self.echo {
+            return write_line(&mut *self.port, tx, self.terminator);
+        }
+
+        // The echo would otherwise be taken for the reply to the next command.
+        write_line(&mut *self.port, tx.clone(), self.terminator)?;
+        let timeout = SerialPort::timeout(&*self.port);
+        discard_echo(&mut *self.port, &mut self.buffer, &tx, timeout)
+    }
+
+    // Only reading and writing involve the terminator, the echo and the buffer, everything else
+    // is the same as for the bare port.
+    fn is_connected(&mut self) -> bool {
+        self.port.is_connected()
+    }
+
+    fn timeout(&self) -> Duration {
+        Transport::timeout(&self.port)
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<(), Error> {
+        Transport::set_timeout(&mut self.port, timeout)
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), Error> {
+        self.buffer.clear();
+        Transport::set_baud_rate(&mut self.port, baud_rate)
+    }
+
+    fn read_unsolicited(&mut self) -> Result<Vec<String>, Error> {
+        let pending = self.port.bytes_to_read()? as usize;
+        if pending == 0 && self.buffer.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let timeout = SerialPort::timeout(&*self.port);
+        read_pending(&mut *self.port, &mut self.buffer, pending, timeout)
+    }
+
+    fn read_line(&mut self) -> Result<String, Error> {
+        let timeout = SerialPort::timeout(&*self.port);
+        read_line(&mut *self.port, &mut self.buffer, timeout)
+    }
+}
+
+/// A signal generator reached over a raw TCP socket, such as an Ethernet-to-serial bridge.
+///
+/// Replies are read up to a `\r`, a `\n` or a `\r\n`, whichever line terminator commands are
+/// written with:
+///
+/// ```
+/// use std::{io::{BufRead, BufReader, Write}, net::TcpListener, time::Duration};
+///
+/// use minicircuit_commands::properties::LineTerminator;
+/// use minicircuit_driver::transport::{TcpTransport, Transport};
+///
+/// for (terminator, written) in [
+///     (LineTerminator::Cr, "$FCG,1\r"),
+///     (LineTerminator::Lf, "$FCG,1\n"),
+///     (LineTerminator::CrLf, "$FCG,1\r\n"),
+/// ] {
+///     for reply in ["OK,1,2450\r", "OK,1,2450\n", "OK,1,2450\r\n"] {
+///         let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+///         let addr = listener.local_addr().unwrap();
+///
+///         // A device that reads until the terminator it was sent, then replies with its own.
+///         let device = std::thread::spawn(move || {
+///             let (stream, _) = listener.accept().unwrap();
+///             let mut reader = BufReader::new(stream.try_clone().unwrap());
+///             let mut received = Vec::new();
+///             let end = *written.as_bytes().last().unwrap();
+///             reader.read_until(end, &mut received).unwrap();
+///             (&stream).write_all(reply.as_bytes()).unwrap();
+///             String::from_utf8(received).unwrap()
+///         });
+///
+///         let mut transport = TcpTransport::connect(addr, Duration::from_secs(1))
+///             .unwrap()
+///             .with_line_terminator(terminator);
+///         assert_eq!(transport.write_read("$FCG,1".to_string()).unwrap(), "OK,1,2450");
+///         assert_eq!(device.join().unwrap(), written);
+///     }
+/// }
+/// ```
+///
+/// A reply that arrives split across several reads is put back together:
+///
+/// ```
+/// use std::{io::{BufRead, BufReader, Write}, net::TcpListener, thread, time::Duration};
+///
+/// use minicircuit_driver::transport::{TcpTransport, Transport};
+///
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let addr = listener.local_addr().unwrap();
+///
+/// // A device that trickles out its first reply in three chunks, ending the second with a lone `\r`.
+/// thread::spawn(move || {
+///     let (stream, _) = listener.accept().unwrap();
+///     stream.set_nodelay(true).unwrap();
+///     let mut reader = BufReader::new(stream.try_clone().unwrap());
+///
+///     reader.read_until(b'\r', &mut Vec::new()).unwrap();
+///     for chunk in ["OK,1,", "24", "50\r\n"] {
+///         (&stream).write_all(chunk.as_bytes()).unwrap();
+///         thread::sleep(Duration::from_millis(20));
+///     }
+///
+///     reader.read_until(b'\r', &mut Vec::new()).unwrap();
+///     (&stream).write_all(b"OK,1,90\r").unwrap();
+/// });
+///
+/// let mut transport = TcpTransport::connect(addr, Duration::from_secs(1)).unwrap();
+/// assert_eq!(transport.write_read("$FCG,1".to_string()).unwrap(), "OK,1,2450");
+/// assert_eq!(transport.write_read("$PCG,1".to_string()).unwrap(), "OK,1,90");
+/// ```
+#[derive(Debug)]
+pub struct TcpTransport {
+    stream: TcpStream,
+    addr: SocketAddr,
+    /// How long to wait for a complete response.
+    timeout: Duration,
+    /// Set once the connection has been reset or closed by the other end.
+    closed: bool,
+    /// Written after each command.
+    terminator: LineTerminator,
+    /// Set when the link echoes each command back before the reply.
+    echo: bool,
+    /// What was received past the last line read, such as further lines of a reply.
+    buffer: Vec<u8>,
+}
+
+impl TcpTransport {
+    /// Connects to the given address, using `timeout` both to connect and to wait for each response.
+    pub fn connect(addr: SocketAddr, timeout: Duration) -> Result<Self, Error> {
+        let stream = TcpStream::connect_timeout(&addr, timeout)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        // Commands are small and sent one at a time, so don't hold them back.
+        stream.set_nodelay(true)?;
+
+        Ok(Self {
+            stream,
+            addr,
+            timeout,
+            closed: false,
+            terminator: LineTerminator::default(),
+            echo: false,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Writes commands with the given line terminator instead of the default one.
+    pub fn with_line_terminator(mut self, terminator: LineTerminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// Discards the echo of each command before reading the reply, see
+    /// `TargetProperties::echo_enabled`.
+    ///
+    /// ```
+    /// use std::{io::{BufRead, BufReader, Write}, net::TcpListener, time::Duration};
+    ///
+    /// use minicircuit_driver::transport::{TcpTransport, Transport};
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let addr = listener.local_addr().unwrap();
+    ///
+    /// // A link that echoes the command before the device replies.
+    /// std::thread::spawn(move || {
+    ///     let (stream, _) = listener.accept().unwrap();
+    ///     let mut reader = BufReader::new(stream.try_clone().unwrap());
+    ///     let mut received = Vec::new();
+    ///     reader.read_until(b'\r', &mut received).unwrap();
+    ///     (&stream).write_all(&received).unwrap();
+    ///     (&stream).write_all(b"OK,1,2450\r").unwrap();
+    /// });
+    ///
+    /// let mut transport = TcpTransport::connect(addr, Duration::from_secs(1))
+    ///     .unwrap()
+    ///     .with_echo(true);
+    /// assert_eq!(transport.write_read("$FCG,1".to_string()).unwrap(), "OK,1,2450");
+    /// ```
+    pub fn with_echo(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+
+    /// The address of the signal generator.
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Remembers when an exchange failed because the other end went away.
+    fn note_closed<T>(&mut self, result: &Result<T, Error>) {
+        if let Err(Error {
+            kind: ErrorKind::Io(kind),
+            ..
+        }) = result
+        {
+            if matches!(
+                kind,
+                std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::UnexpectedEof
+            ) {
+                self.closed = true;
+            }
+        }
+    }
+}
+
+impl Transport for TcpTransport {
+    fn write_read(&mut self, tx: String) -> Result<String, Error> {
+        let result = exchange(
+            &mut self.stream,
+            &mut self.buffer,
+            tx,
+            self.terminator,
+            self.echo,
+            self.timeout,
+        );
+        self.note_closed(&result);
+
+        result
+    }
+
+    fn write(&mut self, tx: String) -> Result<(), Error> {
+        let result = if self.echo {
+            // The echo would otherwise be taken for the reply to the next command.
+            write_line(&mut self.stream, tx.clone(), self.terminator)
+                .and_then(|()| discard_echo(&mut self.stream, &mut self.buffer, &tx, self.timeout))
+        } else {
+            write_line(&mut self.stream, tx, self.terminator)
+        };
+        self.note_closed(&result);
+
+        result
+    }
+
+    fn is_connected(&mut self) -> bool {
+        !self.closed
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<(), Error> {
+        self.stream.set_read_timeout(Some(timeout))?;
+        self.timeout = timeout;
+
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> Result<String, Error> {
+        let result = read_line(&mut self.stream, &mut self.buffer, self.timeout);
+        self.note_closed(&result);
+
+        result
+    }
+}
+
+/// Spaces the exchanges over the transport it wraps at least `interval` apart, see
+/// `TargetProperties::min_command_interval`.
+#[derive(Debug)]
+pub(crate) struct PacedTransport {
+    inner: Box<dyn Transport>,
+    interval: Duration,
+    /// When the previous exchange started, `None` before the first one.
+    last_exchange: Option<Instant>,
+}
+
+impl PacedTransport {
+    pub(crate) fn new(inner: Box<dyn Transport>, interval: Duration) -> Self {
+        Self {
+            inner,
+            interval,
+            last_exchange: None,
+        }
+    }
+
+    fn wait_turn(&mut self) {
+        if let Some(last_exchange) = self.last_exchange {
+            let wait = self.interval.saturating_sub(last_exchange.elapsed());
+            if !wait.is_zero() {
+                // The port is held for the whole exchange anyway, so blocking here holds up nothing else.
+                std::thread::sleep(wait);
+            }
+        }
+
+        self.last_exchange = Some(Instant::now());
+    }
+}
+
+impl Transport for PacedTransport {
+    fn write_read(&mut self, tx: String) -> Result<String, Error> {
+        self.wait_turn();
+        self.inner.write_read(tx)
+    }
+
+    fn write(&mut self, tx: String) -> Result<(), Error> {
+        self.wait_turn();
+        self.inner.write(tx)
+    }
+
+    fn is_connected(&mut self) -> bool {
+        self.inner.is_connected()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<(), Error> {
+        self.inner.set_timeout(timeout)
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), Error> {
+        self.inner.set_baud_rate(baud_rate)
+    }
+
+    // Only reads what already arrived, which the device isn't involved in.
+    fn read_unsolicited(&mut self) -> Result<Vec<String>, Error> {
+        self.inner.read_unsolicited()
+    }
+
+    // Part of the reply to the exchange that was already paced.
+    fn read_line(&mut self) -> Result<String, Error> {
+        self.inner.read_line()
+    }
+}
+
+/// Stands in for the device in dry-run mode. Commands are answered before reaching it.
+#[derive(Debug)]
+pub(crate) struct DryRunTransport;
+
+impl Transport for DryRunTransport {
+    fn write_read(&mut self, _tx: String) -> Result<String, Error> {
+        Err(Error::new(
+            ErrorKind::NoDevice,
+            "No device is connected in dry-run mode.",
+        ))
+    }
+
+    fn write(&mut self, _tx: String) -> Result<(), Error> {
+        Err(Error::new(
+            ErrorKind::NoDevice,
+            "No device is connected in dry-run mode.",
+        ))
+    }
+
+    fn is_connected(&mut self) -> bool {
+        true
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn set_timeout(&mut self, _timeout: Duration) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use std::{error::Error, fmt};
+
+use minicircuit_commands::{
+    basic::{
+        frequency::{GetFrequency, SetFrequency},
+        output::{GetRFOutput, SetRFOutput},
+        phase::{GetPhase, SetPhase},
+        setpoint::{
+            GetPAPowerSetpointDBM, GetPAPowerSetpointWatt, SetPAPowerSetpointDBM,
+            SetPAPowerSetpointWatt,
+        },
+    },
+    command::Command,
+    data_types::types::{Attenuation, Dbm, Frequency, Phase, Watt},
+    dll::enable::{GetDLLEnabled, SetDLLEnabled},
+    manual::attenuation::{GetAttenuation, SetAttenuation},
+    response::Response,
+};
+
+/// A setter whose value can be read back with a matching getter, see
+/// `MiniCircuitDriver::set_verified`.
+pub trait VerifiableSetter: Into<Command> {
+    /// The value the setter applies.
+    type Value: PartialEq + fmt::Display + Clone;
+
+    /// The value this setter asks the device to apply.
+    fn requested(&self) -> Self::Value;
+
+    /// The getter reading the value back, on the same channel.
+    fn getter(&self) -> Command;
+
+    /// The value the getter's response reports, `None` if it failed.
+    fn reported(response: &Response) -> Option<Self::Value>;
+}
+
+/// Why a setting couldn't be confirmed, see `MiniCircuitDriver::set_verified`.
+#[derive(Debug, Clone)]
+pub enum VerifyError<T> {
+    /// The driver has not been connected to a signal generator.
+    NotConnected,
+    /// The setter failed, carrying its response.
+    Rejected(Response),
+    /// The setter succeeded but the getter failed, carrying its response.
+    ReadbackFailed(Response),
+    /// The device reports a value other than the one that was set.
+    Mismatch { requested: T, reported: T },
+}
+
+impl<T: fmt::Debug + fmt::Display> Error for VerifyError<T> {}
+
+impl<T: fmt::Display> fmt::Display for VerifyError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConnected => write!(f, "The driver is not connected to a signal generator."),
+            Self::Rejected(response) => {
+                write!(f, "The setting was rejected: {}", describe(response))
+            }
+            Self::ReadbackFailed(response) => write!(
+                f,
+                "The setting couldn't be read back: {}",
+                describe(response)
+            ),
+            Self::Mismatch {
+                requested,
+                reported,
+            } => write!(
+                f,
+                "{} was requested but the device reports {}.",
+                requested, reported
+            ),
+        }
+    }
+}
+
+fn describe(response: &Response) -> String {
+    response.clone().into()
+}
+
+macro_rules! verifiable {
+    ($($setter:ident($field:ident: $value:ty) => $getter:ident, $accessor:ident),* $(,)?) => {
+        $(
+            impl VerifiableSetter for $setter {
+                type Value = $value;
+
+                fn requested(&self) -> Self::Value {
+                    self.$field.clone()
+                }
+
+                fn getter(&self) -> Command {
+                    Command::$getter($getter::new(self.channel.clone()))
+                }
+
+                fn reported(response: &Response) -> Option<Self::Value> {
+                    response.$accessor().map(|get| get.$field.clone())
+                }
+            }
+        )*
+    };
+}
+
+verifiable! {
+    SetFrequency(frequency: Frequency) => GetFrequency, as_frequency,
+    SetPAPowerSetpointWatt(power: Watt) => GetPAPowerSetpointWatt, as_papower_setpoint_watt,
+    SetPAPowerSetpointDBM(power: Dbm) => GetPAPowerSetpointDBM, as_papower_setpoint_dbm,
+    SetPhase(phase: Phase) => GetPhase, as_phase,
+    SetAttenuation(attenuation: Attenuation) => GetAttenuation, as_attenuation,
+    SetRFOutput(enabled: bool) => GetRFOutput, as_rf_output,
+    SetDLLEnabled(enabled: bool) => GetDLLEnabled, as_dll_enabled,
+}
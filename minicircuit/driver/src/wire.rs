@@ -0,0 +1,123 @@
+use std::{
+    fmt::{self, Debug},
+    sync::Arc,
+    time::Duration,
+};
+
+use minicircuit_commands::properties::LineTerminator;
+use serialport::Error;
+
+use super::transport::Transport;
+
+/// Which way bytes passed over the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireDirection {
+    /// Written to the signal generator, including the line terminator.
+    Sent,
+    /// A line read from the signal generator, without its terminator.
+    Received,
+}
+
+/// Called with every command written and every line read, see `MiniCircuitDriver::on_wire`.
+#[derive(Clone)]
+pub(crate) struct WireHook(Arc<WireFn>);
+
+type WireFn = dyn Fn(WireDirection, &[u8]) + Send + Sync;
+
+impl WireHook {
+    pub(crate) fn new<F>(hook: F) -> Self
+    where
+        F: Fn(WireDirection, &[u8]) + Send + Sync + 'static,
+    {
+        Self(Arc::new(hook))
+    }
+
+    fn call(&self, direction: WireDirection, bytes: &[u8]) {
+        (self.0)(direction, bytes)
+    }
+}
+
+impl Debug for WireHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WireHook")
+    }
+}
+
+/// Passes everything through to the transport it wraps, showing the hook what goes over the wire.
+#[derive(Debug)]
+pub(crate) struct WireTap {
+    inner: Box<dyn Transport>,
+    hook: WireHook,
+    /// The terminator the transport appends, so it is shown as written.
+    terminator: LineTerminator,
+}
+
+impl WireTap {
+    pub(crate) fn new(
+        inner: Box<dyn Transport>,
+        hook: WireHook,
+        terminator: LineTerminator,
+    ) -> Self {
+        Self {
+            inner,
+            hook,
+            terminator,
+        }
+    }
+}
+
+impl Transport for WireTap {
+    fn write_read(&mut self, tx: String) -> Result<String, Error> {
+        // The transport appends the terminator itself, show it as it is written.
+        self.hook.call(
+            WireDirection::Sent,
+            format!("{}{}", tx, self.terminator.as_str()).as_bytes(),
+        );
+
+        let reply = self.inner.write_read(tx)?;
+        self.hook.call(WireDirection::Received, reply.as_bytes());
+
+        Ok(reply)
+    }
+
+    fn write(&mut self, tx: String) -> Result<(), Error> {
+        self.hook.call(
+            WireDirection::Sent,
+            format!("{}{}", tx, self.terminator.as_str()).as_bytes(),
+        );
+
+        self.inner.write(tx)
+    }
+
+    fn is_connected(&mut self) -> bool {
+        self.inner.is_connected()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<(), Error> {
+        self.inner.set_timeout(timeout)
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), Error> {
+        self.inner.set_baud_rate(baud_rate)
+    }
+
+    fn read_unsolicited(&mut self) -> Result<Vec<String>, Error> {
+        let lines = self.inner.read_unsolicited()?;
+        for line in &lines {
+            self.hook.call(WireDirection::Received, line.as_bytes());
+        }
+
+        Ok(lines)
+    }
+
+    fn read_line(&mut self) -> Result<String, Error> {
+        let line = self.inner.read_line()?;
+        self.hook.call(WireDirection::Received, line.as_bytes());
+
+        Ok(line)
+    }
+}
@@ -1,10 +1,7 @@
 use std::time::Duration;
 
 use minicircuit_commands::prelude::*;
-use minicircuit_driver::{
-    driver::MiniCircuitDriver,
-    properties::TargetProperties,
-};
+use minicircuit_driver::prelude::{MiniCircuitDriver, TargetProperties};
 use tokio::runtime::Runtime;
 
 fn main() {
@@ -26,14 +23,14 @@ fn main() {
         let port_name = "COM5"; // Replace with the port name printed by the simulator
         
         // Create a driver that will connect to the simulator
-        let mut target_properties = TargetProperties::default();
-        target_properties.port = Some(port_name.to_string());
+        let target_properties = TargetProperties::builder().port(port_name).build();
         
         let mut driver = MiniCircuitDriver::new(target_properties);
         
         // Connect to the simulator
         match driver.connect() {
-            Ok((tx, mut rx)) => {
+            Ok((tx, response_tx)) => {
+                let mut rx = response_tx.subscribe();
                 println!("Connected to simulator on port {}", port_name);
                 
                 // Add a delay after connecting to ensure the simulator is ready
@@ -57,7 +54,7 @@ fn main() {
                     println!("Sending: {:?}", cmd);
                     
                     // Clear the channel before sending a new command
-                    while let Ok(_) = rx.try_recv() {
+                    while rx.try_recv().is_ok() {
                         // Discard any pending responses
                     }
                     
@@ -65,6 +62,9 @@ fn main() {
                     tx.send(Message {
                         command: cmd.clone(),
                         priority: Priority::Standard,
+                        timeout: None,
+                        tag: None,
+                        channel: None,
                     }).unwrap();
                     
                     // Wait for the response with timeout
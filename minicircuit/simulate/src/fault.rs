@@ -0,0 +1,48 @@
+use std::{collections::HashMap, time::Duration};
+
+/// A way for the simulator to misbehave when answering a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// Carries out the command but replies with text that can't be parsed.
+    Malformed,
+    /// Rejects the command with the given device error code, e.g. `ERR06` for a busy system.
+    DeviceError(String),
+    /// Carries out the command without replying, so the driver runs into its timeout.
+    Drop,
+    /// Carries out the command and replies once the delay has passed.
+    Delay(Duration),
+}
+
+/// The faults the simulator injects, set with `MiniCircuitSimulator::set_faults` before a test runs.
+///
+/// Faults are looked up by the command code, such as `$FCG`. Commands without a fault of their own
+/// fall back to the fault for every command, if there is one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FaultConfig {
+    commands: HashMap<String, Fault>,
+    every_command: Option<Fault>,
+}
+
+impl FaultConfig {
+    /// Returns a configuration without any faults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Injects the fault whenever the command with the given code is received.
+    pub fn on(mut self, code: impl Into<String>, fault: Fault) -> Self {
+        self.commands.insert(code.into(), fault);
+        self
+    }
+
+    /// Injects the fault for every command that has no fault of its own.
+    pub fn on_every_command(mut self, fault: Fault) -> Self {
+        self.every_command = Some(fault);
+        self
+    }
+
+    /// The fault to inject for the command with the given code, if any.
+    pub fn fault_for(&self, code: &str) -> Option<&Fault> {
+        self.commands.get(code).or(self.every_command.as_ref())
+    }
+}
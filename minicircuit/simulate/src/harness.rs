@@ -0,0 +1,270 @@
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use serialport::{Error, ErrorKind};
+
+use minicircuit_commands::{properties::TargetProperties, response::CorrelatedResponse};
+use minicircuit_driver::{driver::MiniCircuitDriver, sender::CommandSender, transport::Transport};
+use tokio::sync::broadcast;
+
+use crate::simulator::MiniCircuitSimulator;
+
+/// Hands every command straight to a simulator in the same process, without a serial port.
+///
+/// Clones share the same simulators. Injected faults apply as they would over a serial port:
+/// a dropped response times out and a delayed one blocks for the delay.
+#[derive(Debug, Clone)]
+pub struct SimulatorTransport {
+    simulators: Vec<Arc<Mutex<MiniCircuitSimulator>>>,
+    timeout: Duration,
+}
+
+impl SimulatorTransport {
+    pub fn new(simulator: Arc<Mutex<MiniCircuitSimulator>>) -> Self {
+        Self::bus(vec![simulator])
+    }
+
+    /// Hands every command to each of the simulators, like boards daisy-chained on one bus.
+    ///
+    /// Only the simulator whose channel ID the command is addressed to answers it.
+    pub fn bus(simulators: Vec<Arc<Mutex<MiniCircuitSimulator>>>) -> Self {
+        Self {
+            simulators,
+            timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+impl Transport for SimulatorTransport {
+    fn write_read(&mut self, tx: String) -> Result<String, Error> {
+        let response = self
+            .simulators
+            .iter()
+            .find_map(|simulator| lock(simulator).respond(&tx));
+
+        match response {
+            Some(response) => Ok(response),
+            None => Err(Error::new(
+                ErrorKind::Io(std::io::ErrorKind::TimedOut),
+                "System timedout while waiting for response from the controller.",
+            )),
+        }
+    }
+
+    // The simulators carry the command out, whatever they'd reply is dropped.
+    fn write(&mut self, tx: String) -> Result<(), Error> {
+        for simulator in &self.simulators {
+            lock(simulator).respond(&tx);
+        }
+
+        Ok(())
+    }
+
+    fn is_connected(&mut self) -> bool {
+        true
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<(), Error> {
+        self.timeout = timeout;
+        Ok(())
+    }
+}
+
+/// A driver connected to a simulator running in-process, for end-to-end tests that don't need
+/// socat or com0com.
+///
+/// Must be started from within a tokio runtime, as the driver spawns its queue loop.
+///
+/// ```
+/// use minicircuit_commands::prelude::*;
+/// use minicircuit_simulate::harness::SimulatorHarness;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut harness = SimulatorHarness::start(TargetProperties::default()).unwrap();
+///
+///     harness
+///         .driver()
+///         .send_await(SetFrequency::new(Channel::default(), Frequency::new(2450)), Priority::Standard)
+///         .await
+///         .unwrap();
+///     assert_eq!(harness.simulator().state().frequency, 2450.0);
+///
+///     harness.simulator().state_mut().temperature = 48.0;
+///     let response = harness
+///         .driver()
+///         .send_await(GetPATemp::default(), Priority::Standard)
+///         .await
+///         .unwrap();
+///     assert_eq!(response.as_pa_temp().unwrap().temperature, Temperature::new(48));
+///
+///     harness.driver_mut().disconnect().await;
+/// }
+/// ```
+///
+/// Several simulators can share the bus, each answering to its own channel ID:
+///
+/// ```
+/// use minicircuit_commands::prelude::*;
+/// use minicircuit_simulate::{harness::SimulatorHarness, simulator::MiniCircuitSimulator};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut second = MiniCircuitSimulator::new();
+///     second.state_mut().channel_id = Channel::new(2);
+///
+///     let mut harness = SimulatorHarness::with_bus(
+///         TargetProperties::default(),
+///         vec![MiniCircuitSimulator::new(), second],
+///     )
+///     .unwrap();
+///     let mut responses = harness.subscribe();
+///
+///     let message = Message::new(
+///         Priority::Standard,
+///         SetFrequency::new(Channel::default(), Frequency::new(2420)),
+///     )
+///     .with_channel(Channel::new(2));
+///     harness.queue_tx().send_wait(message).await.unwrap();
+///     responses.recv().await.unwrap();
+///
+///     assert_eq!(harness.simulator_at(1).state().frequency, 2420.0);
+///     assert_ne!(harness.simulator_at(0).state().frequency, 2420.0);
+///
+///     harness.driver_mut().disconnect().await;
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SimulatorHarness {
+    driver: MiniCircuitDriver,
+    simulators: Vec<Arc<Mutex<MiniCircuitSimulator>>>,
+    queue_tx: CommandSender,
+    response_tx: broadcast::Sender<CorrelatedResponse>,
+}
+
+impl SimulatorHarness {
+    /// Connects a driver with the given properties to a fresh simulator.
+    pub fn start(properties: TargetProperties) -> Result<Self, Error> {
+        Self::with_simulator(properties, MiniCircuitSimulator::new())
+    }
+
+    /// Connects a driver with the given properties to a simulator that was already set up,
+    /// such as with faults injected.
+    pub fn with_simulator(
+        properties: TargetProperties,
+        simulator: MiniCircuitSimulator,
+    ) -> Result<Self, Error> {
+        Self::with_bus(properties, vec![simulator])
+    }
+
+    /// Connects a driver with the given properties to several simulators sharing one bus,
+    /// each answering to its own channel ID.
+    pub fn with_bus(
+        properties: TargetProperties,
+        simulators: Vec<MiniCircuitSimulator>,
+    ) -> Result<Self, Error> {
+        let simulators: Vec<_> = simulators
+            .into_iter()
+            .map(|simulator| Arc::new(Mutex::new(simulator)))
+            .collect();
+
+        let mut driver = MiniCircuitDriver::new(properties);
+        let (queue_tx, response_tx) =
+            driver.connect_transport(SimulatorTransport::bus(simulators.clone()))?;
+
+        Ok(Self {
+            driver,
+            simulators,
+            queue_tx,
+            response_tx,
+        })
+    }
+
+    pub fn driver(&self) -> &MiniCircuitDriver {
+        &self.driver
+    }
+
+    pub fn driver_mut(&mut self) -> &mut MiniCircuitDriver {
+        &mut self.driver
+    }
+
+    /// The simulator the driver is talking to, to inspect or change its state between commands.
+    ///
+    /// Don't hold on to it across an `.await`, the driver can't reach the simulator meanwhile.
+    pub fn simulator(&self) -> MutexGuard<'_, MiniCircuitSimulator> {
+        self.simulator_at(0)
+    }
+
+    /// The simulator at the given position on the bus, in the order they were passed to `with_bus`.
+    ///
+    /// Panics if there is no simulator at that position.
+    pub fn simulator_at(&self, index: usize) -> MutexGuard<'_, MiniCircuitSimulator> {
+        lock(&self.simulators[index])
+    }
+
+    /// Moves the clock of every simulator on the bus forward, see
+    /// `MiniCircuitSimulator::advance_clock`.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use minicircuit_commands::prelude::*;
+    /// use minicircuit_simulate::harness::SimulatorHarness;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut harness = SimulatorHarness::start(TargetProperties::default()).unwrap();
+    ///
+    ///     // An overheating PA trips once it has stayed too hot for the whole grace period.
+    ///     harness.simulator().state_mut().soa.grace_period = 10_000;
+    ///     harness.simulator().state_mut().temperature = 70.0;
+    ///     harness
+    ///         .driver()
+    ///         .send_await(GetPATemp::default(), Priority::Standard)
+    ///         .await
+    ///         .unwrap();
+    ///     assert!(!harness.simulator().state().tripped);
+    ///
+    ///     harness.advance_clock(Duration::from_secs(10));
+    ///     assert!(harness.simulator().state().tripped);
+    ///
+    ///     let response = harness
+    ///         .driver()
+    ///         .send_await(GetUptime::default(), Priority::Standard)
+    ///         .await
+    ///         .unwrap();
+    ///     let Response::GetUptimeResponse(uptime) = response else {
+    ///         panic!("unexpected response {:?}", response);
+    ///     };
+    ///     assert!(uptime.uptime.seconds >= 10);
+    ///
+    ///     harness.driver_mut().disconnect().await;
+    /// }
+    /// ```
+    pub fn advance_clock(&self, by: Duration) {
+        for simulator in &self.simulators {
+            lock(simulator).advance_clock(by);
+        }
+    }
+
+    /// The sender returned when the driver connected.
+    pub fn queue_tx(&self) -> &CommandSender {
+        &self.queue_tx
+    }
+
+    /// Subscribes to every response the driver broadcasts.
+    pub fn subscribe(&self) -> broadcast::Receiver<CorrelatedResponse> {
+        self.response_tx.subscribe()
+    }
+}
+
+fn lock(simulator: &Mutex<MiniCircuitSimulator>) -> MutexGuard<'_, MiniCircuitSimulator> {
+    // A failed assertion while holding the lock shouldn't hide the simulator from the rest of the test.
+    simulator.lock().unwrap_or_else(|e| e.into_inner())
+}
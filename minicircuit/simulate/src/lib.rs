@@ -1 +1,4 @@
+pub mod fault;
+pub mod harness;
+pub mod script;
 pub mod simulator;
@@ -298,7 +298,12 @@ fn run_simulator(simulator: Arc<Mutex<MiniCircuitSimulator>>, port_name: &str) {
                                 // Get the response from the simulator
                                 let response = {
                                     let mut sim = simulator.lock().unwrap();
-                                    sim.process_command(&command)
+                                    sim.respond(&command)
+                                };
+                                let Some(response) = response else {
+                                    println!("Dropping response to: {}", command);
+                                    command_buffer.clear();
+                                    continue;
                                 };
                                 
                                 // Special handling for identity command
@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+
+use crate::{fault::Fault, simulator::SimulatorState};
+
+/// What the simulator does with a command matched by a scripted rule.
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Answers the command as if there were no script, including any injected fault.
+    Respond,
+    /// Replies with the given text instead of carrying out the command.
+    Reply(String),
+    /// Injects the fault for this command alone.
+    Fault(Fault),
+    /// Changes the state before answering as if there were no script,
+    /// e.g. to raise the temperature past its shutdown limit.
+    Update(fn(&mut SimulatorState)),
+}
+
+/// One step of a script, matched against the command code such as `$PTG`.
+///
+/// A rule matches once unless given a repeat count.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// `None` matches every command.
+    code: Option<String>,
+    action: Action,
+    /// The matches left before the script moves on, `None` never moves on.
+    remaining: Option<u32>,
+}
+
+impl Rule {
+    /// A rule for the command with the given code, answered as if there were no script.
+    pub fn on(code: impl Into<String>) -> Self {
+        Self {
+            code: Some(code.into()),
+            action: Action::Respond,
+            remaining: Some(1),
+        }
+    }
+
+    /// A rule for whichever command comes next, answered as if there were no script.
+    pub fn on_any() -> Self {
+        Self {
+            code: None,
+            action: Action::Respond,
+            remaining: Some(1),
+        }
+    }
+
+    /// Replies with the given text instead of carrying out the command.
+    pub fn reply(self, reply: impl Into<String>) -> Self {
+        self.then_do(Action::Reply(reply.into()))
+    }
+
+    /// Injects the fault instead of the one configured for the command.
+    pub fn fault(self, fault: Fault) -> Self {
+        self.then_do(Action::Fault(fault))
+    }
+
+    /// Changes the state before the command is answered.
+    pub fn update(self, update: fn(&mut SimulatorState)) -> Self {
+        self.then_do(Action::Update(update))
+    }
+
+    /// Matches the given number of commands before the script moves on.
+    pub fn times(mut self, times: u32) -> Self {
+        self.remaining = Some(times);
+        self
+    }
+
+    /// Keeps matching for good, so the rules after it are never reached.
+    pub fn forever(mut self) -> Self {
+        self.remaining = None;
+        self
+    }
+
+    fn then_do(mut self, action: Action) -> Self {
+        self.action = action;
+        self
+    }
+
+    fn matches(&self, code: &str) -> bool {
+        self.code.as_deref().is_none_or(|own| own == code)
+    }
+}
+
+/// Rules played back in order, set with `MiniCircuitSimulator::set_script` before a test runs.
+///
+/// Only the first rule that hasn't run its course is looked at. Commands it doesn't match are
+/// answered as if there were no script and leave it waiting, so other traffic such as a
+/// telemetry poll can't throw the scenario off. Once every rule has run, the simulator answers
+/// as if there were no script.
+///
+/// ```
+/// use minicircuit_simulate::script::{Rule, Script};
+///
+/// // Report normal readings five times, then an over-temperature fault, then recover.
+/// let script = Script::new()
+///     .then(Rule::on("$PTG").times(5))
+///     .then(Rule::on("$PTG").update(|state| state.temperature = 70.0))
+///     .then(Rule::on("$ERRC").update(|state| state.temperature = 35.0));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Script {
+    rules: VecDeque<Rule>,
+}
+
+impl Script {
+    /// Returns a script without any rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule to the end of the script.
+    pub fn then(mut self, rule: Rule) -> Self {
+        self.rules.push_back(rule);
+        self
+    }
+
+    /// Whether every rule has run its course.
+    pub fn is_finished(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The number of rules that haven't run their course, including the current one.
+    pub fn remaining(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Advances the script if the current rule matches the command code, returning its action.
+    pub fn next_action(&mut self, code: &str) -> Option<Action> {
+        let rule = self.rules.front_mut().filter(|rule| rule.matches(code))?;
+        let action = rule.action.clone();
+
+        match &mut rule.remaining {
+            Some(remaining) if *remaining > 1 => *remaining -= 1,
+            Some(_) => {
+                self.rules.pop_front();
+            }
+            None => {}
+        }
+
+        Some(action)
+    }
+}
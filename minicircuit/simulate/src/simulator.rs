@@ -1,43 +1,346 @@
 use log::info;
-use std::time::Instant;
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use minicircuit_commands::{
+    data_types::{
+        power::{dbm_to_watt, watt_to_dbm},
+        types::Channel,
+    },
+    error::status::StatusCode,
+};
+
+use crate::{
+    fault::{Fault, FaultConfig},
+    script::{Action, Script},
+};
+
+/// The safe operating area limits and protections of the simulated device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoaConfig {
+    pub temp_enabled: bool,
+    pub reflection_enabled: bool,
+    pub external_watchdog_enabled: bool,
+    pub dissipation_enabled: bool,
+    /// Whether the software watchdog resets the device when a component stops responding.
+    pub watchdog_enabled: bool,
+    /// Temperature limits in °C.
+    pub high_temp: f64,
+    pub shutdown_temp: f64,
+    /// Current limits in A.
+    pub high_current: f64,
+    pub shutdown_current: f64,
+    /// Dissipation limits in W.
+    pub high_dissipation: f64,
+    pub shutdown_dissipation: f64,
+    /// Reflected power limits in dBm.
+    pub high_reflection: f64,
+    pub shutdown_reflection: f64,
+    /// Forward power limits in W.
+    pub high_forward_power: f64,
+    pub shutdown_forward_power: f64,
+    /// Voltage limits in V.
+    pub shutdown_min_voltage: f64,
+    pub low_voltage: f64,
+    pub high_voltage: f64,
+    pub shutdown_max_voltage: f64,
+    /// Grace period in ms.
+    pub grace_period: u64,
+}
+
+impl Default for SoaConfig {
+    fn default() -> Self {
+        Self {
+            temp_enabled: true,
+            reflection_enabled: true,
+            external_watchdog_enabled: false,
+            dissipation_enabled: true,
+            watchdog_enabled: true,
+            high_temp: 55.0,
+            shutdown_temp: 65.0,
+            high_current: 3.0,
+            shutdown_current: 4.0,
+            high_dissipation: 200.0,
+            shutdown_dissipation: 250.0,
+            high_reflection: 47.0,
+            shutdown_reflection: 50.0,
+            high_forward_power: 300.0,
+            shutdown_forward_power: 350.0,
+            shutdown_min_voltage: 10.0,
+            low_voltage: 11.0,
+            high_voltage: 13.0,
+            shutdown_max_voltage: 14.0,
+            grace_period: 0,
+        }
+    }
+}
+
+/// The settings and readings of the simulated device.
+///
+/// Setters write to the state and getters read from it, so a `GetFrequency` reports the frequency
+/// of the last `SetFrequency`. Tests can change the readings directly through `state_mut()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatorState {
+    pub channel_id: Channel,
+    /// Frequency in MHz.
+    pub frequency: f64,
+    /// Phase in degrees.
+    pub phase: f64,
+    pub rf_output_enabled: bool,
+    /// Power setpoint in dBm.
+    pub power_setpoint_dbm: f64,
+    /// Attenuation in dB.
+    pub attenuation: f64,
+    /// Magnitude in %.
+    pub magnitude: f64,
+    /// PA temperature in °C.
+    pub temperature: f64,
+    /// PA voltage in V.
+    pub voltage: f64,
+    /// PA current in A.
+    pub current: f64,
+    /// Share of the forward power that is reflected back.
+    pub reflection: f64,
+    pub soa: SoaConfig,
+    /// Status bits reported by `GetStatus`, kept until `ClearErrors`.
+    pub status: u64,
+    /// PA alarm bits reported by `GetPAErrors`, kept until `ClearErrors`.
+    pub pa_errors: u16,
+    /// Set once a SOA shutdown limit was exceeded. RF output can't be enabled until `ClearErrors`.
+    pub tripped: bool,
+    /// When the ongoing violation of a limit covered by the grace timer started.
+    violation_since: Option<Instant>,
+}
+
+impl Default for SimulatorState {
+    fn default() -> Self {
+        Self {
+            channel_id: Channel::default(),
+            frequency: 2400.0,
+            phase: 0.0,
+            rf_output_enabled: false,
+            power_setpoint_dbm: 10.0,
+            attenuation: 20.0,
+            magnitude: 50.0,
+            temperature: 35.5,
+            voltage: 12.0,
+            current: 0.5,
+            reflection: 0.05,
+            soa: SoaConfig::default(),
+            status: 0,
+            pa_errors: 0,
+            tripped: false,
+            violation_since: None,
+        }
+    }
+}
+
+impl SimulatorState {
+    pub fn power_setpoint_watt(&self) -> f64 {
+        dbm_to_watt(self.power_setpoint_dbm)
+    }
+
+    /// The forward power in W, which follows the setpoint while RF output is enabled.
+    pub fn forward_power(&self) -> f64 {
+        match self.rf_output_enabled {
+            true => self.power_setpoint_watt(),
+            false => 0.0,
+        }
+    }
+
+    /// The reflected power in W.
+    pub fn reflected_power(&self) -> f64 {
+        self.forward_power() * self.reflection
+    }
+
+    /// The power in W turned into heat, the supplied power minus what reaches the load.
+    pub fn dissipation(&self) -> f64 {
+        let delivered = self.forward_power() - self.reflected_power();
+        (self.voltage * self.current - delivered).max(0.0)
+    }
+
+    /// Compares the readings with the SOA limits, raising warnings and tripping on shutdown limits.
+    ///
+    /// Reflection, dissipation and temperature violations only trip once they've lasted for the whole
+    /// grace period. The other shutdown limits trip straight away.
+    pub fn update_soa(&mut self, now: Instant) {
+        let soa = &self.soa;
+        let mut warnings = 0;
+        let mut graced = 0;
+        let mut immediate = 0;
+
+        if soa.temp_enabled {
+            let (warning, shutdown) = exceeded(
+                self.temperature,
+                soa.high_temp,
+                soa.shutdown_temp,
+                StatusCode::HighPATemperature,
+                StatusCode::ShutdownPATemperature,
+            );
+            warnings |= warning;
+            graced |= shutdown;
+        }
+        if soa.reflection_enabled {
+            // No reflected power has no dBm value, and can't exceed a limit either.
+            if let Ok(reflected) = watt_to_dbm(self.reflected_power()) {
+                let (warning, shutdown) = exceeded(
+                    reflected,
+                    soa.high_reflection,
+                    soa.shutdown_reflection,
+                    StatusCode::HighReflectedPower,
+                    StatusCode::ShutdownReflectedPower,
+                );
+                warnings |= warning;
+                graced |= shutdown;
+            }
+        }
+        if soa.dissipation_enabled {
+            let (warning, shutdown) = exceeded(
+                self.dissipation(),
+                soa.high_dissipation,
+                soa.shutdown_dissipation,
+                StatusCode::SOAHighDissipation,
+                StatusCode::SOAShutdownDissipation,
+            );
+            warnings |= warning;
+            graced |= shutdown;
+        }
+
+        let (warning, shutdown) = exceeded(
+            self.current,
+            soa.high_current,
+            soa.shutdown_current,
+            StatusCode::SOAHighCurrent,
+            StatusCode::SOAShutdownCurrent,
+        );
+        warnings |= warning;
+        immediate |= shutdown;
+
+        let (warning, shutdown) = exceeded(
+            self.forward_power(),
+            soa.high_forward_power,
+            soa.shutdown_forward_power,
+            StatusCode::SOAHighForwardPower,
+            StatusCode::SOAShutdownForwardPower,
+        );
+        warnings |= warning;
+        immediate |= shutdown;
+
+        let (warning, shutdown) = exceeded(
+            self.voltage,
+            soa.high_voltage,
+            soa.shutdown_max_voltage,
+            StatusCode::SOAHighVoltage,
+            StatusCode::SOAShutdownMaximumVoltage,
+        );
+        warnings |= warning;
+        immediate |= shutdown;
+
+        // The lower voltage limits are exceeded by falling below them.
+        let (warning, shutdown) = exceeded(
+            -self.voltage,
+            -soa.low_voltage,
+            -soa.shutdown_min_voltage,
+            StatusCode::SOALowVoltage,
+            StatusCode::SOAShutdownMinimumVoltage,
+        );
+        warnings |= warning;
+        immediate |= shutdown;
+
+        let grace_period = soa.grace_period as u128;
+        self.status |= warnings;
+
+        if graced == 0 {
+            self.violation_since = None;
+        } else {
+            let since = *self.violation_since.get_or_insert(now);
+            if now.duration_since(since).as_millis() >= grace_period {
+                immediate |= graced;
+            }
+        }
+
+        if immediate != 0 {
+            self.trip(immediate);
+        }
+    }
+
+    /// Switches RF output off and keeps it off until the errors are cleared.
+    fn trip(&mut self, shutdown: u64) {
+        self.rf_output_enabled = false;
+        self.tripped = true;
+        self.status |= shutdown;
+        self.pa_errors |= pa_alarms(shutdown);
+        if self.pa_errors != 0 {
+            self.status |= StatusCode::PAError.to_bit_value();
+        }
+    }
+
+    /// Clears the reported errors and lifts a trip, like `ClearErrors` on the device.
+    pub fn clear_errors(&mut self) {
+        self.status = 0;
+        self.pa_errors = 0;
+        self.tripped = false;
+        self.violation_since = None;
+    }
+}
 
-use minicircuit_commands::data_types::types::Channel;
+/// Returns the status bits of the limits the value has reached, as `(high, shutdown)`.
+fn exceeded(
+    value: f64,
+    high: f64,
+    shutdown: f64,
+    high_code: StatusCode,
+    shutdown_code: StatusCode,
+) -> (u64, u64) {
+    if value >= shutdown {
+        (0, shutdown_code.to_bit_value())
+    } else if value >= high {
+        (high_code.to_bit_value(), 0)
+    } else {
+        (0, 0)
+    }
+}
+
+/// Maps SOA shutdown status bits to the alarm bits reported by `GetPAErrors`.
+fn pa_alarms(shutdown: u64) -> u16 {
+    let alarms = [
+        (StatusCode::ShutdownReflectedPower, 0),
+        (StatusCode::SOAShutdownForwardPower, 2),
+        (StatusCode::SOAShutdownCurrent, 4),
+        (StatusCode::SOAShutdownMaximumVoltage, 6),
+        (StatusCode::SOAShutdownMinimumVoltage, 7),
+        (StatusCode::ShutdownPATemperature, 10),
+    ];
+
+    alarms
+        .iter()
+        .filter(|(code, _)| shutdown & code.to_bit_value() != 0)
+        .fold(0, |pa_errors, (_, bit)| pa_errors | 1 << bit)
+}
 
 /// Simulates a MiniCircuit device by processing commands and generating responses
+#[derive(Debug)]
 pub struct MiniCircuitSimulator {
-    // Store device state
-    frequency: f64,
-    rf_output_enabled: bool,
-    phase: f64,
-    channel_id: Channel,
-    power_dbm: f64,
-    power_watt: f64,
-    attenuation: f64,
-    magnitude: f64,
-    temperature: f64,
-    voltage: f64,
-    current: f64,
+    state: SimulatorState,
     start_time: Instant,
-    // Add more state variables as needed
+    /// How far the simulated clock runs ahead of the real one, see `advance_clock`.
+    clock_offset: Duration,
     command_log: Vec<String>,
+    faults: FaultConfig,
+    script: Script,
 }
 
 impl MiniCircuitSimulator {
     pub fn new() -> Self {
         Self {
-            frequency: 2400.0, // Default frequency in MHz
-            rf_output_enabled: false,
-            phase: 0.0,
-            channel_id: Channel::default(),
-            power_dbm: 10.0,
-            power_watt: 0.01,
-            attenuation: 20.0,
-            magnitude: 0.5,
-            temperature: 35.5,
-            voltage: 12.0,
-            current: 0.5,
+            state: SimulatorState::default(),
             start_time: Instant::now(),
+            clock_offset: Duration::ZERO,
             command_log: Vec::new(),
+            faults: FaultConfig::default(),
+            script: Script::default(),
         }
     }
 
@@ -46,84 +349,237 @@ impl MiniCircuitSimulator {
         &self.command_log
     }
 
+    pub fn state(&self) -> &SimulatorState {
+        &self.state
+    }
+
+    /// Gives access to the simulated readings, to mimic a heating PA or a mismatched load.
+    pub fn state_mut(&mut self) -> &mut SimulatorState {
+        &mut self.state
+    }
+
+    /// Moves the simulated clock forward, as if the time had passed without any commands.
+    ///
+    /// `GetUptime` counts the skipped time, and a SOA violation that outlasts the grace period
+    /// trips straight away rather than with the next command, so a test needn't sleep through it.
+    pub fn advance_clock(&mut self, by: Duration) {
+        self.clock_offset += by;
+        self.state.update_soa(self.now());
+    }
+
+    /// The time on the simulated clock.
+    fn now(&self) -> Instant {
+        Instant::now() + self.clock_offset
+    }
+
+    /// Replaces the faults injected into the following responses.
+    pub fn set_faults(&mut self, faults: FaultConfig) {
+        self.faults = faults;
+    }
+
+    pub fn faults(&self) -> &FaultConfig {
+        &self.faults
+    }
+
+    /// Replaces the script played back against the following commands.
+    pub fn set_script(&mut self, script: Script) {
+        self.script = script;
+    }
+
+    /// The rules of the script that haven't run their course yet.
+    pub fn script(&self) -> &Script {
+        &self.script
+    }
+
+    /// Processes a command like `process_command`, then applies the injected fault for it.
+    ///
+    /// A scripted rule matching the command takes precedence over the configured fault.
+    /// Returns `None` when the response is dropped. A delayed response blocks the caller for the delay.
+    ///
+    /// Like a board sharing a bus with others, it stays silent, returning `None`, for commands
+    /// addressed to a channel other than its own channel ID.
+    pub fn respond(&mut self, command: &str) -> Option<String> {
+        let mut parts = command.trim().split(',');
+        let code = parts.next().unwrap_or_default();
+
+        if let Some(channel) = parts.next().and_then(|part| part.trim().parse::<u8>().ok()) {
+            if channel != self.state.channel_id.channel_id {
+                return None;
+            }
+        }
+
+        let fault = match self.script.next_action(code) {
+            None | Some(Action::Respond) => self.faults.fault_for(code).cloned(),
+            Some(Action::Reply(reply)) => {
+                // The reply stands in for the device, so the state is left untouched.
+                self.command_log.push(command.trim().to_string());
+                return Some(reply);
+            }
+            Some(Action::Fault(fault)) => Some(fault),
+            Some(Action::Update(update)) => {
+                update(&mut self.state);
+                self.faults.fault_for(code).cloned()
+            }
+        };
+
+        match fault {
+            None => Some(self.process_command(command)),
+            Some(Fault::Malformed) => {
+                self.process_command(command);
+                Some("#?malformed".to_string())
+            }
+            Some(Fault::DeviceError(error_code)) => {
+                // The device rejects the command, so the state is left untouched.
+                self.command_log.push(command.trim().to_string());
+                Some(format!(
+                    "{},{},{}",
+                    code, self.state.channel_id.channel_id, error_code
+                ))
+            }
+            Some(Fault::Drop) => {
+                self.process_command(command);
+                None
+            }
+            Some(Fault::Delay(delay)) => {
+                let response = self.process_command(command);
+                thread::sleep(delay);
+                Some(response)
+            }
+        }
+    }
+
     /// Process a command string and return the appropriate response
     pub fn process_command(&mut self, command: &str) -> String {
         let command = command.trim();
         info!("Processing command: {}", command);
-        
+
         // Log the command
         self.command_log.push(command.to_string());
-        
+
         // Parse the command
         let parts: Vec<&str> = command.split(',').collect();
         if parts.is_empty() {
             return "ERROR: Empty command".to_string();
         }
-        
+
+        // Readings may have changed since the last command, so check them against the limits first
+        self.state.update_soa(self.now());
+
         // Process the command and return the response
         let response = match parts[0] {
             // Basic frequency commands
             "$FCG" => self.handle_get_frequency(),
             "$FCS" => self.handle_set_frequency(parts),
-            
+
             // RF output commands
             "$ECS" => self.handle_set_rf_output(parts),
             "$ECG" => self.handle_get_rf_output(),
-            
+
             // Phase commands
             "$PCG" => self.handle_get_phase(),
             "$PCS" => self.handle_set_phase(parts),
-            
+
             // Identity and information commands
             "$IDN" => self.handle_get_identity(),
             "$TCG" => self.handle_get_isc_temp(),
             "$RTG" => self.handle_get_uptime(),
             "$ST" => self.handle_get_status(),
+            "$PSG" => self.handle_get_pa_errors(),
+            "$ERRC" => self.handle_clear_errors(),
             "$RST" => self.handle_reset_system(),
-            
+
             // Power commands
-            "$PFG" => self.handle_get_power_dbm(),
-            "$PFS" => self.handle_set_power_dbm(parts),
-            "$PWG" => self.handle_get_power_watt(),
-            "$PWS" => self.handle_set_power_watt(parts),
-            
+            "$PWRDG" => self.handle_get_power_dbm(),
+            "$PWRDS" => self.handle_set_power_dbm(parts),
+            "$PWRG" => self.handle_get_power_watt(),
+            "$PWRS" => self.handle_set_power_watt(parts),
+            "$PPG" => self.handle_get_forward_reflected_watt(),
+            "$PPDG" => self.handle_get_forward_reflected_dbm(),
+
             // Attenuation and magnitude commands
-            "$ATG" => self.handle_get_attenuation(),
-            "$ATS" => self.handle_set_attenuation(parts),
-            "$MAG" => self.handle_get_magnitude(),
-            "$MAS" => self.handle_set_magnitude(parts),
-            
+            "$GCG" => self.handle_get_attenuation(),
+            "$GCS" => self.handle_set_attenuation(parts),
+            "$MCG" => self.handle_get_magnitude(),
+            "$MCS" => self.handle_set_magnitude(parts),
+
             // Temperature, voltage, and current commands
-            "$TPG" => self.handle_get_pa_temp(),
-            "$VTG" => self.handle_get_pa_voltage(),
-            "$CTG" => self.handle_get_pa_current(),
-            
+            "$PTG" => self.handle_get_pa_temp(),
+            "$PVG" => self.handle_get_pa_voltage(),
+            "$PIG" => self.handle_get_pa_current(),
+
             // Channel ID commands
-            "$CIG" => self.handle_get_channel_id(),
-            "$CIS" => self.handle_set_channel_id(parts),
-            
+            "$CHANG" => self.handle_get_channel_id(),
+            "$CHANS" => self.handle_set_channel_id(parts),
+
+            // SOA commands
+            "$SOG" => self.handle_get_soa_config(),
+            "$SOA" => self.handle_set_soa_config(parts),
+            "$STG" => {
+                self.handle_get_soa_limits(self.state.soa.high_temp, self.state.soa.shutdown_temp)
+            }
+            "$STS" => self
+                .handle_set_soa_limits(parts, |soa| (&mut soa.high_temp, &mut soa.shutdown_temp)),
+            "$SCG" => self.handle_get_soa_limits(
+                self.state.soa.high_current,
+                self.state.soa.shutdown_current,
+            ),
+            "$SCS" => self.handle_set_soa_limits(parts, |soa| {
+                (&mut soa.high_current, &mut soa.shutdown_current)
+            }),
+            "$SDG" => self.handle_get_soa_limits(
+                self.state.soa.high_dissipation,
+                self.state.soa.shutdown_dissipation,
+            ),
+            "$SDS" => self.handle_set_soa_limits(parts, |soa| {
+                (&mut soa.high_dissipation, &mut soa.shutdown_dissipation)
+            }),
+            "$SPG" => self.handle_get_soa_limits(
+                self.state.soa.high_reflection,
+                self.state.soa.shutdown_reflection,
+            ),
+            "$SPS" => self.handle_set_soa_limits(parts, |soa| {
+                (&mut soa.high_reflection, &mut soa.shutdown_reflection)
+            }),
+            "$SFG" => self.handle_get_soa_limits(
+                self.state.soa.high_forward_power,
+                self.state.soa.shutdown_forward_power,
+            ),
+            "$SFS" => self.handle_set_soa_limits(parts, |soa| {
+                (&mut soa.high_forward_power, &mut soa.shutdown_forward_power)
+            }),
+            "$SVG" => self.handle_get_soa_voltage(),
+            "$SVS" => self.handle_set_soa_voltage(parts),
+            "$SWES" => self.handle_set_soa_watchdog(parts),
+            "$SOAGS" => self.handle_set_soa_grace_timer(parts),
+
             // Add more command handlers as needed
             _ => format!("ERROR: Unknown command {}", parts[0]),
         };
-        
+
+        // A setter may have pushed a reading past its limits
+        self.state.update_soa(self.now());
+
         info!("Command response: {}", response);
         response
     }
 
     fn handle_get_frequency(&self) -> String {
-        format!("OK,{},{:.2}", self.channel_id.channel_id, self.frequency)
+        format!(
+            "OK,{},{:.2}",
+            self.state.channel_id.channel_id, self.state.frequency
+        )
     }
 
     fn handle_set_frequency(&mut self, parts: Vec<&str>) -> String {
         if parts.len() < 3 {
             return "ERROR: Invalid command format".to_string();
         }
-        
+
         match parts[2].parse::<f64>() {
             Ok(freq) => {
-                self.frequency = freq;
+                self.state.frequency = freq;
                 "OK".to_string()
-            },
+            }
             Err(_) => "ERROR: Invalid frequency value".to_string(),
         }
     }
@@ -132,34 +588,45 @@ impl MiniCircuitSimulator {
         if parts.len() < 3 {
             return "ERROR: Invalid command format".to_string();
         }
-        
+
         match parts[2].parse::<u8>() {
+            Ok(1) if self.state.tripped => {
+                // A tripped device refuses to enable RF output until the errors are cleared
+                format!("$ECS,{},ERR7E", self.state.channel_id.channel_id)
+            }
             Ok(value) => {
-                self.rf_output_enabled = value == 1;
+                self.state.rf_output_enabled = value == 1;
                 "OK".to_string()
-            },
+            }
             Err(_) => "ERROR: Invalid value".to_string(),
         }
     }
 
     fn handle_get_rf_output(&self) -> String {
-        format!("OK,{},{}", self.channel_id.channel_id, if self.rf_output_enabled { 1 } else { 0 })
+        format!(
+            "OK,{},{}",
+            self.state.channel_id.channel_id,
+            if self.state.rf_output_enabled { 1 } else { 0 }
+        )
     }
 
     fn handle_get_phase(&self) -> String {
-        format!("OK,{},{:.2}", self.channel_id.channel_id, self.phase)
+        format!(
+            "OK,{},{:.2}",
+            self.state.channel_id.channel_id, self.state.phase
+        )
     }
 
     fn handle_set_phase(&mut self, parts: Vec<&str>) -> String {
         if parts.len() < 3 {
             return "ERROR: Invalid command format".to_string();
         }
-        
+
         match parts[2].parse::<f64>() {
             Ok(phase) => {
-                self.phase = phase;
+                self.state.phase = phase;
                 "OK".to_string()
-            },
+            }
             Err(_) => "ERROR: Invalid phase value".to_string(),
         }
     }
@@ -167,145 +634,319 @@ impl MiniCircuitSimulator {
     fn handle_get_identity(&self) -> String {
         // Format the identity response exactly as expected by the parser
         // The format should be: "OK,<channel>,<manufacturer> <isc_board>,<serial_number>"
-        format!("OK,{},MiniCircuits ISC-2400-XX,SN12345678", self.channel_id.channel_id)
+        format!(
+            "OK,{},MiniCircuits ISC-2400-XX,SN12345678",
+            self.state.channel_id.channel_id
+        )
     }
 
     fn handle_get_isc_temp(&self) -> String {
-        format!("OK,{},{}", self.channel_id.channel_id, self.temperature)
+        format!(
+            "OK,{},{}",
+            self.state.channel_id.channel_id, self.state.temperature
+        )
     }
 
     fn handle_get_uptime(&self) -> String {
-        let uptime = self.start_time.elapsed().as_secs();
-        format!("OK,{},{}", self.channel_id.channel_id, uptime)
+        let uptime = self.now().duration_since(self.start_time).as_secs();
+        format!("OK,{},{}", self.state.channel_id.channel_id, uptime)
     }
 
     fn handle_get_status(&self) -> String {
-        format!("OK,{},0,0", self.channel_id.channel_id)
+        format!(
+            "OK,{},0,{:X}",
+            self.state.channel_id.channel_id, self.state.status
+        )
     }
 
-    fn handle_reset_system(&mut self) -> String {
-        // Reset device state to defaults
-        self.frequency = 2400.0;
-        self.rf_output_enabled = false;
-        self.phase = 0.0;
-        self.power_dbm = 10.0;
-        self.power_watt = 0.01;
-        self.attenuation = 20.0;
-        self.magnitude = 0.5;
-        self.start_time = Instant::now();
+    fn handle_get_pa_errors(&self) -> String {
+        format!(
+            "OK,{},{:X}",
+            self.state.channel_id.channel_id, self.state.pa_errors
+        )
+    }
+
+    fn handle_clear_errors(&mut self) -> String {
+        self.state.clear_errors();
         "OK".to_string()
     }
 
-    // New command handlers
+    fn handle_reset_system(&mut self) -> String {
+        // Reset device state to defaults, the channel id is kept like on the real device
+        self.state = SimulatorState {
+            channel_id: self.state.channel_id.clone(),
+            ..SimulatorState::default()
+        };
+        self.start_time = self.now();
+        "OK".to_string()
+    }
 
     fn handle_get_power_dbm(&self) -> String {
-        format!("OK,{},{:.2}", self.channel_id.channel_id, self.power_dbm)
+        format!(
+            "OK,{},{:.2}",
+            self.state.channel_id.channel_id, self.state.power_setpoint_dbm
+        )
     }
 
     fn handle_set_power_dbm(&mut self, parts: Vec<&str>) -> String {
         if parts.len() < 3 {
             return "ERROR: Invalid command format".to_string();
         }
-        
+
         match parts[2].parse::<f64>() {
             Ok(power) => {
-                self.power_dbm = power;
-                // Update watts based on dBm
-                self.power_watt = 10.0_f64.powf(self.power_dbm / 10.0) / 1000.0;
+                self.state.power_setpoint_dbm = power;
                 "OK".to_string()
-            },
+            }
             Err(_) => "ERROR: Invalid power value".to_string(),
         }
     }
 
     fn handle_get_power_watt(&self) -> String {
-        format!("OK,{},{:.6}", self.channel_id.channel_id, self.power_watt)
+        format!(
+            "OK,{},{:.6}",
+            self.state.channel_id.channel_id,
+            self.state.power_setpoint_watt()
+        )
     }
 
     fn handle_set_power_watt(&mut self, parts: Vec<&str>) -> String {
         if parts.len() < 3 {
             return "ERROR: Invalid command format".to_string();
         }
-        
-        match parts[2].parse::<f64>() {
-            Ok(power) => {
-                self.power_watt = power;
-                // Update dBm based on watts
-                self.power_dbm = 10.0 * (self.power_watt * 1000.0).log10();
+
+        match parts[2].parse::<f64>().map(watt_to_dbm) {
+            Ok(Ok(power)) => {
+                self.state.power_setpoint_dbm = power;
                 "OK".to_string()
-            },
-            Err(_) => "ERROR: Invalid power value".to_string(),
+            }
+            _ => "ERROR: Invalid power value".to_string(),
         }
     }
 
+    fn handle_get_forward_reflected_watt(&self) -> String {
+        format!(
+            "OK,{},{:.6},{:.6}",
+            self.state.channel_id.channel_id,
+            self.state.forward_power(),
+            self.state.reflected_power()
+        )
+    }
+
+    fn handle_get_forward_reflected_dbm(&self) -> String {
+        // Without RF output there's no power to express in dBm, report the noise floor instead.
+        let to_dbm = |watt: f64| watt_to_dbm(watt).unwrap_or(-100.0);
+
+        format!(
+            "OK,{},{:.2},{:.2}",
+            self.state.channel_id.channel_id,
+            to_dbm(self.state.forward_power()),
+            to_dbm(self.state.reflected_power())
+        )
+    }
+
     fn handle_get_attenuation(&self) -> String {
-        format!("OK,{},{:.2}", self.channel_id.channel_id, self.attenuation)
+        format!(
+            "OK,{},{:.2}",
+            self.state.channel_id.channel_id, self.state.attenuation
+        )
     }
 
     fn handle_set_attenuation(&mut self, parts: Vec<&str>) -> String {
         if parts.len() < 3 {
             return "ERROR: Invalid command format".to_string();
         }
-        
+
         match parts[2].parse::<f64>() {
             Ok(att) => {
-                self.attenuation = att;
+                self.state.attenuation = att;
                 "OK".to_string()
-            },
+            }
             Err(_) => "ERROR: Invalid attenuation value".to_string(),
         }
     }
 
     fn handle_get_magnitude(&self) -> String {
-        format!("OK,{},{:.2}", self.channel_id.channel_id, self.magnitude)
+        format!(
+            "OK,{},{:.2}",
+            self.state.channel_id.channel_id, self.state.magnitude
+        )
     }
 
     fn handle_set_magnitude(&mut self, parts: Vec<&str>) -> String {
         if parts.len() < 3 {
             return "ERROR: Invalid command format".to_string();
         }
-        
+
         match parts[2].parse::<f64>() {
             Ok(mag) => {
-                self.magnitude = mag;
+                self.state.magnitude = mag;
                 "OK".to_string()
-            },
+            }
             Err(_) => "ERROR: Invalid magnitude value".to_string(),
         }
     }
 
     fn handle_get_pa_temp(&self) -> String {
-        format!("OK,{},{}", self.channel_id.channel_id, self.temperature)
+        format!(
+            "OK,{},{}",
+            self.state.channel_id.channel_id, self.state.temperature
+        )
     }
 
     fn handle_get_pa_voltage(&self) -> String {
-        format!("OK,{},{}", self.channel_id.channel_id, self.voltage)
+        format!(
+            "OK,{},{}",
+            self.state.channel_id.channel_id, self.state.voltage
+        )
     }
 
     fn handle_get_pa_current(&self) -> String {
-        format!("OK,{},{}", self.channel_id.channel_id, self.current)
+        format!(
+            "OK,{},{}",
+            self.state.channel_id.channel_id, self.state.current
+        )
     }
 
     fn handle_get_channel_id(&self) -> String {
-        format!("OK,{}", self.channel_id.channel_id)
+        format!("OK,{}", self.state.channel_id.channel_id)
     }
 
     fn handle_set_channel_id(&mut self, parts: Vec<&str>) -> String {
         if parts.len() < 3 {
             return "ERROR: Invalid command format".to_string();
         }
-        
+
         match parts[2].parse::<u8>() {
             Ok(id) => {
-                self.channel_id = Channel { channel_id: id };
+                self.state.channel_id = Channel { channel_id: id };
                 "OK".to_string()
-            },
+            }
             Err(_) => "ERROR: Invalid channel ID value".to_string(),
         }
     }
-}
 
+    fn handle_get_soa_config(&self) -> String {
+        let soa = &self.state.soa;
+        format!(
+            "OK,{} temp:{} reflection:{} watchdog:{}",
+            self.state.channel_id.channel_id,
+            soa.temp_enabled as u8,
+            soa.reflection_enabled as u8,
+            soa.external_watchdog_enabled as u8
+        )
+    }
 
+    fn handle_set_soa_config(&mut self, parts: Vec<&str>) -> String {
+        // $SOA,<channel>,<temp>,1,<reflection>,<external watchdog>,<dissipation>
+        if parts.len() < 7 {
+            return "ERROR: Invalid command format".to_string();
+        }
+
+        let flags: Result<Vec<bool>, _> = [2, 4, 5, 6]
+            .iter()
+            .map(|&i| parts[i].parse::<u8>().map(|value| value == 1))
+            .collect();
+
+        match flags {
+            Ok(flags) => {
+                let soa = &mut self.state.soa;
+                soa.temp_enabled = flags[0];
+                soa.reflection_enabled = flags[1];
+                soa.external_watchdog_enabled = flags[2];
+                soa.dissipation_enabled = flags[3];
+                "OK".to_string()
+            }
+            Err(_) => "ERROR: Invalid SOA configuration".to_string(),
+        }
+    }
+
+    fn handle_get_soa_limits(&self, high: f64, shutdown: f64) -> String {
+        format!(
+            "OK,{},{},{}",
+            self.state.channel_id.channel_id, high, shutdown
+        )
+    }
+
+    /// Stores the high and shutdown limit of one of the SOA measurements.
+    fn handle_set_soa_limits(
+        &mut self,
+        parts: Vec<&str>,
+        limits: fn(&mut SoaConfig) -> (&mut f64, &mut f64),
+    ) -> String {
+        if parts.len() < 4 {
+            return "ERROR: Invalid command format".to_string();
+        }
 
+        match (parts[2].parse::<f64>(), parts[3].parse::<f64>()) {
+            (Ok(new_high), Ok(new_shutdown)) => {
+                let (high, shutdown) = limits(&mut self.state.soa);
+                *high = new_high;
+                *shutdown = new_shutdown;
+                "OK".to_string()
+            }
+            _ => "ERROR: Invalid SOA limit value".to_string(),
+        }
+    }
+
+    fn handle_get_soa_voltage(&self) -> String {
+        let soa = &self.state.soa;
+        format!(
+            "OK,{},{},{},{},{}",
+            self.state.channel_id.channel_id,
+            soa.shutdown_min_voltage,
+            soa.low_voltage,
+            soa.high_voltage,
+            soa.shutdown_max_voltage
+        )
+    }
 
+    fn handle_set_soa_voltage(&mut self, parts: Vec<&str>) -> String {
+        if parts.len() < 6 {
+            return "ERROR: Invalid command format".to_string();
+        }
+
+        let limits: Result<Vec<f64>, _> =
+            parts[2..6].iter().map(|part| part.parse::<f64>()).collect();
 
+        match limits {
+            Ok(limits) => {
+                let soa = &mut self.state.soa;
+                soa.shutdown_min_voltage = limits[0];
+                soa.low_voltage = limits[1];
+                soa.high_voltage = limits[2];
+                soa.shutdown_max_voltage = limits[3];
+                "OK".to_string()
+            }
+            Err(_) => "ERROR: Invalid SOA voltage value".to_string(),
+        }
+    }
+
+    fn handle_set_soa_watchdog(&mut self, parts: Vec<&str>) -> String {
+        if parts.len() < 3 {
+            return "ERROR: Invalid command format".to_string();
+        }
+
+        match parts[2].parse::<bool>() {
+            Ok(enabled) => {
+                self.state.soa.watchdog_enabled = enabled;
+                "OK".to_string()
+            }
+            Err(_) => "ERROR: Invalid value".to_string(),
+        }
+    }
+
+    fn handle_set_soa_grace_timer(&mut self, parts: Vec<&str>) -> String {
+        if parts.len() < 3 {
+            return "ERROR: Invalid command format".to_string();
+        }
+
+        match parts[2].parse::<u64>() {
+            Ok(grace_period) => {
+                self.state.soa.grace_period = grace_period;
+                "OK".to_string()
+            }
+            Err(_) => "ERROR: Invalid grace period value".to_string(),
+        }
+    }
+}